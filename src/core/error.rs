@@ -5,15 +5,83 @@ pub enum NumalError {
     InvalidInput(String),
     DidNotConverge,
     DerivativeNotComputable,
+    /// A line search could not satisfy its acceptance conditions; carries
+    /// the step with the lowest objective value tried and that value
+    LineSearchFailed {
+        reason: LineSearchFailure,
+        step: f64,
+        fx: f64,
+    },
+    /// The constraints of the problem admit no feasible point; carries a
+    /// certificate, a combination of the constraints that cannot hold, in
+    /// the form documented by the solver
+    Infeasible {
+        certificate: Vec<f64>,
+    },
+    /// The objective is unbounded below on the feasible set; carries a
+    /// direction of unbounded descent
+    Unbounded {
+        direction: Vec<f64>,
+    },
+    /// Rounding errors keep an integral from reaching the requested
+    /// accuracy; carries the best estimate of the integral and of its error
+    Roundoff {
+        estimate: f64,
+        error: f64,
+    },
+    /// An adaptive integrator used up its subdivisions before reaching the
+    /// requested accuracy; carries the best estimate of the integral and of
+    /// its error
+    SubdivisionLimit {
+        estimate: f64,
+        error: f64,
+    },
     LibErr(String),
 }
 
+/// Why a line search gave up
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineSearchFailure {
+    /// The trial-step budget ran out
+    MaxIterations,
+    /// The interval of uncertainty shrank below its resolution
+    IntervalCollapsed,
+    /// The step reached its lower or upper bound
+    StepBound,
+}
+
+impl fmt::Display for LineSearchFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineSearchFailure::MaxIterations => write!(f, "MAXIMUM ITERATIONS REACHED"),
+            LineSearchFailure::IntervalCollapsed => write!(f, "INTERVAL COLLAPSED"),
+            LineSearchFailure::StepBound => write!(f, "STEP AT BOUND"),
+        }
+    }
+}
+
 impl fmt::Display for NumalError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             NumalError::InvalidInput(msg) => write!(f, "INVALID INPUT: {msg}"),
             NumalError::DidNotConverge => write!(f, "FAILED TO CONVERGE"),
             NumalError::DerivativeNotComputable => write!(f, "DERIVATIVE NOT COMPUTABLE"),
+            NumalError::LineSearchFailed { reason, step, fx } => {
+                write!(
+                    f,
+                    "LINE SEARCH FAILED: {reason} (BEST STEP {step}, F = {fx})"
+                )
+            }
+            NumalError::Infeasible { .. } => write!(f, "PROBLEM IS INFEASIBLE"),
+            NumalError::Unbounded { .. } => write!(f, "PROBLEM IS UNBOUNDED"),
+            NumalError::Roundoff { estimate, error } => write!(
+                f,
+                "ROUNDOFF ERROR DETECTED (BEST ESTIMATE {estimate}, ERROR {error})"
+            ),
+            NumalError::SubdivisionLimit { estimate, error } => write!(
+                f,
+                "MAXIMUM SUBDIVISIONS REACHED (BEST ESTIMATE {estimate}, ERROR {error})"
+            ),
             NumalError::LibErr(msg) => write!(f, "NUMAL LIB ERROR: {msg}"),
         }
     }
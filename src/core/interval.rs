@@ -0,0 +1,462 @@
+//! Interval arithmetic with outward rounding (Moore, 1966).
+//!
+//! Every operation returns an interval containing the exact result for all
+//! arguments in its operands. Rust offers no control over the rounding mode,
+//! so each computed endpoint is moved one unit in the last place outwards,
+//! which is enough for the correctly rounded arithmetic operations and the
+//! square root. The elementary functions are widened by a few units, which
+//! covers the error of common libm implementations. An interval evaluation of
+//! an expression therefore bounds its range over a box, though often loosely
+//! when a variable occurs more than once.
+//!
+//! The empty interval, with NaN endpoints, is the result of a function
+//! applied entirely outside its domain; arguments partly outside are
+//! restricted to the domain first.
+
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// Outward widening, in units in the last place, of the elementary functions.
+const LIBM_ULPS: u32 = 2;
+
+/// A closed interval `[lo, hi]` of real numbers, possibly unbounded
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Interval {
+    pub const EMPTY: Interval = Interval {
+        lo: f64::NAN,
+        hi: f64::NAN,
+    };
+    pub const ENTIRE: Interval = Interval {
+        lo: f64::NEG_INFINITY,
+        hi: f64::INFINITY,
+    };
+
+    /// The interval `[lo, hi]`; it is empty unless `lo <= hi`
+    pub fn new(lo: f64, hi: f64) -> Self {
+        if lo <= hi {
+            Interval { lo, hi }
+        } else {
+            Interval::EMPTY
+        }
+    }
+
+    /// The degenerate interval `[x, x]`
+    pub const fn point(x: f64) -> Self {
+        Interval { lo: x, hi: x }
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.lo.is_nan() || self.hi.is_nan()
+    }
+
+    pub fn contains(self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    pub fn width(self) -> f64 {
+        self.hi - self.lo
+    }
+
+    /// Midpoint of a bounded interval
+    pub fn mid(self) -> f64 {
+        0.5 * self.lo + 0.5 * self.hi
+    }
+
+    /// Smallest interval containing both
+    pub fn hull(self, other: Interval) -> Self {
+        if self.is_empty() {
+            return other;
+        }
+        if other.is_empty() {
+            return self;
+        }
+        Interval::new(self.lo.min(other.lo), self.hi.max(other.hi))
+    }
+
+    /// Range of `|x|`
+    pub fn abs(self) -> Self {
+        if self.lo >= 0.0 {
+            self
+        } else if self.hi <= 0.0 {
+            -self
+        } else {
+            Interval::new(0.0, self.hi.max(-self.lo))
+        }
+    }
+
+    /// Range of `x^2`, tighter than `x * x`
+    pub fn sqr(self) -> Self {
+        let a = self.abs();
+        let lo = if a.lo == 0.0 {
+            0.0
+        } else {
+            down(a.lo * a.lo, 1)
+        };
+        Interval::new(lo, up(a.hi * a.hi, 1))
+    }
+
+    /// Range of `x^n`
+    pub fn powi(self, n: i32) -> Self {
+        if self.is_empty() {
+            return self;
+        }
+        let power = self.powu(n.unsigned_abs());
+        if n < 0 {
+            Interval::point(1.0) / power
+        } else {
+            power
+        }
+    }
+
+    // Range of `x^n` for a non-negative exponent
+    fn powu(self, n: u32) -> Self {
+        if n == 0 {
+            return Interval::point(1.0);
+        }
+        if n.is_multiple_of(2) {
+            let a = self.abs();
+            return Interval::new(pow_down(a.lo, n), pow_up(a.hi, n));
+        }
+        // Odd powers are increasing
+        let lo = if self.lo >= 0.0 {
+            pow_down(self.lo, n)
+        } else {
+            -pow_up(-self.lo, n)
+        };
+        let hi = if self.hi >= 0.0 {
+            pow_up(self.hi, n)
+        } else {
+            -pow_down(-self.hi, n)
+        };
+        Interval::new(lo, hi)
+    }
+
+    /// Range of the square root over the non-negative part
+    pub fn sqrt(self) -> Self {
+        if self.is_empty() || self.hi < 0.0 {
+            return Interval::EMPTY;
+        }
+        let lo = if self.lo <= 0.0 {
+            0.0
+        } else {
+            down(self.lo.sqrt(), 1).max(0.0)
+        };
+        Interval::new(lo, up(self.hi.sqrt(), 1))
+    }
+
+    pub fn exp(self) -> Self {
+        if self.is_empty() {
+            return self;
+        }
+        Interval::new(
+            down(self.lo.exp(), LIBM_ULPS).max(0.0),
+            up(self.hi.exp(), LIBM_ULPS),
+        )
+    }
+
+    /// Range of the natural logarithm over the positive part
+    pub fn ln(self) -> Self {
+        if self.is_empty() || self.hi <= 0.0 {
+            return Interval::EMPTY;
+        }
+        let lo = if self.lo <= 0.0 {
+            f64::NEG_INFINITY
+        } else {
+            down(self.lo.ln(), LIBM_ULPS)
+        };
+        Interval::new(lo, up(self.hi.ln(), LIBM_ULPS))
+    }
+
+    pub fn cos(self) -> Self {
+        if self.is_empty() {
+            return self;
+        }
+        if self.width().is_nan() || self.width() >= TAU {
+            return Interval::new(-1.0, 1.0);
+        }
+        let (a, b) = (self.lo.cos(), self.hi.cos());
+        let lo = if meets_period(self, PI) {
+            -1.0
+        } else {
+            down(a.min(b), LIBM_ULPS).max(-1.0)
+        };
+        let hi = if meets_period(self, 0.0) {
+            1.0
+        } else {
+            up(a.max(b), LIBM_ULPS).min(1.0)
+        };
+        Interval::new(lo, hi)
+    }
+
+    pub fn sin(self) -> Self {
+        let half_pi = Interval::new(down(FRAC_PI_2, 1), up(FRAC_PI_2, 1));
+        (self - half_pi).cos()
+    }
+}
+
+// Whether `x` may contain a point `offset + 2 k pi` for an integer k; errs
+// on the side of yes.
+fn meets_period(x: Interval, offset: f64) -> bool {
+    let a = (x.lo - offset) / TAU;
+    let b = (x.hi - offset) / TAU;
+    let slack = 4.0 * f64::EPSILON * a.abs().max(b.abs()).max(1.0);
+    (a - slack).ceil() <= (b + slack).floor()
+}
+
+fn down(x: f64, ulps: u32) -> f64 {
+    (0..ulps).fold(x, |v, _| v.next_down())
+}
+
+fn up(x: f64, ulps: u32) -> f64 {
+    (0..ulps).fold(x, |v, _| v.next_up())
+}
+
+// Lower and upper bounds on `a^n` for `a >= 0`, by squaring.
+fn pow_down(a: f64, n: u32) -> f64 {
+    if a == 0.0 {
+        return 0.0;
+    }
+    power(a, n, |p| down(p, 1).max(0.0))
+}
+
+fn pow_up(a: f64, n: u32) -> f64 {
+    power(a, n, |p| up(p, 1))
+}
+
+// `a^n` for `a >= 0` by repeated squaring, applying `round` to every product
+// so that the rounding is outward throughout.
+fn power(a: f64, mut n: u32, round: impl Fn(f64) -> f64) -> f64 {
+    let (mut base, mut p) = (a, 1.0);
+    while n > 0 {
+        if n & 1 == 1 {
+            p = round(p * base);
+        }
+        n >>= 1;
+        if n > 0 {
+            base = round(base * base);
+        }
+    }
+    p
+}
+
+// Product of two endpoints, taking `0 * inf` as zero.
+fn product(a: f64, b: f64) -> f64 {
+    if a == 0.0 || b == 0.0 { 0.0 } else { a * b }
+}
+
+impl From<f64> for Interval {
+    fn from(x: f64) -> Self {
+        Interval::point(x)
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            write!(f, "[empty]")
+        } else {
+            write!(f, "[{}, {}]", self.lo, self.hi)
+        }
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+    fn add(self, rhs: Interval) -> Interval {
+        Interval::new(down(self.lo + rhs.lo, 1), up(self.hi + rhs.hi, 1))
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+    fn sub(self, rhs: Interval) -> Interval {
+        Interval::new(down(self.lo - rhs.hi, 1), up(self.hi - rhs.lo, 1))
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+    fn mul(self, rhs: Interval) -> Interval {
+        if self.is_empty() || rhs.is_empty() {
+            return Interval::EMPTY;
+        }
+        let p = [
+            product(self.lo, rhs.lo),
+            product(self.lo, rhs.hi),
+            product(self.hi, rhs.lo),
+            product(self.hi, rhs.hi),
+        ];
+        let lo = p.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = p.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Interval::new(down(lo, 1), up(hi, 1))
+    }
+}
+
+impl Div for Interval {
+    type Output = Interval;
+    // Division by an interval containing zero gives the entire line
+    fn div(self, rhs: Interval) -> Interval {
+        if self.is_empty() || rhs.is_empty() || (rhs.lo == 0.0 && rhs.hi == 0.0) {
+            return Interval::EMPTY;
+        }
+        if rhs.contains(0.0) {
+            return Interval::ENTIRE;
+        }
+        let q = [
+            self.lo / rhs.lo,
+            self.lo / rhs.hi,
+            self.hi / rhs.lo,
+            self.hi / rhs.hi,
+        ];
+        // An infinite numerator over an infinite denominator bounds nothing
+        if q.iter().any(|v| v.is_nan()) {
+            return Interval::ENTIRE;
+        }
+        let lo = q.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = q.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Interval::new(down(lo, 1), up(hi, 1))
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+    fn neg(self) -> Interval {
+        Interval {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+}
+
+impl Add<f64> for Interval {
+    type Output = Interval;
+    fn add(self, rhs: f64) -> Interval {
+        self + Interval::point(rhs)
+    }
+}
+
+impl Sub<f64> for Interval {
+    type Output = Interval;
+    fn sub(self, rhs: f64) -> Interval {
+        self - Interval::point(rhs)
+    }
+}
+
+impl Mul<f64> for Interval {
+    type Output = Interval;
+    fn mul(self, rhs: f64) -> Interval {
+        self * Interval::point(rhs)
+    }
+}
+
+impl Div<f64> for Interval {
+    type Output = Interval;
+    fn div(self, rhs: f64) -> Interval {
+        self / Interval::point(rhs)
+    }
+}
+
+impl Add<Interval> for f64 {
+    type Output = Interval;
+    fn add(self, rhs: Interval) -> Interval {
+        Interval::point(self) + rhs
+    }
+}
+
+impl Sub<Interval> for f64 {
+    type Output = Interval;
+    fn sub(self, rhs: Interval) -> Interval {
+        Interval::point(self) - rhs
+    }
+}
+
+impl Mul<Interval> for f64 {
+    type Output = Interval;
+    fn mul(self, rhs: Interval) -> Interval {
+        Interval::point(self) * rhs
+    }
+}
+
+impl Div<Interval> for f64 {
+    type Output = Interval;
+    fn div(self, rhs: Interval) -> Interval {
+        Interval::point(self) / rhs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_encloses_exact_result() {
+        let third = Interval::point(1.0) / 3.0;
+        assert!(third.lo < 1.0 / 3.0 && 1.0 / 3.0 < third.hi);
+        let x = Interval::new(-1.0, 2.0);
+        let y = Interval::new(3.0, 4.0);
+        let p = x * y;
+        assert!(p.contains(-4.0) && p.contains(8.0) && p.width() < 12.0 + 1e-14);
+        let d = x - x;
+        assert!(d.contains(-3.0) && d.contains(3.0));
+    }
+
+    #[test]
+    fn division_by_interval_containing_zero_is_unbounded() {
+        let x = Interval::new(1.0, 2.0);
+        assert_eq!(x / Interval::new(-1.0, 1.0), Interval::ENTIRE);
+        assert!((x / 0.0).is_empty());
+        let q = x / Interval::new(0.5, 4.0);
+        assert!(q.contains(0.25) && q.contains(4.0));
+    }
+
+    #[test]
+    fn even_powers_are_non_negative() {
+        let x = Interval::new(-2.0, 1.0);
+        assert_eq!(x.sqr(), Interval::new(0.0, up(4.0, 1)));
+        assert_eq!(x.powi(4).lo, 0.0);
+        assert!(x.powi(3).contains(-8.0) && x.powi(3).contains(1.0));
+        assert!(x.powi(-2).hi == f64::INFINITY);
+    }
+
+    #[test]
+    fn most_negative_exponent_does_not_overflow() {
+        let x = Interval::new(2.0, 3.0).powi(i32::MIN);
+        assert!(x.contains(0.0) && x.hi.is_finite());
+    }
+
+    #[test]
+    fn large_exponents_are_fast_and_enclose_the_power() {
+        let x = Interval::new(0.5, 1.0).powi(i32::MAX);
+        assert!(x.contains(0.0) && x.contains(1.0) && x.hi < 1.001, "{x:?}");
+        let x = Interval::point(3.0).powi(13);
+        assert!(x.contains(1594323.0) && x.width() < 1e-8, "{x:?}");
+    }
+
+    #[test]
+    fn trigonometric_ranges_cover_extrema() {
+        let c = Interval::new(-0.5, 4.0).cos();
+        assert_eq!(c.hi, 1.0);
+        assert_eq!(c.lo, -1.0);
+        let s = Interval::new(0.1, 0.2).sin();
+        assert!(s.contains(0.1f64.sin()) && s.contains(0.2f64.sin()) && s.width() < 0.1);
+        let s = Interval::new(1.0, 2.0).sin();
+        assert_eq!(s.hi, 1.0);
+        assert!(s.lo <= 1f64.sin());
+    }
+
+    #[test]
+    fn functions_are_restricted_to_their_domain() {
+        assert_eq!(Interval::new(-4.0, 4.0).sqrt().lo, 0.0);
+        assert!(Interval::new(-4.0, -1.0).sqrt().is_empty());
+        assert_eq!(Interval::new(0.0, 1.0).ln().lo, f64::NEG_INFINITY);
+        assert!(Interval::new(-2.0, 0.0).ln().is_empty());
+        let e = Interval::new(0.0, 1.0).exp();
+        assert!(e.contains(1.0) && e.contains(std::f64::consts::E));
+    }
+}
@@ -0,0 +1,568 @@
+use std::ops::{Index, IndexMut};
+
+/// A dense, row-major matrix of `f64`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    /// A `rows x cols` matrix of zeros
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: vec![0.0; rows * cols],
+        }
+    }
+
+    /// The `n x n` identity matrix
+    pub fn identity(n: usize) -> Self {
+        let mut m = Matrix::zeros(n, n);
+        for i in 0..n {
+            m[(i, i)] = 1.0;
+        }
+        m
+    }
+
+    /// Builds a matrix from its entries listed row by row.
+    ///
+    /// Panics if `data.len() != rows * cols`.
+    pub fn from_row_slice(rows: usize, cols: usize, data: &[f64]) -> Self {
+        assert_eq!(data.len(), rows * cols, "matrix data has wrong length");
+        Matrix {
+            rows,
+            cols,
+            data: data.to_vec(),
+        }
+    }
+
+    /// Builds a matrix whose `(i, j)` entry is `f(i, j)`
+    pub fn from_fn<F: FnMut(usize, usize) -> f64>(rows: usize, cols: usize, mut f: F) -> Self {
+        let mut m = Matrix::zeros(rows, cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                m[(i, j)] = f(i, j);
+            }
+        }
+        m
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Entries in row-major order
+    pub fn as_slice(&self) -> &[f64] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [f64] {
+        &mut self.data
+    }
+
+    pub fn row(&self, i: usize) -> &[f64] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> &mut [f64] {
+        &mut self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    /// Copy of column `j`
+    pub fn col(&self, j: usize) -> Vec<f64> {
+        (0..self.rows).map(|i| self[(i, j)]).collect()
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        Matrix::from_fn(self.cols, self.rows, |i, j| self[(j, i)])
+    }
+
+    /// Matrix-vector product `A x`
+    pub fn mul_vec(&self, x: &[f64]) -> Vec<f64> {
+        assert_eq!(x.len(), self.cols, "dimension mismatch in mul_vec");
+        (0..self.rows).map(|i| dot(self.row(i), x)).collect()
+    }
+
+    /// Transposed matrix-vector product `A^T x`
+    pub fn tr_mul_vec(&self, x: &[f64]) -> Vec<f64> {
+        assert_eq!(x.len(), self.rows, "dimension mismatch in tr_mul_vec");
+        let mut y = vec![0.0; self.cols];
+        for (i, &xi) in x.iter().enumerate() {
+            for (yj, &aij) in y.iter_mut().zip(self.row(i)) {
+                *yj += aij * xi;
+            }
+        }
+        y
+    }
+
+    /// Matrix product `A B`
+    pub fn matmul(&self, other: &Matrix) -> Matrix {
+        assert_eq!(self.cols, other.rows, "dimension mismatch in matmul");
+        let mut c = Matrix::zeros(self.rows, other.cols);
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let aik = self[(i, k)];
+                if aik == 0.0 {
+                    continue;
+                }
+                for (cij, &bkj) in c.row_mut(i).iter_mut().zip(other.row(k)) {
+                    *cij += aik * bkj;
+                }
+            }
+        }
+        c
+    }
+
+    /// Adds `alpha * u v^T` in place
+    pub fn rank1_update(&mut self, alpha: f64, u: &[f64], v: &[f64]) {
+        for (i, &ui) in u.iter().enumerate() {
+            for (aij, &vj) in self.row_mut(i).iter_mut().zip(v) {
+                *aij += alpha * ui * vj;
+            }
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.data.iter().all(|v| v.is_finite())
+    }
+
+    /// Largest absolute entry
+    pub fn max_abs(&self) -> f64 {
+        self.data.iter().fold(0.0, |m, v| m.max(v.abs()))
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = f64;
+    fn index(&self, (i, j): (usize, usize)) -> &f64 {
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut f64 {
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+/// Inner product of two vectors
+pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Euclidean norm, scaled to avoid overflow and underflow
+pub fn norm(a: &[f64]) -> f64 {
+    let scale = a.iter().fold(0.0f64, |m, v| m.max(v.abs()));
+    if scale == 0.0 || !scale.is_finite() {
+        return scale;
+    }
+    scale * a.iter().map(|v| (v / scale).powi(2)).sum::<f64>().sqrt()
+}
+
+/// LU factorization with partial pivoting of a square matrix
+pub(crate) struct Lu {
+    lu: Matrix,
+    piv: Vec<usize>,
+}
+
+impl Lu {
+    /// Factorizes `a`, returning `None` when it is numerically singular
+    pub(crate) fn new(a: &Matrix) -> Option<Lu> {
+        assert_eq!(a.rows, a.cols, "LU requires a square matrix");
+        let n = a.rows;
+        let mut lu = a.clone();
+        let mut piv: Vec<usize> = (0..n).collect();
+        let threshold = n as f64 * f64::EPSILON * a.max_abs();
+        for k in 0..n {
+            let p = (k..n)
+                .max_by(|&i, &j| lu[(i, k)].abs().total_cmp(&lu[(j, k)].abs()))
+                .unwrap_or(k);
+            if lu[(p, k)].abs() <= threshold || lu[(p, k)].is_nan() {
+                return None;
+            }
+            if p != k {
+                piv.swap(p, k);
+                for j in 0..n {
+                    lu.data.swap(p * n + j, k * n + j);
+                }
+            }
+            let pivot = lu[(k, k)];
+            for i in k + 1..n {
+                let m = lu[(i, k)] / pivot;
+                lu[(i, k)] = m;
+                if m != 0.0 {
+                    for j in k + 1..n {
+                        lu[(i, j)] -= m * lu[(k, j)];
+                    }
+                }
+            }
+        }
+        Some(Lu { lu, piv })
+    }
+
+    /// Solves `A x = b`
+    pub(crate) fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.lu.rows;
+        let mut x: Vec<f64> = self.piv.iter().map(|&p| b[p]).collect();
+        for i in 0..n {
+            let s = dot(&self.lu.row(i)[..i], &x[..i]);
+            x[i] -= s;
+        }
+        for i in (0..n).rev() {
+            let s = dot(&self.lu.row(i)[i + 1..], &x[i + 1..]);
+            x[i] = (x[i] - s) / self.lu[(i, i)];
+        }
+        x
+    }
+
+    /// The inverse of the factorized matrix
+    pub(crate) fn inverse(&self) -> Matrix {
+        let n = self.lu.rows;
+        let mut inv = Matrix::zeros(n, n);
+        let mut e = vec![0.0; n];
+        for j in 0..n {
+            e[j] = 1.0;
+            for (i, v) in self.solve(&e).into_iter().enumerate() {
+                inv[(i, j)] = v;
+            }
+            e[j] = 0.0;
+        }
+        inv
+    }
+}
+
+/// Cholesky factorization `A = L Lᵀ` of a symmetric positive definite matrix
+pub(crate) struct Cholesky {
+    l: Matrix,
+}
+
+impl Cholesky {
+    /// Factorizes `a + shift I` from its lower triangle, returning `None`
+    /// unless it is numerically positive definite
+    pub(crate) fn new(a: &Matrix, shift: f64) -> Option<Cholesky> {
+        assert_eq!(a.rows, a.cols, "Cholesky requires a square matrix");
+        let n = a.rows;
+        let mut l = Matrix::zeros(n, n);
+        let threshold = n as f64 * f64::EPSILON * (a.max_abs() + shift.abs());
+        for j in 0..n {
+            let d = a[(j, j)] + shift - dot(&l.row(j)[..j], &l.row(j)[..j]);
+            if d <= threshold || d.is_nan() {
+                return None;
+            }
+            let ljj = d.sqrt();
+            l[(j, j)] = ljj;
+            for i in j + 1..n {
+                let s = dot(&l.row(i)[..j], &l.row(j)[..j]);
+                l[(i, j)] = (a[(i, j)] - s) / ljj;
+            }
+        }
+        Some(Cholesky { l })
+    }
+
+    /// Solves `L y = b`
+    pub(crate) fn solve_lower(&self, b: &[f64]) -> Vec<f64> {
+        let mut y = b.to_vec();
+        for i in 0..y.len() {
+            let s = dot(&self.l.row(i)[..i], &y[..i]);
+            y[i] = (y[i] - s) / self.l[(i, i)];
+        }
+        y
+    }
+
+    /// Solves `A x = b`
+    pub(crate) fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.l.rows;
+        let mut x = self.solve_lower(b);
+        for i in (0..n).rev() {
+            let s: f64 = (i + 1..n).map(|k| self.l[(k, i)] * x[k]).sum();
+            x[i] = (x[i] - s) / self.l[(i, i)];
+        }
+        x
+    }
+}
+
+/// Householder QR factorization with column pivoting, `A P = Q R`, of an
+/// `m x n` matrix
+pub(crate) struct Qr {
+    r: Matrix,
+    // Householder vectors scaled so that each reflection is `I - v v^T`,
+    // the k-th acting on rows `k..m`
+    v: Vec<Vec<f64>>,
+    perm: Vec<usize>,
+}
+
+impl Qr {
+    /// Factorizes `a`, bringing the column of largest remaining norm to
+    /// the front at each step
+    pub(crate) fn new(a: &Matrix) -> Qr {
+        let (m, n) = (a.rows, a.cols);
+        let mut w = a.clone();
+        let mut perm: Vec<usize> = (0..n).collect();
+        let mut v = Vec::with_capacity(m.min(n));
+        for k in 0..m.min(n) {
+            let tail = |w: &Matrix, j: usize| (k..m).map(|i| w[(i, j)]).collect::<Vec<_>>();
+            let norms: Vec<f64> = (k..n).map(|j| norm(&tail(&w, j))).collect();
+            let p = k
+                + (0..n - k)
+                    .max_by(|&i, &j| norms[i].total_cmp(&norms[j]))
+                    .unwrap_or(0);
+            if p != k {
+                perm.swap(p, k);
+                for i in 0..m {
+                    w.data.swap(i * n + p, i * n + k);
+                }
+            }
+            let mut h = tail(&w, k);
+            let alpha = if h[0] > 0.0 {
+                -norms[p - k]
+            } else {
+                norms[p - k]
+            };
+            if alpha != 0.0 {
+                h[0] -= alpha;
+                let scale = (2.0 / dot(&h, &h)).sqrt();
+                h.iter_mut().for_each(|x| *x *= scale);
+                for j in k..n {
+                    let s = dot(&h, &tail(&w, j));
+                    for (i, hi) in (k..m).zip(&h) {
+                        w[(i, j)] -= s * hi;
+                    }
+                }
+            }
+            v.push(h);
+        }
+        let r = Matrix::from_fn(m.min(n), n, |i, j| if j >= i { w[(i, j)] } else { 0.0 });
+        Qr { r, v, perm }
+    }
+
+    /// The `min(m, n) x n` upper trapezoidal factor
+    pub(crate) fn r(&self) -> &Matrix {
+        &self.r
+    }
+
+    /// Column `j` of `R` belongs to column `perm()[j]` of `A`
+    pub(crate) fn perm(&self) -> &[usize] {
+        &self.perm
+    }
+
+    /// Numerical rank: the number of diagonal entries of `R` above
+    /// `max(m, n) eps |r_00|`
+    pub(crate) fn rank(&self) -> usize {
+        let k = self.r.rows;
+        if k == 0 {
+            return 0;
+        }
+        let threshold =
+            self.v[0].len().max(self.r.cols) as f64 * f64::EPSILON * self.r[(0, 0)].abs();
+        (0..k)
+            .take_while(|&i| self.r[(i, i)].abs() > threshold)
+            .count()
+    }
+
+    /// `Q^T b`
+    pub(crate) fn qt_mul(&self, b: &[f64]) -> Vec<f64> {
+        let mut y = b.to_vec();
+        for (k, h) in self.v.iter().enumerate() {
+            let s = dot(h, &y[k..]);
+            y[k..].iter_mut().zip(h).for_each(|(yi, hi)| *yi -= s * hi);
+        }
+        y
+    }
+
+    /// `Q b`
+    pub(crate) fn q_mul(&self, b: &[f64]) -> Vec<f64> {
+        let mut y = b.to_vec();
+        for (k, h) in self.v.iter().enumerate().rev() {
+            let s = dot(h, &y[k..]);
+            y[k..].iter_mut().zip(h).for_each(|(yi, hi)| *yi -= s * hi);
+        }
+        y
+    }
+
+    /// Basic least-squares solution of `A x = b`: the components beyond
+    /// the numerical rank, in pivoted order, are zero
+    pub(crate) fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let rank = self.rank();
+        let mut z = self.qt_mul(b);
+        z.truncate(rank);
+        for i in (0..rank).rev() {
+            let s = dot(&self.r.row(i)[i + 1..rank], &z[i + 1..]);
+            z[i] = (z[i] - s) / self.r[(i, i)];
+        }
+        let mut x = vec![0.0; self.r.cols];
+        for (zi, &p) in z.iter().zip(&self.perm) {
+            x[p] = *zi;
+        }
+        x
+    }
+}
+
+// Sweeps of Jacobi rotations after which the SVD gives up.
+const JACOBI_SWEEPS: usize = 60;
+
+/// Thin singular value decomposition `A = U diag(s) V^T` of an `m x n`
+/// matrix, with the `min(m, n)` singular values in decreasing order
+pub(crate) struct Svd {
+    pub(crate) u: Matrix,
+    pub(crate) s: Vec<f64>,
+    pub(crate) v: Matrix,
+}
+
+impl Svd {
+    /// Computes the decomposition by one-sided Jacobi rotations, which
+    /// find small singular values to high relative accuracy. Returns `None`
+    /// if the rotations fail to converge.
+    pub(crate) fn new(a: &Matrix) -> Option<Svd> {
+        if a.rows < a.cols {
+            let t = Svd::new(&a.transpose())?;
+            return Some(Svd {
+                u: t.v,
+                s: t.s,
+                v: t.u,
+            });
+        }
+        let (m, n) = (a.rows, a.cols);
+        // Columns of A and V are kept as rows of `w` and `vt`
+        let mut w = a.transpose();
+        let mut vt = Matrix::identity(n);
+        let rotate = |mat: &mut Matrix, p: usize, q: usize, c: f64, s: f64| {
+            for k in 0..mat.cols {
+                let (x, y) = (mat[(p, k)], mat[(q, k)]);
+                mat[(p, k)] = c * x - s * y;
+                mat[(q, k)] = s * x + c * y;
+            }
+        };
+        let mut converged = false;
+        for _ in 0..JACOBI_SWEEPS {
+            let mut rotated = false;
+            for p in 0..n {
+                for q in p + 1..n {
+                    let alpha = dot(w.row(p), w.row(p));
+                    let beta = dot(w.row(q), w.row(q));
+                    let gamma = dot(w.row(p), w.row(q));
+                    if gamma.abs() <= f64::EPSILON * (alpha * beta).sqrt() {
+                        continue;
+                    }
+                    rotated = true;
+                    let zeta = (beta - alpha) / (2.0 * gamma);
+                    let t = zeta.signum() / (zeta.abs() + zeta.hypot(1.0));
+                    let c = 1.0 / t.hypot(1.0);
+                    rotate(&mut w, p, q, c, c * t);
+                    rotate(&mut vt, p, q, c, c * t);
+                }
+            }
+            if !rotated {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return None;
+        }
+        let norms: Vec<f64> = (0..n).map(|j| norm(w.row(j))).collect();
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&i, &j| norms[j].total_cmp(&norms[i]));
+        let s: Vec<f64> = order.iter().map(|&j| norms[j]).collect();
+        let u = Matrix::from_fn(m, n, |i, k| {
+            let j = order[k];
+            if norms[j] > 0.0 {
+                w[(j, i)] / norms[j]
+            } else {
+                0.0
+            }
+        });
+        let v = Matrix::from_fn(n, n, |i, k| vt[(order[k], i)]);
+        Some(Svd { u, s, v })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lu_solves_pivoted_system() {
+        let a = Matrix::from_row_slice(3, 3, &[0.0, 2.0, 1.0, 1.0, 1.0, 1.0, 2.0, 1.0, 3.0]);
+        let x = [1.0, -2.0, 3.0];
+        let b = a.mul_vec(&x);
+        let sol = Lu::new(&a).unwrap().solve(&b);
+        for (s, e) in sol.iter().zip(x) {
+            assert!((s - e).abs() < 1e-14);
+        }
+    }
+
+    #[test]
+    fn lu_detects_singular_matrix() {
+        let a = Matrix::from_row_slice(2, 2, &[1.0, 2.0, 2.0, 4.0]);
+        assert!(Lu::new(&a).is_none());
+    }
+
+    #[test]
+    fn inverse_times_matrix_is_identity() {
+        let a = Matrix::from_row_slice(2, 2, &[4.0, 7.0, 2.0, 6.0]);
+        let prod = a.matmul(&Lu::new(&a).unwrap().inverse());
+        for (p, e) in prod.as_slice().iter().zip(Matrix::identity(2).as_slice()) {
+            assert!((p - e).abs() < 1e-14);
+        }
+    }
+
+    #[test]
+    fn cholesky_solves_shifted_system() {
+        let a = Matrix::from_row_slice(3, 3, &[4.0, 2.0, 0.0, 2.0, 5.0, 1.0, 0.0, 1.0, 3.0]);
+        let x = [1.0, -1.0, 2.0];
+        let mut b = a.mul_vec(&x);
+        b.iter_mut().zip(x).for_each(|(bi, xi)| *bi += 0.5 * xi);
+        let sol = Cholesky::new(&a, 0.5).unwrap().solve(&b);
+        for (s, e) in sol.iter().zip(x) {
+            assert!((s - e).abs() < 1e-14);
+        }
+        let indefinite = Matrix::from_row_slice(2, 2, &[1.0, 2.0, 2.0, 1.0]);
+        assert!(Cholesky::new(&indefinite, 0.0).is_none());
+        assert!(Cholesky::new(&indefinite, 1.5).is_some());
+    }
+
+    #[test]
+    fn qr_solves_least_squares_and_detects_rank() {
+        // Line through (0, 1), (1, 3), (2, 4): intercept 7/6, slope 3/2
+        let a = Matrix::from_row_slice(3, 2, &[1.0, 0.0, 1.0, 1.0, 1.0, 2.0]);
+        let qr = Qr::new(&a);
+        let x = qr.solve(&[1.0, 3.0, 4.0]);
+        assert!((x[0] - 7.0 / 6.0).abs() < 1e-14 && (x[1] - 1.5).abs() < 1e-14);
+        let b = [0.5, -1.0, 2.0];
+        for (u, v) in qr.q_mul(&qr.qt_mul(&b)).iter().zip(b) {
+            assert!((u - v).abs() < 1e-14);
+        }
+        assert_eq!(qr.rank(), 2);
+        let dependent = Matrix::from_row_slice(3, 2, &[1.0, 2.0, 2.0, 4.0, 3.0, 6.0]);
+        assert_eq!(Qr::new(&dependent).rank(), 1);
+    }
+
+    #[test]
+    fn svd_reconstructs_matrix() {
+        for a in [
+            Matrix::from_row_slice(3, 2, &[3.0, 2.0, 2.0, 3.0, 2.0, -2.0]),
+            Matrix::from_row_slice(2, 3, &[3.0, 2.0, 2.0, 2.0, 3.0, -2.0]),
+        ] {
+            let svd = Svd::new(&a).unwrap();
+            assert!((svd.s[0] - 5.0).abs() < 1e-14 && (svd.s[1] - 3.0).abs() < 1e-14);
+            let us = Matrix::from_fn(svd.u.rows(), 2, |i, k| svd.u[(i, k)] * svd.s[k]);
+            let prod = us.matmul(&svd.v.transpose());
+            for (p, e) in prod.as_slice().iter().zip(a.as_slice()) {
+                assert!((p - e).abs() < 1e-14);
+            }
+        }
+    }
+
+    #[test]
+    fn norm_avoids_overflow() {
+        assert!((norm(&[3e200, 4e200]) / 5e200 - 1.0).abs() < 1e-15);
+        assert_eq!(norm(&[]), 0.0);
+    }
+}
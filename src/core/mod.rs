@@ -1,2 +1,25 @@
+pub mod complex;
 pub mod error;
+pub mod interval;
+pub mod linalg;
+pub mod rng;
+pub mod special;
 pub mod tolerance;
+
+use crate::NumalError;
+
+// Rejects an initial guess that is empty or not finite, for the solvers of
+// several variables.
+pub(crate) fn check_start(x0: &[f64]) -> Result<(), NumalError> {
+    if x0.is_empty() {
+        return Err(NumalError::InvalidInput(
+            "initial guess must have at least one component".to_string(),
+        ));
+    }
+    if x0.iter().any(|v| !v.is_finite()) {
+        return Err(NumalError::InvalidInput(
+            "initial guess must be finite".to_string(),
+        ));
+    }
+    Ok(())
+}
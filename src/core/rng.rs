@@ -0,0 +1,108 @@
+//! Deterministic pseudo-random numbers for the stochastic methods.
+//!
+//! The generator is xoshiro256** (Blackman & Vigna, 2021) with its state
+//! expanded from a 64-bit seed by SplitMix64, so a seed reproduces the same
+//! stream on every platform. It is not suitable for cryptography.
+
+use std::f64::consts::PI;
+
+/// Seedable pseudo-random number generator
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rng {
+    s: [u64; 4],
+}
+
+impl Rng {
+    /// Generator whose stream is determined by `seed`
+    pub fn new(seed: u64) -> Rng {
+        let mut z = seed;
+        let mut split_mix = || {
+            z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut v = z;
+            v = (v ^ (v >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            v = (v ^ (v >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            v ^ (v >> 31)
+        };
+        Rng {
+            s: [split_mix(), split_mix(), split_mix(), split_mix()],
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// Uniform on `[0, 1)` with 53 random bits
+    pub fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Uniform on `[a, b)`
+    pub fn uniform_in(&mut self, a: f64, b: f64) -> f64 {
+        a + (b - a) * self.uniform()
+    }
+
+    /// Uniform on `0..n`; `n` must be positive
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range");
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// Standard normal deviate by the Box–Muller transform
+    pub fn normal(&mut self) -> f64 {
+        let r = (-2.0 * (1.0 - self.uniform()).ln()).sqrt();
+        r * (2.0 * PI * self.uniform()).cos()
+    }
+
+    /// Cauchy deviate with the given location and scale
+    pub fn cauchy(&mut self, location: f64, scale: f64) -> f64 {
+        location + scale * (PI * (self.uniform() - 0.5)).tan()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_determines_stream() {
+        let (mut a, mut b, mut c) = (Rng::new(7), Rng::new(7), Rng::new(8));
+        let xs: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        let ys: Vec<u64> = (0..10).map(|_| b.next_u64()).collect();
+        let zs: Vec<u64> = (0..10).map(|_| c.next_u64()).collect();
+        assert_eq!(xs, ys);
+        assert_ne!(xs, zs);
+    }
+
+    #[test]
+    fn uniform_deviates_have_expected_moments() {
+        let mut rng = Rng::new(1);
+        let n = 100_000;
+        let xs: Vec<f64> = (0..n).map(|_| rng.uniform()).collect();
+        assert!(xs.iter().all(|&x| (0.0..1.0).contains(&x)));
+        let mean = xs.iter().sum::<f64>() / n as f64;
+        assert!((mean - 0.5).abs() < 5e-3);
+        let mut counts = [0; 5];
+        (0..n).for_each(|_| counts[rng.below(5)] += 1);
+        assert!(counts.iter().all(|&c| (c as f64 - 20_000.0).abs() < 600.0));
+    }
+
+    #[test]
+    fn normal_deviates_have_expected_moments() {
+        let mut rng = Rng::new(2);
+        let n = 100_000;
+        let xs: Vec<f64> = (0..n).map(|_| rng.normal()).collect();
+        let mean = xs.iter().sum::<f64>() / n as f64;
+        let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64;
+        assert!(mean.abs() < 1e-2 && (var - 1.0).abs() < 2e-2);
+    }
+}
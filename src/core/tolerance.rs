@@ -1,12 +1,14 @@
 use crate::NumalError;
 
 /// Predefined tolerance levels
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum Tolerance {
     /// Custom absolute and relative tolerances
     Custom { eps_abs: f64, eps_rel: f64 },
     /// A 'stricter' tolerance (e.g. 1e-12, 1e-10)
     Strict,
     /// The default tolerance (e.g. 1e-8, 1e-6)
+    #[default]
     Default,
     /// A loose tolerance (e.g. 1e-6, 1e-4)
     Loose,
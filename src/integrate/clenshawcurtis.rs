@@ -0,0 +1,427 @@
+//! Clenshaw–Curtis and Fejér quadrature.
+//!
+//! All three rules interpolate the integrand at Chebyshev points
+//! `x = cos(theta)` on `[-1, 1]` and integrate the interpolant exactly. For a
+//! parameter `n`, Clenshaw–Curtis (1960) uses `theta = k pi / n` for
+//! `k = 0..=n`, Fejér's second rule the same points without the ends and
+//! Fejér's first rule the midpoints `theta = (k + 1/2) pi / n`. Their weights
+//! are inverse discrete Fourier transforms of simple sequences (Waldvogel,
+//! 2006), computed here by a mixed-radix FFT in `O(n (p_1 + ... + p_k))`
+//! operations for `n = p_1 ... p_k`. That is `O(n log n)` for the `n` of the
+//! adaptive integrators, which are powers of two or three times a small
+//! initial value, but the rules with an arbitrary number of points take
+//! `O(n^2)` when `n` is prime.
+//!
+//! The point sets are nested: doubling `n` keeps every Clenshaw–Curtis and
+//! Fejér-2 point and tripling `n` keeps every Fejér-1 point. The adaptive
+//! integrators refine `n` that way, evaluating the integrand only at the new
+//! points, until successive estimates agree, but as a guard against
+//! estimates that agree by accident, not before `n` has grown `MIN_LEVEL`
+//! times.
+
+use super::{GaussRule, Integral, check_limits, check_points, eval};
+use crate::NumalError;
+use crate::core::complex::Complex;
+use crate::core::tolerance::{Tolerance, is_close};
+use std::f64::consts::PI;
+
+// Number of refinements before an estimate is tested for convergence.
+const MIN_LEVEL: usize = 2;
+
+/// Options for [`clenshaw_curtis`], [`fejer1`] and [`fejer2`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClenshawCurtisOptions {
+    /// Tolerance between successive estimates; see the
+    /// [module documentation](super)
+    pub tol: Tolerance,
+    /// Maximum number of integrand evaluations
+    pub max_evaluations: usize,
+}
+
+impl Default for ClenshawCurtisOptions {
+    fn default() -> Self {
+        ClenshawCurtisOptions {
+            tol: Tolerance::Default,
+            max_evaluations: 1 << 16,
+        }
+    }
+}
+
+// The three rules, which differ in their points and in how they nest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    ClenshawCurtis,
+    Fejer1,
+    Fejer2,
+}
+
+impl Kind {
+    // Parameter n of the coarsest rule.
+    fn initial(self) -> usize {
+        match self {
+            Kind::ClenshawCurtis | Kind::Fejer2 => 8,
+            Kind::Fejer1 => 9,
+        }
+    }
+
+    // Factor by which n grows with each refinement.
+    fn factor(self) -> usize {
+        match self {
+            Kind::ClenshawCurtis | Kind::Fejer2 => 2,
+            Kind::Fejer1 => 3,
+        }
+    }
+
+    // Indices k of the points for parameter n.
+    fn indices(self, n: usize) -> std::ops::Range<usize> {
+        match self {
+            Kind::ClenshawCurtis => 0..n + 1,
+            Kind::Fejer1 => 0..n,
+            Kind::Fejer2 => 1..n,
+        }
+    }
+
+    // The point with index k for parameter n.
+    fn node(self, n: usize, k: usize) -> f64 {
+        let offset = if self == Kind::Fejer1 { 0.5 } else { 0.0 };
+        (PI * (k as f64 + offset) / n as f64).cos()
+    }
+
+    // Weight of each point for parameter n, indexed by k.
+    fn weights(self, n: usize) -> Vec<f64> {
+        if self == Kind::ClenshawCurtis && n == 1 {
+            // The trapezoid rule, too short for the transform
+            return vec![1.0, 1.0];
+        }
+        let mut w = waldvogel(self, n);
+        if self == Kind::ClenshawCurtis {
+            // The transform is periodic, so the last weight repeats the first
+            w.push(w[0]);
+        }
+        w
+    }
+}
+
+/// Clenshaw–Curtis rule with `points >= 2` points on `[-1, 1]`, including
+/// both ends.
+pub fn clenshaw_curtis_rule(points: usize) -> Result<GaussRule, NumalError> {
+    check_points(points, 2)?;
+    Ok(rule(Kind::ClenshawCurtis, points - 1))
+}
+
+/// Fejér's first rule with `points >= 1` points on `[-1, 1]`.
+pub fn fejer1_rule(points: usize) -> Result<GaussRule, NumalError> {
+    check_points(points, 1)?;
+    Ok(rule(Kind::Fejer1, points))
+}
+
+/// Fejér's second rule with `points >= 1` points on `[-1, 1]`.
+pub fn fejer2_rule(points: usize) -> Result<GaussRule, NumalError> {
+    check_points(points, 1)?;
+    Ok(rule(Kind::Fejer2, points + 1))
+}
+
+/// Integrates `f` over `[a, b]` by nested Clenshaw–Curtis rules.
+///
+/// Doubles the number of panels between estimates, starting from 9 points.
+/// Returns [`NumalError::DidNotConverge`] if the estimates are not within
+/// tolerance by `max_evaluations` evaluations.
+pub fn clenshaw_curtis<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    opts: &ClenshawCurtisOptions,
+) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    refine(&f, a, b, Kind::ClenshawCurtis, opts)
+}
+
+/// Integrates `f` over `[a, b]` by nested rules of Fejér's first kind.
+///
+/// Triples the number of points between estimates, starting from 9 points,
+/// and never evaluates `f` at the limits. Returns
+/// [`NumalError::DidNotConverge`] if the estimates are not within tolerance
+/// by `max_evaluations` evaluations.
+pub fn fejer1<F>(f: F, a: f64, b: f64, opts: &ClenshawCurtisOptions) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    refine(&f, a, b, Kind::Fejer1, opts)
+}
+
+/// Integrates `f` over `[a, b]` by nested rules of Fejér's second kind.
+///
+/// Doubles the number of panels between estimates, starting from 7 points,
+/// and never evaluates `f` at the limits. Returns
+/// [`NumalError::DidNotConverge`] if the estimates are not within tolerance
+/// by `max_evaluations` evaluations.
+pub fn fejer2<F>(f: F, a: f64, b: f64, opts: &ClenshawCurtisOptions) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    refine(&f, a, b, Kind::Fejer2, opts)
+}
+
+// The rule for parameter n with its nodes in ascending order.
+fn rule(kind: Kind, n: usize) -> GaussRule {
+    let weights = kind.weights(n);
+    let (nodes, weights) = kind
+        .indices(n)
+        .rev()
+        .map(|k| (kind.node(n, k), weights[k]))
+        .unzip();
+    GaussRule { nodes, weights }
+}
+
+// Refines the rule until two estimates agree, keeping the values at the
+// points shared with the coarser rule.
+fn refine<F>(
+    f: &F,
+    a: f64,
+    b: f64,
+    kind: Kind,
+    opts: &ClenshawCurtisOptions,
+) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    check_limits(a, b)?;
+    let centre = 0.5 * (a + b);
+    let half = 0.5 * (b - a);
+    let mut n = kind.initial();
+    let mut values: Vec<Option<f64>> = Vec::new();
+    let mut evaluations = 0;
+    let mut previous = None;
+    let mut level = 0;
+    while kind.indices(n).len() <= opts.max_evaluations {
+        // Index k for n / factor is index factor k (+ 1 for Fejér-1) for n
+        let factor = kind.factor();
+        let shift = if kind == Kind::Fejer1 { 1 } else { 0 };
+        let mut next = vec![None; n + 1];
+        for (k, value) in values.into_iter().enumerate() {
+            if value.is_some() {
+                next[factor * k + shift] = value;
+            }
+        }
+        values = next;
+        let weights = kind.weights(n);
+        let mut sum = 0.0;
+        for k in kind.indices(n) {
+            let fx = match values[k] {
+                Some(fx) => fx,
+                None => {
+                    evaluations += 1;
+                    eval(f, centre + half * kind.node(n, k))?
+                }
+            };
+            values[k] = Some(fx);
+            sum += weights[k] * fx;
+        }
+        let estimate = half * sum;
+        if let Some(previous) = previous
+            && level >= MIN_LEVEL
+            && is_close(estimate, previous, opts.tol).is_ok()
+        {
+            return Ok(Integral {
+                value: estimate,
+                error: (estimate - previous).abs(),
+                evaluations,
+            });
+        }
+        previous = Some(estimate);
+        n *= factor;
+        level += 1;
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+// Weights for parameter n by Waldvogel's inverse transforms, indexed by k;
+// the Fejér-2 weight at k = 0 is zero. Needs n >= 2 for the Clenshaw–Curtis
+// and Fejér-2 rules.
+fn waldvogel(kind: Kind, n: usize) -> Vec<f64> {
+    // l odd numbers below n and m = n - l
+    let l = n / 2;
+    let m = n - l;
+    let v: Vec<Complex> = match kind {
+        Kind::Fejer1 => {
+            let mut v0: Vec<Complex> = (0..m)
+                .map(|k| {
+                    let k = k as f64;
+                    Complex::from_polar(2.0 / (1.0 - 4.0 * k * k), PI * k / n as f64)
+                })
+                .collect();
+            v0.resize(n + 1, Complex::ZERO);
+            (0..n).map(|i| v0[i] + v0[n - i].conj()).collect()
+        }
+        Kind::ClenshawCurtis | Kind::Fejer2 => {
+            let mut v0: Vec<f64> = (0..l)
+                .map(|j| {
+                    let odd = (2 * j + 1) as f64;
+                    2.0 / (odd * (odd - 2.0))
+                })
+                .collect();
+            v0.push(1.0 / (2 * l - 1) as f64);
+            v0.resize(n + 1, 0.0);
+            let mut v2: Vec<f64> = (0..n).map(|i| -v0[i] - v0[n - i]).collect();
+            if kind == Kind::ClenshawCurtis {
+                let scale = 1.0 / (n * n - 1 + n % 2) as f64;
+                for g in v2.iter_mut() {
+                    *g -= scale;
+                }
+                v2[l] += n as f64 * scale;
+                v2[m] += n as f64 * scale;
+            }
+            v2.into_iter().map(Complex::from).collect()
+        }
+    };
+    fft(&v).into_iter().map(|z| z.re / n as f64).collect()
+}
+
+// Sums x_j e^(2 pi i j k / n) for each k by recursive mixed-radix
+// decimation in time, splitting off the smallest prime factor of n.
+fn fft(x: &[Complex]) -> Vec<Complex> {
+    let n = x.len();
+    if n <= 1 {
+        return x.to_vec();
+    }
+    let p = (2..=n).find(|&p| n.is_multiple_of(p)).unwrap_or(n);
+    let m = n / p;
+    let parts: Vec<Vec<Complex>> = (0..p)
+        .map(|r| fft(&x[r..].iter().step_by(p).copied().collect::<Vec<_>>()))
+        .collect();
+    (0..n)
+        .map(|k| {
+            let mut sum = Complex::ZERO;
+            for (r, part) in parts.iter().enumerate() {
+                let twiddle = 2.0 * PI * ((r * k) % n) as f64 / n as f64;
+                sum += part[k % m] * Complex::from_polar(1.0, twiddle);
+            }
+            sum
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Rule =
+        fn(fn(f64) -> f64, f64, f64, &ClenshawCurtisOptions) -> Result<Integral, NumalError>;
+
+    fn strict() -> ClenshawCurtisOptions {
+        ClenshawCurtisOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        is_close(a, b, Tolerance::Strict).is_ok()
+    }
+
+    #[test]
+    fn rules_are_exact_for_polynomials_of_their_degree() {
+        let r = clenshaw_curtis_rule(3).unwrap();
+        for (got, want) in r.weights.iter().zip([1.0 / 3.0, 4.0 / 3.0, 1.0 / 3.0]) {
+            assert!(close(*got, want), "{r:?}");
+        }
+        for points in [2, 5, 12, 33] {
+            let rules = [
+                clenshaw_curtis_rule(points).unwrap(),
+                fejer1_rule(points).unwrap(),
+                fejer2_rule(points).unwrap(),
+            ];
+            for r in rules {
+                assert_eq!(r.nodes.len(), points);
+                assert!(r.nodes.windows(2).all(|w| w[0] < w[1]));
+                for k in 0..points as i32 {
+                    let exact = if k % 2 == 1 {
+                        0.0
+                    } else {
+                        2.0 / (k + 1) as f64
+                    };
+                    assert!(close(r.sum(|x| x.powi(k)), exact), "{points} {k}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fft_weights_match_the_direct_sums() {
+        // Fejér-2 weights are 4 sin(t) / n * sum sin((2j - 1) t) / (2j - 1)
+        for n in [7, 12, 30] {
+            let w = waldvogel(Kind::Fejer2, n);
+            for (k, &wk) in w.iter().enumerate().skip(1) {
+                let t = PI * k as f64 / n as f64;
+                let sum: f64 = (1..=n / 2)
+                    .map(|j| ((2 * j - 1) as f64 * t).sin() / (2 * j - 1) as f64)
+                    .sum();
+                assert!(close(wk, 4.0 * t.sin() / n as f64 * sum), "{n} {k}");
+            }
+        }
+    }
+
+    #[test]
+    fn every_rule_integrates_smooth_functions() {
+        let runge: fn(f64) -> f64 = |x| 1.0 / (1.0 + 25.0 * x * x);
+        let exact = 0.4 * 5f64.atan();
+        let rules: [Rule; 3] = [clenshaw_curtis, fejer1, fejer2];
+        for rule in rules {
+            let r = rule(runge, -1.0, 1.0, &strict()).unwrap();
+            assert!((r.value - exact).abs() < 1e-12, "{r:?}");
+            let r = rule(f64::exp, 0.0, 1.0, &strict()).unwrap();
+            assert!((r.value - (1f64.exp() - 1.0)).abs() < 1e-13, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn accidental_agreement_is_not_accepted() {
+        // T_32 equals 1 at the Clenshaw–Curtis points for n = 8 and n = 16
+        let f = |x: f64| (32.0 * x.acos()).cos();
+        let r = clenshaw_curtis(f, -1.0, 1.0, &Default::default()).unwrap();
+        assert!((r.value + 2.0 / 1023.0).abs() < 1e-12, "{r:?}");
+    }
+
+    #[test]
+    fn refinement_reuses_every_evaluation() {
+        // Each integrand is evaluated once per point of the finest rule
+        let f = |x: f64| (3.0 * x).cos();
+        let r = clenshaw_curtis(f, 0.0, 2.0, &strict()).unwrap();
+        assert!((r.evaluations - 1).is_power_of_two(), "{r:?}");
+        let r = fejer2(f, 0.0, 2.0, &strict()).unwrap();
+        assert!((r.evaluations + 1).is_power_of_two(), "{r:?}");
+        let r = fejer1(f, 0.0, 2.0, &strict()).unwrap();
+        assert!([27, 81, 243].contains(&r.evaluations), "{r:?}");
+        assert!((r.value - 6f64.sin() / 3.0).abs() < 1e-12, "{r:?}");
+    }
+
+    #[test]
+    fn fejer_rules_avoid_the_limits() {
+        let f = |x: f64| 1.0 / x.sqrt();
+        let r = clenshaw_curtis(f, 0.0, 1.0, &Default::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let opts = ClenshawCurtisOptions {
+            tol: Tolerance::Loose,
+            ..Default::default()
+        };
+        for rule in [fejer1, fejer2] {
+            let r = rule(f, 0.0, 1.0, &opts).unwrap();
+            assert!((r.value - 2.0).abs() < 1e-3, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn reversed_limits_and_invalid_inputs() {
+        let r = clenshaw_curtis(f64::sin, PI, 0.0, &Default::default()).unwrap();
+        assert!((r.value + 2.0).abs() < 1e-8, "{r:?}");
+        let r = fejer1(f64::exp, 0.0, f64::INFINITY, &Default::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        assert!(matches!(
+            clenshaw_curtis_rule(1),
+            Err(NumalError::InvalidInput(_))
+        ));
+        assert!(matches!(fejer2_rule(0), Err(NumalError::InvalidInput(_))));
+    }
+}
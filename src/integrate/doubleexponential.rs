@@ -0,0 +1,395 @@
+//! Double-exponential quadrature (Takahasi and Mori, 1974).
+//!
+//! Each method substitutes `x = phi(t)` so that the transformed integrand
+//! decays double exponentially as `|t|` grows, and then applies the
+//! trapezoid rule in `t`, which converges exponentially for such
+//! integrands. The tanh-sinh rule `phi(t) = tanh(pi/2 sinh t)` maps a finite
+//! interval and tolerates integrable singularities at both limits, the
+//! exp-sinh rule `phi(t) = exp(pi/2 sinh t)` maps a half-infinite range and
+//! the sinh-sinh rule `phi(t) = sinh(pi/2 sinh t)` the whole real line.
+//!
+//! The step is halved from level to level until successive estimates agree,
+//! so that each level only adds the nodes at odd multiples of the new step.
+//! The nodes of each rule are computed once, on first use, and shared by all
+//! later calls.
+//!
+//! Near a finite limit the nodes crowd together closer than `x - a` can
+//! resolve, so [`tanh_sinh`] and [`exp_sinh`] pass the integrand the
+//! distance from `x` to the nearer finite limit, computed from the
+//! transform without cancellation. Where `x` rounds onto the limit only
+//! the distance tells the node apart, and the node is skipped unless the
+//! integrand is finite there.
+
+use super::{Integral, check_limits, eval};
+use crate::NumalError;
+use crate::core::tolerance::{Tolerance, is_close};
+use std::f64::consts::FRAC_PI_2;
+use std::sync::{Arc, Mutex, PoisonError};
+
+// Level of the first estimate tested for convergence.
+const MIN_LEVEL: usize = 3;
+
+// Nodes of the three rules. The ranges of t keep the abscissae and their
+// distances to the limits within the range of f64.
+static TANH_SINH: Abscissae = Abscissae::new(tanh_sinh_nodes, 6.1);
+static EXP_SINH: Abscissae = Abscissae::new(exp_sinh_nodes, 6.7);
+static SINH_SINH: Abscissae = Abscissae::new(sinh_sinh_nodes, 6.7);
+
+/// Options for [`tanh_sinh`], [`exp_sinh`] and [`sinh_sinh`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DoubleExponentialOptions {
+    /// Tolerance between successive estimates; see the
+    /// [module documentation](super)
+    pub tol: Tolerance,
+    /// Maximum number of step halvings
+    pub max_levels: usize,
+}
+
+impl Default for DoubleExponentialOptions {
+    fn default() -> Self {
+        DoubleExponentialOptions {
+            tol: Tolerance::Default,
+            max_levels: 12,
+        }
+    }
+}
+
+/// Integrates `f` over the finite interval `[a, b]` by the tanh-sinh rule.
+///
+/// The integrand is called as `f(x, d)`, where `d` is the distance from `x`
+/// to the nearer of the limits, accurate to working precision even where
+/// `x - a` or `b - x` would cancel. Suited to integrands with algebraic or
+/// logarithmic singularities at the limits. Returns
+/// [`NumalError::DidNotConverge`] if the estimates are not within tolerance
+/// after `max_levels` halvings.
+pub fn tanh_sinh<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    opts: &DoubleExponentialOptions,
+) -> Result<Integral, NumalError>
+where
+    F: Fn(f64, f64) -> f64,
+{
+    check_limits(a, b)?;
+    let half = 0.5 * (b - a);
+    refine(&TANH_SINH, opts, |node| {
+        // Measure from the limit the node approaches
+        let x = if node.x < 0.0 {
+            a + half * node.xc
+        } else {
+            b - half * node.xc
+        };
+        let d = half.abs() * node.xc;
+        if x == a || x == b {
+            return Ok(Some(node.w * half * at_limit(f(x, d))));
+        }
+        Ok(Some(node.w * half * eval(&|x| f(x, d), x)?))
+    })
+}
+
+/// Integrates `f` over a half-infinite range by the exp-sinh rule.
+///
+/// Exactly one of `a` and `b` must be infinite. The integrand is called as
+/// `f(x, d)`, where `d` is the distance from `x` to the finite limit. Suited
+/// to integrands that decay at infinity at least algebraically, including
+/// those singular at the finite limit. Returns
+/// [`NumalError::DidNotConverge`] if the estimates are not within tolerance
+/// after `max_levels` halvings.
+pub fn exp_sinh<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    opts: &DoubleExponentialOptions,
+) -> Result<Integral, NumalError>
+where
+    F: Fn(f64, f64) -> f64,
+{
+    if a.is_nan() || b.is_nan() {
+        return Err(NumalError::InvalidInput(format!(
+            "limits must not be NaN, got [{a}, {b}]"
+        )));
+    }
+    let (lo, hi, sign) = if a <= b { (a, b, 1.0) } else { (b, a, -1.0) };
+    // `limit + direction * d` runs from the finite limit towards infinity
+    let (limit, direction) = match (lo.is_finite(), hi.is_finite()) {
+        (true, false) => (lo, 1.0),
+        (false, true) => (hi, -1.0),
+        _ => {
+            return Err(NumalError::InvalidInput(format!(
+                "exactly one limit must be infinite, got [{a}, {b}]"
+            )));
+        }
+    };
+    refine(&EXP_SINH, opts, |node| {
+        let x = limit + direction * node.x;
+        if !x.is_finite() {
+            return Ok(None);
+        }
+        if x == limit {
+            return Ok(Some(sign * node.w * at_limit(f(x, node.x))));
+        }
+        Ok(Some(sign * node.w * eval(&|x| f(x, node.x), x)?))
+    })
+}
+
+/// Integrates `f` over the whole real line by the sinh-sinh rule.
+///
+/// Suited to integrands that decay at least algebraically in both
+/// directions. Returns [`NumalError::DidNotConverge`] if the estimates are
+/// not within tolerance after `max_levels` halvings.
+pub fn sinh_sinh<F>(f: F, opts: &DoubleExponentialOptions) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    refine(&SINH_SINH, opts, |node| {
+        if !node.x.is_finite() {
+            return Ok(None);
+        }
+        Ok(Some(node.w * eval(&f, node.x)?))
+    })
+}
+
+// Drops the value at a node that rounds onto a limit unless finite, as an
+// integrand that ignores the distance cannot resolve the node.
+fn at_limit(fx: f64) -> f64 {
+    if fx.is_finite() { fx } else { 0.0 }
+}
+
+// Sums the weighted terms over the nodes of successive levels, scaling by
+// the step, until two estimates agree. `term` returns `None` for a node it
+// skips without calling the integrand.
+fn refine<T>(
+    abscissae: &Abscissae,
+    opts: &DoubleExponentialOptions,
+    term: T,
+) -> Result<Integral, NumalError>
+where
+    T: Fn(&Node) -> Result<Option<f64>, NumalError>,
+{
+    let mut sum = 0.0;
+    let mut evaluations = 0;
+    let mut estimate = 0.0;
+    for level in 0..=opts.max_levels {
+        for node in abscissae.level(level).iter() {
+            if let Some(value) = term(node)? {
+                sum += value;
+                evaluations += 1;
+            }
+        }
+        let previous = estimate;
+        estimate = sum * 0.5f64.powi(level as i32);
+        if level >= MIN_LEVEL && is_close(estimate, previous, opts.tol).is_ok() {
+            return Ok(Integral {
+                value: estimate,
+                error: (estimate - previous).abs(),
+                evaluations,
+            });
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+// A node of a rule: the abscissa, its distance to the limit it approaches
+// and the weight `phi'(t)`.
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    x: f64,
+    xc: f64,
+    w: f64,
+}
+
+// The nodes of a rule level by level for `0 <= |t| <= t_max`, computed on
+// demand. Level 0 has unit step and level `k` adds the odd multiples of
+// `2^-k`.
+struct Abscissae {
+    nodes: fn(f64) -> [Node; 2],
+    t_max: f64,
+    levels: Mutex<Vec<Arc<[Node]>>>,
+}
+
+impl Abscissae {
+    const fn new(nodes: fn(f64) -> [Node; 2], t_max: f64) -> Self {
+        Abscissae {
+            nodes,
+            t_max,
+            levels: Mutex::new(Vec::new()),
+        }
+    }
+
+    // Nodes added at `level`, computing any levels not yet cached.
+    fn level(&self, level: usize) -> Arc<[Node]> {
+        let mut levels = self.levels.lock().unwrap_or_else(PoisonError::into_inner);
+        while levels.len() <= level {
+            let k = levels.len();
+            let h = 0.5f64.powi(k as i32);
+            let mut nodes = Vec::new();
+            let (mut t, stride) = if k == 0 { (0.0, 1.0) } else { (h, 2.0 * h) };
+            while t <= self.t_max {
+                let [right, left] = (self.nodes)(t);
+                nodes.push(right);
+                if t > 0.0 {
+                    nodes.push(left);
+                }
+                t += stride;
+            }
+            levels.push(nodes.into());
+        }
+        Arc::clone(&levels[level])
+    }
+}
+
+// Nodes of the tanh-sinh rule on [-1, 1] at t and -t, with 1 - |x| found
+// as 1 / (e^u cosh u) rather than by subtraction.
+fn tanh_sinh_nodes(t: f64) -> [Node; 2] {
+    let u = FRAC_PI_2 * t.sinh();
+    let cosh_u = u.cosh();
+    let x = u.tanh();
+    let xc = 1.0 / (u.exp() * cosh_u);
+    let w = FRAC_PI_2 * t.cosh() / (cosh_u * cosh_u);
+    [Node { x, xc, w }, Node { x: -x, xc, w }]
+}
+
+// Nodes of the exp-sinh rule on [0, inf) at t and -t; x is its own
+// distance to the limit.
+fn exp_sinh_nodes(t: f64) -> [Node; 2] {
+    let u = FRAC_PI_2 * t.sinh();
+    let scale = FRAC_PI_2 * t.cosh();
+    let (x, y) = (u.exp(), (-u).exp());
+    [
+        Node {
+            x,
+            xc: x,
+            w: scale * x,
+        },
+        Node {
+            x: y,
+            xc: y,
+            w: scale * y,
+        },
+    ]
+}
+
+// Nodes of the sinh-sinh rule on the real line at t and -t.
+fn sinh_sinh_nodes(t: f64) -> [Node; 2] {
+    let u = FRAC_PI_2 * t.sinh();
+    let x = u.sinh();
+    let w = FRAC_PI_2 * t.cosh() * u.cosh();
+    [Node { x, xc: x, w }, Node { x: -x, xc: x, w }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::f64::consts::PI;
+
+    fn strict() -> DoubleExponentialOptions {
+        DoubleExponentialOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tanh_sinh_handles_endpoint_singularities() {
+        let r = tanh_sinh(|x, _| 1.0 / x.sqrt(), 0.0, 1.0, &strict()).unwrap();
+        assert!((r.value - 2.0).abs() < 1e-12, "{r:?}");
+        let r = tanh_sinh(|x: f64, _| x.ln(), 0.0, 1.0, &strict()).unwrap();
+        assert!((r.value + 1.0).abs() < 1e-12, "{r:?}");
+        let r = tanh_sinh(|x: f64, _| x.exp(), 0.0, 1.0, &strict()).unwrap();
+        assert!((r.value - (1f64.exp() - 1.0)).abs() < 1e-13, "{r:?}");
+    }
+
+    #[test]
+    fn evaluations_count_every_call() {
+        // Infinite at the nodes that round onto 1, which are dropped
+        let calls = Cell::new(0);
+        let f = |x: f64, _| {
+            calls.set(calls.get() + 1);
+            1.0 / (x - 1.0).sqrt()
+        };
+        let r = tanh_sinh(f, 1.0, 2.0, &Default::default()).unwrap();
+        assert_eq!(r.evaluations, calls.get());
+    }
+
+    #[test]
+    fn distance_to_the_limit_avoids_cancellation() {
+        // 1 / sqrt(1 - x^2) with 1 - x^2 = d (2 - d) near either limit
+        let f = |_, d: f64| 1.0 / (d * (2.0 - d)).sqrt();
+        let r = tanh_sinh(f, -1.0, 1.0, &strict()).unwrap();
+        assert!((r.value - PI).abs() < 1e-13, "{r:?}");
+        // Far from the origin, 1002 - x itself would cancel near 1002
+        let f = |x: f64, d: f64| 1.0 / if x > 1001.0 { d } else { 1002.0 - x }.sqrt();
+        let r = tanh_sinh(f, 1e3, 1e3 + 2.0, &strict()).unwrap();
+        assert!((r.value - 8f64.sqrt()).abs() < 1e-12, "{r:?}");
+        let naive = tanh_sinh(|x, _| 1.0 / (1002.0 - x).sqrt(), 1e3, 1e3 + 2.0, &strict());
+        assert!(naive.is_err() || (naive.unwrap().value - 8f64.sqrt()).abs() > 1e-10);
+    }
+
+    #[test]
+    fn exp_sinh_integrates_half_infinite_ranges() {
+        let r = exp_sinh(|x: f64, _| (-x).exp(), 0.0, f64::INFINITY, &strict()).unwrap();
+        assert!((r.value - 1.0).abs() < 1e-12, "{r:?}");
+        let f = |x: f64, _| 1.0 / (1.0 + x * x);
+        let r = exp_sinh(f, f64::NEG_INFINITY, 0.0, &strict()).unwrap();
+        assert!((r.value - PI / 2.0).abs() < 1e-12, "{r:?}");
+        // Singular at the finite limit as well
+        let r = exp_sinh(
+            |_, d: f64| (-d).exp() / d.sqrt(),
+            1.0,
+            f64::INFINITY,
+            &strict(),
+        );
+        let r = r.unwrap();
+        assert!((r.value - PI.sqrt()).abs() < 1e-12, "{r:?}");
+        let r = exp_sinh(|x: f64, _| x.exp(), 0.0, f64::NEG_INFINITY, &strict()).unwrap();
+        assert!((r.value + 1.0).abs() < 1e-12, "{r:?}");
+    }
+
+    #[test]
+    fn sinh_sinh_integrates_the_real_line() {
+        let r = sinh_sinh(|x: f64| (-x * x).exp(), &strict()).unwrap();
+        assert!((r.value - PI.sqrt()).abs() < 1e-12, "{r:?}");
+        let r = sinh_sinh(|x: f64| 1.0 / (1.0 + x * x), &strict()).unwrap();
+        assert!((r.value - PI).abs() < 1e-12, "{r:?}");
+    }
+
+    #[test]
+    fn abscissae_are_cached_across_calls() {
+        let f = |x: f64, _| x.cos();
+        let first = tanh_sinh(f, 0.0, 1.0, &strict()).unwrap();
+        let cached = TANH_SINH.level(2);
+        let second = tanh_sinh(f, 0.0, 1.0, &strict()).unwrap();
+        assert_eq!(first, second);
+        assert!(Arc::ptr_eq(&cached, &TANH_SINH.level(2)));
+        // Level k > 0 holds the odd multiples of 2^-k on both sides
+        assert_eq!(cached.len(), 2 * 12);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let f = |x: f64, _| x;
+        let r = tanh_sinh(f, 0.0, f64::INFINITY, &Default::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = exp_sinh(f, 0.0, 1.0, &Default::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = exp_sinh(f, f64::NEG_INFINITY, f64::INFINITY, &Default::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let f = |x: f64, _| (-x.abs()).exp();
+        let r = exp_sinh(f, 0.0, f64::NAN, &Default::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = exp_sinh(f, f64::NAN, 0.0, &Default::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn exhausted_levels_did_not_converge() {
+        let opts = DoubleExponentialOptions {
+            max_levels: 4,
+            ..strict()
+        };
+        let r = sinh_sinh(|x: f64| x.sin().powi(2) / (1.0 + x * x), &opts);
+        assert_eq!(r, Err(NumalError::DidNotConverge));
+    }
+}
@@ -0,0 +1,590 @@
+//! Nodes and weights of Gaussian quadrature for the classical weights.
+//!
+//! An `n`-point Gauss rule for a weight `w` on an interval integrates
+//! `w p` exactly for every polynomial `p` of degree below `2n`. Its nodes are
+//! the eigenvalues of the symmetric tridiagonal Jacobi matrix built from the
+//! three-term recurrence of the orthogonal polynomials for `w`, and its
+//! weights are `mu0` times the squared first components of the normalized
+//! eigenvectors, with `mu0` the integral of `w` (Golub and Welsch, 1969).
+//! The eigenproblem is solved by the implicit QL method, which costs
+//! `O(n^2)`.
+//!
+//! Gauss–Legendre rules with more than 100 points follow Hale and Townsend
+//! (2013) instead: each node is found in `O(1)` by Newton's method on
+//! `theta = acos x`, started from Tricomi's interior expansion or, near the
+//! ends, from the Bessel-function expansion of Gatteschi, and evaluating
+//! `P_n(cos theta)` by Stieltjes' asymptotic series. The few
+//! nodes too near `x = +-1` for that series use the three-term recurrence.
+//! Bogaert (2014) avoids the Newton step with expansions accurate to double
+//! precision, but it would save only a constant factor: from these starting
+//! values Newton's method needs a few iterations per node, so both methods
+//! cost `O(n)` for the rule and agree to rounding, and only one is provided.
+//!
+//! Gauss–Lobatto and Gauss–Radau rules for the Legendre weight fix both
+//! ends or the left end of `[-1, 1]` as nodes, and their free nodes are
+//! those of Gauss–Jacobi rules.
+
+use super::check_points;
+use crate::NumalError;
+use crate::core::special::ln_gamma;
+use std::f64::consts::PI;
+
+// Gauss–Legendre rules with more points use the asymptotic method.
+const ASYMPTOTIC_MIN: usize = 100;
+
+// Stieltjes' series is used where 2 n sin(theta) exceeds this, leaving about
+// ten nodes at each end to the recurrence.
+const STIELTJES_MIN: f64 = 60.0;
+
+// Sweeps of the QL method allowed per eigenvalue.
+const MAX_QL_SWEEPS: usize = 60;
+
+/// Nodes and weights of a quadrature rule, in ascending order of node
+#[derive(Clone, Debug, PartialEq)]
+pub struct GaussRule {
+    /// Abscissae
+    pub nodes: Vec<f64>,
+    /// Weights, with the weight function folded in
+    pub weights: Vec<f64>,
+}
+
+impl GaussRule {
+    /// Applies the rule to `f`, approximating the integral of `w f`.
+    pub fn sum<F: Fn(f64) -> f64>(&self, f: F) -> f64 {
+        self.nodes
+            .iter()
+            .zip(&self.weights)
+            .map(|(&x, &w)| w * f(x))
+            .sum()
+    }
+}
+
+/// Kind of Chebyshev weight on `[-1, 1]`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChebyshevKind {
+    /// `1 / sqrt(1 - x^2)`
+    First,
+    /// `sqrt(1 - x^2)`
+    Second,
+}
+
+/// Gauss–Legendre rule for the weight 1 on `[-1, 1]`.
+pub fn gauss_legendre(n: usize) -> Result<GaussRule, NumalError> {
+    check_points(n, 1)?;
+    if n > ASYMPTOTIC_MIN {
+        return Ok(legendre_asymptotic(n));
+    }
+    let diagonal = vec![0.0; n];
+    let off: Vec<f64> = (1..n)
+        .map(|i| {
+            let i = i as f64;
+            i / (4.0 * i * i - 1.0).sqrt()
+        })
+        .collect();
+    Ok(symmetrize(golub_welsch(diagonal, &off, 2.0)?))
+}
+
+/// Gauss–Jacobi rule for the weight `(1 - x)^alpha (1 + x)^beta` on
+/// `[-1, 1]`, with `alpha, beta > -1`.
+pub fn gauss_jacobi(n: usize, alpha: f64, beta: f64) -> Result<GaussRule, NumalError> {
+    check_points(n, 1)?;
+    check_exponent("alpha", alpha)?;
+    check_exponent("beta", beta)?;
+    let ab = alpha + beta;
+    let diagonal: Vec<f64> = (0..n)
+        .map(|i| {
+            let s = 2.0 * i as f64 + ab;
+            if i == 0 {
+                // The general form is 0 / 0 when alpha + beta = 0
+                (beta - alpha) / (ab + 2.0)
+            } else {
+                (beta * beta - alpha * alpha) / (s * (s + 2.0))
+            }
+        })
+        .collect();
+    let off: Vec<f64> = (1..n)
+        .map(|i| {
+            let k = i as f64;
+            let s = 2.0 * k + ab;
+            if i == 1 {
+                // The general form is 0 / 0 when alpha + beta = -1
+                (4.0 * (1.0 + alpha) * (1.0 + beta) / ((s * s) * (s + 1.0))).sqrt()
+            } else {
+                (4.0 * k * (k + alpha) * (k + beta) * (k + ab) / (s * s * (s + 1.0) * (s - 1.0)))
+                    .sqrt()
+            }
+        })
+        .collect();
+    let mu0 = ((ab + 1.0) * 2f64.ln() + ln_gamma(alpha + 1.0) + ln_gamma(beta + 1.0)
+        - ln_gamma(ab + 2.0))
+    .exp();
+    let rule = golub_welsch(diagonal, &off, mu0)?;
+    Ok(if alpha == beta {
+        symmetrize(rule)
+    } else {
+        rule
+    })
+}
+
+/// Gauss–Laguerre rule for the weight `x^alpha e^-x` on `[0, inf)`, with
+/// `alpha > -1`.
+pub fn gauss_laguerre(n: usize, alpha: f64) -> Result<GaussRule, NumalError> {
+    check_points(n, 1)?;
+    check_exponent("alpha", alpha)?;
+    let diagonal: Vec<f64> = (0..n).map(|i| 2.0 * i as f64 + alpha + 1.0).collect();
+    let off: Vec<f64> = (1..n)
+        .map(|i| {
+            let i = i as f64;
+            (i * (i + alpha)).sqrt()
+        })
+        .collect();
+    golub_welsch(diagonal, &off, ln_gamma(alpha + 1.0).exp())
+}
+
+/// Gauss–Hermite rule for the weight `e^(-x^2)` on the real line.
+pub fn gauss_hermite(n: usize) -> Result<GaussRule, NumalError> {
+    check_points(n, 1)?;
+    let off: Vec<f64> = (1..n).map(|i| (0.5 * i as f64).sqrt()).collect();
+    Ok(symmetrize(golub_welsch(vec![0.0; n], &off, PI.sqrt())?))
+}
+
+/// Gauss–Chebyshev rule of the first or second kind on `[-1, 1]`, from
+/// closed forms.
+pub fn gauss_chebyshev(n: usize, kind: ChebyshevKind) -> Result<GaussRule, NumalError> {
+    check_points(n, 1)?;
+    let m = n as f64;
+    let (nodes, weights) = (1..=n)
+        .rev()
+        .map(|k| {
+            let k = k as f64;
+            match kind {
+                ChebyshevKind::First => ((PI * (2.0 * k - 1.0) / (2.0 * m)).cos(), PI / m),
+                ChebyshevKind::Second => {
+                    let theta = PI * k / (m + 1.0);
+                    (theta.cos(), PI / (m + 1.0) * theta.sin().powi(2))
+                }
+            }
+        })
+        .unzip();
+    Ok(GaussRule { nodes, weights })
+}
+
+/// Gauss–Lobatto rule for the weight 1 on `[-1, 1]`, with both ends among
+/// its `n >= 2` nodes; exact for polynomials of degree below `2n - 2`.
+pub fn gauss_lobatto(n: usize) -> Result<GaussRule, NumalError> {
+    check_points(n, 2)?;
+    // The interior nodes are the zeros of P'_{n-1}
+    let interior = if n > 2 {
+        gauss_jacobi(n - 2, 1.0, 1.0)?.nodes
+    } else {
+        Vec::new()
+    };
+    let mut nodes = vec![-1.0];
+    nodes.extend(interior);
+    nodes.push(1.0);
+    let scale = 2.0 / (n * (n - 1)) as f64;
+    let weights = nodes
+        .iter()
+        .map(|&x| scale / legendre(n - 1, x).0.powi(2))
+        .collect();
+    Ok(GaussRule { nodes, weights })
+}
+
+/// Gauss–Radau rule for the weight 1 on `[-1, 1]`, with the left end among
+/// its `n` nodes; exact for polynomials of degree below `2n - 1`.
+pub fn gauss_radau(n: usize) -> Result<GaussRule, NumalError> {
+    check_points(n, 1)?;
+    // The free nodes are the zeros of (P_{n-1} + P_n) / (1 + x)
+    let free = if n > 1 {
+        gauss_jacobi(n - 1, 0.0, 1.0)?.nodes
+    } else {
+        Vec::new()
+    };
+    let mut nodes = vec![-1.0];
+    nodes.extend(free);
+    let m = n as f64;
+    let weights = nodes
+        .iter()
+        .map(|&x| {
+            if x == -1.0 {
+                2.0 / (m * m)
+            } else {
+                (1.0 - x) / (m * m * legendre(n - 1, x).0.powi(2))
+            }
+        })
+        .collect();
+    Ok(GaussRule { nodes, weights })
+}
+
+// Validates an exponent of the weight, which must keep it integrable.
+fn check_exponent(name: &str, value: f64) -> Result<(), NumalError> {
+    if !(value > -1.0 && value.is_finite()) {
+        return Err(NumalError::InvalidInput(format!(
+            "{name} must be finite and greater than -1, got {value}"
+        )));
+    }
+    Ok(())
+}
+
+// Builds the rule from the Jacobi matrix with the given diagonal and
+// off-diagonal and the integral `mu0` of the weight.
+fn golub_welsch(diagonal: Vec<f64>, off: &[f64], mu0: f64) -> Result<GaussRule, NumalError> {
+    let (values, first) = tridiagonal_eigen(diagonal, off)?;
+    let mut pairs: Vec<(f64, f64)> = values
+        .into_iter()
+        .zip(first)
+        .map(|(x, z)| (x, mu0 * z * z))
+        .collect();
+    pairs.sort_by(|p, q| p.0.total_cmp(&q.0));
+    let (nodes, weights) = pairs.into_iter().unzip();
+    Ok(GaussRule { nodes, weights })
+}
+
+// Averages the mirrored nodes and weights of a rule for an even weight, so
+// that odd integrands sum to zero exactly.
+fn symmetrize(mut rule: GaussRule) -> GaussRule {
+    let n = rule.nodes.len();
+    for i in 0..n / 2 {
+        let j = n - 1 - i;
+        let x = 0.5 * (rule.nodes[j] - rule.nodes[i]);
+        let w = 0.5 * (rule.weights[i] + rule.weights[j]);
+        (rule.nodes[i], rule.nodes[j]) = (-x, x);
+        (rule.weights[i], rule.weights[j]) = (w, w);
+    }
+    if n % 2 == 1 {
+        rule.nodes[n / 2] = 0.0;
+    }
+    rule
+}
+
+// Eigenvalues of the symmetric tridiagonal matrix with diagonal `d` and
+// off-diagonal `off`, with the first components of the normalized
+// eigenvectors, by the implicit QL method with Wilkinson shifts.
+fn tridiagonal_eigen(mut d: Vec<f64>, off: &[f64]) -> Result<(Vec<f64>, Vec<f64>), NumalError> {
+    let n = d.len();
+    let mut e = off.to_vec();
+    e.push(0.0);
+    let mut z = vec![0.0; n];
+    z[0] = 1.0;
+    for l in 0..n {
+        let mut sweeps = 0;
+        loop {
+            // Find the first negligible off-diagonal element from l on
+            let mut m = l;
+            while m + 1 < n && e[m].abs() > f64::EPSILON * (d[m].abs() + d[m + 1].abs()) {
+                m += 1;
+            }
+            if m == l {
+                break;
+            }
+            sweeps += 1;
+            if sweeps > MAX_QL_SWEEPS {
+                return Err(NumalError::DidNotConverge);
+            }
+            let mut g = (d[l + 1] - d[l]) / (2.0 * e[l]);
+            let mut r = g.hypot(1.0);
+            g = d[m] - d[l] + e[l] / (g + r.copysign(g));
+            let (mut s, mut c, mut p) = (1.0, 1.0, 0.0);
+            let mut deflated = false;
+            for i in (l..m).rev() {
+                let f = s * e[i];
+                let b = c * e[i];
+                r = f.hypot(g);
+                e[i + 1] = r;
+                if r == 0.0 {
+                    // Underflow: split the matrix and sweep again
+                    d[i + 1] -= p;
+                    e[m] = 0.0;
+                    deflated = true;
+                    break;
+                }
+                s = f / r;
+                c = g / r;
+                g = d[i + 1] - p;
+                r = (d[i] - g) * s + 2.0 * c * b;
+                p = s * r;
+                d[i + 1] = g + p;
+                g = c * r - b;
+                let zf = z[i + 1];
+                z[i + 1] = s * z[i] + c * zf;
+                z[i] = c * z[i] - s * zf;
+            }
+            if !deflated {
+                d[l] -= p;
+                e[l] = g;
+                e[m] = 0.0;
+            }
+        }
+    }
+    Ok((d, z))
+}
+
+// Gauss–Legendre rule by the method of Hale and Townsend, computing the
+// nodes in (0, pi/2] of theta and reflecting them.
+fn legendre_asymptotic(n: usize) -> GaussRule {
+    let m = n as f64;
+    let v = m + 0.5;
+    // Scale of Stieltjes' series, 2 / sqrt(pi) * Gamma(n + 1) / Gamma(n + 3/2)
+    let scale = 2.0 / PI.sqrt() * (ln_gamma(m + 1.0) - ln_gamma(m + 1.5)).exp();
+    let half = n.div_ceil(2);
+    let mut nodes = vec![0.0; n];
+    let mut weights = vec![0.0; n];
+    for k in 1..=half {
+        let kf = k as f64;
+        let mut theta = if kf < 0.1 * m {
+            // Gatteschi's expansion about the k-th zero of J_0
+            let j = bessel_j0_zero(k);
+            let alpha = j / v;
+            alpha + (alpha / alpha.tan() - 1.0) / (8.0 * v * v * alpha)
+        } else {
+            // Tricomi's expansion
+            let phi = (4.0 * kf - 1.0) * PI / (4.0 * m + 2.0);
+            let s = phi.sin();
+            let x = (1.0
+                - (m - 1.0) / (8.0 * m * m * m)
+                - (39.0 - 28.0 / (s * s)) / (384.0 * m * m * m * m))
+                * phi.cos();
+            x.acos()
+        };
+        let mut derivative = 0.0;
+        for _ in 0..10 {
+            let (p, dp) = if 2.0 * m * theta.sin() >= STIELTJES_MIN {
+                stieltjes(n, theta, scale)
+            } else {
+                legendre_theta(n, theta)
+            };
+            derivative = dp;
+            let step = p / dp;
+            theta -= step;
+            if step.abs() <= 2.0 * f64::EPSILON * theta {
+                break;
+            }
+        }
+        // Weights are 2 / (dP/dtheta)^2 at the node
+        let w = 2.0 / (derivative * derivative);
+        let x = theta.cos();
+        nodes[n - k] = x;
+        nodes[k - 1] = -x;
+        weights[n - k] = w;
+        weights[k - 1] = w;
+    }
+    GaussRule { nodes, weights }
+}
+
+// P_n(cos theta) and its derivative in theta by Stieltjes' series
+// P_n = scale * sum h_m cos(a_m) / (2 sin theta)^(m + 1/2), with
+// a_m = (n + m + 1/2) theta - (m + 1/2) pi / 2.
+fn stieltjes(n: usize, theta: f64, scale: f64) -> (f64, f64) {
+    let m = n as f64;
+    let (sin, cos) = theta.sin_cos();
+    let cot = cos / sin;
+    let base = 2.0 * sin;
+    let (mut p, mut dp) = (0.0, 0.0);
+    let first = 1.0 / base.sqrt();
+    let mut h = first;
+    for j in 0..40 {
+        let jf = j as f64;
+        let a = (m + jf + 0.5) * theta - (jf + 0.5) * 0.5 * PI;
+        let (sin_a, cos_a) = a.sin_cos();
+        p += h * cos_a;
+        dp -= h * ((m + jf + 0.5) * sin_a + (jf + 0.5) * cot * cos_a);
+        if h <= f64::EPSILON * first {
+            break;
+        }
+        h *= (jf + 0.5) * (jf + 0.5) / ((jf + 1.0) * (m + jf + 1.5) * base);
+    }
+    (scale * p, scale * dp)
+}
+
+// P_n(cos theta) and its derivative in theta by the three-term recurrence.
+fn legendre_theta(n: usize, theta: f64) -> (f64, f64) {
+    let (sin, x) = theta.sin_cos();
+    let (p, previous) = legendre(n, x);
+    (p, n as f64 * (x * p - previous) / sin)
+}
+
+// P_n(x) and P_{n-1}(x) by the three-term recurrence.
+fn legendre(n: usize, x: f64) -> (f64, f64) {
+    let (mut previous, mut p) = (0.0, 1.0);
+    for k in 0..n {
+        let k = k as f64;
+        let next = ((2.0 * k + 1.0) * x * p - k * previous) / (k + 1.0);
+        previous = p;
+        p = next;
+    }
+    (p, previous)
+}
+
+// McMahon's expansion of the k-th positive zero of J_0.
+fn bessel_j0_zero(k: usize) -> f64 {
+    let b = (k as f64 - 0.25) * PI;
+    let b2 = 1.0 / (b * b);
+    b + (0.125 + b2 * (-31.0 / 384.0 + b2 * 3779.0 / 15360.0)) / b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::tolerance::{Tolerance, is_close};
+
+    fn close(a: f64, b: f64) -> bool {
+        is_close(a, b, Tolerance::Strict).is_ok()
+    }
+
+    // Checks that the rule reproduces the moments of the weight up to
+    // `degree`, relative to the moments of |x|^k that bound the rounding.
+    fn exact_to<M: Fn(i32) -> f64>(rule: &GaussRule, degree: i32, moment: M) {
+        for k in 0..=degree {
+            let sum = rule.sum(|x| x.powi(k));
+            let scale = rule.sum(|x| x.abs().powi(k)).max(1.0);
+            let want = moment(k);
+            assert!(close(sum / scale, want / scale), "x^{k}: {sum} vs {want}");
+        }
+    }
+
+    // Integral of x^k over [-1, 1].
+    fn legendre_moment(k: i32) -> f64 {
+        if k % 2 == 1 {
+            0.0
+        } else {
+            2.0 / (k + 1) as f64
+        }
+    }
+
+    #[test]
+    fn legendre_rules_are_exact_to_degree_2n_minus_1() {
+        let r = gauss_legendre(3).unwrap();
+        let x = 0.6f64.sqrt();
+        for (got, want) in r.nodes.iter().zip([-x, 0.0, x]) {
+            assert!(close(*got, want), "{r:?}");
+        }
+        for (got, want) in r.weights.iter().zip([5.0 / 9.0, 8.0 / 9.0, 5.0 / 9.0]) {
+            assert!(close(*got, want), "{r:?}");
+        }
+        for n in [1, 2, 7, 20] {
+            exact_to(
+                &gauss_legendre(n).unwrap(),
+                2 * n as i32 - 1,
+                legendre_moment,
+            );
+        }
+    }
+
+    #[test]
+    fn asymptotic_legendre_matches_golub_welsch() {
+        // Both methods at the crossover
+        let n = ASYMPTOTIC_MIN + 1;
+        let fast = legendre_asymptotic(n);
+        let off: Vec<f64> = (1..n)
+            .map(|i| i as f64 / (4.0 * (i * i) as f64 - 1.0).sqrt())
+            .collect();
+        let slow = golub_welsch(vec![0.0; n], &off, 2.0).unwrap();
+        for i in 0..n {
+            assert!(
+                close(fast.nodes[i], slow.nodes[i]),
+                "node {i} {} {}",
+                fast.nodes[i],
+                slow.nodes[i]
+            );
+            assert!(
+                close(fast.weights[i], slow.weights[i]),
+                "weight {i} {} {}",
+                fast.weights[i],
+                slow.weights[i]
+            );
+        }
+        // A rule too large for O(n^2) methods
+        let r = gauss_legendre(20_000).unwrap();
+        assert!(r.nodes.windows(2).all(|w| w[0] < w[1]));
+        assert!(close(r.sum(|_| 1.0), 2.0));
+        let exact = 2.0 * 1e3f64.sin() / 1e3;
+        assert!(close(r.sum(|x| (1e3 * x).cos()), exact));
+    }
+
+    #[test]
+    fn jacobi_rules_integrate_their_weight() {
+        // (1 - x)(1 + x)^2 x^k is a polynomial, integrated by Gauss–Legendre
+        let legendre = gauss_legendre(10).unwrap();
+        let r = gauss_jacobi(6, 1.0, 2.0).unwrap();
+        exact_to(&r, 11, |k| {
+            legendre.sum(|x| (1.0 - x) * (1.0 + x).powi(2) * x.powi(k))
+        });
+        // alpha = beta = 0 is the Legendre weight
+        let r = gauss_jacobi(8, 0.0, 0.0).unwrap();
+        assert_eq!(r.nodes.len(), 8);
+        exact_to(&r, 15, legendre_moment);
+        // The integral of (1 - x)^(-1/2) (1 + x)^(1/2) is pi
+        let r = gauss_jacobi(5, -0.5, 0.5).unwrap();
+        assert!(close(r.sum(|_| 1.0), PI));
+    }
+
+    #[test]
+    fn laguerre_and_hermite_moments() {
+        // Moments of x^alpha e^-x are Gamma(k + alpha + 1)
+        let alpha = 0.5;
+        let r = gauss_laguerre(8, alpha).unwrap();
+        exact_to(&r, 15, |k| ln_gamma(k as f64 + alpha + 1.0).exp());
+        let r = gauss_laguerre(12, 0.0).unwrap();
+        exact_to(&r, 23, |k| (1..=k).map(f64::from).product());
+        // Moments of e^(-x^2) are Gamma((k + 1) / 2) for even k
+        let r = gauss_hermite(10).unwrap();
+        exact_to(&r, 19, |k| {
+            if k % 2 == 1 {
+                0.0
+            } else {
+                ln_gamma(0.5 * (k + 1) as f64).exp()
+            }
+        });
+    }
+
+    #[test]
+    fn chebyshev_rules_match_their_moments() {
+        let r = gauss_chebyshev(6, ChebyshevKind::First).unwrap();
+        assert!(r.nodes.windows(2).all(|w| w[0] < w[1]));
+        exact_to(&r, 4, |k| match k {
+            0 => PI,
+            2 => PI / 2.0,
+            4 => 3.0 * PI / 8.0,
+            _ => 0.0,
+        });
+        let r = gauss_chebyshev(6, ChebyshevKind::Second).unwrap();
+        exact_to(&r, 4, |k| match k {
+            0 => PI / 2.0,
+            2 => PI / 8.0,
+            4 => PI / 16.0,
+            _ => 0.0,
+        });
+    }
+
+    #[test]
+    fn lobatto_and_radau_include_the_ends() {
+        let r = gauss_lobatto(5).unwrap();
+        let x = (3.0f64 / 7.0).sqrt();
+        for (got, want) in r.nodes.iter().zip([-1.0, -x, 0.0, x, 1.0]) {
+            assert!(close(*got, want), "{r:?}");
+        }
+        let w = [0.1, 49.0 / 90.0, 32.0 / 45.0, 49.0 / 90.0, 0.1];
+        for (got, want) in r.weights.iter().zip(w) {
+            assert!(close(*got, want), "{r:?}");
+        }
+        exact_to(&gauss_lobatto(2).unwrap(), 1, legendre_moment);
+        exact_to(&gauss_lobatto(9).unwrap(), 15, legendre_moment);
+        let r = gauss_radau(6).unwrap();
+        assert_eq!(r.nodes[0], -1.0);
+        exact_to(&r, 10, legendre_moment);
+        exact_to(&gauss_radau(1).unwrap(), 0, legendre_moment);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        assert!(matches!(
+            gauss_legendre(0),
+            Err(NumalError::InvalidInput(_))
+        ));
+        assert!(matches!(gauss_lobatto(1), Err(NumalError::InvalidInput(_))));
+        let r = gauss_jacobi(4, -1.0, 0.0);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = gauss_laguerre(4, f64::NAN);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,933 @@
+//! Adaptive Gauss–Kronrod quadrature after QUADPACK (Piessens et al., 1983).
+//!
+//! A Kronrod rule adds `n + 1` nodes to an `n`-point Gauss rule, raising its
+//! degree to `3n + 1` (Kronrod, 1964), and the difference between the two
+//! estimates gauges the error on an interval. As in QUADPACK that difference
+//! is rescaled by `(200 |K - G| / I)^1.5`, with `I` the integral of
+//! `|f - K / (b - a)|`, and floored at the rounding error of the rule.
+//!
+//! [`qag`] keeps the subintervals in a priority queue and bisects the one
+//! with the largest error until the errors sum to at most
+//! `max(eps_abs, eps_rel |value|)`. [`qags`] also extrapolates the sequence
+//! of estimates by Wynn's epsilon algorithm (Wynn, 1956), which converges
+//! for integrable singularities at or near the endpoints (de Doncker, 1978).
+//! Rounding errors that keep either from the requested accuracy are
+//! reported as [`NumalError::Roundoff`] and running out of subdivisions as
+//! [`NumalError::SubdivisionLimit`], both carrying the best estimate. An
+//! interval too narrow to bisect in floating point, which QUADPACK reports
+//! separately as bad integrand behaviour, is reported as `Roundoff` too: in
+//! both cases no further subdivision can improve the estimate.
+
+use super::{Integral, check_limits, eval};
+use crate::NumalError;
+use crate::core::tolerance::Tolerance;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+// Kronrod abscissae on [0, 1] in descending order ending with the centre;
+// the Gauss abscissae are those at odd positions.
+const XGK15: [f64; 8] = [
+    0.9914553711208126,
+    0.9491079123427585,
+    0.8648644233597691,
+    0.7415311855993945,
+    0.5860872354676911,
+    0.4058451513773972,
+    0.20778495500789848,
+    0.0,
+];
+const WGK15: [f64; 8] = [
+    0.022935322010529224,
+    0.06309209262997856,
+    0.10479001032225019,
+    0.14065325971552592,
+    0.1690047266392679,
+    0.19035057806478542,
+    0.20443294007529889,
+    0.20948214108472782,
+];
+const WG7: [f64; 4] = [
+    0.1294849661688697,
+    0.27970539148927664,
+    0.3818300505051189,
+    0.4179591836734694,
+];
+
+const XGK21: [f64; 11] = [
+    0.9956571630258081,
+    0.9739065285171717,
+    0.9301574913557082,
+    0.8650633666889845,
+    0.7808177265864169,
+    0.6794095682990244,
+    0.5627571346686047,
+    0.4333953941292472,
+    0.2943928627014602,
+    0.14887433898163122,
+    0.0,
+];
+const WGK21: [f64; 11] = [
+    0.011694638867371874,
+    0.032558162307964725,
+    0.054755896574351995,
+    0.07503967481091996,
+    0.0931254545836976,
+    0.10938715880229764,
+    0.12349197626206584,
+    0.13470921731147334,
+    0.14277593857706009,
+    0.14773910490133849,
+    0.1494455540029169,
+];
+const WG10: [f64; 5] = [
+    0.06667134430868814,
+    0.1494513491505806,
+    0.21908636251598204,
+    0.26926671930999635,
+    0.29552422471475287,
+];
+
+const XGK31: [f64; 16] = [
+    0.9980022986933971,
+    0.9879925180204854,
+    0.9677390756791391,
+    0.937273392400706,
+    0.8972645323440819,
+    0.8482065834104272,
+    0.790418501442466,
+    0.7244177313601701,
+    0.650996741297417,
+    0.5709721726085388,
+    0.4850818636402397,
+    0.3941513470775634,
+    0.29918000715316884,
+    0.20119409399743451,
+    0.1011420669187175,
+    0.0,
+];
+const WGK31: [f64; 16] = [
+    0.005377479872923349,
+    0.015007947329316122,
+    0.02546084732671532,
+    0.03534636079137585,
+    0.04458975132476488,
+    0.05348152469092809,
+    0.06200956780067064,
+    0.06985412131872826,
+    0.07684968075772038,
+    0.08308050282313302,
+    0.08856444305621176,
+    0.09312659817082532,
+    0.09664272698362368,
+    0.09917359872179196,
+    0.10076984552387559,
+    0.10133000701479154,
+];
+const WG15: [f64; 8] = [
+    0.03075324199611727,
+    0.07036604748810812,
+    0.10715922046717194,
+    0.13957067792615432,
+    0.16626920581699392,
+    0.1861610000155622,
+    0.19843148532711158,
+    0.2025782419255613,
+];
+
+const XGK61: [f64; 31] = [
+    0.9994844100504906,
+    0.9968934840746495,
+    0.9916309968704046,
+    0.9836681232797472,
+    0.9731163225011262,
+    0.9600218649683075,
+    0.94437444474856,
+    0.9262000474292743,
+    0.9055733076999078,
+    0.8825605357920527,
+    0.8572052335460612,
+    0.8295657623827684,
+    0.799727835821839,
+    0.7677774321048262,
+    0.7337900624532268,
+    0.6978504947933158,
+    0.6600610641266269,
+    0.6205261829892429,
+    0.5793452358263617,
+    0.5366241481420199,
+    0.49248046786177857,
+    0.44703376953808915,
+    0.4004012548303944,
+    0.3527047255308781,
+    0.30407320227362505,
+    0.25463692616788985,
+    0.20452511668230988,
+    0.15386991360858354,
+    0.10280693796673702,
+    0.0514718425553177,
+    0.0,
+];
+const WGK61: [f64; 31] = [
+    0.0013890136986770077,
+    0.003890461127099884,
+    0.0066307039159312926,
+    0.009273279659517764,
+    0.011823015253496341,
+    0.014369729507045804,
+    0.01692088918905327,
+    0.019414141193942382,
+    0.021828035821609193,
+    0.0241911620780806,
+    0.0265099548823331,
+    0.02875404876504129,
+    0.030907257562387762,
+    0.03298144705748372,
+    0.034979338028060025,
+    0.03688236465182123,
+    0.038678945624727595,
+    0.040374538951535956,
+    0.041969810215164244,
+    0.04345253970135607,
+    0.04481480013316266,
+    0.04605923827100699,
+    0.04718554656929915,
+    0.04818586175708713,
+    0.04905543455502978,
+    0.04979568342707421,
+    0.05040592140278235,
+    0.05088179589874961,
+    0.051221547849258774,
+    0.05142612853745902,
+    0.05149472942945157,
+];
+const WG30: [f64; 15] = [
+    0.007968192496166605,
+    0.01846646831109096,
+    0.02878470788332337,
+    0.03879919256962705,
+    0.04840267283059405,
+    0.057493156217619065,
+    0.06597422988218049,
+    0.0737559747377052,
+    0.08075589522942021,
+    0.08689978720108298,
+    0.09212252223778612,
+    0.09636873717464425,
+    0.09959342058679527,
+    0.1017623897484055,
+    0.10285265289355884,
+];
+
+// Capacity of the epsilon table, as QUADPACK's limexp.
+const EPSILON_TABLE: usize = 50;
+
+/// Gauss–Kronrod pair applied to each subinterval
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KronrodRule {
+    /// 7-point Gauss and 15-point Kronrod rule
+    G7K15,
+    /// 10-point Gauss and 21-point Kronrod rule
+    #[default]
+    G10K21,
+    /// 15-point Gauss and 31-point Kronrod rule
+    G15K31,
+    /// 30-point Gauss and 61-point Kronrod rule, suited to oscillatory
+    /// integrands
+    G30K61,
+}
+
+impl KronrodRule {
+    /// Number of integrand evaluations per subinterval
+    pub fn points(self) -> usize {
+        2 * self.table().0.len() - 1
+    }
+
+    // Kronrod abscissae, Kronrod weights and Gauss weights.
+    fn table(self) -> (&'static [f64], &'static [f64], &'static [f64]) {
+        match self {
+            KronrodRule::G7K15 => (&XGK15, &WGK15, &WG7),
+            KronrodRule::G10K21 => (&XGK21, &WGK21, &WG10),
+            KronrodRule::G15K31 => (&XGK31, &WGK31, &WG15),
+            KronrodRule::G30K61 => (&XGK61, &WGK61, &WG30),
+        }
+    }
+}
+
+/// Options for [`qag`] and [`qags`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuadOptions {
+    /// Rule applied to each subinterval
+    pub rule: KronrodRule,
+    /// Tolerance on the summed error estimates
+    pub tol: Tolerance,
+    /// Maximum number of subintervals
+    pub max_subdivisions: usize,
+}
+
+impl Default for QuadOptions {
+    fn default() -> Self {
+        QuadOptions {
+            rule: KronrodRule::default(),
+            tol: Tolerance::Default,
+            max_subdivisions: 100,
+        }
+    }
+}
+
+// A subinterval with its Kronrod estimate, ordered by its error estimate.
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    a: f64,
+    b: f64,
+    value: f64,
+    error: f64,
+}
+
+impl Segment {
+    fn width(&self) -> f64 {
+        (self.b - self.a).abs()
+    }
+}
+
+impl PartialEq for Segment {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Segment {}
+
+impl PartialOrd for Segment {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Segment {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.error.total_cmp(&other.error)
+    }
+}
+
+// Outcome of a rule on one interval: the segment together with the
+// integrals of |f| and of |f - mean|, as QUADPACK's resabs and resasc.
+struct Estimate {
+    segment: Segment,
+    absolute: f64,
+    deviation: f64,
+}
+
+// Ways an adaptive integration can stop short of the tolerance.
+enum Failure {
+    Roundoff,
+    SubdivisionLimit,
+    Divergence,
+}
+
+/// Integrates `f` over `[a, b]` by globally adaptive Gauss–Kronrod
+/// quadrature (QUADPACK's QAG).
+///
+/// Suited to integrands that are smooth apart from a few local
+/// difficulties. Returns [`NumalError::Roundoff`] if rounding errors keep the
+/// estimate from the tolerance and [`NumalError::SubdivisionLimit`] after
+/// `max_subdivisions` subintervals.
+pub fn qag<F>(f: F, a: f64, b: f64, opts: &QuadOptions) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    check_options(a, b, opts)?;
+    let rule = opts.rule;
+    let first = apply(&f, rule, a, b)?;
+    let mut evaluations = rule.points();
+    let (mut area, mut errsum) = (first.segment.value, first.segment.error);
+    let bound = error_bound(opts.tol, area);
+    if errsum <= bound && errsum != first.deviation || errsum == 0.0 {
+        return Ok(Integral {
+            value: area,
+            error: errsum,
+            evaluations,
+        });
+    }
+    if errsum <= 50.0 * f64::EPSILON * first.absolute && errsum > bound {
+        return Err(NumalError::Roundoff {
+            estimate: area,
+            error: errsum,
+        });
+    }
+    let mut queue = BinaryHeap::from([first.segment]);
+    let (mut iroff1, mut iroff2) = (0, 0);
+    for last in 2..=opts.max_subdivisions {
+        let worst = queue.pop().unwrap();
+        let (left, right) = bisect(&f, rule, &worst)?;
+        evaluations += 2 * rule.points();
+        let area12 = left.segment.value + right.segment.value;
+        let erro12 = left.segment.error + right.segment.error;
+        errsum += erro12 - worst.error;
+        area += area12 - worst.value;
+        if left.deviation != left.segment.error && right.deviation != right.segment.error {
+            if (worst.value - area12).abs() <= 1e-5 * area12.abs() && erro12 >= 0.99 * worst.error {
+                iroff1 += 1;
+            }
+            if last > 10 && erro12 > worst.error {
+                iroff2 += 1;
+            }
+        }
+        queue.push(left.segment);
+        queue.push(right.segment);
+        if errsum <= error_bound(opts.tol, area) {
+            return Ok(Integral {
+                value: total(&queue),
+                error: errsum,
+                evaluations,
+            });
+        }
+        if iroff1 >= 6 || iroff2 >= 20 || too_narrow(&left.segment, &right.segment) {
+            return Err(NumalError::Roundoff {
+                estimate: total(&queue),
+                error: errsum,
+            });
+        }
+    }
+    Err(NumalError::SubdivisionLimit {
+        estimate: total(&queue),
+        error: errsum,
+    })
+}
+
+/// Integrates `f` over `[a, b]` by adaptive Gauss–Kronrod quadrature with
+/// epsilon extrapolation (QUADPACK's QAGS).
+///
+/// Handles integrable singularities at or near the endpoints, such as
+/// `x^-0.5` or `ln x` at 0, that defeat [`qag`]. Returns
+/// [`NumalError::Roundoff`] if rounding errors in the integrand or in the
+/// extrapolation keep the estimate from the tolerance,
+/// [`NumalError::SubdivisionLimit`] after `max_subdivisions` subintervals
+/// and [`NumalError::DidNotConverge`] if the integral appears to diverge.
+pub fn qags<F>(f: F, a: f64, b: f64, opts: &QuadOptions) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    check_options(a, b, opts)?;
+    let rule = opts.rule;
+    let first = apply(&f, rule, a, b)?;
+    let mut evaluations = rule.points();
+    let defabs = first.absolute;
+    let (mut area, mut errsum) = (first.segment.value, first.segment.error);
+    let mut errbnd = error_bound(opts.tol, area);
+    if errsum <= errbnd && errsum != first.deviation || errsum == 0.0 {
+        return Ok(Integral {
+            value: area,
+            error: errsum,
+            evaluations,
+        });
+    }
+    if errsum <= 100.0 * f64::EPSILON * defabs && errsum > errbnd {
+        return Err(NumalError::Roundoff {
+            estimate: area,
+            error: errsum,
+        });
+    }
+    // The integrand does not change sign if |I| equals the integral of |f|
+    let one_signed = area.abs() >= (1.0 - 50.0 * f64::EPSILON) * defabs;
+    let mut table = Epsilon::new(area);
+    let mut queue = BinaryHeap::from([first.segment]);
+    let (mut result, mut abserr) = (area, f64::MAX);
+    let mut correction = 0.0;
+    // Intervals no wider than `small` are left alone while the error on the
+    // wider ones, `erlarg`, exceeds `ertest`
+    let (mut small, mut erlarg, mut ertest) = (0.0, 0.0, 0.0);
+    let (mut extrapolating, mut no_extrapolation) = (false, false);
+    let mut stalled = 0;
+    let (mut iroff1, mut iroff2, mut iroff3) = (0, 0, 0);
+    let mut failure = None;
+    for last in 2..=opts.max_subdivisions {
+        // While extrapolating, bisect the worst of the wide intervals
+        let worst = if extrapolating {
+            pop_widest_worst(&mut queue, small)
+        } else {
+            queue.pop().unwrap()
+        };
+        let (left, right) = bisect(&f, rule, &worst)?;
+        evaluations += 2 * rule.points();
+        let area12 = left.segment.value + right.segment.value;
+        let erro12 = left.segment.error + right.segment.error;
+        errsum += erro12 - worst.error;
+        area += area12 - worst.value;
+        if left.deviation != left.segment.error && right.deviation != right.segment.error {
+            if (worst.value - area12).abs() <= 1e-5 * area12.abs() && erro12 >= 0.99 * worst.error {
+                if extrapolating {
+                    iroff2 += 1;
+                } else {
+                    iroff1 += 1;
+                }
+            }
+            if last > 10 && erro12 > worst.error {
+                iroff3 += 1;
+            }
+        }
+        queue.push(left.segment);
+        queue.push(right.segment);
+        errbnd = error_bound(opts.tol, area);
+        if errsum <= errbnd {
+            return Ok(Integral {
+                value: total(&queue),
+                error: errsum,
+                evaluations,
+            });
+        }
+        if iroff1 + iroff2 >= 10 || iroff3 >= 20 || too_narrow(&left.segment, &right.segment) {
+            failure = Some(Failure::Roundoff);
+            break;
+        }
+        if last == opts.max_subdivisions {
+            failure = Some(Failure::SubdivisionLimit);
+            break;
+        }
+        if last == 2 {
+            small = 0.375 * (b - a).abs();
+            erlarg = errsum;
+            ertest = errbnd;
+            table.push(area);
+            continue;
+        }
+        if no_extrapolation {
+            continue;
+        }
+        erlarg -= worst.error;
+        if left.segment.width() > small {
+            erlarg += erro12;
+        }
+        if !extrapolating {
+            // Extrapolate only once the next interval is among the smallest
+            if queue.peek().unwrap().width() > small {
+                continue;
+            }
+            extrapolating = true;
+        }
+        if iroff2 < 5 && erlarg > ertest && queue.iter().any(|s| s.width() > small) {
+            continue;
+        }
+        let (reseps, abseps) = table.extrapolate(area);
+        stalled += 1;
+        if stalled > 5 && abserr < 1e-3 * errsum {
+            failure = Some(Failure::Roundoff);
+        }
+        if abseps < abserr {
+            stalled = 0;
+            abserr = abseps;
+            result = reseps;
+            correction = erlarg;
+            ertest = error_bound(opts.tol, reseps);
+            if abserr <= ertest {
+                break;
+            }
+        }
+        if table.len() == 1 {
+            no_extrapolation = true;
+        }
+        if failure.is_some() {
+            break;
+        }
+        extrapolating = false;
+        small *= 0.5;
+        erlarg = errsum;
+    }
+
+    // Settle between the extrapolated result and the sum over the intervals
+    let mut use_sum = abserr == f64::MAX;
+    if !use_sum {
+        let mut check_divergence = true;
+        if failure.is_some() || iroff2 >= 5 {
+            if iroff2 >= 5 {
+                abserr += correction;
+            }
+            failure.get_or_insert(Failure::Roundoff);
+            if result != 0.0 && area != 0.0 {
+                use_sum = abserr / result.abs() > errsum / area.abs();
+            } else if abserr > errsum {
+                use_sum = true;
+            } else if area == 0.0 {
+                check_divergence = false;
+            }
+        }
+        if !use_sum
+            && check_divergence
+            && (one_signed || result.abs().max(area.abs()) > 0.01 * defabs)
+        {
+            let ratio = result / area;
+            if !(0.01..=100.0).contains(&ratio) || errsum > area.abs() {
+                failure = Some(Failure::Divergence);
+            }
+        }
+    }
+    let (value, error) = if use_sum {
+        (total(&queue), errsum)
+    } else {
+        (result, abserr)
+    };
+    match failure {
+        None => Ok(Integral {
+            value,
+            error,
+            evaluations,
+        }),
+        Some(Failure::Roundoff) => Err(NumalError::Roundoff {
+            estimate: value,
+            error,
+        }),
+        Some(Failure::SubdivisionLimit) => Err(NumalError::SubdivisionLimit {
+            estimate: value,
+            error,
+        }),
+        Some(Failure::Divergence) => Err(NumalError::DidNotConverge),
+    }
+}
+
+// Validates the limits and the subdivision budget.
+fn check_options(a: f64, b: f64, opts: &QuadOptions) -> Result<(), NumalError> {
+    check_limits(a, b)?;
+    if opts.max_subdivisions == 0 {
+        return Err(NumalError::InvalidInput(
+            "max_subdivisions must be at least 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Error requested for an integral estimated as `value`.
+fn error_bound(tol: Tolerance, value: f64) -> f64 {
+    tol.eps_abs().max(tol.eps_rel() * value.abs())
+}
+
+// Sums the estimates over the subintervals afresh, free of the rounding
+// errors accumulated in the running total.
+fn total(queue: &BinaryHeap<Segment>) -> f64 {
+    queue.iter().map(|s| s.value).sum()
+}
+
+// True if the halves of an interval are too narrow to split again in
+// floating point, QUADPACK's ier = 3, reported as roundoff.
+fn too_narrow(left: &Segment, right: &Segment) -> bool {
+    left.a.abs().max(right.b.abs())
+        <= (1.0 + 100.0 * f64::EPSILON) * (left.b.abs() + 1000.0 * f64::MIN_POSITIVE)
+}
+
+// Removes the interval with the largest error among those wider than
+// `small`, or the largest overall if there are none.
+fn pop_widest_worst(queue: &mut BinaryHeap<Segment>, small: f64) -> Segment {
+    let mut narrow = Vec::new();
+    while let Some(s) = queue.pop() {
+        if s.width() > small {
+            queue.extend(narrow);
+            return s;
+        }
+        narrow.push(s);
+    }
+    // Every interval is narrow; the first set aside has the largest error
+    let worst = narrow.remove(0);
+    queue.extend(narrow);
+    worst
+}
+
+// Applies the rule to both halves of a segment.
+fn bisect<F>(f: &F, rule: KronrodRule, s: &Segment) -> Result<(Estimate, Estimate), NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    let mid = 0.5 * (s.a + s.b);
+    Ok((apply(f, rule, s.a, mid)?, apply(f, rule, mid, s.b)?))
+}
+
+// Applies the Kronrod rule and its embedded Gauss rule on [a, b], as
+// QUADPACK's qk routines.
+fn apply<F>(f: &F, rule: KronrodRule, a: f64, b: f64) -> Result<Estimate, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    let (xgk, wgk, wg) = rule.table();
+    let n = xgk.len() - 1;
+    let centre = 0.5 * (a + b);
+    let half = 0.5 * (b - a);
+    let fc = eval(f, centre)?;
+    let mut resk = wgk[n] * fc;
+    // The centre is a Gauss node when the Gauss rule has an odd count
+    let mut resg = if n % 2 == 1 { wg[n / 2] * fc } else { 0.0 };
+    let mut resabs = resk.abs();
+    let mut samples = Vec::with_capacity(n);
+    for (j, (&x, &w)) in xgk[..n].iter().zip(wgk).enumerate() {
+        let f1 = eval(f, centre - half * x)?;
+        let f2 = eval(f, centre + half * x)?;
+        resk += w * (f1 + f2);
+        resabs += w * (f1.abs() + f2.abs());
+        if j % 2 == 1 {
+            resg += wg[j / 2] * (f1 + f2);
+        }
+        samples.push((f1, f2));
+    }
+    let mean = 0.5 * resk;
+    let mut resasc = wgk[n] * (fc - mean).abs();
+    for ((f1, f2), &w) in samples.into_iter().zip(wgk) {
+        resasc += w * ((f1 - mean).abs() + (f2 - mean).abs());
+    }
+    let width = half.abs();
+    let (absolute, deviation) = (resabs * width, resasc * width);
+    let mut error = ((resk - resg) * half).abs();
+    if deviation != 0.0 && error != 0.0 {
+        error = deviation * (200.0 * error / deviation).powf(1.5).min(1.0);
+    }
+    if absolute > f64::MIN_POSITIVE / (50.0 * f64::EPSILON) {
+        error = error.max(50.0 * f64::EPSILON * absolute);
+    }
+    Ok(Estimate {
+        segment: Segment {
+            a,
+            b,
+            value: resk * half,
+            error,
+        },
+        absolute,
+        deviation,
+    })
+}
+
+// Wynn's epsilon table over a sequence of estimates, as QUADPACK's qelg;
+// it keeps the last three extrapolated values to estimate the error.
+struct Epsilon {
+    table: Vec<f64>,
+    count: usize,
+    recent: [f64; 3],
+    calls: usize,
+}
+
+impl Epsilon {
+    fn new(first: f64) -> Self {
+        let mut table = vec![0.0; EPSILON_TABLE + 3];
+        table[0] = first;
+        Epsilon {
+            table,
+            count: 1,
+            recent: [0.0; 3],
+            calls: 0,
+        }
+    }
+
+    // Number of estimates held.
+    fn len(&self) -> usize {
+        self.count
+    }
+
+    // Appends an estimate without extrapolating.
+    fn push(&mut self, value: f64) {
+        self.table[self.count] = value;
+        self.count += 1;
+    }
+
+    // Appends an estimate and returns the extrapolated limit with its error
+    // estimate.
+    fn extrapolate(&mut self, value: f64) -> (f64, f64) {
+        self.push(value);
+        self.calls += 1;
+        let e = &mut self.table;
+        let num = self.count;
+        let mut n = num;
+        let mut result = e[n - 1];
+        let mut abserr = f64::MAX;
+        if n < 3 {
+            return (result, abserr);
+        }
+        e[n + 1] = e[n - 1];
+        let newelm = (n - 1) / 2;
+        e[n - 1] = f64::MAX;
+        // Diagonals of the table are stored in place; k1 indexes the
+        // current element of the new diagonal
+        let mut k1 = n - 1;
+        for i in 0..newelm {
+            let res = e[k1 + 2];
+            let e0 = e[k1 - 2];
+            let e1 = e[k1 - 1];
+            let e2 = res;
+            let delta2 = e2 - e1;
+            let err2 = delta2.abs();
+            let tol2 = e2.abs().max(e1.abs()) * f64::EPSILON;
+            let delta3 = e1 - e0;
+            let err3 = delta3.abs();
+            let tol3 = e1.abs().max(e0.abs()) * f64::EPSILON;
+            if err2 <= tol2 && err3 <= tol3 {
+                // e0, e1 and e2 agree to machine accuracy: converged
+                return (res, (err2 + err3).max(5.0 * f64::EPSILON * res.abs()));
+            }
+            let e3 = e[k1];
+            e[k1] = e1;
+            let delta1 = e1 - e3;
+            let err1 = delta1.abs();
+            let tol1 = e1.abs().max(e3.abs()) * f64::EPSILON;
+            // Two equal elements end the table at this diagonal
+            if err1 <= tol1 || err2 <= tol2 || err3 <= tol3 {
+                n = 2 * i + 1;
+                break;
+            }
+            let ss = 1.0 / delta1 + 1.0 / delta2 - 1.0 / delta3;
+            // So does an irregular element
+            if (ss * e1).abs() <= 1e-4 {
+                n = 2 * i + 1;
+                break;
+            }
+            let res = e1 + 1.0 / ss;
+            e[k1] = res;
+            k1 -= 2;
+            let error = err2 + (res - e2).abs() + err3;
+            if error <= abserr {
+                abserr = error;
+                result = res;
+            }
+        }
+        // Shift the table, dropping the oldest diagonal once it is full
+        if n == EPSILON_TABLE {
+            n = 2 * (EPSILON_TABLE / 2) - 1;
+        }
+        let mut ib = if num % 2 == 1 { 0 } else { 1 };
+        for _ in 0..=newelm {
+            e[ib] = e[ib + 2];
+            ib += 2;
+        }
+        if num != n {
+            e.copy_within(num - n..num, 0);
+        }
+        self.count = n;
+        if self.calls < 4 {
+            self.recent[self.calls - 1] = result;
+            abserr = f64::MAX;
+        } else {
+            abserr = self.recent.iter().map(|r| (result - r).abs()).sum();
+            self.recent = [self.recent[1], self.recent[2], result];
+        }
+        (result, abserr.max(5.0 * f64::EPSILON * result.abs()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn strict(rule: KronrodRule) -> QuadOptions {
+        QuadOptions {
+            rule,
+            tol: Tolerance::Strict,
+            ..Default::default()
+        }
+    }
+
+    const RULES: [KronrodRule; 4] = [
+        KronrodRule::G7K15,
+        KronrodRule::G10K21,
+        KronrodRule::G15K31,
+        KronrodRule::G30K61,
+    ];
+
+    #[test]
+    fn rules_are_exact_to_degree_3n_plus_1() {
+        for (rule, n) in RULES.into_iter().zip([7, 10, 15, 30]) {
+            let degree = 3 * n + 1;
+            // x^degree over [0, 1] in one application of the rule
+            let r = apply(&|x: f64| x.powi(degree), rule, 0.0, 1.0).unwrap();
+            let exact = 1.0 / (degree + 1) as f64;
+            assert!((r.segment.value - exact).abs() < 1e-15, "{rule:?}");
+            assert_eq!(rule.points(), 2 * n as usize + 1);
+        }
+    }
+
+    #[test]
+    fn qag_integrates_a_peaked_integrand() {
+        // A narrow peak at 0.3 forces subdivision near it
+        let f = |x: f64| 1.0 / ((x - 0.3).powi(2) + 1e-4);
+        let exact = 100.0 * ((70.0f64).atan() + (30.0f64).atan());
+        for rule in RULES {
+            let r = qag(f, 0.0, 1.0, &strict(rule)).unwrap();
+            assert!((r.value - exact).abs() < 1e-9 * exact, "{rule:?} {r:?}");
+            assert!(r.error <= 1e-10 * exact, "{rule:?} {r:?}");
+        }
+    }
+
+    #[test]
+    fn qags_extrapolates_endpoint_singularities() {
+        let opts = strict(KronrodRule::G10K21);
+        let r = qags(|x: f64| 1.0 / (1.0 - x).powf(0.9), 0.0, 1.0, &opts).unwrap();
+        assert!((r.value - 10.0).abs() < 1e-8, "{r:?}");
+        let r = qags(|x: f64| x.ln() / x.sqrt(), 0.0, 1.0, &opts).unwrap();
+        assert!((r.value + 4.0).abs() < 1e-10, "{r:?}");
+        // Without extrapolation it takes many more evaluations
+        let plain = qag(|x: f64| x.ln() / x.sqrt(), 0.0, 1.0, &opts).unwrap();
+        assert!(r.evaluations * 2 < plain.evaluations, "{r:?} {plain:?}");
+    }
+
+    #[test]
+    fn oscillatory_integrand_with_the_61_point_rule() {
+        let f = |x: f64| (50.0 * x).cos() * x;
+        let exact = (50.0 * PI).sin() * PI / 50.0 + ((50.0 * PI).cos() - 1.0) / 2500.0;
+        let r = qags(f, 0.0, PI, &strict(KronrodRule::G30K61)).unwrap();
+        assert!((r.value - exact).abs() < 1e-12, "{r:?}");
+    }
+
+    #[test]
+    fn exhausted_subdivisions_carry_the_best_estimate() {
+        let opts = QuadOptions {
+            max_subdivisions: 3,
+            ..strict(KronrodRule::G7K15)
+        };
+        let r = qag(|x: f64| x.sqrt(), 0.0, 1.0, &opts);
+        match r {
+            Err(NumalError::SubdivisionLimit { estimate, error }) => {
+                assert!((estimate - 2.0 / 3.0).abs() < error, "{estimate} {error}");
+                assert!(error > 1e-12 && error < 1e-2, "{error}");
+            }
+            _ => panic!("{r:?}"),
+        }
+    }
+
+    #[test]
+    fn unreachable_tolerance_reports_roundoff() {
+        // Noise at the 1e-10 level keeps the error from falling below it
+        let f = |x: f64| x + 1e-10 * (1e6 * x).sin().signum();
+        let opts = QuadOptions {
+            tol: Tolerance::Custom {
+                eps_abs: 1e-15,
+                eps_rel: 0.0,
+            },
+            max_subdivisions: 1000,
+            ..Default::default()
+        };
+        let r = qags(f, 0.0, 1.0, &opts);
+        match r {
+            Err(NumalError::Roundoff { estimate, .. }) => {
+                assert!((estimate - 0.5).abs() < 1e-8, "{estimate}")
+            }
+            _ => panic!("{r:?}"),
+        }
+    }
+
+    #[test]
+    fn error_at_the_rounding_floor_within_tolerance_is_subdivided() {
+        // The first error estimate equals the deviation, so it is not
+        // trusted, but it is within tolerance and is no sign of roundoff
+        let f = |x: f64| 1.0 + 2e-14 * (40.0 * x).sin();
+        let opts = QuadOptions::default();
+        let r = qags(f, 0.0, 1.0, &opts).unwrap();
+        assert!((r.value - 1.0).abs() < 1e-13, "{r:?}");
+        assert!(r.evaluations > opts.rule.points(), "{r:?}");
+    }
+
+    #[test]
+    fn reversed_limits_and_invalid_inputs() {
+        let r = qags(f64::sin, PI, 0.0, &Default::default()).unwrap();
+        assert!((r.value + 2.0).abs() < 1e-10, "{r:?}");
+        let r = qag(f64::exp, 0.0, f64::NAN, &Default::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let opts = QuadOptions {
+            max_subdivisions: 0,
+            ..Default::default()
+        };
+        let r = qags(f64::exp, 0.0, 1.0, &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
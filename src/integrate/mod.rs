@@ -0,0 +1,81 @@
+//! Numerical integration of functions of one variable.
+//!
+//! Most integrands are plain `Fn(f64) -> f64` closures over finite limits
+//! `a` and `b`, which may be given in either order. The tanh-sinh and
+//! exp-sinh rules instead call `f(x, d)` with the distance `d` from `x` to
+//! the nearer finite limit, and together with the sinh-sinh rule they also
+//! cover half-infinite ranges and the whole real line. Each integrator
+//! returns an [`Integral`] with an error estimate and stops once that
+//! estimate is small under the caller's
+//! [`Tolerance`](crate::core::tolerance::Tolerance). An integrand that is not
+//! finite at a sample point is an error, except at a double-exponential node
+//! that rounds onto a limit.
+//!
+//! The Newton–Cotes, double-exponential, Clenshaw–Curtis and Fejér
+//! integrators refine a nested sequence of rules and accept an estimate once
+//! it is within the tolerance of the estimate before it, reporting the
+//! difference of the two as the error. The adaptive Gauss–Kronrod
+//! integrators instead subdivide until the summed error estimates of the
+//! subintervals are small.
+
+pub mod clenshawcurtis;
+pub mod doubleexponential;
+pub mod gauss;
+pub mod gausskronrod;
+pub mod newtoncotes;
+
+pub use clenshawcurtis::{
+    ClenshawCurtisOptions, clenshaw_curtis, clenshaw_curtis_rule, fejer1, fejer1_rule, fejer2,
+    fejer2_rule,
+};
+pub use doubleexponential::{DoubleExponentialOptions, exp_sinh, sinh_sinh, tanh_sinh};
+pub use gauss::{
+    ChebyshevKind, GaussRule, gauss_chebyshev, gauss_hermite, gauss_jacobi, gauss_laguerre,
+    gauss_legendre, gauss_lobatto, gauss_radau,
+};
+pub use gausskronrod::{KronrodRule, QuadOptions, qag, qags};
+pub use newtoncotes::{NewtonCotesOptions, boole, romberg, simpson, trapezoid};
+
+use crate::NumalError;
+
+/// Outcome of a successful integration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Integral {
+    /// Estimate of the integral
+    pub value: f64,
+    /// Estimate of the absolute error of `value`
+    pub error: f64,
+    /// Number of integrand evaluations
+    pub evaluations: usize,
+}
+
+// Validates the limits of integration.
+pub(crate) fn check_limits(a: f64, b: f64) -> Result<(), NumalError> {
+    if !a.is_finite() || !b.is_finite() {
+        return Err(NumalError::InvalidInput(format!(
+            "limits of integration must be finite, got [{a}, {b}]"
+        )));
+    }
+    Ok(())
+}
+
+// Validates the number of points of a rule.
+pub(crate) fn check_points(n: usize, min: usize) -> Result<(), NumalError> {
+    if n < min {
+        return Err(NumalError::InvalidInput(format!(
+            "number of points must be at least {min}, got {n}"
+        )));
+    }
+    Ok(())
+}
+
+// Evaluates the integrand, rejecting values that are not finite.
+pub(crate) fn eval<F: Fn(f64) -> f64>(f: &F, x: f64) -> Result<f64, NumalError> {
+    let fx = f(x);
+    if !fx.is_finite() {
+        return Err(NumalError::InvalidInput(format!(
+            "integrand is not finite at x = {x}: {fx}"
+        )));
+    }
+    Ok(fx)
+}
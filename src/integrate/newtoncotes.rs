@@ -0,0 +1,212 @@
+//! Composite Newton–Cotes rules and Romberg integration.
+//!
+//! All four methods halve the panel width `h` from level to level, so the
+//! trapezoid sums `T_k` over `2^k` panels reuse every earlier sample. The
+//! composite Simpson and Boole rules are the first and second columns of
+//! Richardson extrapolation of `T_k` in powers of `h^2`, and Romberg
+//! integration (Romberg, 1955) extrapolates as far as the levels allow.
+//! Levels are added until successive estimates agree, but as a guard
+//! against samples that agree by accident, as for periodic integrands, no
+//! estimate is accepted before `2^MIN_LEVEL` panels.
+
+use super::{Integral, check_limits, eval};
+use crate::NumalError;
+use crate::core::tolerance::{Tolerance, is_close};
+
+// Level of the first estimate tested for convergence.
+const MIN_LEVEL: usize = 4;
+
+/// Options for [`trapezoid`], [`simpson`], [`boole`] and [`romberg`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewtonCotesOptions {
+    /// Tolerance between successive estimates; see the
+    /// [module documentation](super)
+    pub tol: Tolerance,
+    /// Maximum number of panel halvings, giving at most `2^max_levels + 1`
+    /// evaluations
+    pub max_levels: usize,
+}
+
+impl Default for NewtonCotesOptions {
+    fn default() -> Self {
+        NewtonCotesOptions {
+            tol: Tolerance::Default,
+            max_levels: 20,
+        }
+    }
+}
+
+/// Integrates `f` over `[a, b]` by the composite trapezoid rule.
+///
+/// Returns [`NumalError::DidNotConverge`] if the estimates are not within
+/// tolerance after `max_levels` halvings.
+pub fn trapezoid<F>(f: F, a: f64, b: f64, opts: &NewtonCotesOptions) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    extrapolate(&f, a, b, 0, opts)
+}
+
+/// Integrates `f` over `[a, b]` by the composite Simpson rule.
+///
+/// Returns [`NumalError::DidNotConverge`] if the estimates are not within
+/// tolerance after `max_levels` halvings.
+pub fn simpson<F>(f: F, a: f64, b: f64, opts: &NewtonCotesOptions) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    extrapolate(&f, a, b, 1, opts)
+}
+
+/// Integrates `f` over `[a, b]` by the composite Boole rule.
+///
+/// Returns [`NumalError::DidNotConverge`] if the estimates are not within
+/// tolerance after `max_levels` halvings.
+pub fn boole<F>(f: F, a: f64, b: f64, opts: &NewtonCotesOptions) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    extrapolate(&f, a, b, 2, opts)
+}
+
+/// Integrates `f` over `[a, b]` by Romberg integration.
+///
+/// Converges fastest for smooth integrands; singularities in the
+/// derivatives slow it to the rate of the trapezoid rule. Returns
+/// [`NumalError::DidNotConverge`] if the estimates are not within tolerance
+/// after `max_levels` halvings.
+pub fn romberg<F>(f: F, a: f64, b: f64, opts: &NewtonCotesOptions) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    extrapolate(&f, a, b, usize::MAX, opts)
+}
+
+// Builds the Romberg tableau row by row up to column `columns`, taking the
+// last entry of each row as the estimate at that level.
+fn extrapolate<F>(
+    f: &F,
+    a: f64,
+    b: f64,
+    columns: usize,
+    opts: &NewtonCotesOptions,
+) -> Result<Integral, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    check_limits(a, b)?;
+    let mut h = b - a;
+    let mut row = vec![0.5 * h * (eval(f, a)? + eval(f, b)?)];
+    let mut evaluations = 2;
+    let mut estimate = row[0];
+    for level in 1..=opts.max_levels {
+        // The new samples are the midpoints of the 2^(level - 1) panels
+        let panels = 1usize << (level - 1);
+        let mut sum = 0.0;
+        for i in 0..panels {
+            sum += eval(f, a + (i as f64 + 0.5) * h)?;
+        }
+        evaluations += panels;
+        h *= 0.5;
+        let mut next = vec![0.5 * row[0] + h * sum];
+        let mut factor = 1.0;
+        for j in 0..row.len().min(columns) {
+            factor *= 4.0;
+            next.push(next[j] + (next[j] - row[j]) / (factor - 1.0));
+        }
+        row = next;
+        let previous = estimate;
+        estimate = row[row.len() - 1];
+        if level >= MIN_LEVEL && is_close(estimate, previous, opts.tol).is_ok() {
+            return Ok(Integral {
+                value: estimate,
+                error: (estimate - previous).abs(),
+                evaluations,
+            });
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    type Rule = fn(fn(f64) -> f64, f64, f64, &NewtonCotesOptions) -> Result<Integral, NumalError>;
+
+    fn strict() -> NewtonCotesOptions {
+        NewtonCotesOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn every_rule_integrates_exp() {
+        let exact = 1f64.exp() - 1.0;
+        let rules: [Rule; 4] = [trapezoid, simpson, boole, romberg];
+        for rule in rules {
+            let r = rule(f64::exp, 0.0, 1.0, &strict()).unwrap();
+            assert!((r.value - exact).abs() < 1e-10, "{r:?}");
+            assert!(r.error < 1e-9, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn higher_order_rules_need_fewer_evaluations() {
+        let f = |x: f64| 1.0 / (1.0 + x * x);
+        let counts: Vec<usize> = [trapezoid, simpson, boole, romberg]
+            .iter()
+            .map(|rule| rule(f, 0.0, 1.0, &strict()).unwrap().evaluations)
+            .collect();
+        assert!(counts.windows(2).all(|w| w[1] <= w[0]), "{counts:?}");
+        assert!(counts[3] < counts[0] / 100, "{counts:?}");
+    }
+
+    #[test]
+    fn polynomials_are_exact_to_the_rule_degree() {
+        // Simpson is exact for cubics and Boole for quintics
+        let cubic = |x: f64| x * x * x - 2.0 * x + 1.0;
+        let r = simpson(cubic, -1.0, 2.0, &strict()).unwrap();
+        assert!((r.value - 3.75).abs() < 1e-13, "{r:?}");
+        assert_eq!(r.evaluations, 17);
+        let quintic = |x: f64| x.powi(5) + x * x;
+        let r = boole(quintic, 0.0, 1.0, &strict()).unwrap();
+        assert!((r.value - 0.5).abs() < 1e-14, "{r:?}");
+    }
+
+    #[test]
+    fn periodic_integrand_is_not_accepted_too_early() {
+        // The first four levels sample sin^2(8 pi x) only at its zeros
+        let f = |x: f64| (8.0 * PI * x).sin().powi(2);
+        let r = romberg(f, 0.0, 1.0, &Default::default()).unwrap();
+        assert!((r.value - 0.5).abs() < 1e-8, "{r:?}");
+    }
+
+    #[test]
+    fn reversed_limits_negate_the_integral() {
+        let r = romberg(f64::sin, PI, 0.0, &Default::default()).unwrap();
+        assert!((r.value + 2.0).abs() < 1e-8, "{r:?}");
+        let r = trapezoid(f64::sin, 1.0, 1.0, &Default::default()).unwrap();
+        assert_eq!(r.value, 0.0);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let r = simpson(f64::exp, 0.0, f64::INFINITY, &Default::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = simpson(|x: f64| 1.0 / x, 0.0, 1.0, &Default::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn exhausted_levels_did_not_converge() {
+        let opts = NewtonCotesOptions {
+            max_levels: 8,
+            ..strict()
+        };
+        let r = trapezoid(f64::sqrt, 0.0, 1.0, &opts);
+        assert_eq!(r, Err(NumalError::DidNotConverge));
+    }
+}
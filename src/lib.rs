@@ -1,2 +1,6 @@
 pub mod core;
+pub mod integrate;
+pub mod lsq;
+pub mod optimize;
+pub mod roots;
 pub use core::error::NumalError;
@@ -0,0 +1,295 @@
+//! Linear least squares with bounds on the variables.
+//!
+//! [`bvls`] is the active-set method of Stark & Parker (1995): variables
+//! are held at a bound or free, the free ones solve the unconstrained
+//! subproblem, and each outer step frees the bound variable whose
+//! multiplier most violates the optimality conditions, then interpolates
+//! back into the box while the subproblem solution leaves it. With every
+//! variable bounded below by zero it reduces to the non-negative least
+//! squares algorithm of Lawson & Hanson (1974), which is what [`nnls`]
+//! runs.
+
+use super::check_system;
+use crate::NumalError;
+use crate::core::linalg::{Matrix, Qr, norm};
+use crate::optimize::Bounds;
+
+/// Solution of a bound-constrained linear least-squares problem
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundedLsq {
+    pub x: Vec<f64>,
+    /// Euclidean norm of `A x - b`
+    pub residual_norm: f64,
+    /// Multipliers `w = A^T (b - A x)`: zero for free variables, at most
+    /// zero at lower bounds and at least zero at upper bounds, up to
+    /// rounding
+    pub dual: Vec<f64>,
+    /// Number of variables freed or fixed at a bound
+    pub iterations: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Lower,
+    Upper,
+    Free,
+}
+
+/// Lawson–Hanson non-negative least squares, `min ||A x - b||` subject to
+/// `x >= 0`.
+pub fn nnls(a: &Matrix, b: &[f64]) -> Result<BoundedLsq, NumalError> {
+    let n = a.cols();
+    let bounds = Bounds::new(vec![0.0; n], vec![f64::INFINITY; n])?;
+    bvls(a, b, &bounds)
+}
+
+/// Bounded-variable least squares, `min ||A x - b||` subject to
+/// `lower <= x <= upper`.
+///
+/// Infinite bounds are allowed; variables unbounded on both sides are
+/// always free. Returns [`NumalError::DidNotConverge`] if the active set
+/// changes more than `10 (n + 1)` times.
+pub fn bvls(a: &Matrix, b: &[f64], bounds: &Bounds) -> Result<BoundedLsq, NumalError> {
+    check_system(a, b)?;
+    let (m, n) = (a.rows(), a.cols());
+    if bounds.dim() != n {
+        return Err(NumalError::InvalidInput(format!(
+            "bounds have dimension {} but the matrix has {n} columns",
+            bounds.dim()
+        )));
+    }
+    let (lower, upper) = (bounds.lower(), bounds.upper());
+    let mut state = vec![State::Free; n];
+    let mut x = vec![0.0; n];
+    for j in 0..n {
+        if lower[j].is_finite() {
+            (state[j], x[j]) = (State::Lower, lower[j]);
+        } else if upper[j].is_finite() {
+            (state[j], x[j]) = (State::Upper, upper[j]);
+        }
+    }
+    // Multipliers below this size count as zero
+    let a_norm = (0..n)
+        .map(|j| a.col(j).iter().map(|v| v.abs()).sum::<f64>())
+        .fold(0.0, f64::max);
+    let b_norm = b.iter().fold(0.0f64, |s, v| s.max(v.abs()));
+    let tol = 10.0 * f64::EPSILON * m.max(n) as f64 * a_norm * b_norm;
+    let max_changes = 10 * (n + 1);
+    let mut changes = 0;
+    if state.contains(&State::Free) {
+        changes += descend(a, b, lower, upper, &mut state, &mut x, None);
+    }
+    // Variables that returned to their bound as soon as they were freed;
+    // skipped until another variable is freed successfully
+    let mut blocked = vec![false; n];
+    loop {
+        let w = multipliers(a, b, &x);
+        let candidate = (0..n)
+            .filter(|&j| !blocked[j] && lower[j] < upper[j])
+            .filter_map(|j| match state[j] {
+                State::Lower if w[j] > tol => Some((j, w[j])),
+                State::Upper if w[j] < -tol => Some((j, -w[j])),
+                _ => None,
+            })
+            .max_by(|p, q| p.1.total_cmp(&q.1));
+        let Some((t, _)) = candidate else {
+            let r: Vec<f64> = a.mul_vec(&x).iter().zip(b).map(|(p, q)| p - q).collect();
+            return Ok(BoundedLsq {
+                residual_norm: norm(&r),
+                x,
+                dual: w,
+                iterations: changes,
+            });
+        };
+        let previous = state[t];
+        state[t] = State::Free;
+        changes += 1 + descend(a, b, lower, upper, &mut state, &mut x, Some(t));
+        if state[t] == previous {
+            blocked[t] = true;
+        } else {
+            blocked.iter_mut().for_each(|v| *v = false);
+        }
+        if changes > max_changes {
+            return Err(NumalError::DidNotConverge);
+        }
+    }
+}
+
+// `A^T (b - A x)`
+fn multipliers(a: &Matrix, b: &[f64], x: &[f64]) -> Vec<f64> {
+    let r: Vec<f64> = b.iter().zip(a.mul_vec(x)).map(|(p, q)| p - q).collect();
+    a.tr_mul_vec(&r)
+}
+
+// Inner loop: moves the free variables towards the solution of their
+// unconstrained subproblem, fixing at its bound each variable that stops
+// the move, until the subproblem solution lies inside the box. If the
+// variable `entering` is stopped immediately it is returned to its bound
+// without moving the others. Returns the number of variables fixed.
+fn descend(
+    a: &Matrix,
+    b: &[f64],
+    lower: &[f64],
+    upper: &[f64],
+    state: &mut [State],
+    x: &mut [f64],
+    entering: Option<usize>,
+) -> usize {
+    let mut fixed = 0;
+    loop {
+        let free: Vec<usize> = (0..x.len()).filter(|&j| state[j] == State::Free).collect();
+        if free.is_empty() {
+            return fixed;
+        }
+        let sub = Matrix::from_fn(a.rows(), free.len(), |i, k| a[(i, free[k])]);
+        let rhs: Vec<f64> = (0..a.rows())
+            .map(|i| {
+                let bound: f64 = (0..x.len())
+                    .filter(|&j| state[j] != State::Free)
+                    .map(|j| a[(i, j)] * x[j])
+                    .sum();
+                b[i] - bound
+            })
+            .collect();
+        let z = Qr::new(&sub).solve(&rhs);
+        // Largest step along z - x that stays in the box, and the variable
+        // that limits it
+        let mut step = 1.0;
+        let mut blocking = None;
+        for (&j, &zj) in free.iter().zip(&z) {
+            let (bound, side) = if zj <= lower[j] {
+                (lower[j], State::Lower)
+            } else if zj >= upper[j] {
+                (upper[j], State::Upper)
+            } else {
+                continue;
+            };
+            let t = if x[j] == zj {
+                0.0
+            } else {
+                ((x[j] - bound) / (x[j] - zj)).clamp(0.0, 1.0)
+            };
+            if t < step || blocking.is_none() {
+                step = t;
+                blocking = Some((j, side));
+            }
+        }
+        let Some((j, side)) = blocking else {
+            for (&j, zj) in free.iter().zip(z) {
+                x[j] = zj;
+            }
+            return fixed;
+        };
+        if step == 0.0 && entering == Some(j) && fixed == 0 {
+            state[j] = side;
+            x[j] = if side == State::Lower {
+                lower[j]
+            } else {
+                upper[j]
+            };
+            return fixed;
+        }
+        for (&k, zk) in free.iter().zip(z) {
+            x[k] += step * (zk - x[k]);
+        }
+        // Fix the blocking variable and any other that reached a bound
+        for &k in &free {
+            let side = if k == j {
+                Some(side)
+            } else if x[k] <= lower[k] {
+                Some(State::Lower)
+            } else if x[k] >= upper[k] {
+                Some(State::Upper)
+            } else {
+                None
+            };
+            if let Some(side) = side {
+                state[k] = side;
+                x[k] = if side == State::Lower {
+                    lower[k]
+                } else {
+                    upper[k]
+                };
+                fixed += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsq::line;
+
+    // Checks feasibility and the sign conditions on the multipliers.
+    fn assert_kkt(r: &BoundedLsq, bounds: &Bounds) {
+        for ((&x, &w), (&l, &u)) in
+            r.x.iter()
+                .zip(&r.dual)
+                .zip(bounds.lower().iter().zip(bounds.upper()))
+        {
+            assert!(l <= x && x <= u, "{r:?}");
+            if x == l && l != u {
+                assert!(w <= 1e-12, "{r:?}");
+            } else if x == u && l != u {
+                assert!(w >= -1e-12, "{r:?}");
+            } else if l != u {
+                assert!(w.abs() < 1e-12, "{r:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn nnls_clips_negative_component() {
+        // The unconstrained solution is (1.5, -1)
+        let a = Matrix::from_row_slice(3, 2, &[1.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+        let r = nnls(&a, &[2.0, 1.0, -1.0]).unwrap();
+        assert!((r.x[0] - 1.5).abs() < 1e-15 && r.x[1] == 0.0);
+        assert!((r.dual[1] + 1.0).abs() < 1e-15);
+        assert!((r.residual_norm - 1.5f64.sqrt()).abs() < 1e-15);
+    }
+
+    #[test]
+    fn nnls_satisfies_optimality_conditions() {
+        let a = Matrix::from_fn(8, 5, |i, j| ((i * 7 + j * 3) % 11) as f64 - 5.0);
+        let b: Vec<f64> = (0..8).map(|i| (i as f64).sin() * 4.0).collect();
+        let r = nnls(&a, &b).unwrap();
+        let n = a.cols();
+        let bounds = Bounds::new(vec![0.0; n], vec![f64::INFINITY; n]).unwrap();
+        assert_kkt(&r, &bounds);
+        assert!(r.x.contains(&0.0) && r.x.iter().any(|&v| v > 0.0));
+    }
+
+    #[test]
+    fn bvls_handles_mixed_bounds() {
+        let a = Matrix::from_fn(10, 4, |i, j| (0.3 * (i + 1) as f64).powi(j as i32));
+        let b: Vec<f64> = (0..10)
+            .map(|i| (0.3 * (i + 1) as f64).exp() * 3.0)
+            .collect();
+        let bounds = Bounds::new(
+            vec![f64::NEG_INFINITY, -1.0, 2.0, 0.5],
+            vec![f64::INFINITY, 1.0, 2.0, 0.6],
+        )
+        .unwrap();
+        let r = bvls(&a, &b, &bounds).unwrap();
+        assert_kkt(&r, &bounds);
+        assert_eq!(r.x[2], 2.0);
+    }
+
+    #[test]
+    fn inactive_bounds_reproduce_unconstrained_solution() {
+        let bounds = Bounds::new(vec![0.0, f64::NEG_INFINITY], vec![10.0, 10.0]).unwrap();
+        let r = bvls(&line::design(), &line::Y, &bounds).unwrap();
+        for (x, e) in r.x.iter().zip(line::FIT) {
+            assert!((x - e).abs() < 1e-14, "{r:?}");
+        }
+        assert!(r.dual.iter().all(|w| w.abs() < 1e-14));
+    }
+
+    #[test]
+    fn wrong_bound_dimension_is_rejected() {
+        let bounds = Bounds::new(vec![0.0], vec![1.0]).unwrap();
+        let r = bvls(&Matrix::identity(2), &[1.0, 1.0], &bounds);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
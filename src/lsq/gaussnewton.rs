@@ -0,0 +1,200 @@
+//! Damped Gauss–Newton method.
+//!
+//! Each step solves the linearized problem `min ||F + J p||` by a QR
+//! factorization with column pivoting, which yields the basic solution when
+//! J is rank deficient, and is then halved until the sum of squares
+//! decreases sufficiently. Convergence is fast on problems with small
+//! residuals and slow or absent on those with large ones, for which
+//! [`levenberg_marquardt`](super::levenberg_marquardt) is the safer choice.
+
+use super::{LsqResult, Termination, analytic, finish, minpack_tolerances, orthogonality, start};
+use crate::NumalError;
+use crate::core::linalg::{Matrix, Qr, dot, norm};
+use crate::core::tolerance::Tolerance;
+use crate::roots::system::fd_jacobian;
+
+// Sufficient-decrease constant and smallest damping factor of the line
+// search.
+const ARMIJO: f64 = 1e-4;
+const MIN_DAMPING: f64 = 1e-10;
+
+/// Options for [`gauss_newton`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GaussNewtonOptions {
+    /// Convergence tolerance; see the [module documentation](super)
+    pub tol: Tolerance,
+    pub max_iter: usize,
+}
+
+impl Default for GaussNewtonOptions {
+    fn default() -> Self {
+        GaussNewtonOptions {
+            tol: Tolerance::Default,
+            max_iter: 100,
+        }
+    }
+}
+
+fn gauss_newton_impl<F, J>(
+    f: F,
+    mut jac: J,
+    x0: &[f64],
+    m: usize,
+    opts: &GaussNewtonOptions,
+) -> Result<LsqResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+    J: FnMut(&F, &[f64], &[f64], &mut Matrix) -> Result<usize, NumalError>,
+{
+    let mut fx = start(&f, x0, m)?;
+    let (ftol, xtol, gtol) = minpack_tolerances(opts.tol);
+    let n = x0.len();
+    let mut x = x0.to_vec();
+    let mut fnorm = norm(&fx);
+    let mut evals = 1;
+    let mut j = Matrix::zeros(m, n);
+    let mut ft = vec![0.0; m];
+    for iter in 0..opts.max_iter {
+        evals += jac(&f, &x, &fx, &mut j)?;
+        if orthogonality(&j, &fx, fnorm) <= gtol {
+            return finish(&f, &mut jac, x, fx, Termination::Orthogonality, iter, evals);
+        }
+        let p: Vec<f64> = Qr::new(&j).solve(&fx).into_iter().map(|v| -v).collect();
+        // The derivative of ||F||^2 along the Gauss-Newton step is
+        // -2 ||J p||^2
+        let slope = 2.0 * dot(&fx, &j.mul_vec(&p));
+        let mut t = 1.0;
+        let (xt, ftnorm) = loop {
+            let xt: Vec<f64> = x.iter().zip(&p).map(|(a, b)| a + t * b).collect();
+            f(&xt, &mut ft);
+            evals += 1;
+            let ftnorm = norm(&ft);
+            if ftnorm.is_finite() && ftnorm.powi(2) <= fnorm.powi(2) + ARMIJO * t * slope {
+                break (xt, ftnorm);
+            }
+            t *= 0.5;
+            if t < MIN_DAMPING {
+                return Err(NumalError::DidNotConverge);
+            }
+        };
+        let reduction = 1.0 - (ftnorm / fnorm).powi(2);
+        let small_step = t * norm(&p) <= xtol * norm(&xt);
+        x = xt;
+        fx.copy_from_slice(&ft);
+        fnorm = ftnorm;
+        if reduction <= ftol {
+            return finish(
+                &f,
+                &mut jac,
+                x,
+                fx,
+                Termination::ResidualReduction,
+                iter + 1,
+                evals,
+            );
+        }
+        if small_step {
+            return finish(&f, &mut jac, x, fx, Termination::StepSize, iter + 1, evals);
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+/// Damped Gauss–Newton method with a user-supplied Jacobian.
+///
+/// `f(x, fx)` writes the `m` residuals at `x` and `jac(x, j)` their `m x n`
+/// Jacobian. A non-finite Jacobian is reported as
+/// [`NumalError::DerivativeNotComputable`].
+pub fn gauss_newton<F, J>(
+    f: F,
+    jac: J,
+    x0: &[f64],
+    m: usize,
+    opts: &GaussNewtonOptions,
+) -> Result<LsqResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+    J: Fn(&[f64], &mut Matrix),
+{
+    gauss_newton_impl(f, analytic(jac), x0, m, opts)
+}
+
+/// Damped Gauss–Newton method with a forward-difference Jacobian.
+pub fn gauss_newton_fd<F>(
+    f: F,
+    x0: &[f64],
+    m: usize,
+    opts: &GaussNewtonOptions,
+) -> Result<LsqResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    gauss_newton_impl(f, fd_jacobian, x0, m, opts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsq::line;
+
+    // y = 2 exp(-t / 2) + 1 sampled at t = 0, 0.5, ..., 5
+    fn decay(x: &[f64], fx: &mut [f64]) {
+        for (i, r) in fx.iter_mut().enumerate() {
+            let t = 0.5 * i as f64;
+            *r = x[0] * (-x[1] * t).exp() + x[2] - (2.0 * (-0.5 * t).exp() + 1.0);
+        }
+    }
+
+    fn decay_jac(x: &[f64], j: &mut Matrix) {
+        for i in 0..j.rows() {
+            let t = 0.5 * i as f64;
+            let e = (-x[1] * t).exp();
+            j.row_mut(i).copy_from_slice(&[e, -x[0] * t * e, 1.0]);
+        }
+    }
+
+    #[test]
+    fn fits_exponential_decay() {
+        let opts = GaussNewtonOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        for r in [
+            gauss_newton(decay, decay_jac, &[1.0, 1.0, 0.0], 11, &opts),
+            gauss_newton_fd(decay, &[1.0, 1.0, 0.0], 11, &opts),
+        ] {
+            let r = r.unwrap();
+            for (x, e) in r.x.iter().zip([2.0, 0.5, 1.0]) {
+                assert!((x - e).abs() < 1e-8, "{r:?}");
+            }
+            assert!(r.residual_norm < 1e-8);
+        }
+    }
+
+    #[test]
+    fn linear_problem_converges_after_one_step() {
+        let opts = GaussNewtonOptions::default();
+        let r = gauss_newton_fd(line::residuals, &[0.0, 0.0], 3, &opts).unwrap();
+        for (x, e) in r.x.iter().zip(line::FIT) {
+            assert!((x - e).abs() < 1e-6, "{r:?}");
+        }
+        assert!(r.iterations <= 2, "{r:?}");
+    }
+
+    #[test]
+    fn failed_finite_differences_are_reported() {
+        // sqrt(1 - x) is not defined to the right of the starting point
+        let f = |x: &[f64], fx: &mut [f64]| {
+            fx[0] = (1.0 - x[0]).sqrt();
+            fx[1] = x[0];
+        };
+        let r = gauss_newton_fd(f, &[1.0], 2, &GaussNewtonOptions::default());
+        assert_eq!(r, Err(NumalError::DerivativeNotComputable));
+    }
+
+    #[test]
+    fn too_few_residuals_are_rejected() {
+        let r = gauss_newton_fd(decay, &[1.0, 1.0, 0.0], 2, &GaussNewtonOptions::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
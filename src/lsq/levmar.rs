@@ -0,0 +1,553 @@
+//! Levenberg–Marquardt method in the style of MINPACK `lmder` and `lmdif`.
+//!
+//! Each iteration factorizes the Jacobian as `J P = Q R` and takes the step
+//! that minimizes the linearized residuals inside the trust region
+//! `||D p|| <= delta`, where the scaling D follows the largest column norms
+//! of J seen so far. The Levenberg–Marquardt parameter of that step is found
+//! by Moré's (1978) safeguarded Newton iteration, with each trial value
+//! solved by Givens rotations on R rather than by forming `J^T J`.
+//!
+//! With geodesic acceleration (Transtrum & Sethna, 2012) each step gains a
+//! second-order correction from the curvature of the residuals along it,
+//! estimated with one extra evaluation. This helps on the long curved
+//! valleys typical of curve fitting. The correction is only kept when it is
+//! small relative to the step, and the trust region shrinks otherwise.
+
+use super::{LsqResult, Termination, analytic, finish, minpack_tolerances, orthogonality, start};
+use crate::NumalError;
+use crate::core::linalg::{Matrix, Qr, norm};
+use crate::core::tolerance::Tolerance;
+use crate::roots::system::fd_jacobian;
+
+// Iterations of the search for the Levenberg-Marquardt parameter.
+const LMPAR_ITER: usize = 10;
+// Relative step of the finite difference for the second directional
+// derivative, and the largest ratio of acceleration to velocity accepted.
+const GEODESIC_STEP: f64 = 0.1;
+const MAX_ACCELERATION: f64 = 0.75;
+
+/// Options for [`levenberg_marquardt`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevMarOptions {
+    /// Convergence tolerance; see the [module documentation](super)
+    pub tol: Tolerance,
+    /// Budget of evaluations of F, including finite-difference ones
+    pub max_evaluations: usize,
+    /// Initial trust radius relative to `||D x0||`, MINPACK's `factor`
+    pub factor: f64,
+    /// Scale the parameters by the column norms of the Jacobian, as MINPACK
+    /// `mode = 1`; otherwise D is the identity
+    pub scale: bool,
+    /// Correct each step with geodesic acceleration
+    pub geodesic_acceleration: bool,
+}
+
+impl Default for LevMarOptions {
+    fn default() -> Self {
+        LevMarOptions {
+            tol: Tolerance::Default,
+            max_evaluations: 10_000,
+            factor: 100.0,
+            scale: true,
+            geodesic_acceleration: false,
+        }
+    }
+}
+
+fn scaled_norm(v: &[f64], diag: &[f64]) -> f64 {
+    norm(&v.iter().zip(diag).map(|(a, d)| a * d).collect::<Vec<_>>())
+}
+
+// Givens rotation (cos, sin) that zeroes `b` against `a`.
+fn givens(a: f64, b: f64) -> (f64, f64) {
+    if b.abs() > a.abs() {
+        let cot = a / b;
+        let sin = 0.5 / (0.25 + 0.25 * cot * cot).sqrt();
+        (sin * cot, sin)
+    } else {
+        let tan = b / a;
+        let cos = 0.5 / (0.25 + 0.25 * tan * tan).sqrt();
+        (cos, cos * tan)
+    }
+}
+
+// MINPACK `qrsolv`: solves `min ||[J; D] x - [b; 0]||` given `J P = Q R`
+// and `qtb`, the leading part of `Q^T b`. Returns x and the upper
+// triangular S with `S^T S = P^T (J^T J + D^2) P`.
+fn qrsolv(r: &Matrix, perm: &[usize], d: &[f64], qtb: &[f64]) -> (Vec<f64>, Matrix) {
+    let n = r.cols();
+    let mut s = Matrix::from_fn(n, n, |i, j| r[(i, j)]);
+    let mut z = qtb[..n].to_vec();
+    let mut row = vec![0.0; n];
+    for j in 0..n {
+        let dj = d[perm[j]];
+        if dj == 0.0 {
+            continue;
+        }
+        // Eliminate the row of D for column j into S
+        row.iter_mut().for_each(|v| *v = 0.0);
+        row[j] = dj;
+        let mut extra = 0.0;
+        for k in j..n {
+            if row[k] == 0.0 {
+                continue;
+            }
+            let (cos, sin) = givens(s[(k, k)], row[k]);
+            for (i, ri) in row.iter_mut().enumerate().skip(k) {
+                let a = s[(k, i)];
+                s[(k, i)] = cos * a + sin * *ri;
+                *ri = cos * *ri - sin * a;
+            }
+            let a = z[k];
+            z[k] = cos * a + sin * extra;
+            extra = cos * extra - sin * a;
+        }
+    }
+    // Back substitution, truncated at the first zero pivot
+    let nsing = (0..n).find(|&j| s[(j, j)] == 0.0).unwrap_or(n);
+    z[nsing..].iter_mut().for_each(|v| *v = 0.0);
+    for j in (0..nsing).rev() {
+        let t: f64 = (j + 1..nsing).map(|i| s[(j, i)] * z[i]).sum();
+        z[j] = (z[j] - t) / s[(j, j)];
+    }
+    let mut x = vec![0.0; n];
+    for (zj, &p) in z.iter().zip(perm) {
+        x[p] = *zj;
+    }
+    (x, s)
+}
+
+// MINPACK `lmpar`: the parameter `par` for which the solution x of
+// `min ||[J; sqrt(par) D] x - [b; 0]||` has `||D x||` within 10% of
+// `delta`, or zero if the Gauss-Newton solution is already inside. Returns
+// par, x and the triangular factor S of `qrsolv`.
+fn lmpar(
+    r: &Matrix,
+    perm: &[usize],
+    diag: &[f64],
+    qtb: &[f64],
+    delta: f64,
+    par: f64,
+) -> (f64, Vec<f64>, Matrix) {
+    let n = r.cols();
+    let (gn, _) = qrsolv(r, perm, &vec![0.0; n], qtb);
+    let mut dxnorm = scaled_norm(&gn, diag);
+    let mut fp = dxnorm - delta;
+    if fp <= 0.1 * delta {
+        return (0.0, gn, r.clone());
+    }
+    // phi(par) = ||D x|| - delta is convex and decreasing; bound its root
+    // below by a Newton step from zero, when R has full rank
+    let newton_direction = |x: &[f64], dxnorm: f64| -> Vec<f64> {
+        perm.iter()
+            .map(|&l| diag[l] * (diag[l] * x[l] / dxnorm))
+            .collect()
+    };
+    let full_rank = (0..n).all(|j| r[(j, j)] != 0.0);
+    let mut parl = 0.0;
+    if full_rank {
+        let mut w = newton_direction(&gn, dxnorm);
+        for j in 0..n {
+            let t: f64 = (0..j).map(|i| r[(i, j)] * w[i]).sum();
+            w[j] = (w[j] - t) / r[(j, j)];
+        }
+        let t = norm(&w);
+        parl = fp / delta / t / t;
+    }
+    // and above by the scaled gradient
+    let g: Vec<f64> = (0..n)
+        .map(|j| (0..=j).map(|i| r[(i, j)] * qtb[i]).sum::<f64>() / diag[perm[j]])
+        .collect();
+    let gnorm = norm(&g);
+    let mut paru = gnorm / delta;
+    if paru == 0.0 {
+        paru = f64::MIN_POSITIVE / delta.min(0.1);
+    }
+    let mut par = par.max(parl).min(paru);
+    if par == 0.0 {
+        par = gnorm / dxnorm;
+    }
+    let mut iter = 0;
+    loop {
+        iter += 1;
+        if par == 0.0 {
+            par = f64::MIN_POSITIVE.max(1e-3 * paru);
+        }
+        let d: Vec<f64> = diag.iter().map(|v| par.sqrt() * v).collect();
+        let (x, s) = qrsolv(r, perm, &d, qtb);
+        dxnorm = scaled_norm(&x, diag);
+        let previous = fp;
+        fp = dxnorm - delta;
+        if fp.abs() <= 0.1 * delta
+            || (parl == 0.0 && fp <= previous && previous < 0.0)
+            || iter == LMPAR_ITER
+        {
+            return (par, x, s);
+        }
+        // Newton correction of par, with phi' from S^T w = P^T D^2 x
+        let mut w = newton_direction(&x, dxnorm);
+        for j in 0..n {
+            w[j] /= s[(j, j)];
+            let t = w[j];
+            for i in j + 1..n {
+                w[i] -= s[(j, i)] * t;
+            }
+        }
+        let t = norm(&w);
+        let parc = fp / delta / t / t;
+        if fp > 0.0 {
+            parl = parl.max(par);
+        } else if fp < 0.0 {
+            paru = paru.min(par);
+        }
+        par = parl.max(par + parc);
+    }
+}
+
+// Solves `P^T (J^T J + par D^2) P z = rhs` given the factor S of `qrsolv`,
+// with zero components past a singular pivot, and returns `P z`.
+fn normal_solve(s: &Matrix, perm: &[usize], rhs: &[f64]) -> Vec<f64> {
+    let n = s.cols();
+    let nsing = (0..n).find(|&j| s[(j, j)] == 0.0).unwrap_or(n);
+    let mut z: Vec<f64> = perm.iter().map(|&l| rhs[l]).collect();
+    z[nsing..].iter_mut().for_each(|v| *v = 0.0);
+    for j in 0..nsing {
+        let t: f64 = (0..j).map(|i| s[(i, j)] * z[i]).sum();
+        z[j] = (z[j] - t) / s[(j, j)];
+    }
+    for j in (0..nsing).rev() {
+        let t: f64 = (j + 1..nsing).map(|i| s[(j, i)] * z[i]).sum();
+        z[j] = (z[j] - t) / s[(j, j)];
+    }
+    let mut x = vec![0.0; n];
+    for (zj, &p) in z.iter().zip(perm) {
+        x[p] = *zj;
+    }
+    x
+}
+
+fn levmar_impl<F, J>(
+    f: F,
+    mut jac: J,
+    x0: &[f64],
+    m: usize,
+    opts: &LevMarOptions,
+) -> Result<LsqResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+    J: FnMut(&F, &[f64], &[f64], &mut Matrix) -> Result<usize, NumalError>,
+{
+    if !(opts.factor > 0.0 && opts.factor.is_finite()) {
+        return Err(NumalError::InvalidInput(format!(
+            "trust region factor must be positive, got {}",
+            opts.factor
+        )));
+    }
+    let mut fx = start(&f, x0, m)?;
+    let (ftol, xtol, gtol) = minpack_tolerances(opts.tol);
+    let n = x0.len();
+    let mut x = x0.to_vec();
+    let mut fnorm = norm(&fx);
+    let mut evals = 1;
+    let mut j = Matrix::zeros(m, n);
+    let mut ft = vec![0.0; m];
+    let mut diag = vec![0.0; n];
+    let (mut delta, mut xnorm, mut par) = (0.0, 0.0, 0.0);
+    // Successful iterations, as counted by MINPACK
+    let mut iter = 1;
+    loop {
+        evals += jac(&f, &x, &fx, &mut j)?;
+        let qr = Qr::new(&j);
+        let colnorms: Vec<f64> = (0..n).map(|k| norm(&j.col(k))).collect();
+        if iter == 1 {
+            diag = if opts.scale {
+                colnorms
+                    .iter()
+                    .map(|&c| if c == 0.0 { 1.0 } else { c })
+                    .collect()
+            } else {
+                vec![1.0; n]
+            };
+            xnorm = scaled_norm(&x, &diag);
+            delta = if xnorm > 0.0 {
+                opts.factor * xnorm
+            } else {
+                opts.factor
+            };
+        }
+        let qtf = qr.qt_mul(&fx);
+        let gnorm = orthogonality(&j, &fx, fnorm);
+        if gnorm <= gtol {
+            return finish(
+                &f,
+                &mut jac,
+                x,
+                fx,
+                Termination::Orthogonality,
+                iter - 1,
+                evals,
+            );
+        }
+        if opts.scale {
+            diag.iter_mut()
+                .zip(&colnorms)
+                .for_each(|(d, &c)| *d = d.max(c));
+        }
+
+        loop {
+            let (p_par, v, s) = lmpar(qr.r(), qr.perm(), &diag, &qtf, delta, par);
+            par = p_par;
+            let v: Vec<f64> = v.iter().map(|v| -v).collect();
+            let vnorm = scaled_norm(&v, &diag);
+            let mut p = v.clone();
+            let mut accepted_shape = true;
+            if opts.geodesic_acceleration {
+                // Second directional derivative of F along v by finite
+                // differences, then the acceleration a solving
+                // (J^T J + par D^2) a = -J^T F''
+                let xh: Vec<f64> = x
+                    .iter()
+                    .zip(&v)
+                    .map(|(a, b)| a + GEODESIC_STEP * b)
+                    .collect();
+                f(&xh, &mut ft);
+                evals += 1;
+                let jv = j.mul_vec(&v);
+                let fvv: Vec<f64> = (0..m)
+                    .map(|i| 2.0 / GEODESIC_STEP * ((ft[i] - fx[i]) / GEODESIC_STEP - jv[i]))
+                    .collect();
+                let rhs: Vec<f64> = j.tr_mul_vec(&fvv).iter().map(|g| -g).collect();
+                let a = normal_solve(&s, qr.perm(), &rhs);
+                let anorm = scaled_norm(&a, &diag);
+                if anorm.is_finite() && 2.0 * anorm <= MAX_ACCELERATION * vnorm {
+                    p.iter_mut().zip(&a).for_each(|(p, a)| *p += 0.5 * a);
+                } else {
+                    accepted_shape = false;
+                }
+            }
+            let pnorm = scaled_norm(&p, &diag);
+            if iter == 1 {
+                delta = delta.min(pnorm);
+            }
+            let xt: Vec<f64> = x.iter().zip(&p).map(|(a, b)| a + b).collect();
+            let (fnorm1, actred) = if accepted_shape {
+                f(&xt, &mut ft);
+                evals += 1;
+                let fnorm1 = norm(&ft);
+                let actred = if 0.1 * fnorm1 < fnorm {
+                    1.0 - (fnorm1 / fnorm).powi(2)
+                } else {
+                    -1.0
+                };
+                (fnorm1, actred)
+            } else {
+                // A rejected acceleration counts as a step without gain
+                (fnorm, 0.0)
+            };
+            // Reduction predicted by the linear model for the velocity, and
+            // the directional derivative of the sum of squares
+            let t1 = norm(&j.mul_vec(&v)) / fnorm;
+            let t2 = par.sqrt() * vnorm / fnorm;
+            let prered = t1 * t1 + t2 * t2 / 0.5;
+            let dirder = -(t1 * t1 + t2 * t2);
+            let ratio = if prered != 0.0 { actred / prered } else { 0.0 };
+
+            if ratio <= 0.25 {
+                let mut t = if actred >= 0.0 {
+                    0.5
+                } else {
+                    0.5 * dirder / (dirder + 0.5 * actred)
+                };
+                if 0.1 * fnorm1 >= fnorm || t < 0.1 || t.is_nan() {
+                    t = 0.1;
+                }
+                delta = t * delta.min(pnorm / 0.1);
+                par /= t;
+            } else if par == 0.0 || ratio >= 0.75 {
+                delta = pnorm / 0.5;
+                par *= 0.5;
+            }
+            if ratio >= 1e-4 {
+                x = xt;
+                fx.copy_from_slice(&ft);
+                xnorm = scaled_norm(&x, &diag);
+                fnorm = fnorm1;
+                iter += 1;
+            }
+
+            // The tests of MINPACK, including those that find the tolerance
+            // below rounding level, which end the iteration the same way
+            let small_reduction = |tol: f64| actred.abs() <= tol && prered <= tol && ratio <= 2.0;
+            let done = if small_reduction(ftol) || small_reduction(f64::EPSILON) {
+                Some(Termination::ResidualReduction)
+            } else if delta <= xtol * xnorm || delta <= f64::EPSILON * xnorm {
+                Some(Termination::StepSize)
+            } else if gnorm <= f64::EPSILON {
+                Some(Termination::Orthogonality)
+            } else {
+                None
+            };
+            if let Some(reason) = done {
+                return finish(&f, &mut jac, x, fx, reason, iter - 1, evals);
+            }
+            if evals >= opts.max_evaluations {
+                return Err(NumalError::DidNotConverge);
+            }
+            if ratio >= 1e-4 {
+                break;
+            }
+        }
+    }
+}
+
+/// Levenberg–Marquardt method with a user-supplied Jacobian, after MINPACK
+/// `lmder`.
+///
+/// `f(x, fx)` writes the `m` residuals at `x` and `jac(x, j)` their `m x n`
+/// Jacobian. A non-finite Jacobian is reported as
+/// [`NumalError::DerivativeNotComputable`].
+pub fn levenberg_marquardt<F, J>(
+    f: F,
+    jac: J,
+    x0: &[f64],
+    m: usize,
+    opts: &LevMarOptions,
+) -> Result<LsqResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+    J: Fn(&[f64], &mut Matrix),
+{
+    levmar_impl(f, analytic(jac), x0, m, opts)
+}
+
+/// Levenberg–Marquardt method with a forward-difference Jacobian, after
+/// MINPACK `lmdif`.
+pub fn levenberg_marquardt_fd<F>(
+    f: F,
+    x0: &[f64],
+    m: usize,
+    opts: &LevMarOptions,
+) -> Result<LsqResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    levmar_impl(f, fd_jacobian, x0, m, opts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsq::line;
+
+    // Rosenbrock's function as residuals, minimum zero at (1, 1)
+    fn rosenbrock(x: &[f64], fx: &mut [f64]) {
+        fx[0] = 10.0 * (x[1] - x[0] * x[0]);
+        fx[1] = 1.0 - x[0];
+    }
+
+    fn rosenbrock_jac(x: &[f64], j: &mut Matrix) {
+        j.row_mut(0).copy_from_slice(&[-20.0 * x[0], 10.0]);
+        j.row_mut(1).copy_from_slice(&[-1.0, 0.0]);
+    }
+
+    // Meyer's thermistor model y = x0 exp(x1 / (t + x2)), a classic badly
+    // scaled fit with residual sum of squares 87.9458
+    const MEYER_Y: [f64; 16] = [
+        34780.0, 28610.0, 23650.0, 19630.0, 16370.0, 13720.0, 11540.0, 9744.0, 8261.0, 7030.0,
+        6005.0, 5147.0, 4427.0, 3820.0, 3307.0, 2872.0,
+    ];
+
+    fn meyer(x: &[f64], fx: &mut [f64]) {
+        for (i, (r, y)) in fx.iter_mut().zip(MEYER_Y).enumerate() {
+            let t = 50.0 + 5.0 * i as f64;
+            *r = x[0] * (x[1] / (t + x[2])).exp() - y;
+        }
+    }
+
+    fn meyer_jac(x: &[f64], j: &mut Matrix) {
+        for i in 0..16 {
+            let t = 50.0 + 5.0 * i as f64;
+            let e = (x[1] / (t + x[2])).exp();
+            j.row_mut(i).copy_from_slice(&[
+                e,
+                x[0] * e / (t + x[2]),
+                -x[0] * x[1] * e / (t + x[2]).powi(2),
+            ]);
+        }
+    }
+
+    #[test]
+    fn solves_rosenbrock() {
+        let opts = LevMarOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        for r in [
+            levenberg_marquardt(rosenbrock, rosenbrock_jac, &[-1.2, 1.0], 2, &opts),
+            levenberg_marquardt_fd(rosenbrock, &[-1.2, 1.0], 2, &opts),
+        ] {
+            let r = r.unwrap();
+            assert!(
+                (r.x[0] - 1.0).abs() < 1e-8 && (r.x[1] - 1.0).abs() < 1e-8,
+                "{r:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn fits_meyer_function() {
+        let opts = LevMarOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = levenberg_marquardt(meyer, meyer_jac, &[0.02, 4000.0, 250.0], 16, &opts).unwrap();
+        assert!(
+            (r.residual_norm.powi(2) - 87.945_855_17).abs() < 1e-4,
+            "{r:?}"
+        );
+        let expected = [5.609_636_47e-3, 6.181_346_35e3, 3.452_236_35e2];
+        for (x, e) in r.x.iter().zip(expected) {
+            assert!(((x - e) / e).abs() < 1e-6, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn geodesic_acceleration_reaches_same_fit() {
+        let opts = LevMarOptions {
+            tol: Tolerance::Strict,
+            geodesic_acceleration: true,
+            ..Default::default()
+        };
+        let r = levenberg_marquardt(meyer, meyer_jac, &[0.02, 4000.0, 250.0], 16, &opts).unwrap();
+        assert!(
+            (r.residual_norm.powi(2) - 87.945_855_17).abs() < 1e-4,
+            "{r:?}"
+        );
+        let r = levenberg_marquardt(rosenbrock, rosenbrock_jac, &[-1.2, 1.0], 2, &opts).unwrap();
+        assert!((r.x[0] - 1.0).abs() < 1e-8, "{r:?}");
+    }
+
+    #[test]
+    fn covariance_of_linear_fit() {
+        let opts = LevMarOptions::default();
+        let r = levenberg_marquardt_fd(line::residuals, &[0.0, 0.0], 3, &opts).unwrap();
+        for (c, e) in r.covariance.as_slice().iter().zip(line::COVARIANCE) {
+            assert!((c - e).abs() < 1e-7, "{r:?}");
+        }
+        // The forward-difference Jacobian is exact up to rounding for a
+        // linear model
+        for (j, e) in r.jacobian.as_slice().iter().zip(line::design().as_slice()) {
+            assert!((j - e).abs() < 1e-6, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn failed_finite_differences_are_reported() {
+        let f = |x: &[f64], fx: &mut [f64]| {
+            fx[0] = (1.0 - x[0]).sqrt();
+            fx[1] = x[0];
+        };
+        let r = levenberg_marquardt_fd(f, &[1.0], 2, &LevMarOptions::default());
+        assert_eq!(r, Err(NumalError::DerivativeNotComputable));
+    }
+}
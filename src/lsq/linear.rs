@@ -0,0 +1,154 @@
+//! Minimum-norm solutions of dense linear least-squares problems.
+//!
+//! Both methods treat `A` as having numerical rank r, the number of
+//! singular values (or diagonal entries of the pivoted R) above
+//! `max(m, n) eps` times the largest, and return the shortest minimizer of
+//! `||A x - b||` for that rank. The QR method uses a complete orthogonal
+//! decomposition `A P = Q [T 0; 0 0] Z^T` (Golub & Van Loan, 2013, §5.5.2);
+//! the SVD is slower but more reliable at deciding the rank.
+
+use super::check_system;
+use crate::NumalError;
+use crate::core::linalg::{Matrix, Qr, Svd, dot, norm};
+
+/// Factorization used by [`lstsq`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LstsqMethod {
+    /// Complete orthogonal decomposition from QR with column pivoting
+    #[default]
+    Qr,
+    /// Singular value decomposition
+    Svd,
+}
+
+/// Minimum-norm least-squares solution
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearLsq {
+    pub x: Vec<f64>,
+    /// Euclidean norm of `A x - b`
+    pub residual_norm: f64,
+    /// Numerical rank of `A`
+    pub rank: usize,
+}
+
+/// Minimum-norm solution of `min ||A x - b||` for an `m x n` matrix of
+/// either shape.
+///
+/// Fails with [`NumalError::DidNotConverge`] only if the SVD does.
+pub fn lstsq(a: &Matrix, b: &[f64], method: LstsqMethod) -> Result<LinearLsq, NumalError> {
+    check_system(a, b)?;
+    let (x, rank) = match method {
+        LstsqMethod::Qr => complete_orthogonal(a, b),
+        LstsqMethod::Svd => {
+            let svd = Svd::new(a).ok_or(NumalError::DidNotConverge)?;
+            let rank = svd_rank(&svd, a);
+            let mut x = vec![0.0; a.cols()];
+            for k in 0..rank {
+                let c = dot(&svd.u.col(k), b) / svd.s[k];
+                x.iter_mut()
+                    .enumerate()
+                    .for_each(|(i, xi)| *xi += c * svd.v[(i, k)]);
+            }
+            (x, rank)
+        }
+    };
+    let r: Vec<f64> = a.mul_vec(&x).iter().zip(b).map(|(p, q)| p - q).collect();
+    Ok(LinearLsq {
+        residual_norm: norm(&r),
+        x,
+        rank,
+    })
+}
+
+// Number of singular values above `max(m, n) eps s_max`.
+pub(super) fn svd_rank(svd: &Svd, a: &Matrix) -> usize {
+    let cutoff = a.rows().max(a.cols()) as f64 * f64::EPSILON * svd.s.first().unwrap_or(&0.0);
+    svd.s.iter().take_while(|&&s| s > cutoff).count()
+}
+
+// Minimum-norm solution from a second QR factorization of the leading rows
+// of R, which turns `R_top y = c` into a triangular system for `Z^T y`.
+fn complete_orthogonal(a: &Matrix, b: &[f64]) -> (Vec<f64>, usize) {
+    let n = a.cols();
+    let qr = Qr::new(a);
+    let rank = qr.rank();
+    let mut x = vec![0.0; n];
+    if rank == 0 {
+        return (x, 0);
+    }
+    let c = qr.qt_mul(b);
+    // R_top^T P2 = Z R2, so R_top y = c becomes R2^T (Z^T y) = P2^T c
+    let top = Matrix::from_fn(rank, n, |i, j| qr.r()[(i, j)]);
+    let z = Qr::new(&top.transpose());
+    let r2 = z.r();
+    let mut w = vec![0.0; n];
+    for i in 0..rank {
+        let s: f64 = (0..i).map(|k| r2[(k, i)] * w[k]).sum();
+        w[i] = (c[z.perm()[i]] - s) / r2[(i, i)];
+    }
+    let y = z.q_mul(&w);
+    for (&p, yi) in qr.perm().iter().zip(y) {
+        x[p] = yi;
+    }
+    (x, rank)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsq::line;
+
+    const METHODS: [LstsqMethod; 2] = [LstsqMethod::Qr, LstsqMethod::Svd];
+
+    #[test]
+    fn fits_overdetermined_line() {
+        for method in METHODS {
+            let r = lstsq(&line::design(), &line::Y, method).unwrap();
+            for (x, e) in r.x.iter().zip(line::FIT) {
+                assert!((x - e).abs() < 1e-14, "{method:?}: {r:?}");
+            }
+            assert!((r.residual_norm - (1.0f64 / 6.0).sqrt()).abs() < 1e-14);
+            assert_eq!(r.rank, 2);
+        }
+    }
+
+    #[test]
+    fn rank_deficient_problem_gets_minimum_norm_solution() {
+        // The second column is twice the first; the minimizers are
+        // x0 + 2 x1 = 1 and the shortest is (1, 2) / 5
+        let a = Matrix::from_row_slice(3, 3, &[1.0, 2.0, 0.0, 1.0, 2.0, 1.0, 1.0, 2.0, 2.0]);
+        for method in METHODS {
+            let r = lstsq(&a, &[1.0, 2.0, 3.0], method).unwrap();
+            assert_eq!(r.rank, 2);
+            for (x, e) in r.x.iter().zip([0.2, 0.4, 1.0]) {
+                assert!((x - e).abs() < 1e-13, "{method:?}: {r:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn underdetermined_problem_gets_minimum_norm_solution() {
+        let a = Matrix::from_row_slice(2, 4, &[1.0, 1.0, 1.0, 1.0, 1.0, -1.0, 0.0, 0.0]);
+        for method in METHODS {
+            let r = lstsq(&a, &[4.0, 0.0], method).unwrap();
+            assert!(r.residual_norm < 1e-14);
+            for x in &r.x {
+                assert!((x - 1.0).abs() < 1e-14, "{method:?}: {r:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn zero_matrix_has_rank_zero() {
+        for method in METHODS {
+            let r = lstsq(&Matrix::zeros(2, 2), &[1.0, 1.0], method).unwrap();
+            assert_eq!((r.x, r.rank), (vec![0.0, 0.0], 0));
+        }
+    }
+
+    #[test]
+    fn mismatched_right_hand_side_is_rejected() {
+        let r = lstsq(&Matrix::identity(2), &[1.0], LstsqMethod::Qr);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,237 @@
+//! Least-squares fitting.
+//!
+//! Linear problems `min ||A x - b||` with a dense `m x n` [`Matrix`] are
+//! solved in minimum norm by [`lstsq`], under bounds on the variables by
+//! [`nnls`] and [`bvls`], and with Tikhonov regularization by [`ridge()`].
+//!
+//! Nonlinear problems minimize `||F(x)||^2` for residuals F: R^n -> R^m with
+//! `m >= n`, supplied as `f(x, fx)` writing the `m` residuals into `fx`.
+//! Jacobians are written into an `m x n` [`Matrix`] whose entry `(i, j)` is
+//! dF_i/dx_j; the `_fd` variants approximate it by forward differences.
+//!
+//! The stopping tests are those of MINPACK, with `ftol = eps_rel^2`,
+//! `xtol = eps_rel` and `gtol = eps_abs` taken from the [`Tolerance`]: the
+//! sum of squares is within `eps_rel^2` of its minimum relative to its size,
+//! the parameters are within `eps_rel` of the solution relative to their
+//! norm, or the residuals are orthogonal to the columns of the Jacobian to
+//! within a cosine of `eps_abs`.
+
+pub mod bounded;
+pub mod gaussnewton;
+pub mod levmar;
+pub mod linear;
+pub mod ridge;
+
+pub use bounded::{BoundedLsq, bvls, nnls};
+pub use gaussnewton::{GaussNewtonOptions, gauss_newton, gauss_newton_fd};
+pub use levmar::{LevMarOptions, levenberg_marquardt, levenberg_marquardt_fd};
+pub use linear::{LinearLsq, LstsqMethod, lstsq};
+pub use ridge::{RidgeParameter, RidgeSolution, ridge};
+
+use crate::NumalError;
+use crate::core::check_start;
+use crate::core::linalg::{Matrix, Qr, dot, norm};
+use crate::core::tolerance::Tolerance;
+
+/// Why a nonlinear least-squares solver stopped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Termination {
+    /// The actual and predicted relative reductions of the sum of squares
+    /// are at most `eps_rel^2`
+    ResidualReduction,
+    /// The last step, or the trust region, is within `eps_rel` of the
+    /// parameters relative to their norm
+    StepSize,
+    /// The cosine of the angle between the residuals and every column of the
+    /// Jacobian is at most `eps_abs`
+    Orthogonality,
+}
+
+/// Outcome of a successful nonlinear least-squares fit
+#[derive(Clone, Debug, PartialEq)]
+pub struct LsqResult {
+    /// The fitted parameters
+    pub x: Vec<f64>,
+    /// Residuals F(x) at the solution
+    pub residuals: Vec<f64>,
+    /// Euclidean norm of the residuals
+    pub residual_norm: f64,
+    /// Covariance estimate `s^2 (J^T J)^-1` of the parameters, with
+    /// `s^2 = ||F||^2 / (m - n)`; unscaled when `m = n`. As in MINPACK
+    /// `covar`, parameters whose columns of J depend on the others get zero
+    /// rows and columns.
+    pub covariance: Matrix,
+    /// Jacobian at the solution
+    pub jacobian: Matrix,
+    pub termination: Termination,
+    /// Number of iterations performed
+    pub iterations: usize,
+    /// Number of evaluations of F, including finite-difference ones
+    pub evaluations: usize,
+}
+
+// Validates the problem and evaluates the residuals at the starting point.
+fn start<F>(f: &F, x0: &[f64], m: usize) -> Result<Vec<f64>, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    check_start(x0)?;
+    if m < x0.len() {
+        return Err(NumalError::InvalidInput(format!(
+            "{m} residuals cannot determine {} parameters",
+            x0.len()
+        )));
+    }
+    let mut fx = vec![0.0; m];
+    f(x0, &mut fx);
+    if fx.iter().any(|v| !v.is_finite()) {
+        return Err(NumalError::InvalidInput(
+            "residuals are not finite at the initial guess".to_string(),
+        ));
+    }
+    Ok(fx)
+}
+
+// Validates the matrix and right-hand side of a linear problem.
+fn check_system(a: &Matrix, b: &[f64]) -> Result<(), NumalError> {
+    if a.rows() == 0 || a.cols() == 0 {
+        return Err(NumalError::InvalidInput(
+            "matrix must have at least one row and column".to_string(),
+        ));
+    }
+    if b.len() != a.rows() {
+        return Err(NumalError::InvalidInput(format!(
+            "right-hand side has {} components but the matrix has {} rows",
+            b.len(),
+            a.rows()
+        )));
+    }
+    if !a.is_finite() || b.iter().any(|v| !v.is_finite()) {
+        return Err(NumalError::InvalidInput(
+            "matrix and right-hand side must be finite".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// The MINPACK tolerances `(ftol, xtol, gtol)`.
+fn minpack_tolerances(tol: Tolerance) -> (f64, f64, f64) {
+    (tol.eps_rel().powi(2), tol.eps_rel(), tol.eps_abs())
+}
+
+// Wraps a user Jacobian in the signature of `fd_jacobian`.
+fn analytic<F, J>(
+    jac: J,
+) -> impl FnMut(&F, &[f64], &[f64], &mut Matrix) -> Result<usize, NumalError>
+where
+    J: Fn(&[f64], &mut Matrix),
+{
+    move |_: &F, x: &[f64], _: &[f64], m: &mut Matrix| {
+        jac(x, m);
+        if m.is_finite() {
+            Ok(0)
+        } else {
+            Err(NumalError::DerivativeNotComputable)
+        }
+    }
+}
+
+// Largest cosine of the angle between the residuals and a column of the
+// Jacobian; zero residuals count as orthogonal.
+fn orthogonality(jac: &Matrix, fx: &[f64], fnorm: f64) -> f64 {
+    if fnorm == 0.0 {
+        return 0.0;
+    }
+    jac.tr_mul_vec(fx)
+        .iter()
+        .enumerate()
+        .map(|(j, g)| {
+            let c = norm(&jac.col(j));
+            if c == 0.0 {
+                0.0
+            } else {
+                (g / (c * fnorm)).abs()
+            }
+        })
+        .fold(0.0, f64::max)
+}
+
+// Covariance estimate from the Jacobian, following MINPACK `covar`.
+fn covariance(jac: &Matrix, fnorm: f64) -> Matrix {
+    let (m, n) = (jac.rows(), jac.cols());
+    let qr = Qr::new(jac);
+    let (r, perm, rank) = (qr.r(), qr.perm(), qr.rank());
+    // Inverse of the leading nonsingular block of R
+    let mut rinv = Matrix::zeros(rank, rank);
+    for c in 0..rank {
+        for i in (0..=c).rev() {
+            let s: f64 = (i + 1..=c).map(|k| r[(i, k)] * rinv[(k, c)]).sum();
+            let e = if i == c { 1.0 } else { 0.0 };
+            rinv[(i, c)] = (e - s) / r[(i, i)];
+        }
+    }
+    let s2 = if m > n {
+        fnorm * fnorm / (m - n) as f64
+    } else {
+        1.0
+    };
+    let mut cov = Matrix::zeros(n, n);
+    for a in 0..rank {
+        for b in 0..rank {
+            cov[(perm[a], perm[b])] = s2 * dot(rinv.row(a), rinv.row(b));
+        }
+    }
+    cov
+}
+
+// Evaluates the Jacobian and covariance at the solution.
+fn finish<F, J>(
+    f: &F,
+    jac: &mut J,
+    x: Vec<f64>,
+    fx: Vec<f64>,
+    termination: Termination,
+    iterations: usize,
+    evaluations: usize,
+) -> Result<LsqResult, NumalError>
+where
+    J: FnMut(&F, &[f64], &[f64], &mut Matrix) -> Result<usize, NumalError>,
+{
+    let mut jacobian = Matrix::zeros(fx.len(), x.len());
+    let used = jac(f, &x, &fx, &mut jacobian)?;
+    let residual_norm = norm(&fx);
+    Ok(LsqResult {
+        covariance: covariance(&jacobian, residual_norm),
+        x,
+        residuals: fx,
+        residual_norm,
+        jacobian,
+        termination,
+        iterations,
+        evaluations: evaluations + used,
+    })
+}
+
+// The straight line through (0, 1), (1, 3), (2, 4), shared by the solver
+// tests. The fit is (7/6, 3/2) with residual variance s^2 = 1/6, and since
+// X^T X = [[3, 3], [3, 5]] its covariance is s^2 (X^T X)^-1.
+#[cfg(test)]
+mod line {
+    use crate::core::linalg::Matrix;
+
+    pub(super) const Y: [f64; 3] = [1.0, 3.0, 4.0];
+    pub(super) const FIT: [f64; 2] = [7.0 / 6.0, 1.5];
+    pub(super) const COVARIANCE: [f64; 4] = [5.0 / 36.0, -3.0 / 36.0, -3.0 / 36.0, 3.0 / 36.0];
+
+    // The design matrix, with rows (1, t) for t = 0, 1, 2
+    pub(super) fn design() -> Matrix {
+        Matrix::from_row_slice(3, 2, &[1.0, 0.0, 1.0, 1.0, 1.0, 2.0])
+    }
+
+    // The residuals x0 + x1 t - y
+    pub(super) fn residuals(x: &[f64], fx: &mut [f64]) {
+        for (i, (r, y)) in fx.iter_mut().zip(Y).enumerate() {
+            *r = x[0] + x[1] * i as f64 - y;
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Tikhonov (ridge) regularization.
+//!
+//! Minimizes `||A x - b||^2 + lambda ||x||^2` through the SVD of A, whose
+//! solution damps the component along each singular vector by the filter
+//! factor `f_i = s_i^2 / (s_i^2 + lambda)`. The parameter can be chosen by
+//! generalized cross-validation (Golub, Heath & Wahba, 1979), minimizing
+//! `||A x - b||^2 / (m - sum f_i)^2`, or at the corner of the L-curve
+//! (Hansen & O'Leary, 1993), the point of largest curvature of
+//! `(log ||A x - b||, log ||x||)`. Both search a logarithmic grid of
+//! lambda between the squares of the extreme singular values and refine the
+//! best point with Brent's method.
+
+use super::check_system;
+use crate::NumalError;
+use crate::core::linalg::{Matrix, Svd, dot, norm};
+use crate::core::tolerance::Tolerance;
+use crate::optimize::scalar::brent;
+
+// Points of the grid search and iterations of its refinement.
+const GRID_POINTS: usize = 100;
+const REFINE_ITER: usize = 100;
+
+/// How [`ridge`] chooses the regularization parameter
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RidgeParameter {
+    /// A given `lambda >= 0`
+    Fixed(f64),
+    /// Generalized cross-validation
+    Gcv,
+    /// Corner of the L-curve
+    LCurve,
+}
+
+/// Regularized solution
+#[derive(Clone, Debug, PartialEq)]
+pub struct RidgeSolution {
+    pub x: Vec<f64>,
+    /// Regularization parameter used
+    pub lambda: f64,
+    /// Euclidean norm of `A x - b`
+    pub residual_norm: f64,
+    /// Euclidean norm of `x`
+    pub solution_norm: f64,
+    /// Effective number of parameters, the sum of the filter factors
+    pub effective_dof: f64,
+}
+
+/// Solves `min ||A x - b||^2 + lambda ||x||^2` with `lambda` fixed or
+/// chosen from the data.
+///
+/// With `lambda = 0` this is the minimum-norm least-squares solution, using
+/// every nonzero singular value. Fails with [`NumalError::DidNotConverge`]
+/// only if the SVD does.
+pub fn ridge(
+    a: &Matrix,
+    b: &[f64],
+    parameter: RidgeParameter,
+) -> Result<RidgeSolution, NumalError> {
+    check_system(a, b)?;
+    if let RidgeParameter::Fixed(lambda) = parameter
+        && !(lambda >= 0.0 && lambda.is_finite())
+    {
+        return Err(NumalError::InvalidInput(format!(
+            "regularization parameter must be finite and non-negative, got {lambda}"
+        )));
+    }
+    let svd = Svd::new(a).ok_or(NumalError::DidNotConverge)?;
+    let spectrum = Spectrum::new(&svd, b);
+    let lambda = match parameter {
+        RidgeParameter::Fixed(lambda) => lambda,
+        RidgeParameter::Gcv => spectrum.select(|t| spectrum.gcv(t.exp())),
+        RidgeParameter::LCurve => spectrum.select(|t| -spectrum.curvature(t.exp())),
+    };
+    let mut x = vec![0.0; a.cols()];
+    let mut effective_dof = 0.0;
+    for (k, (&s, &beta)) in svd.s.iter().zip(&spectrum.beta).enumerate() {
+        if s > 0.0 {
+            let f = s * s / (s * s + lambda);
+            effective_dof += f;
+            x.iter_mut()
+                .enumerate()
+                .for_each(|(i, xi)| *xi += f * beta / s * svd.v[(i, k)]);
+        }
+    }
+    let r: Vec<f64> = a.mul_vec(&x).iter().zip(b).map(|(p, q)| p - q).collect();
+    Ok(RidgeSolution {
+        residual_norm: norm(&r),
+        solution_norm: norm(&x),
+        x,
+        lambda,
+        effective_dof,
+    })
+}
+
+// Singular values with the coefficients of b along the left singular
+// vectors, from which the residual and solution norms follow for any
+// lambda.
+struct Spectrum {
+    s: Vec<f64>,
+    beta: Vec<f64>,
+    // Squared norm of the part of b outside the range of A
+    outside: f64,
+    rows: usize,
+}
+
+impl Spectrum {
+    fn new(svd: &Svd, b: &[f64]) -> Spectrum {
+        let beta: Vec<f64> = (0..svd.s.len()).map(|k| dot(&svd.u.col(k), b)).collect();
+        let inside: f64 = svd
+            .s
+            .iter()
+            .zip(&beta)
+            .filter(|(s, _)| **s > 0.0)
+            .map(|(_, c)| c * c)
+            .sum();
+        Spectrum {
+            s: svd.s.clone(),
+            beta,
+            outside: (dot(b, b) - inside).max(0.0),
+            rows: b.len(),
+        }
+    }
+
+    // Nonzero singular values with their filter factors and coefficients.
+    fn terms(&self, lambda: f64) -> impl Iterator<Item = (f64, f64, f64)> + '_ {
+        self.s
+            .iter()
+            .zip(&self.beta)
+            .filter(|(s, _)| **s > 0.0)
+            .map(move |(&s, &beta)| (s, s * s / (s * s + lambda), beta))
+    }
+
+    fn gcv(&self, lambda: f64) -> f64 {
+        let (mut rho, mut dof) = (self.outside, 0.0);
+        for (_, f, beta) in self.terms(lambda) {
+            rho += ((1.0 - f) * beta).powi(2);
+            dof += f;
+        }
+        rho / (self.rows as f64 - dof).powi(2)
+    }
+
+    // Curvature of `(log ||A x - b||, log ||x||)` as a function of
+    // `log lambda`, along which the filter factors have derivatives
+    // `-f (1 - f)` and `f (1 - f) (1 - 2 f)`.
+    fn curvature(&self, lambda: f64) -> f64 {
+        let (mut rho, mut rho1, mut rho2) = (self.outside, 0.0, 0.0);
+        let (mut eta, mut eta1, mut eta2) = (0.0, 0.0, 0.0);
+        for (s, f, beta) in self.terms(lambda) {
+            let (g, g1, g2) = (1.0 - f, f * (1.0 - f), -f * (1.0 - f) * (1.0 - 2.0 * f));
+            let b2 = beta * beta;
+            rho += g * g * b2;
+            rho1 += 2.0 * g * g1 * b2;
+            rho2 += 2.0 * (g1 * g1 + g * g2) * b2;
+            let c2 = b2 / (s * s);
+            eta += f * f * c2;
+            eta1 += -2.0 * f * g1 * c2;
+            eta2 += 2.0 * (g1 * g1 - f * g2) * c2;
+        }
+        // x = log(rho) / 2 and y = log(eta) / 2
+        let (x1, x2) = (
+            rho1 / (2.0 * rho),
+            (rho2 * rho - rho1 * rho1) / (2.0 * rho * rho),
+        );
+        let (y1, y2) = (
+            eta1 / (2.0 * eta),
+            (eta2 * eta - eta1 * eta1) / (2.0 * eta * eta),
+        );
+        (x1 * y2 - x2 * y1) / (x1 * x1 + y1 * y1).powf(1.5)
+    }
+
+    // Minimizes `objective(log lambda)` over a grid spanning the squared
+    // singular values, refined by Brent's method between the neighbours of
+    // the best grid point.
+    fn select<F: Fn(f64) -> f64>(&self, objective: F) -> f64 {
+        let s_max = self.s[0];
+        if s_max == 0.0 {
+            return 0.0;
+        }
+        let s_min = self.s.iter().rev().find(|&&s| s > 0.0).unwrap_or(&s_max);
+        let lo = 2.0 * s_min.max(16.0 * f64::EPSILON * s_max).ln();
+        let hi = 2.0 * s_max.ln();
+        let grid: Vec<f64> = (0..GRID_POINTS)
+            .map(|i| lo + (hi - lo) * i as f64 / (GRID_POINTS - 1) as f64)
+            .collect();
+        let values: Vec<f64> = grid.iter().map(|&t| objective(t)).collect();
+        let best = (0..GRID_POINTS)
+            .filter(|&i| values[i].is_finite())
+            .min_by(|&i, &j| values[i].total_cmp(&values[j]))
+            .unwrap_or(GRID_POINTS - 1);
+        let (a, b) = (
+            grid[best.saturating_sub(1)],
+            grid[(best + 1).min(GRID_POINTS - 1)],
+        );
+        match brent(&objective, a, b, Tolerance::Default, REFINE_ITER) {
+            Ok(r) if r.fx < values[best] => r.x.exp(),
+            _ => grid[best].exp(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::linalg::Cholesky;
+    use crate::lsq::{LstsqMethod, lstsq};
+
+    // Discretized first-kind integral equation with kernel 1 / (s + t + 1),
+    // exact solution of ones and data perturbed by 1e-4
+    fn ill_posed() -> (Matrix, Vec<f64>) {
+        let a = Matrix::from_fn(20, 10, |i, j| 1.0 / (i + j + 1) as f64);
+        let b = a
+            .mul_vec(&[1.0; 10])
+            .iter()
+            .enumerate()
+            .map(|(i, v)| v + 1e-4 * (7.3 * i as f64).sin())
+            .collect();
+        (a, b)
+    }
+
+    fn error(x: &[f64]) -> f64 {
+        x.iter().map(|v| (v - 1.0).powi(2)).sum::<f64>().sqrt()
+    }
+
+    #[test]
+    fn fixed_parameter_solves_regularized_normal_equations() {
+        let (a, b) = ill_posed();
+        let r = ridge(&a, &b, RidgeParameter::Fixed(1e-3)).unwrap();
+        let x = Cholesky::new(&a.transpose().matmul(&a), 1e-3)
+            .unwrap()
+            .solve(&a.tr_mul_vec(&b));
+        for (u, v) in r.x.iter().zip(&x) {
+            assert!((u - v).abs() < 1e-10, "{r:?}");
+        }
+        assert!(r.effective_dof > 0.0 && r.effective_dof < 10.0);
+    }
+
+    #[test]
+    fn zero_parameter_gives_least_squares_solution() {
+        let a = Matrix::from_row_slice(3, 2, &[1.0, 0.0, 1.0, 1.0, 1.0, 2.0]);
+        let b = [1.0, 3.0, 4.0];
+        let r = ridge(&a, &b, RidgeParameter::Fixed(0.0)).unwrap();
+        let ls = lstsq(&a, &b, LstsqMethod::Qr).unwrap();
+        for (u, v) in r.x.iter().zip(&ls.x) {
+            assert!((u - v).abs() < 1e-14);
+        }
+        assert!((r.effective_dof - 2.0).abs() < 1e-14);
+    }
+
+    #[test]
+    fn selected_parameters_stabilize_ill_posed_problem() {
+        let (a, b) = ill_posed();
+        let unregularized = error(&lstsq(&a, &b, LstsqMethod::Svd).unwrap().x);
+        for parameter in [RidgeParameter::Gcv, RidgeParameter::LCurve] {
+            let r = ridge(&a, &b, parameter).unwrap();
+            assert!(r.lambda > 0.0);
+            assert!(
+                error(&r.x) < 1e-2 * unregularized,
+                "{parameter:?}: {} vs {unregularized}",
+                error(&r.x)
+            );
+        }
+    }
+
+    #[test]
+    fn gcv_choice_minimizes_gcv_function() {
+        let (a, b) = ill_posed();
+        let r = ridge(&a, &b, RidgeParameter::Gcv).unwrap();
+        let spectrum = Spectrum::new(&Svd::new(&a).unwrap(), &b);
+        let g = spectrum.gcv(r.lambda);
+        for factor in [0.5, 0.9, 1.1, 2.0] {
+            assert!(g <= spectrum.gcv(factor * r.lambda));
+        }
+    }
+
+    #[test]
+    fn negative_parameter_is_rejected() {
+        let r = ridge(
+            &Matrix::identity(2),
+            &[1.0, 1.0],
+            RidgeParameter::Fixed(-1.0),
+        );
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,325 @@
+//! Simulated annealing (Kirkpatrick, Gelatt & Vecchi, 1983).
+//!
+//! The search proceeds in stages at a fixed temperature T, each a run of
+//! Gaussian moves accepted by the Metropolis rule: always if the objective
+//! decreases and with probability `exp(-delta / T)` otherwise. Between
+//! stages the temperature follows a [`CoolingSchedule`] and the move size is
+//! rescaled to keep the acceptance rate between 40% and 60%, as in Corana et
+//! al. (1987). The search ends when the temperature reaches a given fraction
+//! of the initial one, or earlier once the values at the end of several
+//! consecutive stages are within [`Tolerance`] of each other and of the best
+//! value seen, the test of Corana et al.; either way it returns the best
+//! point seen. The slow logarithmic and fast schedules need a much larger
+//! final temperature than geometric cooling to end within the budget.
+
+use super::{Bounds, Budgeted, Minimum, bounded_start};
+use crate::NumalError;
+use crate::core::rng::Rng;
+use crate::core::tolerance::{Tolerance, is_close};
+
+// Acceptance probability of a typical uphill move at the initial
+// temperature, when the temperature is estimated.
+const INITIAL_ACCEPTANCE: f64 = 0.8;
+
+/// Temperature as a function of the stage number
+pub trait CoolingSchedule {
+    /// Temperature of stage `k`, equal to `t0` at `k = 0`
+    fn temperature(&self, t0: f64, k: usize) -> f64;
+
+    /// Validates the parameters of the schedule before the search starts
+    fn check(&self) -> Result<(), NumalError> {
+        Ok(())
+    }
+}
+
+/// Standard cooling schedules
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cooling {
+    /// Geometric cooling `t0 alpha^k` with `0 < alpha < 1`
+    Exponential(f64),
+    /// `t0 ln 2 / ln(k + 2)`, the slow schedule of Geman & Geman (1984)
+    Logarithmic,
+    /// `t0 / (k + 1)`, the fast schedule of Szu & Hartley (1987)
+    Fast,
+}
+
+impl Default for Cooling {
+    fn default() -> Self {
+        Cooling::Exponential(0.95)
+    }
+}
+
+impl CoolingSchedule for Cooling {
+    fn temperature(&self, t0: f64, k: usize) -> f64 {
+        match *self {
+            Cooling::Exponential(alpha) => t0 * alpha.powi(k as i32),
+            Cooling::Logarithmic => t0 * 2f64.ln() / (k as f64 + 2.0).ln(),
+            Cooling::Fast => t0 / (k as f64 + 1.0),
+        }
+    }
+
+    fn check(&self) -> Result<(), NumalError> {
+        match *self {
+            Cooling::Exponential(alpha) if !(alpha > 0.0 && alpha < 1.0) => Err(
+                NumalError::InvalidInput(format!("cooling factor must be in (0, 1), got {alpha}")),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<F: Fn(f64, usize) -> f64> CoolingSchedule for F {
+    fn temperature(&self, t0: f64, k: usize) -> f64 {
+        self(t0, k)
+    }
+}
+
+/// Options for [`simulated_annealing`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnealingOptions {
+    /// Initial temperature; `None` to choose it so that a typical uphill
+    /// move from the initial guess is accepted with probability 0.8
+    pub initial_temperature: Option<f64>,
+    /// Initial standard deviation of the moves in each component
+    pub step: f64,
+    /// Moves per stage; `None` for `20 n`
+    pub stage_length: Option<usize>,
+    /// Temperature, relative to the initial one, at which the search ends
+    pub final_temperature: f64,
+    /// Number of consecutive stages ending within tolerance of each other
+    /// and of the best value that ends the search
+    pub settled_stages: usize,
+    pub tol: Tolerance,
+    pub max_evaluations: usize,
+    /// Seed of the random number generator; equal seeds give equal runs
+    pub seed: u64,
+}
+
+impl Default for AnnealingOptions {
+    fn default() -> Self {
+        AnnealingOptions {
+            initial_temperature: None,
+            step: 1.0,
+            stage_length: None,
+            final_temperature: 1e-8,
+            settled_stages: 5,
+            tol: Tolerance::Default,
+            max_evaluations: 100_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Minimizes `f` by simulated annealing from `x0`, optionally within
+/// `bounds`, cooling according to `cooling`.
+///
+/// Moves that leave the box are projected back onto it. Returns
+/// [`NumalError::DidNotConverge`] if the evaluation budget runs out first,
+/// and [`NumalError::InvalidInput`] if the schedule fails its
+/// [`check`](CoolingSchedule::check) or gives a temperature that is not
+/// finite and positive.
+pub fn simulated_annealing<F, C>(
+    f: F,
+    x0: &[f64],
+    bounds: Option<&Bounds>,
+    cooling: &C,
+    opts: &AnnealingOptions,
+) -> Result<Minimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+    C: CoolingSchedule + ?Sized,
+{
+    let mut x = bounded_start(x0, bounds)?;
+    let n = x.len();
+    cooling.check()?;
+    if !(opts.step > 0.0 && opts.step.is_finite()) {
+        return Err(NumalError::InvalidInput(format!(
+            "step must be finite and positive, got {}",
+            opts.step
+        )));
+    }
+    if !(0.0..1.0).contains(&opts.final_temperature) {
+        return Err(NumalError::InvalidInput(format!(
+            "final temperature must be in [0, 1) relative to the initial one, got {}",
+            opts.final_temperature
+        )));
+    }
+    let stage_length = opts.stage_length.unwrap_or(20 * n).max(1);
+    let mut rng = Rng::new(opts.seed);
+    let mut objective = Budgeted::new(&f, opts.max_evaluations);
+    let mut fx = objective.eval(&x).ok_or(NumalError::DidNotConverge)?;
+    if fx == f64::INFINITY {
+        return Err(NumalError::InvalidInput(
+            "objective is not finite at the initial guess".to_string(),
+        ));
+    }
+    let mut step = opts.step;
+    // Larger moves would mostly be projected onto the boundary
+    let max_step = bounds.map_or(f64::INFINITY, |b| {
+        b.lower()
+            .iter()
+            .zip(b.upper())
+            .map(|(l, u)| u - l)
+            .fold(0.0, f64::max)
+    });
+    let propose = |x: &[f64], step: f64, rng: &mut Rng| {
+        let mut y: Vec<f64> = x.iter().map(|v| v + step * rng.normal()).collect();
+        if let Some(b) = bounds {
+            b.project(&mut y);
+        }
+        y
+    };
+    let t0 = match opts.initial_temperature {
+        Some(t) if t > 0.0 && t.is_finite() => t,
+        Some(t) => {
+            return Err(NumalError::InvalidInput(format!(
+                "initial temperature must be finite and positive, got {t}"
+            )));
+        }
+        None => {
+            // Mean uphill change over a sample of moves from x0
+            let (mut total, mut count) = (0.0, 0);
+            for _ in 0..10 * n {
+                let y = propose(&x, step, &mut rng);
+                let fy = objective.eval(&y).ok_or(NumalError::DidNotConverge)?;
+                if fy > fx && fy.is_finite() {
+                    total += fy - fx;
+                    count += 1;
+                }
+            }
+            if count == 0 {
+                1.0
+            } else {
+                -(total / count as f64) / INITIAL_ACCEPTANCE.ln()
+            }
+        }
+    };
+    let (mut f_last, mut settled_stages) = (fx, 0);
+    let mut stage = 0;
+    loop {
+        let temperature = cooling.temperature(t0, stage);
+        if !(temperature > 0.0 && temperature.is_finite()) {
+            return Err(NumalError::InvalidInput(format!(
+                "cooling schedule gave temperature {temperature} at stage {stage}"
+            )));
+        }
+        let mut accepted = 0;
+        for _ in 0..stage_length {
+            let y = propose(&x, step, &mut rng);
+            let fy = objective.eval(&y).ok_or(NumalError::DidNotConverge)?;
+            if fy <= fx || rng.uniform() < (-(fy - fx) / temperature).exp() {
+                (x, fx) = (y, fy);
+                accepted += 1;
+            }
+        }
+        let rate = accepted as f64 / stage_length as f64;
+        if rate > 0.6 {
+            step = max_step.min(step * (1.0 + 2.0 * (rate - 0.6) / 0.4));
+        } else if rate < 0.4 {
+            step /= 1.0 + 2.0 * (0.4 - rate) / 0.4;
+        }
+        stage += 1;
+        let settled = is_close(fx, f_last, opts.tol).is_ok()
+            && is_close(fx, objective.f_best, opts.tol).is_ok();
+        f_last = fx;
+        settled_stages = if settled { settled_stages + 1 } else { 0 };
+        if settled_stages >= opts.settled_stages || temperature <= opts.final_temperature * t0 {
+            return Ok(objective.minimum(stage));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::rastrigin;
+
+    // Local minimum near x = 1.13 and global minimum near x = -1.30
+    fn double_well(x: &[f64]) -> f64 {
+        (x[0] * x[0] - 1.5).powi(2) + x[0]
+    }
+
+    #[test]
+    fn escapes_local_minimum_of_styblinski_tang() {
+        // Four minima, the global one at (-2.9035, -2.9035)
+        let f = |x: &[f64]| -> f64 {
+            x.iter()
+                .map(|v| 0.5 * (v.powi(4) - 16.0 * v * v + 5.0 * v))
+                .sum()
+        };
+        let bounds = Bounds::new(vec![-5.0; 2], vec![5.0; 2]).unwrap();
+        let r = simulated_annealing(
+            f,
+            &[2.75, 2.75],
+            Some(&bounds),
+            &Cooling::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        assert!(r.x.iter().all(|v| (v + 2.903534).abs() < 1e-4), "{r:?}");
+    }
+
+    #[test]
+    fn every_schedule_finds_global_well() {
+        let opts = AnnealingOptions {
+            final_temperature: 0.1,
+            seed: 1,
+            ..Default::default()
+        };
+        let custom = |t0: f64, k: usize| t0 / (1.0 + (k * k) as f64);
+        let schedules: [&dyn CoolingSchedule; 4] = [
+            &Cooling::Exponential(0.8),
+            &Cooling::Logarithmic,
+            &Cooling::Fast,
+            &custom,
+        ];
+        for cooling in schedules {
+            let r = simulated_annealing(double_well, &[1.1], None, cooling, &opts).unwrap();
+            assert!(r.x[0] < -1.0, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn bounds_are_respected() {
+        let bounds = Bounds::new(vec![0.0], vec![2.0]).unwrap();
+        let r = simulated_annealing(
+            double_well,
+            &[1.5],
+            Some(&bounds),
+            &Cooling::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        // The derivative vanishes at the local minimum inside the box
+        let x = r.x[0];
+        assert!((4.0 * x * (x * x - 1.5) + 1.0).abs() < 1e-3, "{r:?}");
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        let r = simulated_annealing(
+            rastrigin,
+            &[1.0],
+            None,
+            &Cooling::Exponential(0.0),
+            &Default::default(),
+        );
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        for alpha in [1.0, 1.5, f64::NAN] {
+            let r = simulated_annealing(
+                rastrigin,
+                &[1.0],
+                None,
+                &Cooling::Exponential(alpha),
+                &Default::default(),
+            );
+            assert!(matches!(r, Err(NumalError::InvalidInput(_))), "{alpha}");
+        }
+        let opts = AnnealingOptions {
+            initial_temperature: Some(-1.0),
+            ..Default::default()
+        };
+        let r = simulated_annealing(rastrigin, &[1.0], None, &Cooling::Fast, &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
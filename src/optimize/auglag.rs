@@ -0,0 +1,265 @@
+//! Augmented Lagrangian method in the style of LANCELOT.
+//!
+//! Each outer iteration minimizes the Powell-Hestenes-Rockafellar augmented
+//! Lagrangian
+//!
+//! `f(x) + sum((w_i^2 - lambda_i^2) / (2 mu))`,  `w_i = lambda_i - mu c_i`,
+//!
+//! with `w_i` clipped at zero for inequalities, which handles `c_i >= 0`
+//! without slack variables and is once continuously differentiable. The
+//! subproblems are solved by [`lbfgs`]. Following Conn, Gould & Toint (and
+//! Nocedal & Wright, Framework 17.4), the multipliers are set to `w` when the
+//! constraint residuals have decreased enough and the penalty is increased
+//! otherwise, with subproblem and feasibility tolerances tightened as the
+//! iteration proceeds.
+
+use super::Termination;
+use super::constrained::{ConstrainedMinimum, Constraint, Point, constrained_minimum, kkt, start};
+use super::quasinewton::{LbfgsOptions, lbfgs};
+use crate::NumalError;
+use crate::core::tolerance::Tolerance;
+use std::cell::{Cell, RefCell};
+
+/// Options for [`augmented_lagrangian`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AugLagOptions {
+    /// KKT tolerance on stationarity, violation and complementarity
+    pub tol: Tolerance,
+    /// Maximum number of subproblems solved
+    pub max_iter: usize,
+    /// Initial penalty parameter `mu`
+    pub penalty: f64,
+    /// Factor by which `mu` grows when the residuals did not decrease enough
+    pub penalty_growth: f64,
+    /// Iteration budget of each subproblem
+    pub max_inner_iter: usize,
+}
+
+impl Default for AugLagOptions {
+    fn default() -> Self {
+        AugLagOptions {
+            tol: Tolerance::Default,
+            max_iter: 50,
+            penalty: 10.0,
+            penalty_growth: 10.0,
+            max_inner_iter: 1_000,
+        }
+    }
+}
+
+// Shifted multiplier estimates `w` at `p`, clipped at zero for inequalities.
+fn shifted(p: &Point, constraints: &[Constraint], lambda: &[f64], mu: f64) -> Vec<f64> {
+    p.c.iter()
+        .zip(lambda)
+        .zip(constraints)
+        .map(|((c, l), con)| {
+            let w = l - mu * c;
+            if con.is_equality() { w } else { w.max(0.0) }
+        })
+        .collect()
+}
+
+// Minimizes the augmented Lagrangian from `x0` until its gradient meets
+// `tol`. On the stiff subproblems of a large penalty, L-BFGS also stops on
+// short steps or failed line searches far from the minimum, so it is
+// restarted from the best point seen while that still makes progress.
+fn subproblem<F>(
+    merit: &F,
+    x0: &[f64],
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<Vec<f64>, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    let mut g = vec![0.0; x0.len()];
+    let best = RefCell::new((merit(x0, &mut g), x0.to_vec()));
+    let tracked = |x: &[f64], g: &mut [f64]| {
+        let f = merit(x, g);
+        let mut best = best.borrow_mut();
+        if f < best.0 {
+            *best = (f, x.to_vec());
+        }
+        f
+    };
+    let mut x = x0.to_vec();
+    let mut budget = max_iter;
+    while budget > 0 {
+        let opts = LbfgsOptions {
+            tol,
+            max_iter: budget,
+            ..Default::default()
+        };
+        let done = match lbfgs(tracked, &x, &opts) {
+            Ok(r) => {
+                budget -= r.iterations.min(budget);
+                r.termination == Termination::GradientNorm
+            }
+            Err(NumalError::LineSearchFailed { .. }) => {
+                budget -= 1;
+                false
+            }
+            Err(e) => return Err(e),
+        };
+        let next = best.borrow().1.clone();
+        if done || next == x {
+            break;
+        }
+        x = next;
+    }
+    Ok(best.into_inner().1)
+}
+
+/// Minimizes `fg` subject to `constraints` with an augmented Lagrangian
+/// method.
+///
+/// `fg(x, g)` returns `f(x)` and writes the gradient into `g`.
+pub fn augmented_lagrangian<F>(
+    fg: F,
+    constraints: &[Constraint],
+    x0: &[f64],
+    opts: &AugLagOptions,
+) -> Result<ConstrainedMinimum, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    if !(opts.penalty > 0.0 && opts.penalty_growth > 1.0) {
+        return Err(NumalError::InvalidInput(format!(
+            "penalty must be positive and its growth factor above 1, got {} and {}",
+            opts.penalty, opts.penalty_growth
+        )));
+    }
+    let mut p = start(&fg, constraints, x0)?;
+    let evals = Cell::new(1);
+    let mut lambda = vec![0.0; constraints.len()];
+    let mut mu = opts.penalty;
+    let mut omega = 1.0 / mu;
+    let mut eta = mu.powf(-0.1);
+    for iter in 1..=opts.max_iter {
+        let merit = |x: &[f64], g: &mut [f64]| -> f64 {
+            evals.set(evals.get() + 1);
+            let q = Point::new(&fg, constraints, x);
+            let w = shifted(&q, constraints, &lambda, mu);
+            g.copy_from_slice(&q.lagrangian_gradient(&w));
+            q.f + w
+                .iter()
+                .zip(&lambda)
+                .map(|(w, l)| (w * w - l * l) / (2.0 * mu))
+                .sum::<f64>()
+        };
+        // The gradient of the subproblem is that of the Lagrangian at the
+        // updated multipliers, so there is no need to go below half the
+        // stationarity tolerance
+        let floor = 0.5
+            * (opts.tol.eps_abs()
+                + opts.tol.eps_rel() * p.g.iter().fold(0.0, |m: f64, v| m.max(v.abs())));
+        // Step and function-change tests are kept at rounding level, leaving
+        // the gradient test to end the subproblem
+        let inner = Tolerance::Custom {
+            eps_abs: omega.max(floor),
+            eps_rel: f64::EPSILON,
+        };
+        let x = subproblem(&merit, &p.x, inner, opts.max_inner_iter)?;
+        p = Point::new(&fg, constraints, &x);
+        evals.set(evals.get() + 1);
+        let w = shifted(&p, constraints, &lambda, mu);
+        // (lambda - w) / mu is c for equalities and min(c, lambda / mu) for
+        // inequalities: zero exactly when feasible and complementary
+        let residual = lambda
+            .iter()
+            .zip(&w)
+            .fold(0.0, |m: f64, (l, w)| m.max((l - w).abs() / mu));
+        if residual <= eta {
+            lambda = w;
+            if kkt(&p, constraints, &lambda, opts.tol).1 {
+                return Ok(constrained_minimum(
+                    p,
+                    constraints,
+                    lambda,
+                    iter,
+                    evals.get(),
+                ));
+            }
+            eta = (eta / mu.powf(0.9)).max(opts.tol.eps_abs());
+            omega /= mu;
+        } else {
+            mu *= opts.penalty_growth;
+            eta = mu.powf(-0.1);
+            omega = 1.0 / mu;
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems;
+
+    #[test]
+    fn solves_hs71() {
+        let (f, cons) = testproblems::hs71();
+        let opts = AugLagOptions {
+            tol: Tolerance::Loose,
+            ..Default::default()
+        };
+        let r = augmented_lagrangian(f, &cons, &[1.0, 5.0, 5.0, 1.0], &opts).unwrap();
+        let expected = [1.0, 4.742_999_64, 3.821_149_98, 1.379_408_29];
+        for (x, e) in r.x.iter().zip(expected) {
+            assert!((x - e).abs() < 1e-4, "{r:?}");
+        }
+        assert!((r.fx - 17.014_017_29).abs() < 1e-6);
+        assert!(r.constraint_violation <= 1e-6, "{r:?}");
+        assert!(r.multipliers[0] > 0.0 && r.multipliers[2] > 0.0);
+    }
+
+    #[test]
+    fn equality_multiplier() {
+        // min x + y on the unit circle: x = y = -1/√2, lambda = -1/√2
+        let f = |x: &[f64], g: &mut [f64]| {
+            g.copy_from_slice(&[1.0, 1.0]);
+            x[0] + x[1]
+        };
+        let circle = Constraint::equality(|x: &[f64], g: &mut [f64]| {
+            g[0] = 2.0 * x[0];
+            g[1] = 2.0 * x[1];
+            x[0] * x[0] + x[1] * x[1] - 1.0
+        });
+        let r = augmented_lagrangian(f, &[circle], &[1.0, 0.0], &AugLagOptions::default()).unwrap();
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        assert!(
+            (r.x[0] + h).abs() < 1e-6 && (r.x[1] + h).abs() < 1e-6,
+            "{r:?}"
+        );
+        assert!((r.multipliers[0] + h).abs() < 1e-5, "{r:?}");
+    }
+
+    #[test]
+    fn inactive_inequality_has_zero_multiplier() {
+        let f = |x: &[f64], g: &mut [f64]| {
+            g[0] = 2.0 * (x[0] - 1.0);
+            (x[0] - 1.0).powi(2)
+        };
+        let con = Constraint::inequality(|x: &[f64], g: &mut [f64]| {
+            g[0] = -1.0;
+            3.0 - x[0]
+        });
+        let r = augmented_lagrangian(f, &[con], &[0.0], &AugLagOptions::default()).unwrap();
+        assert!((r.x[0] - 1.0).abs() < 1e-6);
+        assert!(r.multipliers[0].abs() < 1e-8);
+    }
+
+    #[test]
+    fn invalid_penalty_is_rejected() {
+        let opts = AugLagOptions {
+            penalty_growth: 1.0,
+            ..Default::default()
+        };
+        let f = |x: &[f64], g: &mut [f64]| {
+            g[0] = 1.0;
+            x[0]
+        };
+        let r = augmented_lagrangian(f, &[], &[0.0], &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
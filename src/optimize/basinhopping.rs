@@ -0,0 +1,266 @@
+//! Basin hopping (Wales & Doye, 1997).
+//!
+//! Each hop perturbs the current point by a uniform random step, runs a
+//! local minimizer from there and accepts the new local minimum by the
+//! Metropolis rule at a fixed temperature, so the search walks between
+//! basins of attraction rather than individual points. The step size is
+//! adjusted every few hops towards an acceptance rate of one half.
+//!
+//! The local minimizer can be any method of this module wrapped in a
+//! closure, such as `|f, x| nelder_mead(f, x, &opts)`. It is handed the
+//! objective to evaluate, which counts every evaluation, those of failed
+//! runs included; a gradient-based method calls it for the value, as in
+//! `|f, x| bfgs(|x, g| { grad(x, g); f(x) }, x, &opts)`, and its result
+//! converts with [`Minimum::from`].
+
+use std::cell::Cell;
+
+use super::{Bounds, Minimum, bounded_start};
+use crate::NumalError;
+use crate::core::rng::Rng;
+use crate::core::tolerance::{Tolerance, is_close};
+
+// Hops between step-size updates, the target acceptance rate and the factor
+// by which the step changes.
+const ADJUST_INTERVAL: usize = 10;
+const TARGET_ACCEPTANCE: f64 = 0.5;
+const STEP_FACTOR: f64 = 0.9;
+
+/// Options for [`basin_hopping`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BasinHoppingOptions {
+    /// Number of hops
+    pub hops: usize,
+    /// Initial half-width of the uniform perturbation in each component
+    pub step: f64,
+    /// Temperature of the Metropolis test on local minimum values
+    pub temperature: f64,
+    /// Consecutive hops without improvement of the best minimum that end
+    /// the search early; `None` to always make every hop
+    pub stall_hops: Option<usize>,
+    /// Tolerance below which a change of the best minimum is no improvement
+    pub tol: Tolerance,
+    /// Budget of objective evaluations
+    pub max_evaluations: usize,
+    /// Seed of the random number generator; equal seeds give equal runs
+    pub seed: u64,
+}
+
+impl Default for BasinHoppingOptions {
+    fn default() -> Self {
+        BasinHoppingOptions {
+            hops: 100,
+            step: 0.5,
+            temperature: 1.0,
+            stall_hops: None,
+            tol: Tolerance::Default,
+            max_evaluations: 1_000_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Basin hopping on `f` from `x0`, optionally within `bounds`, with local
+/// minimizations by `local`, which is called with `f` and a start.
+///
+/// Perturbed points are projected onto the box, and local minima outside it
+/// are rejected, except that the one from `x0` is projected onto it. A hop
+/// whose local minimization fails is rejected as well; only the local
+/// minimization from `x0` must succeed. Returns
+/// [`NumalError::DidNotConverge`] if the evaluation budget runs out.
+pub fn basin_hopping<F, L>(
+    f: F,
+    mut local: L,
+    x0: &[f64],
+    bounds: Option<&Bounds>,
+    opts: &BasinHoppingOptions,
+) -> Result<Minimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+    L: FnMut(&dyn Fn(&[f64]) -> f64, &[f64]) -> Result<Minimum, NumalError>,
+{
+    let x0 = bounded_start(x0, bounds)?;
+    for (name, v) in [("step", opts.step), ("temperature", opts.temperature)] {
+        if !(v > 0.0 && v.is_finite()) {
+            return Err(NumalError::InvalidInput(format!(
+                "{name} must be finite and positive, got {v}"
+            )));
+        }
+    }
+    let inside = |x: &[f64]| {
+        bounds.is_none_or(|b| {
+            x.iter()
+                .zip(b.lower().iter().zip(b.upper()))
+                .all(|(v, (l, u))| l <= v && v <= u)
+        })
+    };
+    let evals = Cell::new(0);
+    let counted = |x: &[f64]| {
+        evals.set(evals.get() + 1);
+        f(x)
+    };
+    let mut rng = Rng::new(opts.seed);
+    let mut current = local(&counted, &x0)?;
+    if let Some(b) = bounds.filter(|_| !inside(&current.x)) {
+        b.project(&mut current.x);
+        current.fx = counted(&current.x);
+    }
+    let mut best = current.clone();
+    let (mut step, mut accepted, mut stalled) = (opts.step, 0, 0);
+    for hop in 1..=opts.hops {
+        if evals.get() >= opts.max_evaluations {
+            return Err(NumalError::DidNotConverge);
+        }
+        let mut x: Vec<f64> = current
+            .x
+            .iter()
+            .map(|v| v + rng.uniform_in(-step, step))
+            .collect();
+        if let Some(b) = bounds {
+            b.project(&mut x);
+        }
+        let mut improved = false;
+        if let Ok(m) = local(&counted, &x) {
+            let uphill = m.fx - current.fx;
+            let metropolis = uphill <= 0.0 || rng.uniform() < (-uphill / opts.temperature).exp();
+            if metropolis && m.fx.is_finite() && inside(&m.x) {
+                improved = m.fx < best.fx && is_close(m.fx, best.fx, opts.tol).is_err();
+                if m.fx < best.fx {
+                    best = m.clone();
+                }
+                current = m;
+                accepted += 1;
+            }
+        }
+        if hop % ADJUST_INTERVAL == 0 {
+            let rate = accepted as f64 / ADJUST_INTERVAL as f64;
+            step = if rate > TARGET_ACCEPTANCE {
+                step / STEP_FACTOR
+            } else {
+                step * STEP_FACTOR
+            };
+            accepted = 0;
+        }
+        stalled = if improved { 0 } else { stalled + 1 };
+        if opts.stall_hops.is_some_and(|s| stalled >= s) {
+            return Ok(Minimum {
+                iterations: hop,
+                evaluations: evals.get(),
+                ..best
+            });
+        }
+    }
+    Ok(Minimum {
+        iterations: opts.hops,
+        evaluations: evals.get(),
+        ..best
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::{rastrigin, rastrigin_grad};
+    use crate::optimize::{BfgsOptions, NelderMeadOptions, bfgs, nelder_mead};
+
+    #[test]
+    fn hops_between_rastrigin_basins_with_bfgs() {
+        let local = |f: &dyn Fn(&[f64]) -> f64, x: &[f64]| {
+            let fg = |x: &[f64], g: &mut [f64]| {
+                rastrigin_grad(x, g);
+                f(x)
+            };
+            bfgs(fg, x, &BfgsOptions::default()).map(Minimum::from)
+        };
+        let r = basin_hopping(
+            rastrigin,
+            local,
+            &[3.0, -4.0, 2.0],
+            None,
+            &Default::default(),
+        )
+        .unwrap();
+        assert!(r.fx < 1e-10, "{r:?}");
+        assert_eq!(r.iterations, 100);
+    }
+
+    #[test]
+    fn stalls_end_the_search_early() {
+        let local =
+            |f: &dyn Fn(&[f64]) -> f64, x: &[f64]| nelder_mead(f, x, &NelderMeadOptions::default());
+        let opts = BasinHoppingOptions {
+            step: 1.0,
+            stall_hops: Some(20),
+            ..Default::default()
+        };
+        let r = basin_hopping(rastrigin, local, &[2.0, 2.0], None, &opts).unwrap();
+        assert!(r.iterations < 100 && r.fx < 1e-6, "{r:?}");
+    }
+
+    #[test]
+    fn minima_outside_bounds_are_rejected() {
+        // The global minimum near x = -1.30 lies outside the box
+        let f = |x: &[f64]| (x[0] * x[0] - 1.5).powi(2) + x[0];
+        let local =
+            |f: &dyn Fn(&[f64]) -> f64, x: &[f64]| nelder_mead(f, x, &NelderMeadOptions::default());
+        let bounds = Bounds::new(vec![-1.0], vec![2.0]).unwrap();
+        let r = basin_hopping(f, local, &[1.5], Some(&bounds), &Default::default()).unwrap();
+        assert!(r.x[0] > 1.0, "{r:?}");
+    }
+
+    #[test]
+    fn first_minimum_outside_bounds_is_projected() {
+        let f = |x: &[f64]| (x[0] - 5.0).powi(2);
+        let local =
+            |f: &dyn Fn(&[f64]) -> f64, x: &[f64]| nelder_mead(f, x, &NelderMeadOptions::default());
+        let bounds = Bounds::new(vec![-1.0], vec![2.0]).unwrap();
+        let opts = BasinHoppingOptions {
+            hops: 0,
+            ..Default::default()
+        };
+        let r = basin_hopping(f, local, &[1.5], Some(&bounds), &opts).unwrap();
+        assert_eq!((r.x[0], r.fx), (2.0, 9.0));
+    }
+
+    #[test]
+    fn evaluations_of_failed_runs_are_counted() {
+        // Every local run evaluates twice, and all but the first fail
+        let runs = Cell::new(0);
+        let local = |f: &dyn Fn(&[f64]) -> f64, x: &[f64]| {
+            let fx = f(x).min(f(x));
+            runs.set(runs.get() + 1);
+            if runs.get() > 1 {
+                return Err(NumalError::DidNotConverge);
+            }
+            Ok(Minimum {
+                x: x.to_vec(),
+                fx,
+                iterations: 1,
+                evaluations: 2,
+            })
+        };
+        let opts = BasinHoppingOptions {
+            hops: 10,
+            ..Default::default()
+        };
+        let r = basin_hopping(rastrigin, local, &[1.0], None, &opts).unwrap();
+        assert_eq!(r.evaluations, 22);
+    }
+
+    #[test]
+    fn failing_start_and_budget_are_reported() {
+        let local = |_: &dyn Fn(&[f64]) -> f64, _: &[f64]| -> Result<Minimum, NumalError> {
+            Err(NumalError::DidNotConverge)
+        };
+        let r = basin_hopping(rastrigin, local, &[0.0], None, &Default::default());
+        assert_eq!(r, Err(NumalError::DidNotConverge));
+        let local =
+            |f: &dyn Fn(&[f64]) -> f64, x: &[f64]| nelder_mead(f, x, &NelderMeadOptions::default());
+        let opts = BasinHoppingOptions {
+            max_evaluations: 500,
+            ..Default::default()
+        };
+        let r = basin_hopping(rastrigin, local, &[2.0, 2.0], None, &opts);
+        assert_eq!(r, Err(NumalError::DidNotConverge));
+    }
+}
@@ -0,0 +1,725 @@
+//! BOBYQA, Bound Optimization BY Quadratic Approximation (Powell, 2009).
+//!
+//! A derivative-free method for `min f(x)` within bounds `l <= x <= u`. The
+//! objective is approximated by a quadratic model interpolating it at `m`
+//! points, `2n + 1` by default. With fewer than the `(n + 1)(n + 2) / 2`
+//! points that determine a quadratic, the remaining freedom is taken up as
+//! in NEWUOA by the least-change update, which minimizes the Frobenius norm
+//! of the change in the model Hessian. Each iteration minimizes the model
+//! within a trust region of radius `delta` and the bounds by truncated
+//! conjugate gradients, and the new point replaces the interpolation point
+//! whose removal best keeps the interpolation system well conditioned. When
+//! the model is poor because a point lies far away, that point is moved to
+//! where its Lagrange function is largest instead. A lower bound `rho` on
+//! `delta` decreases only when steps of its size stop making progress, until
+//! it reaches the final radius `eps_abs + eps_rel ||x0||_inf` from
+//! [`Tolerance`].
+//!
+//! Unlike Powell's code, which keeps the inverse `H` of the interpolation
+//! (KKT) matrix up to date by a low-rank update when a point is replaced,
+//! this implementation factorizes the `(m + n + 1)`-square matrix afresh,
+//! at a cost of `O((m + n)^3)` per replacement instead of `O((m + n)^2)`.
+//! The work is negligible next to an expensive objective for the small `n`
+//! BOBYQA is meant for, and avoids the accumulation of rounding errors that
+//! Powell counters by periodically shifting the base point.
+//!
+//! Every point evaluated lies within the bounds. Objective values that are
+//! NaN count as `+inf`; values beyond `1e30` in magnitude are clamped to it
+//! before they enter the model, which is rebuilt from scratch once a clamped
+//! point leaves the interpolation set.
+
+use super::{Bounds, Budgeted, HUGE, Minimum, bounded_start};
+use crate::NumalError;
+use crate::core::linalg::{Lu, Matrix, dot, norm};
+use crate::core::tolerance::Tolerance;
+
+/// Options for [`bobyqa`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BobyqaOptions {
+    /// Initial trust-region radius, about a tenth of the expected distance
+    /// to the solution; reduced to half the smallest width of the box
+    pub initial_radius: f64,
+    /// Number of interpolation points, between `n + 2` and
+    /// `(n + 1)(n + 2) / 2`; `None` for `2n + 1`
+    pub interpolation_points: Option<usize>,
+    /// Sets the final trust-region radius
+    pub tol: Tolerance,
+    pub max_evaluations: usize,
+}
+
+impl Default for BobyqaOptions {
+    fn default() -> Self {
+        BobyqaOptions {
+            initial_radius: 0.5,
+            interpolation_points: None,
+            tol: Tolerance::Default,
+            max_evaluations: 10_000,
+        }
+    }
+}
+
+// The quadratic `c + g (x - base) + (x - base) h (x - base) / 2`.
+struct Quadratic {
+    base: Vec<f64>,
+    c: f64,
+    g: Vec<f64>,
+    h: Matrix,
+}
+
+impl Quadratic {
+    fn value(&self, x: &[f64]) -> f64 {
+        let s: Vec<f64> = x.iter().zip(&self.base).map(|(x, b)| x - b).collect();
+        self.c + dot(&self.g, &s) + 0.5 * dot(&s, &self.h.mul_vec(&s))
+    }
+
+    fn gradient(&self, x: &[f64]) -> Vec<f64> {
+        let s: Vec<f64> = x.iter().zip(&self.base).map(|(x, b)| x - b).collect();
+        self.g
+            .iter()
+            .zip(self.h.mul_vec(&s))
+            .map(|(g, hs)| g + hs)
+            .collect()
+    }
+
+    // Moves the base point to `x` without changing the function.
+    fn rebase(&mut self, x: &[f64]) {
+        self.c = self.value(x);
+        self.g = self.gradient(x);
+        self.base = x.to_vec();
+    }
+}
+
+// The interpolation points and their values, with the inverse of the KKT
+// matrix of the least-change update for the points relative to the best
+// one, divided by `scale`.
+struct Interpolation {
+    points: Vec<Vec<f64>>,
+    values: Vec<f64>,
+    best: usize,
+    scale: f64,
+    inverse: Matrix,
+}
+
+impl Interpolation {
+    // Offset of `x` from the best point, divided by `scale`.
+    fn scaled(&self, x: &[f64]) -> Vec<f64> {
+        x.iter()
+            .zip(&self.points[self.best])
+            .map(|(x, b)| (x - b) / self.scale)
+            .collect()
+    }
+
+    // Factorizes the matrix `[A e Y; e' 0 0; Y' 0 0]` with
+    // `A_ij = (y_i' y_j)^2 / 2` for the scaled points `y_i`, returning
+    // whether it is nonsingular. This is the `O((m + n)^3)` step that
+    // Powell's update of the inverse avoids; see the module documentation.
+    fn factor(&mut self) -> bool {
+        let (m, n) = (self.points.len(), self.points[0].len());
+        let best = &self.points[self.best];
+        self.scale = self
+            .points
+            .iter()
+            .map(|p| norm(&p.iter().zip(best).map(|(p, b)| p - b).collect::<Vec<_>>()))
+            .fold(0.0, f64::max);
+        let ys: Vec<Vec<f64>> = self.points.iter().map(|p| self.scaled(p)).collect();
+        let mut w = Matrix::zeros(m + n + 1, m + n + 1);
+        for i in 0..m {
+            for j in 0..m {
+                w[(i, j)] = 0.5 * dot(&ys[i], &ys[j]).powi(2);
+            }
+            w[(i, m)] = 1.0;
+            w[(m, i)] = 1.0;
+            for k in 0..n {
+                w[(i, m + 1 + k)] = ys[i][k];
+                w[(m + 1 + k, i)] = ys[i][k];
+            }
+        }
+        match Lu::new(&w) {
+            Some(lu) => {
+                self.inverse = lu.inverse();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // The column of the interpolation matrix that the point `x` would
+    // contribute.
+    fn column(&self, x: &[f64]) -> Vec<f64> {
+        let t = self.scaled(x);
+        let mut w: Vec<f64> = self
+            .points
+            .iter()
+            .map(|p| 0.5 * dot(&self.scaled(p), &t).powi(2))
+            .collect();
+        w.push(1.0);
+        w.extend(t);
+        w
+    }
+
+    // Value at `x` of the Lagrange function of point `k`, which is one at
+    // that point and zero at the others.
+    fn lagrange(&self, k: usize, x: &[f64]) -> f64 {
+        dot(self.inverse.row(k), &self.column(x))
+    }
+
+    // Gradient of the Lagrange function of point `k` at the best point.
+    fn lagrange_gradient(&self, k: usize) -> Vec<f64> {
+        let m = self.points.len();
+        self.inverse.row(k)[m + 1..]
+            .iter()
+            .map(|g| g / self.scale)
+            .collect()
+    }
+
+    // Powell's denominators `alpha_k beta + tau_k^2`, proportional to the
+    // determinant of the interpolation matrix after point `k` is replaced
+    // by `x`.
+    fn denominators(&self, x: &[f64]) -> Vec<f64> {
+        let m = self.points.len();
+        let w = self.column(x);
+        let t = self.scaled(x);
+        let beta = 0.5 * dot(&t, &t).powi(2) - dot(&w, &self.inverse.mul_vec(&w));
+        (0..m)
+            .map(|k| self.inverse[(k, k)] * beta + dot(self.inverse.row(k), &w).powi(2))
+            .collect()
+    }
+
+    // Adds to `model` the least-change correction that makes it
+    // interpolate the values.
+    fn update(&self, model: &mut Quadratic) {
+        let (m, n) = (self.points.len(), self.points[0].len());
+        model.rebase(&self.points[self.best]);
+        let residuals: Vec<f64> = self
+            .points
+            .iter()
+            .zip(&self.values)
+            .map(|(p, v)| v - model.value(p))
+            .collect();
+        let coefficients: Vec<f64> = (0..m + n + 1)
+            .map(|i| dot(&self.inverse.row(i)[..m], &residuals))
+            .collect();
+        model.c += coefficients[m];
+        for (g, c) in model.g.iter_mut().zip(&coefficients[m + 1..]) {
+            *g += c / self.scale;
+        }
+        for (p, lambda) in self.points.iter().zip(&coefficients) {
+            let y: Vec<f64> = self.scaled(p).iter().map(|v| v / self.scale).collect();
+            model.h.rank1_update(*lambda, &y, &y);
+        }
+    }
+}
+
+/// Minimizes `f` from `x0` by BOBYQA, without derivatives, optionally within
+/// `bounds`.
+///
+/// Returns [`NumalError::DidNotConverge`] if the evaluation budget runs out
+/// before the trust region shrinks to its final radius.
+pub fn bobyqa<F>(
+    f: F,
+    x0: &[f64],
+    bounds: Option<&Bounds>,
+    opts: &BobyqaOptions,
+) -> Result<Minimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let mut x = bounded_start(x0, bounds)?;
+    let n = x.len();
+    let npt = opts.interpolation_points.unwrap_or(2 * n + 1);
+    if npt < n + 2 || npt > (n + 1) * (n + 2) / 2 {
+        return Err(NumalError::InvalidInput(format!(
+            "number of interpolation points must be between {} and {}, got {npt}",
+            n + 2,
+            (n + 1) * (n + 2) / 2
+        )));
+    }
+    if !(opts.initial_radius > 0.0 && opts.initial_radius.is_finite()) {
+        return Err(NumalError::InvalidInput(format!(
+            "initial radius must be finite and positive, got {}",
+            opts.initial_radius
+        )));
+    }
+    let (lower, upper) = match bounds {
+        Some(b) => (b.lower().to_vec(), b.upper().to_vec()),
+        None => (vec![f64::NEG_INFINITY; n], vec![f64::INFINITY; n]),
+    };
+    let width = lower
+        .iter()
+        .zip(&upper)
+        .map(|(l, u)| u - l)
+        .fold(f64::INFINITY, f64::min);
+    if width <= 0.0 {
+        return Err(NumalError::InvalidInput(
+            "bounds must have positive width in every dimension".to_string(),
+        ));
+    }
+    let mut rho = opts.initial_radius.min(0.5 * width);
+    let rho_end = (opts.tol.eps_abs()
+        + opts.tol.eps_rel() * x.iter().fold(0.0, |m: f64, v| m.max(v.abs())))
+    .min(rho);
+    // Keep the initial points inside the box: coordinates within rho of a
+    // bound are moved onto it or rho away from it
+    for i in 0..n {
+        if x[i] > lower[i] && x[i] < lower[i] + rho {
+            x[i] = lower[i] + rho;
+        } else if x[i] < upper[i] && x[i] > upper[i] - rho {
+            x[i] = upper[i] - rho;
+        }
+    }
+    let mut objective = Budgeted::new(&f, opts.max_evaluations);
+    let (points, values) = initial_points(&mut objective, &x, &lower, &upper, rho, npt)?;
+    let mut interp = Interpolation {
+        points,
+        values,
+        best: 0,
+        scale: 1.0,
+        inverse: Matrix::zeros(0, 0),
+    };
+    interp.best = (0..npt)
+        .min_by(|&i, &j| interp.values[i].total_cmp(&interp.values[j]))
+        .unwrap_or(0);
+    if !interp.factor() {
+        return Err(NumalError::DidNotConverge);
+    }
+    let mut model = Quadratic {
+        base: interp.points[interp.best].clone(),
+        c: 0.0,
+        g: vec![0.0; n],
+        h: Matrix::zeros(n, n),
+    };
+    interp.update(&mut model);
+    let mut delta = rho;
+    let mut iterations = 0;
+    loop {
+        let xopt = interp.points[interp.best].clone();
+        let fopt = interp.values[interp.best];
+        let g = model.gradient(&xopt);
+        let d = trust_region_step(&g, &model.h, &xopt, &lower, &upper, delta);
+        let dnorm = norm(&d);
+        let dist = |p: &[f64]| norm(&p.iter().zip(&xopt).map(|(p, x)| p - x).collect::<Vec<_>>());
+        let farthest = (0..npt)
+            .map(|k| (k, dist(&interp.points[k])))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0));
+        let mut reduce = false;
+        if dnorm < 0.5 * rho {
+            // The step is too short to be worth an evaluation: improve the
+            // geometry if a point is far away, or else reduce rho
+            delta = (0.1 * delta).max(rho);
+            if delta <= 1.5 * rho {
+                delta = rho;
+            }
+            if farthest.1 > 10.0 * rho {
+                move_point(
+                    &mut objective,
+                    &mut interp,
+                    &mut model,
+                    farthest.0,
+                    (0.1 * farthest.1).min(delta).max(rho),
+                    &lower,
+                    &upper,
+                )?;
+            } else {
+                reduce = true;
+            }
+        } else {
+            iterations += 1;
+            let xnew: Vec<f64> = (0..n)
+                .map(|i| (xopt[i] + d[i]).clamp(lower[i], upper[i]))
+                .collect();
+            let fnew = objective.eval_clamped(&xnew)?;
+            let predicted = -(dot(&g, &d) + 0.5 * dot(&d, &model.h.mul_vec(&d)));
+            let ratio = if predicted > 0.0 {
+                (fopt - fnew) / predicted
+            } else {
+                -1.0
+            };
+            delta = if ratio <= 0.1 {
+                (0.5 * delta).min(dnorm)
+            } else if ratio <= 0.7 {
+                (0.5 * delta).max(dnorm)
+            } else {
+                (0.5 * delta).max(2.0 * dnorm)
+            };
+            if delta <= 1.5 * rho {
+                delta = rho;
+            }
+            // Replace the point whose removal keeps the interpolation
+            // system best conditioned, weighting far points up; the best
+            // point stays unless the new one is better
+            let denominators = interp.denominators(&xnew);
+            let knew = (0..npt)
+                .filter(|&k| k != interp.best || fnew < fopt)
+                .map(|k| {
+                    let weight = (dist(&interp.points[k]) / delta).powi(4).max(1.0);
+                    (k, weight * denominators[k].abs())
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((k, score)) = knew
+                && score > 0.0
+            {
+                replace(&mut interp, &mut model, k, xnew, fnew)?;
+            }
+            if ratio < 0.1 {
+                if farthest.1 > (2.0 * delta).max(10.0 * rho) {
+                    move_point(
+                        &mut objective,
+                        &mut interp,
+                        &mut model,
+                        farthest.0,
+                        (0.1 * farthest.1).min(delta).max(rho),
+                        &lower,
+                        &upper,
+                    )?;
+                } else if ratio <= 0.0 && delta.max(dnorm) <= rho {
+                    reduce = true;
+                }
+            }
+        }
+        if reduce {
+            if rho <= rho_end {
+                return Ok(objective.minimum(iterations));
+            }
+            let old = rho;
+            let ratio = rho / rho_end;
+            rho = if ratio <= 16.0 {
+                rho_end
+            } else if ratio <= 250.0 {
+                ratio.sqrt() * rho_end
+            } else {
+                0.1 * rho
+            };
+            delta = (0.5 * old).max(rho);
+        }
+    }
+}
+
+// Evaluates `f` at `x0`, at `x0 +- rho e_i`, stepping `2 rho` instead of
+// `-rho` from a lower bound and `-2 rho` instead of `rho` from an upper one,
+// and then at points displaced along two axes.
+fn initial_points<F: Fn(&[f64]) -> f64>(
+    objective: &mut Budgeted<F>,
+    x0: &[f64],
+    lower: &[f64],
+    upper: &[f64],
+    rho: f64,
+    npt: usize,
+) -> Result<(Vec<Vec<f64>>, Vec<f64>), NumalError> {
+    let n = x0.len();
+    let mut points = vec![x0.to_vec()];
+    let mut values = vec![objective.eval_clamped(x0)?];
+    let first: Vec<f64> = (0..n)
+        .map(|i| if x0[i] >= upper[i] { -rho } else { rho })
+        .collect();
+    let mut second = vec![None; n];
+    let axis = |i: usize, step: f64| {
+        let mut y = x0.to_vec();
+        y[i] += step;
+        y
+    };
+    for (i, &step) in first.iter().enumerate().take(npt - 1) {
+        points.push(axis(i, step));
+    }
+    for i in 0..n.min(npt - 1 - n) {
+        let step = if x0[i] <= lower[i] || x0[i] >= upper[i] {
+            2.0 * first[i]
+        } else {
+            -first[i]
+        };
+        second[i] = Some(step);
+        points.push(axis(i, step));
+    }
+    for p in &points[1..] {
+        values.push(objective.eval_clamped(p)?);
+    }
+    // Each further point takes, along each of its two axes, the step of
+    // the better of the two points on that axis
+    let best_step = |i: usize, values: &[f64]| match second[i] {
+        Some(s) if values[n + 1 + i] < values[1 + i] => s,
+        _ => first[i],
+    };
+    let pairs = (1..n).flat_map(|gap| (0..n - gap).map(move |p| (p, p + gap)));
+    for (p, q) in pairs.take(npt.saturating_sub(2 * n + 1)) {
+        let mut y = x0.to_vec();
+        y[p] += best_step(p, &values);
+        y[q] += best_step(q, &values);
+        values.push(objective.eval_clamped(&y)?);
+        points.push(y);
+    }
+    Ok((points, values))
+}
+
+// Puts `x` with value `fx` in place of point `k` and updates the model.
+fn replace(
+    interp: &mut Interpolation,
+    model: &mut Quadratic,
+    k: usize,
+    x: Vec<f64>,
+    fx: f64,
+) -> Result<(), NumalError> {
+    interp.points[k] = x;
+    let old = std::mem::replace(&mut interp.values[k], fx);
+    if fx < interp.values[interp.best] {
+        interp.best = k;
+    }
+    if !interp.factor() {
+        return Err(NumalError::DidNotConverge);
+    }
+    if old >= HUGE {
+        // The least-change update would keep the curvature fitted to the
+        // clamped value, so the model is built afresh
+        model.c = 0.0;
+        model.g.fill(0.0);
+        model.h = Matrix::zeros(model.g.len(), model.g.len());
+    }
+    interp.update(model);
+    Ok(())
+}
+
+// Moves point `k` to where its Lagrange function is largest within the
+// bounds and `radius` of the best point, trying the lines through the best
+// point and the other points and the direction of the gradient, as in
+// Powell's ALTMOV. Far points move by a tenth of their distance, clamped to
+// [rho, delta].
+fn move_point<F: Fn(&[f64]) -> f64>(
+    objective: &mut Budgeted<F>,
+    interp: &mut Interpolation,
+    model: &mut Quadratic,
+    k: usize,
+    radius: f64,
+    lower: &[f64],
+    upper: &[f64],
+) -> Result<(), NumalError> {
+    let xopt = interp.points[interp.best].clone();
+    let n = xopt.len();
+    // Range of t keeping xopt + t u within the ball and the bounds
+    let range = |u: &[f64]| {
+        let r = radius / norm(u);
+        let (mut lo, mut hi) = (-r, r);
+        for i in 0..n {
+            if u[i] > 0.0 {
+                hi = hi.min((upper[i] - xopt[i]) / u[i]);
+                lo = lo.max((lower[i] - xopt[i]) / u[i]);
+            } else if u[i] < 0.0 {
+                hi = hi.min((lower[i] - xopt[i]) / u[i]);
+                lo = lo.max((upper[i] - xopt[i]) / u[i]);
+            }
+        }
+        (lo, hi)
+    };
+    let along = |u: &[f64], t: f64| -> Vec<f64> {
+        (0..n)
+            .map(|i| (xopt[i] + t * u[i]).clamp(lower[i], upper[i]))
+            .collect()
+    };
+    let mut directions: Vec<Vec<f64>> = interp
+        .points
+        .iter()
+        .enumerate()
+        .filter(|&(j, _)| j != interp.best)
+        .map(|(_, p)| p.iter().zip(&xopt).map(|(p, x)| p - x).collect())
+        .collect();
+    directions.push(interp.lagrange_gradient(k));
+    let mut best: Option<(Vec<f64>, f64)> = None;
+    for u in directions.iter().filter(|u| norm(u) > 0.0) {
+        let (lo, hi) = range(u);
+        // The Lagrange function is quadratic along the line, with its
+        // extremum, if any, where the derivative vanishes
+        let (l0, l1, lm) = (
+            interp.lagrange(k, &xopt),
+            interp.lagrange(k, &along(u, 1.0)),
+            interp.lagrange(k, &along(u, -1.0)),
+        );
+        let (a, b) = (0.5 * (l1 - lm), 0.5 * (l1 + lm) - l0);
+        let mut ts = vec![lo, hi];
+        if b != 0.0 && (lo..=hi).contains(&(-a / (2.0 * b))) {
+            ts.push(-a / (2.0 * b));
+        }
+        for t in ts {
+            let y = along(u, t);
+            let value = interp.lagrange(k, &y).abs();
+            if best.as_ref().is_none_or(|(_, v)| value > *v) {
+                best = Some((y, value));
+            }
+        }
+    }
+    let Some((y, _)) = best else {
+        return Err(NumalError::DidNotConverge);
+    };
+    let fy = objective.eval_clamped(&y)?;
+    replace(interp, model, k, y, fy)
+}
+
+// Approximately minimizes `g d + d h d / 2` subject to `||d|| <= delta` and
+// `lower <= x + d <= upper` by conjugate gradients on the free variables,
+// fixing a variable and restarting when the step reaches its bound and
+// stopping at the trust-region boundary, as in Powell's TRSBOX.
+fn trust_region_step(
+    g: &[f64],
+    h: &Matrix,
+    x: &[f64],
+    lower: &[f64],
+    upper: &[f64],
+    delta: f64,
+) -> Vec<f64> {
+    let n = g.len();
+    let mut d = vec![0.0; n];
+    let mut fixed: Vec<bool> = (0..n)
+        .map(|i| (x[i] <= lower[i] && g[i] >= 0.0) || (x[i] >= upper[i] && g[i] <= 0.0))
+        .collect();
+    'restart: loop {
+        let grad: Vec<f64> = g.iter().zip(h.mul_vec(&d)).map(|(g, hd)| g + hd).collect();
+        let mut r: Vec<f64> = (0..n)
+            .map(|i| if fixed[i] { 0.0 } else { -grad[i] })
+            .collect();
+        let mut s = r.clone();
+        let mut rr = dot(&r, &r);
+        let rr0 = rr;
+        for _ in 0..n {
+            if rr <= 1e-8 * rr0 || rr == 0.0 {
+                break 'restart;
+            }
+            let hs = h.mul_vec(&s);
+            let (ss, ds, dd) = (dot(&s, &s), dot(&d, &s), dot(&d, &d));
+            let room = (delta * delta - dd).max(0.0);
+            let boundary = room / (ds + (ds * ds + ss * room).sqrt()).max(f64::MIN_POSITIVE);
+            let curvature = dot(&s, &hs);
+            let mut alpha = if curvature > 0.0 {
+                (rr / curvature).min(boundary)
+            } else {
+                boundary
+            };
+            let mut blocking = None;
+            for i in (0..n).filter(|&i| !fixed[i] && s[i] != 0.0) {
+                let limit = if s[i] > 0.0 {
+                    upper[i] - x[i] - d[i]
+                } else {
+                    lower[i] - x[i] - d[i]
+                } / s[i];
+                if limit < alpha {
+                    (alpha, blocking) = (limit.max(0.0), Some(i));
+                }
+            }
+            for (di, si) in d.iter_mut().zip(&s) {
+                *di += alpha * si;
+            }
+            if let Some(i) = blocking {
+                d[i] = if s[i] > 0.0 { upper[i] } else { lower[i] } - x[i];
+                fixed[i] = true;
+                continue 'restart;
+            }
+            if alpha == boundary {
+                break 'restart;
+            }
+            for i in (0..n).filter(|&i| !fixed[i]) {
+                r[i] -= alpha * hs[i];
+            }
+            let rr_next = dot(&r, &r);
+            for i in 0..n {
+                s[i] = r[i] + rr_next / rr * s[i];
+            }
+            rr = rr_next;
+        }
+        break;
+    }
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::rosenbrock;
+
+    #[test]
+    fn minimizes_rosenbrock() {
+        let opts = BobyqaOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = bobyqa(rosenbrock, &[-1.2, 1.0], None, &opts).unwrap();
+        assert!(r.x.iter().all(|v| (v - 1.0).abs() < 1e-6), "{r:?}");
+    }
+
+    #[test]
+    fn every_number_of_interpolation_points_works() {
+        let f = |x: &[f64]| {
+            x.iter()
+                .enumerate()
+                .map(|(i, v)| (i + 1) as f64 * (v - 1.0).powi(2))
+                .sum::<f64>()
+                + x[0] * x[2]
+        };
+        // The minimizer solves the linear system of the gradient
+        let expected = [6.0 / 11.0, 1.0, 10.0 / 11.0];
+        for npt in [5, 7, 10] {
+            let opts = BobyqaOptions {
+                interpolation_points: Some(npt),
+                ..Default::default()
+            };
+            let r = bobyqa(f, &[0.0; 3], None, &opts).unwrap();
+            let err =
+                r.x.iter()
+                    .zip(expected)
+                    .map(|(x, e)| (x - e).abs())
+                    .fold(0.0, f64::max);
+            assert!(err < 1e-5, "{npt}: {r:?}");
+        }
+    }
+
+    #[test]
+    fn bounds_are_respected() {
+        // The unconstrained minimum (1, 1, 1, 1) lies outside the box
+        let bounds = Bounds::new(vec![-2.0; 4], vec![0.5, 2.0, 2.0, 2.0]).unwrap();
+        let f = |x: &[f64]| {
+            assert!(x[0] <= 0.5, "evaluated outside the bounds at {x:?}");
+            rosenbrock(x)
+        };
+        let r = bobyqa(f, &[0.4, 0.0, 0.0, 0.0], Some(&bounds), &Default::default()).unwrap();
+        assert_eq!(r.x[0], 0.5, "{r:?}");
+        assert!((r.x[1] - 0.26221).abs() < 1e-4, "{r:?}");
+    }
+
+    #[test]
+    fn uses_fewer_evaluations_than_nelder_mead() {
+        let x0 = [-1.2, 1.0, -1.2, 1.0];
+        let r = bobyqa(rosenbrock, &x0, None, &Default::default()).unwrap();
+        let nm = crate::optimize::nelder_mead(rosenbrock, &x0, &Default::default()).unwrap();
+        assert!(r.fx < 1e-8, "{r:?}");
+        assert!(
+            r.evaluations < nm.evaluations,
+            "{} vs {}",
+            r.evaluations,
+            nm.evaluations
+        );
+    }
+
+    #[test]
+    fn nan_region_is_avoided() {
+        let f = |x: &[f64]| {
+            if x[0] < 0.0 {
+                f64::NAN
+            } else {
+                (x[0] - 0.5).powi(2) + (x[1] - 1.0).powi(2)
+            }
+        };
+        let r = bobyqa(f, &[0.2, 0.0], None, &Default::default()).unwrap();
+        assert!(
+            (r.x[0] - 0.5).abs() < 1e-4 && (r.x[1] - 1.0).abs() < 1e-4,
+            "{r:?}"
+        );
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        let opts = BobyqaOptions {
+            interpolation_points: Some(3),
+            ..Default::default()
+        };
+        let r = bobyqa(rosenbrock, &[0.0, 0.0], None, &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let flat = Bounds::new(vec![0.0, 1.0], vec![1.0, 1.0]).unwrap();
+        let r = bobyqa(rosenbrock, &[0.0, 1.0], Some(&flat), &Default::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
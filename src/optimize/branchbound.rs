@@ -0,0 +1,398 @@
+//! Interval branch and bound (Moore, 1966; Skelboe, 1974; Hansen, 1992).
+//!
+//! The objective is supplied as an interval extension: a function mapping a
+//! box of [`Interval`]s to an interval containing the range of the objective
+//! over it, usually the same expression evaluated in interval arithmetic.
+//! Boxes wait in a priority queue ordered by the lower bound of their
+//! enclosure, and the box with the least lower bound is bisected along its
+//! widest side. Evaluating the extension at the midpoint of each new box
+//! gives a guaranteed upper bound on the global minimum, and boxes whose
+//! lower bound exceeds it are discarded. The least lower bound in the queue
+//! is a guaranteed lower bound, so the search ends with a certified
+//! enclosure of the global minimum once the two bounds are within
+//! [`Tolerance`] of each other; the boxes still in the queue then contain
+//! every global minimizer.
+//!
+//! The natural extension overestimates the range by an amount proportional
+//! to the width of the box, so near a minimizer the lower bounds converge
+//! slowly and, in more than one dimension, the boxes within tolerance of the
+//! minimum multiply as the tolerance shrinks. Given an interval enclosure of
+//! the gradient as well, [`branch_and_bound_grad`] also bounds the range by
+//! the mean-value form `f(m) + g(B) (B - m)`, whose overestimation is
+//! quadratic in the width, and applies the monotonicity test: a box over
+//! which the objective is monotone in some variable holds no minimizer
+//! unless it touches the bounds, and then only on that face.
+//!
+//! The guarantee is only as good as the extensions: they must contain the
+//! range of the objective, and of its gradient, over every box they are
+//! given. The work grows exponentially with the dimension, so the method
+//! suits problems with a handful of variables.
+
+use super::Bounds;
+use crate::NumalError;
+use crate::core::interval::Interval;
+use crate::core::tolerance::{Tolerance, is_close};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Options for [`branch_and_bound`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BranchBoundOptions {
+    /// Tolerance between the lower and upper bounds on the minimum
+    pub tol: Tolerance,
+    /// Maximum number of bisections
+    pub max_iter: usize,
+}
+
+impl Default for BranchBoundOptions {
+    fn default() -> Self {
+        BranchBoundOptions {
+            tol: Tolerance::Default,
+            max_iter: 100_000,
+        }
+    }
+}
+
+/// Outcome of a successful interval branch and bound
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntervalMinimum {
+    /// Interval certified to contain the global minimum
+    pub enclosure: Interval,
+    /// Midpoint of the box that gave the upper bound of `enclosure`
+    pub x: Vec<f64>,
+    /// Boxes not excluded, which together contain every global minimizer
+    pub boxes: Vec<Vec<Interval>>,
+    /// Number of bisections performed
+    pub iterations: usize,
+    /// Number of evaluations of the interval extension
+    pub evaluations: usize,
+}
+
+// A box in the queue, ordered so that the least lower bound comes first.
+struct Candidate {
+    lower: f64,
+    region: Vec<Interval>,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.lower.total_cmp(&self.lower)
+    }
+}
+
+/// Encloses the global minimum of the objective over the finite box
+/// `bounds`, given its interval extension `f`.
+///
+/// Boxes on which the extension is empty, where the objective is undefined,
+/// are discarded. Returns [`NumalError::InvalidInput`] if that leaves
+/// nothing, and [`NumalError::DidNotConverge`] if the bounds are not within
+/// tolerance after `max_iter` bisections.
+pub fn branch_and_bound<F>(
+    f: F,
+    bounds: &Bounds,
+    opts: &BranchBoundOptions,
+) -> Result<IntervalMinimum, NumalError>
+where
+    F: Fn(&[Interval]) -> Interval,
+{
+    search(
+        &|x: &[Interval], _: &mut [Interval]| f(x),
+        false,
+        bounds,
+        opts,
+    )
+}
+
+/// Like [`branch_and_bound`], with `fg(x, g)` returning the interval
+/// extension of the objective over the box `x` and writing an enclosure of
+/// its gradient over the box into `g`.
+pub fn branch_and_bound_grad<F>(
+    fg: F,
+    bounds: &Bounds,
+    opts: &BranchBoundOptions,
+) -> Result<IntervalMinimum, NumalError>
+where
+    F: Fn(&[Interval], &mut [Interval]) -> Interval,
+{
+    search(&fg, true, bounds, opts)
+}
+
+fn search(
+    fg: &dyn Fn(&[Interval], &mut [Interval]) -> Interval,
+    gradient: bool,
+    bounds: &Bounds,
+    opts: &BranchBoundOptions,
+) -> Result<IntervalMinimum, NumalError> {
+    let n = bounds.dim();
+    if n == 0 || !bounds.is_finite() {
+        return Err(NumalError::InvalidInput(
+            "interval branch and bound requires finite bounds in at least one dimension"
+                .to_string(),
+        ));
+    }
+    let root: Vec<Interval> = bounds
+        .lower()
+        .iter()
+        .zip(bounds.upper())
+        .map(|(&l, &u)| Interval::new(l, u))
+        .collect();
+    let mut state = State {
+        fg,
+        gradient,
+        root: root.clone(),
+        evaluations: 0,
+        upper: f64::INFINITY,
+        x: Vec::new(),
+    };
+    let mut queue = BinaryHeap::new();
+    let mut region = root;
+    if let Some(lower) = state.lower_bound(&mut region) {
+        queue.push(Candidate { lower, region });
+    }
+    for iter in 0..opts.max_iter {
+        let Some(best) = queue.pop() else {
+            return Err(NumalError::InvalidInput(
+                "interval extension is empty over the whole box".to_string(),
+            ));
+        };
+        let upper = state.upper;
+        if is_close(best.lower, upper, opts.tol).is_ok() {
+            let mut boxes = vec![best.region];
+            boxes.extend(
+                queue
+                    .into_iter()
+                    .filter(|c| c.lower <= upper)
+                    .map(|c| c.region),
+            );
+            return Ok(IntervalMinimum {
+                enclosure: Interval::new(best.lower, upper),
+                x: state.x,
+                boxes,
+                iterations: iter,
+                evaluations: state.evaluations,
+            });
+        }
+        let widest = (0..n)
+            .max_by(|&a, &b| best.region[a].width().total_cmp(&best.region[b].width()))
+            .unwrap_or(0);
+        let side = best.region[widest];
+        let mid = side.mid();
+        for half in [Interval::new(side.lo, mid), Interval::new(mid, side.hi)] {
+            let mut region = best.region.clone();
+            region[widest] = half;
+            if let Some(lower) = state.lower_bound(&mut region)
+                && lower <= state.upper
+            {
+                queue.push(Candidate { lower, region });
+            }
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+// The extensions with an evaluation count, and the least upper bound on the
+// minimum found so far with the point that gave it.
+struct State<'a> {
+    fg: &'a dyn Fn(&[Interval], &mut [Interval]) -> Interval,
+    gradient: bool,
+    root: Vec<Interval>,
+    evaluations: usize,
+    upper: f64,
+    x: Vec<f64>,
+}
+
+impl State<'_> {
+    fn eval(&mut self, region: &[Interval], g: &mut [Interval]) -> Interval {
+        self.evaluations += 1;
+        g.fill(Interval::ENTIRE);
+        (self.fg)(region, g)
+    }
+
+    // Lower bound on the objective over `region`, or `None` if the region
+    // holds no global minimizer. Lowers the upper bound with the value at
+    // the midpoint, and may shrink the region to a face of the outer box.
+    fn lower_bound(&mut self, region: &mut [Interval]) -> Option<f64> {
+        let n = region.len();
+        let mut g = vec![Interval::ENTIRE; n];
+        let mut scratch = vec![Interval::ENTIRE; n];
+        loop {
+            let range = self.eval(region, &mut g);
+            if range.is_empty() {
+                return None;
+            }
+            let mid: Vec<f64> = region.iter().map(|v| v.mid()).collect();
+            let point: Vec<Interval> = mid.iter().map(|&m| Interval::point(m)).collect();
+            let at_mid = self.eval(&point, &mut scratch);
+            if at_mid.hi < self.upper {
+                (self.upper, self.x) = (at_mid.hi, mid.clone());
+            }
+            if !self.gradient {
+                return Some(range.lo);
+            }
+            // Monotonicity test
+            let mut shrunk = false;
+            for i in 0..n {
+                let side = region[i];
+                let face = if g[i].lo > 0.0 {
+                    side.lo
+                } else if g[i].hi < 0.0 {
+                    side.hi
+                } else {
+                    continue;
+                };
+                if face != self.root[i].lo && face != self.root[i].hi {
+                    return None;
+                }
+                if side.width() > 0.0 {
+                    region[i] = Interval::point(face);
+                    shrunk = true;
+                }
+            }
+            if shrunk {
+                continue;
+            }
+            let mean_value = (0..n).fold(at_mid, |acc, i| acc + g[i] * (region[i] - mid[i]));
+            return Some(range.lo.max(mean_value.lo));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::cube;
+
+    // Six-hump camel: global minimum -1.0316284535 at (0.0898, -0.7126) and
+    // (-0.0898, 0.7126), with four other local minima
+    fn camel(x: &[Interval]) -> Interval {
+        let (u, v) = (x[0], x[1]);
+        4.0 * u.sqr() - 2.1 * u.powi(4) + u.powi(6) / 3.0 + u * v - 4.0 * v.sqr() + 4.0 * v.powi(4)
+    }
+
+    fn camel_grad(x: &[Interval], g: &mut [Interval]) -> Interval {
+        let (u, v) = (x[0], x[1]);
+        g[0] = 8.0 * u - 8.4 * u.powi(3) + 2.0 * u.powi(5) + v;
+        g[1] = u - 8.0 * v + 16.0 * v.powi(3);
+        camel(x)
+    }
+
+    #[test]
+    fn encloses_minimum_of_six_hump_camel() {
+        let r = branch_and_bound_grad(camel_grad, &cube(2, 3.0), &BranchBoundOptions::default())
+            .unwrap();
+        let f_star = -1.031628453489877;
+        assert!(r.enclosure.contains(f_star), "{r:?}");
+        assert!(r.enclosure.width() <= 1e-8 + 1e-6 * f_star.abs());
+        // Both minimizers lie in the remaining boxes
+        for m in [
+            [0.08984201368, -0.7126564032],
+            [-0.08984201368, 0.7126564032],
+        ] {
+            assert!(
+                r.boxes
+                    .iter()
+                    .any(|b| b[0].contains(m[0]) && b[1].contains(m[1])),
+                "{m:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn gradient_avoids_cluster_of_boxes() {
+        let opts = BranchBoundOptions {
+            tol: Tolerance::Custom {
+                eps_abs: 1e-2,
+                eps_rel: 0.0,
+            },
+            ..Default::default()
+        };
+        let plain = branch_and_bound(camel, &cube(2, 3.0), &opts).unwrap();
+        let grad = branch_and_bound_grad(camel_grad, &cube(2, 3.0), &opts).unwrap();
+        assert!(plain.enclosure.contains(-1.031628453489877), "{plain:?}");
+        assert!(
+            grad.iterations < plain.iterations / 4,
+            "{} {}",
+            grad.iterations,
+            plain.iterations
+        );
+    }
+
+    #[test]
+    fn encloses_minimum_with_elementary_functions() {
+        // sin(x) + sin(10x/3) on [2.7, 7.5], minimum at x = 5.145735
+        let f = |x: &[Interval]| x[0].sin() + (x[0] * (10.0 / 3.0)).sin();
+        let bounds = Bounds::new(vec![2.7], vec![7.5]).unwrap();
+        let opts = BranchBoundOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = branch_and_bound(f, &bounds, &opts).unwrap();
+        let x_star: f64 = 5.145735285;
+        let f_star = x_star.sin() + (10.0 * x_star / 3.0).sin();
+        assert!(r.enclosure.contains(f_star), "{r:?}");
+        assert!((r.x[0] - x_star).abs() < 1e-5, "{r:?}");
+    }
+
+    #[test]
+    fn monotone_objective_is_reduced_to_a_face() {
+        // Increasing in x, so the minimum lies on the face x = -2
+        let fg = |x: &[Interval], g: &mut [Interval]| {
+            g[0] = x[0].exp();
+            g[1] = 2.0 * (x[1] + 0.5);
+            x[0].exp() + (x[1] + 0.5).sqr()
+        };
+        let r = branch_and_bound_grad(fg, &cube(2, 2.0), &BranchBoundOptions::default()).unwrap();
+        assert!(r.enclosure.contains((-2f64).exp()), "{r:?}");
+        assert!(
+            r.boxes
+                .iter()
+                .all(|b| b[0] == Interval::point(-2.0) && b[1].contains(-0.5))
+        );
+        assert_eq!(r.x[0], -2.0);
+    }
+
+    #[test]
+    fn undefined_region_is_discarded() {
+        // Defined only for x >= 1, where the minimum is at x = 1
+        let f = |x: &[Interval]| (x[0] - 1.0).sqrt() + x[0];
+        let bounds = Bounds::new(vec![-4.0], vec![4.0]).unwrap();
+        let r = branch_and_bound(f, &bounds, &BranchBoundOptions::default()).unwrap();
+        assert!(r.enclosure.contains(1.0), "{r:?}");
+        let nowhere = |x: &[Interval]| (x[0] - 10.0).sqrt();
+        let r = branch_and_bound(nowhere, &bounds, &BranchBoundOptions::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn exhausted_budget_did_not_converge() {
+        let opts = BranchBoundOptions {
+            max_iter: 10,
+            ..Default::default()
+        };
+        assert_eq!(
+            branch_and_bound(camel, &cube(2, 3.0), &opts),
+            Err(NumalError::DidNotConverge)
+        );
+    }
+
+    #[test]
+    fn unbounded_box_is_rejected() {
+        let unbounded = Bounds::new(vec![0.0], vec![f64::INFINITY]).unwrap();
+        let r = branch_and_bound(camel, &unbounded, &BranchBoundOptions::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,256 @@
+//! Nonlinear conjugate-gradient methods.
+//!
+//! Each iteration searches along `d = -g + beta d_prev`, where the choice of
+//! `beta` distinguishes the variants. Only a handful of vectors are stored,
+//! which makes these methods suited to large problems where even L-BFGS
+//! history is too expensive. The direction is reset to steepest descent
+//! every `restart` iterations, whenever successive gradients lose
+//! orthogonality (Powell's criterion) and whenever `d` fails to be a
+//! descent direction.
+
+use super::linesearch::LineSearch;
+use super::{
+    GradientMinimum, Termination, gradient_minimum, gradient_termination, initial_gradient,
+};
+use crate::NumalError;
+use crate::core::linalg::{dot, norm};
+use crate::core::tolerance::Tolerance;
+
+// Powell's restart threshold on |g·g_prev| / |g|^2.
+const ORTHOGONALITY: f64 = 0.2;
+// Lower-bound parameter of the Hager-Zhang truncation.
+const HZ_ETA: f64 = 0.01;
+
+/// Formula for the conjugate-gradient parameter `beta`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CgBeta {
+    /// Fletcher-Reeves: `|g|^2 / |g_prev|^2`
+    FletcherReeves,
+    /// Polak-Ribière, truncated at zero: `max(0, g·y / |g_prev|^2)`
+    #[default]
+    PolakRibierePlus,
+    /// Hestenes-Stiefel: `g·y / d·y`
+    HestenesStiefel,
+    /// Dai-Yuan: `|g|^2 / d·y`
+    DaiYuan,
+    /// Hager-Zhang (CG_DESCENT), with its lower truncation
+    HagerZhang,
+}
+
+/// Options for [`nonlinear_cg`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CgOptions {
+    /// Gradient, step and function-change tolerance
+    pub tol: Tolerance,
+    pub max_iter: usize,
+    pub beta: CgBeta,
+    /// Iterations between steepest-descent restarts; the dimension of the
+    /// problem if `None`
+    pub restart: Option<usize>,
+    /// Conjugate-gradient directions need a fairly exact line search, hence
+    /// the small curvature constant of the default
+    pub line_search: LineSearch,
+}
+
+impl Default for CgOptions {
+    fn default() -> Self {
+        CgOptions {
+            tol: Tolerance::Default,
+            max_iter: 10_000,
+            beta: CgBeta::default(),
+            restart: None,
+            line_search: LineSearch::StrongWolfe {
+                c1: 1e-4,
+                c2: 0.1,
+                max_iter: 30,
+            },
+        }
+    }
+}
+
+/// Minimizes `fg` with a nonlinear conjugate-gradient method.
+///
+/// `fg(x, g)` returns `f(x)` and writes the gradient into `g`.
+pub fn nonlinear_cg<F>(fg: F, x0: &[f64], opts: &CgOptions) -> Result<GradientMinimum, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    let restart = opts.restart.unwrap_or(x0.len());
+    if restart == 0 {
+        return Err(NumalError::InvalidInput(
+            "restart interval must be at least 1".to_string(),
+        ));
+    }
+    let (mut f, mut g) = initial_gradient(&fg, x0)?;
+    let mut x = x0.to_vec();
+    let mut evals = 1;
+    if g.iter().all(|v| v.abs() <= opts.tol.eps_abs()) {
+        return Ok(gradient_minimum(
+            x,
+            f,
+            g,
+            0,
+            evals,
+            Termination::GradientNorm,
+            None,
+        ));
+    }
+    let mut d: Vec<f64> = g.iter().map(|v| -v).collect();
+    let mut step = cautious_step(&g);
+    let mut since_restart = 0;
+    for iter in 1..=opts.max_iter {
+        let ls = match opts.line_search.search(&fg, &x, f, &g, &d, step) {
+            // The conjugate direction or the extrapolated step can be poorly
+            // scaled; retry once along steepest descent with a cautious step
+            Err(NumalError::LineSearchFailed { .. })
+                if since_restart > 0 || step != cautious_step(&g) =>
+            {
+                d.iter_mut().zip(&g).for_each(|(di, gi)| *di = -gi);
+                since_restart = 0;
+                opts.line_search
+                    .search(&fg, &x, f, &g, &d, cautious_step(&g))?
+            }
+            r => r?,
+        };
+        evals += ls.evaluations;
+        let (x_old, f_old) = (std::mem::replace(&mut x, ls.x), f);
+        let g_old = std::mem::replace(&mut g, ls.grad);
+        f = ls.fx;
+        if let Some(reason) = gradient_termination(&g, &x_old, &x, f_old, f, opts.tol) {
+            return Ok(gradient_minimum(x, f, g, iter, evals, reason, None));
+        }
+
+        since_restart += 1;
+        let gg = dot(&g, &g);
+        let reset = since_restart >= restart || dot(&g, &g_old).abs() >= ORTHOGONALITY * gg;
+        let beta = if reset {
+            0.0
+        } else {
+            beta(opts.beta, &g, &g_old, &d)
+        };
+        let dphi_old = dot(&g_old, &d);
+        for (di, gi) in d.iter_mut().zip(&g) {
+            *di = beta * *di - gi;
+        }
+        let mut dphi = dot(&g, &d);
+        if beta == 0.0 || dphi >= 0.0 || !dphi.is_finite() {
+            d.iter_mut().zip(&g).for_each(|(di, gi)| *di = -gi);
+            dphi = -gg;
+            since_restart = 0;
+        }
+        // Nocedal & Wright (3.60): assume the first-order change along the
+        // new direction matches that of the last step
+        step = ls.step * dphi_old / dphi;
+        if !(step > 0.0 && step.is_finite()) {
+            step = cautious_step(&g);
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+// A unit step, shortened when the gradient is large.
+fn cautious_step(g: &[f64]) -> f64 {
+    (1.0 / norm(g)).min(1.0)
+}
+
+// The conjugacy parameter for the new gradient `g`, the previous gradient
+// `g_old` and the previous direction `d`. Non-finite values, which arise
+// when `d·y` vanishes, are mapped to zero so the caller restarts.
+fn beta(formula: CgBeta, g: &[f64], g_old: &[f64], d: &[f64]) -> f64 {
+    let y: Vec<f64> = g.iter().zip(g_old).map(|(a, b)| a - b).collect();
+    let (gg, gy, dy) = (dot(g, g), dot(g, &y), dot(d, &y));
+    let beta = match formula {
+        CgBeta::FletcherReeves => gg / dot(g_old, g_old),
+        CgBeta::PolakRibierePlus => (gy / dot(g_old, g_old)).max(0.0),
+        CgBeta::HestenesStiefel => gy / dy,
+        CgBeta::DaiYuan => gg / dy,
+        CgBeta::HagerZhang => {
+            let beta = (gy - 2.0 * dot(&y, &y) * dot(d, g) / dy) / dy;
+            let eta = -1.0 / (norm(d) * HZ_ETA.min(norm(g_old)));
+            beta.max(eta)
+        }
+    };
+    if beta.is_finite() { beta } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::rosenbrock_grad;
+
+    #[test]
+    fn every_formula_minimizes_rosenbrock() {
+        for beta in [
+            CgBeta::FletcherReeves,
+            CgBeta::PolakRibierePlus,
+            CgBeta::HestenesStiefel,
+            CgBeta::DaiYuan,
+            CgBeta::HagerZhang,
+        ] {
+            let opts = CgOptions {
+                tol: Tolerance::Strict,
+                beta,
+                ..Default::default()
+            };
+            let r = nonlinear_cg(rosenbrock_grad, &[-1.2, 1.0, -1.2, 1.0], &opts).unwrap();
+            assert!(
+                r.x.iter().all(|v| (v - 1.0).abs() < 1e-5),
+                "{beta:?}: {r:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn quadratic_converges_in_n_steps() {
+        // With exact line searches CG minimizes an n-dimensional quadratic
+        // in at most n iterations
+        let diag = [1.0, 2.0, 5.0, 10.0];
+        let fg = |x: &[f64], g: &mut [f64]| {
+            let mut f = 0.0;
+            for ((gi, xi), a) in g.iter_mut().zip(x).zip(diag) {
+                *gi = a * xi;
+                f += 0.5 * a * xi * xi;
+            }
+            f
+        };
+        let opts = CgOptions {
+            restart: Some(100),
+            line_search: LineSearch::StrongWolfe {
+                c1: 1e-7,
+                c2: 1e-6,
+                max_iter: 50,
+            },
+            ..Default::default()
+        };
+        let r = nonlinear_cg(fg, &[1.0, 1.0, 1.0, 1.0], &opts).unwrap();
+        assert!(r.iterations <= 5, "{r:?}");
+        assert_eq!(r.termination, Termination::GradientNorm);
+    }
+
+    #[test]
+    fn hager_zhang_line_search_pairing() {
+        let opts = CgOptions {
+            tol: Tolerance::Strict,
+            beta: CgBeta::HagerZhang,
+            line_search: LineSearch::hager_zhang(),
+            ..Default::default()
+        };
+        let x0: Vec<f64> = (0..50)
+            .map(|i| if i % 2 == 0 { -1.2 } else { 1.0 })
+            .collect();
+        let r = nonlinear_cg(rosenbrock_grad, &x0, &opts).unwrap();
+        assert!(r.x.iter().all(|v| (v - 1.0).abs() < 1e-5), "{r:?}");
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let opts = CgOptions {
+            restart: Some(0),
+            ..Default::default()
+        };
+        let r = nonlinear_cg(rosenbrock_grad, &[0.0, 0.0], &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = nonlinear_cg(rosenbrock_grad, &[], &CgOptions::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
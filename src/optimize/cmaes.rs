@@ -0,0 +1,338 @@
+//! Covariance matrix adaptation evolution strategy with increasing
+//! population restarts (IPOP-CMA-ES).
+//!
+//! Each generation samples `lambda` points from `N(m, sigma^2 C)`, moves the
+//! mean to a weighted average of the better half, and adapts `C` and `sigma`
+//! from the steps that were selected, with the default parameters of Hansen
+//! (2016). A run ends when every point of a generation is within
+//! [`Tolerance`] of the best one component-wise and in value, or stops making
+//! progress. Restarts follow Auger & Hansen (2005): each doubles the
+//! population and starts from a random point, uniform in the box if the
+//! bounds are finite and near the initial guess otherwise; the best point of
+//! all runs is returned.
+//!
+//! Samples outside the bounds are drawn again, and projected onto the box if
+//! they keep falling outside. Objective values that are NaN count as `+inf`.
+
+use super::{Bounds, Budgeted, Minimum, bounded_start, population_converged};
+use crate::NumalError;
+use crate::core::linalg::{Matrix, Svd, dot, norm};
+use crate::core::rng::Rng;
+use crate::core::tolerance::Tolerance;
+
+// Samples drawn before an infeasible point is projected, and the largest
+// condition number of C a run tolerates.
+const RESAMPLES: usize = 10;
+const MAX_CONDITION: f64 = 1e14;
+
+/// Options for [`cmaes`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CmaEsOptions {
+    /// Initial step size, about a quarter of the width of the region
+    /// expected to contain the minimum
+    pub sigma: f64,
+    /// Population of the first run, at least 2; `None` for `4 + 3 ln n`
+    pub population: Option<usize>,
+    /// Maximum number of restarts, each with twice the population of the
+    /// previous run
+    pub restarts: usize,
+    /// Population-spread tolerance
+    pub tol: Tolerance,
+    /// Evaluation budget shared by all runs
+    pub max_evaluations: usize,
+    /// Seed of the random number generator; equal seeds give equal runs
+    pub seed: u64,
+}
+
+impl Default for CmaEsOptions {
+    fn default() -> Self {
+        CmaEsOptions {
+            sigma: 0.5,
+            population: None,
+            restarts: 9,
+            tol: Tolerance::Default,
+            max_evaluations: 100_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Minimizes `f` by IPOP-CMA-ES, starting from `x0` and optionally within
+/// `bounds`.
+///
+/// Returns [`NumalError::DidNotConverge`] if the evaluation budget runs out
+/// before any run converges.
+pub fn cmaes<F>(
+    f: F,
+    x0: &[f64],
+    bounds: Option<&Bounds>,
+    opts: &CmaEsOptions,
+) -> Result<Minimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let start = bounded_start(x0, bounds)?;
+    let n = x0.len();
+    if !(opts.sigma > 0.0 && opts.sigma.is_finite()) {
+        return Err(NumalError::InvalidInput(format!(
+            "initial step size must be finite and positive, got {}",
+            opts.sigma
+        )));
+    }
+    let mut lambda = opts
+        .population
+        .unwrap_or(4 + (3.0 * (n as f64).ln()).floor() as usize);
+    if lambda < 2 {
+        return Err(NumalError::InvalidInput(format!(
+            "population must have at least 2 members, got {lambda}"
+        )));
+    }
+    let mut rng = Rng::new(opts.seed);
+    let mut objective = Budgeted::new(&f, opts.max_evaluations);
+    let mut generations = 0;
+    let mut converged = false;
+    let mut mean = start;
+    for restart in 0..=opts.restarts {
+        if restart > 0 {
+            lambda *= 2;
+            mean = match bounds {
+                Some(b) if b.is_finite() => (0..n)
+                    .map(|j| rng.uniform_in(b.lower()[j], b.upper()[j]))
+                    .collect(),
+                _ => x0.iter().map(|v| v + opts.sigma * rng.normal()).collect(),
+            };
+        }
+        if let Some(b) = bounds {
+            b.project(&mut mean);
+        }
+        let run = run(&mut objective, mean.clone(), lambda, bounds, opts, &mut rng);
+        generations += run.generations;
+        converged |= run.converged;
+        if objective.remaining() < lambda {
+            break;
+        }
+    }
+    if !converged {
+        return Err(NumalError::DidNotConverge);
+    }
+    Ok(objective.minimum(generations))
+}
+
+// Length of one run and whether its population converged.
+struct Run {
+    generations: usize,
+    converged: bool,
+}
+
+// One CMA-ES run from `mean` with population `lambda`, which ends before a
+// generation that would overrun the budget.
+fn run<F>(
+    objective: &mut Budgeted<'_, F>,
+    mut mean: Vec<f64>,
+    lambda: usize,
+    bounds: Option<&Bounds>,
+    opts: &CmaEsOptions,
+    rng: &mut Rng,
+) -> Run
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let n = mean.len();
+    let nf = n as f64;
+    let mu = lambda / 2;
+    let raw: Vec<f64> = (1..=mu)
+        .map(|i| ((lambda as f64 + 1.0) / 2.0).ln() - (i as f64).ln())
+        .collect();
+    let total: f64 = raw.iter().sum();
+    let weights: Vec<f64> = raw.iter().map(|w| w / total).collect();
+    let mueff = 1.0 / dot(&weights, &weights);
+    let c_sigma = (mueff + 2.0) / (nf + mueff + 5.0);
+    let d_sigma = 1.0 + 2.0 * (((mueff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+    let c_c = (4.0 + mueff / nf) / (nf + 4.0 + 2.0 * mueff / nf);
+    let c_1 = 2.0 / ((nf + 1.3).powi(2) + mueff);
+    let c_mu = (1.0 - c_1).min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((nf + 2.0).powi(2) + mueff));
+    // Expected norm of an N(0, I) vector
+    let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+    let tol = opts.tol;
+    // Generations over which a flat history of best values ends the run;
+    // flat means within the squared tolerances, as the objective is
+    // quadratic near a minimum
+    let history_len = 10 + (30.0 * nf / lambda as f64).ceil() as usize;
+
+    let mut sigma = opts.sigma;
+    let mut c = Matrix::identity(n);
+    let mut b = Matrix::identity(n);
+    let mut d = vec![1.0; n];
+    let (mut p_sigma, mut p_c) = (vec![0.0; n], vec![0.0; n]);
+    let mut history: Vec<f64> = Vec::new();
+    let mut result = Run {
+        generations: 0,
+        converged: false,
+    };
+    let inside = |x: &[f64]| {
+        bounds.is_none_or(|b| {
+            x.iter()
+                .zip(b.lower().iter().zip(b.upper()))
+                .all(|(v, (l, u))| l <= v && v <= u)
+        })
+    };
+    while objective.remaining() >= lambda {
+        let mut xs = Vec::with_capacity(lambda);
+        for _ in 0..lambda {
+            let mut x = Vec::new();
+            for _ in 0..RESAMPLES {
+                let z: Vec<f64> = d.iter().map(|di| di * rng.normal()).collect();
+                let y = b.mul_vec(&z);
+                x = mean.iter().zip(&y).map(|(m, y)| m + sigma * y).collect();
+                if inside(&x) {
+                    break;
+                }
+            }
+            if let Some(b) = bounds {
+                b.project(&mut x);
+            }
+            xs.push(x);
+        }
+        let mut values: Vec<f64> = xs
+            .iter()
+            .map(|x| objective.eval(x).unwrap_or(f64::INFINITY))
+            .collect();
+        let mut order: Vec<usize> = (0..lambda).collect();
+        order.sort_by(|&i, &j| values[i].total_cmp(&values[j]));
+        xs = order.iter().map(|&i| xs[i].clone()).collect();
+        values = order.iter().map(|&i| values[i]).collect();
+        result.generations += 1;
+        if population_converged(&xs, &values, 0, tol) {
+            result.converged = true;
+            return result;
+        }
+        history.push(values[0]);
+        if history.len() >= history_len {
+            let recent = &history[history.len() - history_len..];
+            let (lo, hi) = recent
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+                    (lo.min(v), hi.max(v))
+                });
+            if hi - lo <= tol.eps_abs().powi(2) + tol.eps_rel().powi(2) * lo.abs() {
+                return result;
+            }
+        }
+
+        // Selected steps in units of sigma, and their weighted mean
+        let steps: Vec<Vec<f64>> = xs[..mu]
+            .iter()
+            .map(|x| x.iter().zip(&mean).map(|(x, m)| (x - m) / sigma).collect())
+            .collect();
+        let mut y_w = vec![0.0; n];
+        for (w, y) in weights.iter().zip(&steps) {
+            y_w.iter_mut().zip(y).for_each(|(a, v)| *a += w * v);
+        }
+        mean.iter_mut().zip(&y_w).for_each(|(m, y)| *m += sigma * y);
+
+        // C^(-1/2) y_w = B D^-1 B^T y_w
+        let t: Vec<f64> = b
+            .tr_mul_vec(&y_w)
+            .iter()
+            .zip(&d)
+            .map(|(v, di)| v / di)
+            .collect();
+        let whitened = b.mul_vec(&t);
+        let k_sigma = (c_sigma * (2.0 - c_sigma) * mueff).sqrt();
+        p_sigma
+            .iter_mut()
+            .zip(&whitened)
+            .for_each(|(p, v)| *p = (1.0 - c_sigma) * *p + k_sigma * v);
+        let g = result.generations as i32;
+        let ps_norm = norm(&p_sigma);
+        let h_sigma =
+            ps_norm / (1.0 - (1.0 - c_sigma).powi(2 * g)).sqrt() < (1.4 + 2.0 / (nf + 1.0)) * chi_n;
+        let k_c = if h_sigma {
+            (c_c * (2.0 - c_c) * mueff).sqrt()
+        } else {
+            0.0
+        };
+        p_c.iter_mut()
+            .zip(&y_w)
+            .for_each(|(p, y)| *p = (1.0 - c_c) * *p + k_c * y);
+        let decay = 1.0 - c_1 - c_mu
+            + if h_sigma {
+                0.0
+            } else {
+                c_1 * c_c * (2.0 - c_c)
+            };
+        c.as_mut_slice().iter_mut().for_each(|v| *v *= decay);
+        c.rank1_update(c_1, &p_c, &p_c);
+        for (w, y) in weights.iter().zip(&steps) {
+            c.rank1_update(c_mu * w, y, y);
+        }
+        sigma *= ((c_sigma / d_sigma) * (ps_norm / chi_n - 1.0)).exp();
+
+        // C is positive definite, so its SVD is its eigendecomposition
+        let Some(svd) = Svd::new(&c) else {
+            return result;
+        };
+        let smallest = svd.s[n - 1];
+        if !(smallest > 0.0 && svd.s[0] / smallest <= MAX_CONDITION && sigma.is_finite()) {
+            return result;
+        }
+        b = svd.u;
+        d = svd.s.iter().map(|s| s.sqrt()).collect();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::{rastrigin, rosenbrock};
+
+    #[test]
+    fn single_run_minimizes_rosenbrock() {
+        let opts = CmaEsOptions {
+            restarts: 0,
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = cmaes(rosenbrock, &[-1.0, 2.0, 0.0, 1.5], None, &opts).unwrap();
+        assert!(r.x.iter().all(|v| (v - 1.0).abs() < 1e-8), "{r:?}");
+    }
+
+    #[test]
+    fn restarts_escape_local_minima_of_rastrigin() {
+        let bounds = Bounds::new(vec![-5.12; 5], vec![5.12; 5]).unwrap();
+        let run = |restarts| {
+            let opts = CmaEsOptions {
+                sigma: 2.0,
+                restarts,
+                seed: 1,
+                ..Default::default()
+            };
+            cmaes(rastrigin, &[3.0; 5], Some(&bounds), &opts).unwrap()
+        };
+        let single = run(0);
+        assert!(single.fx > 0.5, "{single:?}");
+        let ipop = run(9);
+        assert!(ipop.fx < 1e-10, "{ipop:?}");
+        assert!(ipop.x.iter().all(|v| v.abs() < 1e-6));
+    }
+
+    #[test]
+    fn bounds_are_respected() {
+        let f = |x: &[f64]| (x[0] + 1.0).powi(2) + (x[1] - 0.5).powi(2);
+        let bounds = Bounds::new(vec![0.0, 0.0], vec![1.0, 1.0]).unwrap();
+        let r = cmaes(f, &[0.8, 0.8], Some(&bounds), &CmaEsOptions::default()).unwrap();
+        assert!((0.0..1e-8).contains(&r.x[0]), "{r:?}");
+        assert!((r.x[1] - 0.5).abs() < 1e-6, "{r:?}");
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        let opts = CmaEsOptions {
+            sigma: 0.0,
+            ..Default::default()
+        };
+        let r = cmaes(rosenbrock, &[0.0, 0.0], None, &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,736 @@
+//! COBYLA, Constrained Optimization BY Linear Approximations (Powell, 1994).
+//!
+//! A derivative-free method for `min f(x)` subject to `c_i(x) >= 0` and
+//! `c_i(x) = 0`. The objective and every constraint are interpolated by
+//! linear functions on a simplex of `n + 1` points. Each iteration minimizes
+//! the linear objective within a trust region of radius `rho` subject to the
+//! linearized constraints or, when they cannot all be satisfied there, first
+//! reduces their largest violation. Steps are judged by the merit function
+//! `f + mu max(0, -c_i)`, whose penalty `mu` grows with the ratio of the
+//! predicted objective increase to the predicted violation decrease. The new
+//! point replaces a vertex chosen to keep the simplex well shaped, and a
+//! vertex is moved when the simplex degenerates. `rho` only decreases,
+//! halving once trust-region steps stop making progress, until it reaches the
+//! final radius `eps_abs + eps_rel ||x0||_inf` from [`Tolerance`].
+//!
+//! Constraints are given by their values alone, as [`CobylaConstraint`]s; an
+//! equality is treated as a pair of opposite inequalities. The multipliers and KKT residual
+//! reported use the gradients of the final linear models. Objective values
+//! that are NaN count as `+inf` and constraint values that are NaN as
+//! `-inf`, so that the point is infeasible; as in BOBYQA, values beyond
+//! `1e30` in magnitude are clamped to it.
+
+use super::constrained::{ConstrainedMinimum, ConstraintKind, Point, constrained_minimum};
+use super::{Budgeted, HUGE, check_start};
+use crate::NumalError;
+use crate::core::linalg::{Matrix, dot, norm};
+use crate::core::tolerance::Tolerance;
+use crate::lsq::{bvls, nnls};
+use crate::optimize::Bounds;
+
+// A simplex is acceptable if no vertex is farther than BETA rho from the
+// pivot and none is closer than ALPHA rho to the opposite face (Powell's
+// beta and alpha).
+const ALPHA: f64 = 0.25;
+const BETA: f64 = 2.1;
+// Distance, relative to rho, of a vertex moved to restore the shape.
+const GAMMA: f64 = 0.5;
+// Vertices farther than DELTA rho from the pivot are replaced first.
+const DELTA: f64 = 1.1;
+
+/// Options for [`cobyla`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CobylaOptions {
+    /// Initial trust-region radius, about a tenth of the expected distance
+    /// to the solution
+    pub initial_radius: f64,
+    /// Sets the final trust-region radius
+    pub tol: Tolerance,
+    pub max_evaluations: usize,
+}
+
+impl Default for CobylaOptions {
+    fn default() -> Self {
+        CobylaOptions {
+            initial_radius: 0.5,
+            tol: Tolerance::Default,
+            max_evaluations: 10_000,
+        }
+    }
+}
+
+/// A constraint function `c(x)` returning its value alone
+pub type CobylaConstraintFn<'a> = Box<dyn Fn(&[f64]) -> f64 + 'a>;
+
+/// A constraint of a problem solved by [`cobyla`], given by its value alone
+pub enum CobylaConstraint<'a> {
+    /// `c(x) = 0`
+    Equality(CobylaConstraintFn<'a>),
+    /// `c(x) >= 0`
+    Inequality(CobylaConstraintFn<'a>),
+}
+
+impl<'a> CobylaConstraint<'a> {
+    /// The equality constraint `c(x) = 0`
+    pub fn equality<C>(c: C) -> Self
+    where
+        C: Fn(&[f64]) -> f64 + 'a,
+    {
+        CobylaConstraint::Equality(Box::new(c))
+    }
+
+    /// The inequality constraint `c(x) >= 0`
+    pub fn inequality<C>(c: C) -> Self
+    where
+        C: Fn(&[f64]) -> f64 + 'a,
+    {
+        CobylaConstraint::Inequality(Box::new(c))
+    }
+
+    pub fn is_equality(&self) -> bool {
+        matches!(self, CobylaConstraint::Equality(_))
+    }
+
+    fn eval(&self, x: &[f64]) -> f64 {
+        match self {
+            CobylaConstraint::Equality(c) | CobylaConstraint::Inequality(c) => c(x),
+        }
+    }
+}
+
+impl ConstraintKind for CobylaConstraint<'_> {
+    fn is_equality(&self) -> bool {
+        CobylaConstraint::is_equality(self)
+    }
+}
+
+// Objective and constraint values at a point, with equalities expanded into
+// `c >= 0` and `-c >= 0`.
+#[derive(Clone)]
+struct Sample {
+    f: f64,
+    c: Vec<f64>,
+    violation: f64,
+}
+
+impl Sample {
+    fn merit(&self, mu: f64) -> f64 {
+        self.f + mu * self.violation
+    }
+}
+
+struct Problem<'a, F> {
+    objective: Budgeted<'a, F>,
+    constraints: &'a [CobylaConstraint<'a>],
+}
+
+impl<F: Fn(&[f64]) -> f64> Problem<'_, F> {
+    fn sample(&mut self, x: &[f64]) -> Result<Sample, NumalError> {
+        let f = self.objective.eval_clamped(x)?;
+        let mut c = Vec::with_capacity(2 * self.constraints.len());
+        for con in self.constraints {
+            // A NaN constraint counts as infinitely violated
+            let v = con.eval(x);
+            let v = if v.is_nan() {
+                -HUGE
+            } else {
+                v.clamp(-HUGE, HUGE)
+            };
+            c.push(v);
+            if con.is_equality() {
+                c.push(-v);
+            }
+        }
+        let violation = c.iter().fold(0.0, |m: f64, v| m.max(-v));
+        Ok(Sample { f, c, violation })
+    }
+
+    // Values of the constraints as given, from the expanded ones.
+    fn values(&self, s: &Sample) -> Vec<f64> {
+        let mut c = s.c.iter();
+        self.constraints
+            .iter()
+            .map(|con| {
+                let v = *c.next().unwrap_or(&0.0);
+                if con.is_equality() {
+                    c.next();
+                }
+                v
+            })
+            .collect()
+    }
+}
+
+// The simplex: the pivot `x` with the best merit, the other vertices at
+// `x + sim[.., j]`, and `simi`, the inverse of `sim`.
+struct Simplex {
+    x: Vec<f64>,
+    pivot: Sample,
+    sim: Matrix,
+    simi: Matrix,
+    vertices: Vec<Sample>,
+}
+
+impl Simplex {
+    // Makes vertex `j` the pivot.
+    fn swap_pivot(&mut self, j: usize) {
+        let n = self.x.len();
+        let v = self.sim.col(j);
+        for (xi, vi) in self.x.iter_mut().zip(&v) {
+            *xi += vi;
+        }
+        for (i, vi) in v.iter().enumerate() {
+            for k in 0..n {
+                self.sim[(i, k)] = if k == j { -vi } else { self.sim[(i, k)] - vi };
+            }
+        }
+        let sum: Vec<f64> = (0..n)
+            .map(|i| (0..n).map(|k| self.simi[(k, i)]).sum())
+            .collect();
+        for (e, s) in self.simi.row_mut(j).iter_mut().zip(sum) {
+            *e = -s;
+        }
+        std::mem::swap(&mut self.pivot, &mut self.vertices[j]);
+    }
+
+    // Replaces vertex `j` by the point `x + d`.
+    fn replace(&mut self, j: usize, d: &[f64], s: Sample) {
+        let n = d.len();
+        for (i, di) in d.iter().enumerate() {
+            self.sim[(i, j)] = *di;
+        }
+        let scale = dot(self.simi.row(j), d);
+        for e in self.simi.row_mut(j) {
+            *e /= scale;
+        }
+        let row = self.simi.row(j).to_vec();
+        for k in (0..n).filter(|&k| k != j) {
+            let t = dot(self.simi.row(k), d);
+            for (e, r) in self.simi.row_mut(k).iter_mut().zip(&row) {
+                *e -= t * r;
+            }
+        }
+        self.vertices[j] = s;
+    }
+
+    // Gradients of the linear interpolants of the objective and of the
+    // expanded constraints.
+    fn gradients(&self) -> (Vec<f64>, Matrix) {
+        let n = self.x.len();
+        let m = self.pivot.c.len();
+        let df: Vec<f64> = self.vertices.iter().map(|v| v.f - self.pivot.f).collect();
+        let g = self.simi.tr_mul_vec(&df);
+        let mut a = Matrix::zeros(m, n);
+        for k in 0..m {
+            let dc: Vec<f64> = self
+                .vertices
+                .iter()
+                .map(|v| v.c[k] - self.pivot.c[k])
+                .collect();
+            a.row_mut(k).copy_from_slice(&self.simi.tr_mul_vec(&dc));
+        }
+        (g, a)
+    }
+
+    // Distances of the vertices from the pivot, and from the faces opposite
+    // the pivot.
+    fn shape(&self) -> (Vec<f64>, Vec<f64>) {
+        let n = self.x.len();
+        let veta = (0..n).map(|j| norm(&self.sim.col(j))).collect();
+        let vsig = (0..n).map(|j| 1.0 / norm(self.simi.row(j))).collect();
+        (veta, vsig)
+    }
+}
+
+/// Minimizes `f` from `x0` subject to `constraints` by COBYLA, without
+/// derivatives.
+///
+/// Returns [`NumalError::DidNotConverge`] if the evaluation budget runs out
+/// before the trust region shrinks to its final radius.
+pub fn cobyla<F>(
+    f: F,
+    constraints: &[CobylaConstraint],
+    x0: &[f64],
+    opts: &CobylaOptions,
+) -> Result<ConstrainedMinimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    check_start(x0)?;
+    let n = x0.len();
+    if !(opts.initial_radius > 0.0 && opts.initial_radius.is_finite()) {
+        return Err(NumalError::InvalidInput(format!(
+            "initial radius must be finite and positive, got {}",
+            opts.initial_radius
+        )));
+    }
+    let rho_end =
+        opts.tol.eps_abs() + opts.tol.eps_rel() * x0.iter().fold(0.0, |m: f64, v| m.max(v.abs()));
+    let mut rho = opts.initial_radius.max(rho_end);
+    let mut problem = Problem {
+        objective: Budgeted::new(&f, opts.max_evaluations),
+        constraints,
+    };
+    let pivot = problem.sample(x0)?;
+    let mut vertices = Vec::with_capacity(n);
+    for j in 0..n {
+        let mut y = x0.to_vec();
+        y[j] += rho;
+        vertices.push(problem.sample(&y)?);
+    }
+    let mut sim = Matrix::identity(n);
+    let mut simi = Matrix::identity(n);
+    for j in 0..n {
+        sim[(j, j)] = rho;
+        simi[(j, j)] = 1.0 / rho;
+    }
+    let mut simplex = Simplex {
+        x: x0.to_vec(),
+        pivot,
+        sim,
+        simi,
+        vertices,
+    };
+    let mut mu = 0.0;
+    let mut iterations = 0;
+    // Whether to take a trust-region step even if the simplex is not
+    // acceptable, as after a successful one
+    let mut trust_step = false;
+    loop {
+        // The vertex with the least merit becomes the pivot, ties going to
+        // the least violation while there is no penalty
+        let (mut best, mut phi_best, mut res_best) =
+            (None, simplex.pivot.merit(mu), simplex.pivot.violation);
+        for (j, v) in simplex.vertices.iter().enumerate() {
+            let phi = v.merit(mu);
+            if phi < phi_best || (phi == phi_best && mu == 0.0 && v.violation < res_best) {
+                (best, phi_best, res_best) = (Some(j), phi, v.violation);
+            }
+        }
+        if let Some(j) = best {
+            simplex.swap_pivot(j);
+        }
+        let (g, a) = simplex.gradients();
+        let (veta, vsig) = simplex.shape();
+        let acceptable =
+            veta.iter().all(|&e| e <= BETA * rho) && vsig.iter().all(|&s| s >= ALPHA * rho);
+        if !acceptable && !trust_step {
+            // Move a vertex to restore the shape of the simplex, on the
+            // side the linear models predict to be better
+            let jdrop = if veta.iter().any(|&e| e > BETA * rho) {
+                (0..n).max_by(|&i, &j| veta[i].total_cmp(&veta[j]))
+            } else {
+                (0..n).min_by(|&i, &j| vsig[i].total_cmp(&vsig[j]))
+            }
+            .unwrap_or(0);
+            let mut dx: Vec<f64> = simplex
+                .simi
+                .row(jdrop)
+                .iter()
+                .map(|e| GAMMA * rho * vsig[jdrop] * e)
+                .collect();
+            let violation_along = |sign: f64| {
+                (0..a.rows())
+                    .map(|k| -(simplex.pivot.c[k] + sign * dot(a.row(k), &dx)))
+                    .fold(0.0, f64::max)
+            };
+            if mu * (violation_along(1.0) - violation_along(-1.0)) > 2.0 * dot(&g, &dx) {
+                dx.iter_mut().for_each(|v| *v = -*v);
+            }
+            let y: Vec<f64> = simplex.x.iter().zip(&dx).map(|(x, d)| x + d).collect();
+            let s = problem.sample(&y)?;
+            simplex.replace(jdrop, &dx, s);
+            trust_step = true;
+            continue;
+        }
+        trust_step = false;
+        let d = trust_region_step(&g, &a, &simplex.pivot.c, rho);
+        let step_norm = norm(&d);
+        let mut trial = None;
+        if step_norm >= 0.5 * rho {
+            // Raise the penalty until the step is predicted to decrease
+            // the merit function
+            let predicted = (0..a.rows())
+                .map(|k| -(simplex.pivot.c[k] + dot(a.row(k), &d)))
+                .fold(0.0, f64::max);
+            let prerec = simplex.pivot.violation - predicted;
+            let sum = dot(&g, &d);
+            if prerec > 0.0 {
+                let barmu = sum / prerec;
+                if mu < 1.5 * barmu {
+                    mu = 2.0 * barmu;
+                    let phi = simplex.pivot.merit(mu);
+                    if simplex.vertices.iter().any(|v| v.merit(mu) < phi) {
+                        trust_step = true;
+                        continue;
+                    }
+                }
+            }
+            trial = Some(mu * prerec - sum);
+        }
+        if let Some(prerem) = trial {
+            iterations += 1;
+            let y: Vec<f64> = simplex.x.iter().zip(&d).map(|(x, d)| x + d).collect();
+            let s = problem.sample(&y)?;
+            let trured = simplex.pivot.merit(mu) - s.merit(mu);
+            // Drop the vertex whose replacement keeps the simplex volume
+            // largest, preferring one far from the pivot
+            let threshold = if trured > 0.0 { 0.0 } else { 1.0 };
+            let weights: Vec<f64> = (0..n).map(|j| dot(simplex.simi.row(j), &d).abs()).collect();
+            let mut jdrop = (0..n)
+                .filter(|&j| weights[j] > threshold)
+                .max_by(|&i, &j| weights[i].total_cmp(&weights[j]));
+            let mut far = DELTA * rho;
+            for j in 0..n {
+                let sigbar = weights[j] * vsig[j];
+                if sigbar >= ALPHA * rho || sigbar >= vsig[j] {
+                    let dist = if trured > 0.0 {
+                        norm(
+                            &(0..n)
+                                .map(|i| simplex.sim[(i, j)] - d[i])
+                                .collect::<Vec<_>>(),
+                        )
+                    } else {
+                        veta[j]
+                    };
+                    if dist > far {
+                        (jdrop, far) = (Some(j), dist);
+                    }
+                }
+            }
+            if let Some(j) = jdrop {
+                simplex.replace(j, &d, s);
+            }
+            if trured > 0.0 && trured >= 0.1 * prerem {
+                trust_step = true;
+                continue;
+            }
+        }
+        if !acceptable {
+            continue;
+        }
+        if rho <= rho_end {
+            let values = problem.values(&simplex.pivot);
+            let multipliers =
+                multipliers(&g, &a, &values, constraints, rho_end + opts.tol.eps_abs());
+            let mut jac = Matrix::zeros(constraints.len(), n);
+            let mut row = 0;
+            for (i, con) in constraints.iter().enumerate() {
+                jac.row_mut(i).copy_from_slice(a.row(row));
+                row += if con.is_equality() { 2 } else { 1 };
+            }
+            let p = Point {
+                x: simplex.x,
+                f: simplex.pivot.f,
+                g,
+                c: values,
+                jac,
+            };
+            return Ok(constrained_minimum(
+                p,
+                constraints,
+                multipliers,
+                iterations,
+                problem.objective.evaluations,
+            ));
+        }
+        rho *= 0.5;
+        if rho <= 1.5 * rho_end {
+            rho = rho_end;
+        }
+        if mu > 0.0 {
+            // Reduce the penalty to what the spread of the values at the
+            // vertices calls for. As in Powell's code, the penalty is dropped
+            // when the smallest value of every constraint at the vertices is
+            // at least half its largest
+            let all = || std::iter::once(&simplex.pivot).chain(&simplex.vertices);
+            let mut denom = 0.0;
+            for k in 0..simplex.pivot.c.len() {
+                let cmin = all().map(|v| v.c[k]).fold(f64::INFINITY, f64::min);
+                let cmax = all().map(|v| v.c[k]).fold(f64::NEG_INFINITY, f64::max);
+                if cmin < 0.5 * cmax {
+                    let spread = cmax.max(0.0) - cmin;
+                    denom = if denom > 0.0 {
+                        spread.min(denom)
+                    } else {
+                        spread
+                    };
+                }
+            }
+            let fmin = all().map(|v| v.f).fold(f64::INFINITY, f64::min);
+            let fmax = all().map(|v| v.f).fold(f64::NEG_INFINITY, f64::max);
+            if denom == 0.0 {
+                mu = 0.0;
+            } else if fmax - fmin < mu * denom {
+                mu = (fmax - fmin) / denom;
+            }
+        }
+    }
+}
+
+// Multipliers of the constraints active within `margin` of their linear
+// models, fitted to the model gradient `g` with the sign of inequality
+// multipliers enforced.
+fn multipliers(
+    g: &[f64],
+    a: &Matrix,
+    values: &[f64],
+    constraints: &[CobylaConstraint],
+    margin: f64,
+) -> Vec<f64> {
+    let mut lambda = vec![0.0; constraints.len()];
+    let mut rows = Vec::new();
+    let mut row = 0;
+    for (i, con) in constraints.iter().enumerate() {
+        if con.is_equality() || values[i] <= margin * (1.0 + norm(a.row(row))) {
+            rows.push((i, row));
+        }
+        row += if con.is_equality() { 2 } else { 1 };
+    }
+    if rows.is_empty() {
+        return lambda;
+    }
+    let normals = Matrix::from_fn(g.len(), rows.len(), |r, j| a[(rows[j].1, r)]);
+    let lower = rows
+        .iter()
+        .map(|&(i, _)| {
+            if constraints[i].is_equality() {
+                f64::NEG_INFINITY
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let Ok(bounds) = Bounds::new(lower, vec![f64::INFINITY; rows.len()]) else {
+        return lambda;
+    };
+    if let Ok(fit) = bvls(&normals, g, &bounds) {
+        for (&(i, _), l) in rows.iter().zip(fit.x) {
+            lambda[i] = l;
+        }
+    }
+    lambda
+}
+
+// Powell's trust-region subproblem: within `||d|| <= rho`, first reduce the
+// largest violation of the linearized constraints `c_k + a_k d >= 0` as far
+// as possible, then decrease `g d` without increasing that violation. The
+// calculation stops early if it reaches the trust-region boundary.
+fn trust_region_step(g: &[f64], a: &Matrix, c: &[f64], rho: f64) -> Vec<f64> {
+    let (n, m) = (g.len(), a.rows());
+    let violation = c.iter().fold(0.0, |v: f64, c| v.max(-c));
+    let mut slack = 0.0;
+    let mut d = vec![0.0; n];
+    if violation > 0.0 {
+        // Minimize t subject to c_k + a_k d + t >= 0 and t >= 0
+        let normals = Matrix::from_fn(m + 1, n + 1, |k, i| match (k < m, i < n) {
+            (true, true) => a[(k, i)],
+            (false, true) => 0.0,
+            (_, false) => 1.0,
+        });
+        let mut offsets = c.to_vec();
+        offsets.push(0.0);
+        let mut h = vec![0.0; n + 1];
+        h[n] = 1.0;
+        let mut z = d.clone();
+        z.push(violation);
+        if descend(&mut z, &h, &normals, &offsets, n, rho) {
+            z.truncate(n);
+            return z;
+        }
+        slack = z[n].max(0.0);
+        z.truncate(n);
+        d = z;
+    }
+    let offsets: Vec<f64> = c.iter().map(|c| c + slack).collect();
+    descend(&mut d, g, a, &offsets, n, rho);
+    d
+}
+
+// Moves `z` along the projection of `-h` onto the cone of directions
+// feasible for the constraints `normals[k] z + offsets[k] >= 0` active at
+// `z`, from face to face until the projection vanishes or the first `ball`
+// components of `z` reach norm `rho`. Returns whether the boundary was
+// reached.
+fn descend(
+    z: &mut [f64],
+    h: &[f64],
+    normals: &Matrix,
+    offsets: &[f64],
+    ball: usize,
+    rho: f64,
+) -> bool {
+    let (p, m) = (z.len(), normals.rows());
+    let h_norm = norm(h);
+    for _ in 0..2 * (m + p) + 10 {
+        let slack: Vec<f64> = (0..m)
+            .map(|k| dot(normals.row(k), z) + offsets[k])
+            .collect();
+        let active: Vec<usize> = (0..m)
+            .filter(|&k| {
+                slack[k] <= 1e-12 * (1.0 + offsets[k].abs() + norm(normals.row(k)) * norm(z))
+            })
+            .collect();
+        let mut s: Vec<f64> = h.iter().map(|v| -v).collect();
+        if !active.is_empty() {
+            let cone = Matrix::from_fn(p, active.len(), |i, j| normals[(active[j], i)]);
+            let Ok(fit) = nnls(&cone, h) else {
+                return false;
+            };
+            for (si, ci) in s.iter_mut().zip(cone.mul_vec(&fit.x)) {
+                *si += ci;
+            }
+        }
+        if norm(&s) <= 1e-12 * h_norm {
+            return false;
+        }
+        let (zb, sb) = (&z[..ball], &s[..ball]);
+        let ss = dot(sb, sb);
+        let mut alpha = f64::INFINITY;
+        if ss > 0.0 {
+            let zs = dot(zb, sb);
+            let room = (rho * rho - dot(zb, zb)).max(0.0);
+            alpha = room / (zs + (zs * zs + ss * room).sqrt()).max(f64::MIN_POSITIVE);
+        }
+        let mut boundary = true;
+        for k in (0..m).filter(|k| !active.contains(k)) {
+            let rate = dot(normals.row(k), &s);
+            if rate < 0.0 && slack[k].max(0.0) / -rate < alpha {
+                alpha = slack[k].max(0.0) / -rate;
+                boundary = false;
+            }
+        }
+        if !alpha.is_finite() {
+            return false;
+        }
+        for (zi, si) in z.iter_mut().zip(&s) {
+            *zi += alpha * si;
+        }
+        if boundary {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimizes_linear_objective_on_disc() {
+        // Powell's test problem 2: min x0 x1 on the unit disc
+        let constraints = [CobylaConstraint::inequality(|x: &[f64]| {
+            1.0 - x[0] * x[0] - x[1] * x[1]
+        })];
+        let opts = CobylaOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = cobyla(|x| x[0] * x[1], &constraints, &[1.0, 1.0], &opts).unwrap();
+        let h = 0.5f64.sqrt();
+        assert!(
+            (r.x[0].abs() - h).abs() < 1e-6 && (r.x[0] + r.x[1]).abs() < 1e-6,
+            "{r:?}"
+        );
+        assert!((r.multipliers[0] - 0.5).abs() < 1e-3, "{r:?}");
+    }
+
+    #[test]
+    fn handles_equality_constraints() {
+        // Hock & Schittkowski problem 6
+        let constraints = [CobylaConstraint::equality(|x: &[f64]| {
+            10.0 * (x[1] - x[0] * x[0])
+        })];
+        let f = |x: &[f64]| (1.0 - x[0]).powi(2);
+        let r = cobyla(f, &constraints, &[-1.2, 1.0], &Default::default()).unwrap();
+        assert!(
+            (r.x[0] - 1.0).abs() < 1e-4 && (r.x[1] - 1.0).abs() < 1e-4,
+            "{r:?}"
+        );
+        assert!(r.constraint_violation < 1e-6, "{r:?}");
+    }
+
+    #[test]
+    fn starts_from_infeasible_point() {
+        // Hock & Schittkowski problem 43, the Rosen-Suzuki problem
+        let f = |x: &[f64]| {
+            x[0] * x[0] + x[1] * x[1] + 2.0 * x[2] * x[2] + x[3] * x[3]
+                - 5.0 * x[0]
+                - 5.0 * x[1]
+                - 21.0 * x[2]
+                + 7.0 * x[3]
+        };
+        let constraints = [
+            CobylaConstraint::inequality(|x: &[f64]| {
+                8.0 - x[0] * x[0] - x[1] * x[1] - x[2] * x[2] - x[3] * x[3] - x[0] + x[1] - x[2]
+                    + x[3]
+            }),
+            CobylaConstraint::inequality(|x: &[f64]| {
+                10.0 - x[0] * x[0] - 2.0 * x[1] * x[1] - x[2] * x[2] - 2.0 * x[3] * x[3]
+                    + x[0]
+                    + x[3]
+            }),
+            CobylaConstraint::inequality(|x: &[f64]| {
+                5.0 - 2.0 * x[0] * x[0] - x[1] * x[1] - x[2] * x[2] - 2.0 * x[0] + x[1] + x[3]
+            }),
+        ];
+        let opts = CobylaOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = cobyla(f, &constraints, &[3.0, 3.0, 3.0, 3.0], &opts).unwrap();
+        let expected = [0.0, 1.0, 2.0, -1.0];
+        assert!(
+            r.x.iter().zip(expected).all(|(x, e)| (x - e).abs() < 1e-5),
+            "{r:?}"
+        );
+        assert!((r.fx + 44.0).abs() < 1e-6, "{r:?}");
+        assert!(
+            r.multipliers[0] > 0.5 && r.multipliers[1].abs() < 1e-3,
+            "{r:?}"
+        );
+    }
+
+    #[test]
+    fn unconstrained_quadratic() {
+        let f = |x: &[f64]| (x[0] - 1.0).powi(2) + 10.0 * (x[1] + 2.0).powi(2);
+        let r = cobyla(f, &[], &[0.0, 0.0], &Default::default()).unwrap();
+        assert!(
+            (r.x[0] - 1.0).abs() < 1e-5 && (r.x[1] + 2.0).abs() < 1e-5,
+            "{r:?}"
+        );
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        let f = |x: &[f64]| x[0] * x[0];
+        let opts = CobylaOptions {
+            initial_radius: 0.0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            cobyla(f, &[], &[1.0], &opts),
+            Err(NumalError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn nan_region_is_avoided() {
+        // The start lies where the objective is NaN, which counts as +inf,
+        // and the constraint is NaN, which counts as violated
+        let f = |x: &[f64]| {
+            if x[0] < 0.0 {
+                f64::NAN
+            } else {
+                (x[0] - 0.5).powi(2) + (x[1] - 1.0).powi(2)
+            }
+        };
+        let cons = [CobylaConstraint::inequality(|x: &[f64]| x[0].sqrt())];
+        let r = cobyla(f, &cons, &[-0.2, 0.0], &Default::default()).unwrap();
+        assert!(
+            (r.x[0] - 0.5).abs() < 1e-4 && (r.x[1] - 1.0).abs() < 1e-4,
+            "{r:?}"
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! Shared definitions for nonlinearly constrained minimization.
+//!
+//! A problem is `min f(x)` subject to equality constraints `c_i(x) = 0` and
+//! inequality constraints `c_i(x) >= 0`. Each constraint is supplied like
+//! the objective, as `c(x, g)` returning the value and writing its gradient.
+//! Multipliers follow the Lagrangian `f - sum(lambda_i c_i)`, so those of
+//! inequalities are nonnegative at a solution.
+
+use crate::NumalError;
+use crate::core::linalg::Matrix;
+use crate::core::tolerance::Tolerance;
+
+/// A constraint function `c(x, g)` returning `c(x)` and writing its gradient
+pub type ConstraintFn<'a> = Box<dyn Fn(&[f64], &mut [f64]) -> f64 + 'a>;
+
+/// A constraint of a nonlinear program
+pub enum Constraint<'a> {
+    /// `c(x) = 0`
+    Equality(ConstraintFn<'a>),
+    /// `c(x) >= 0`
+    Inequality(ConstraintFn<'a>),
+}
+
+impl<'a> Constraint<'a> {
+    /// The equality constraint `c(x) = 0`
+    pub fn equality<C>(c: C) -> Self
+    where
+        C: Fn(&[f64], &mut [f64]) -> f64 + 'a,
+    {
+        Constraint::Equality(Box::new(c))
+    }
+
+    /// The inequality constraint `c(x) >= 0`
+    pub fn inequality<C>(c: C) -> Self
+    where
+        C: Fn(&[f64], &mut [f64]) -> f64 + 'a,
+    {
+        Constraint::Inequality(Box::new(c))
+    }
+
+    pub fn is_equality(&self) -> bool {
+        matches!(self, Constraint::Equality(_))
+    }
+
+    pub(crate) fn eval(&self, x: &[f64], g: &mut [f64]) -> f64 {
+        match self {
+            Constraint::Equality(c) | Constraint::Inequality(c) => c(x, g),
+        }
+    }
+}
+
+// The kind of a constraint, which is all the KKT measures need to know of it;
+// derivative-free solvers take constraints of their own.
+pub(crate) trait ConstraintKind {
+    fn is_equality(&self) -> bool;
+}
+
+impl ConstraintKind for Constraint<'_> {
+    fn is_equality(&self) -> bool {
+        Constraint::is_equality(self)
+    }
+}
+
+/// Outcome of a successful constrained minimization
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstrainedMinimum {
+    /// Location of the minimum
+    pub x: Vec<f64>,
+    /// Objective value at `x`
+    pub fx: f64,
+    /// Lagrange multipliers, in the order the constraints were given
+    pub multipliers: Vec<f64>,
+    /// Largest component of the gradient of the Lagrangian
+    pub kkt_residual: f64,
+    /// Largest `|c_i|` over the equalities and `max(0, -c_i)` over the
+    /// inequalities
+    pub constraint_violation: f64,
+    /// Largest `|lambda_i c_i|` over the inequalities
+    pub complementarity: f64,
+    /// Number of outer iterations performed
+    pub iterations: usize,
+    /// Number of objective evaluations; each also evaluates every constraint
+    pub evaluations: usize,
+}
+
+// Objective, constraints and their derivatives at a point.
+#[derive(Clone)]
+pub(crate) struct Point {
+    pub(crate) x: Vec<f64>,
+    pub(crate) f: f64,
+    pub(crate) g: Vec<f64>,
+    pub(crate) c: Vec<f64>,
+    pub(crate) jac: Matrix,
+}
+
+impl Point {
+    pub(crate) fn new<F>(fg: &F, constraints: &[Constraint], x: &[f64]) -> Point
+    where
+        F: Fn(&[f64], &mut [f64]) -> f64,
+    {
+        let n = x.len();
+        let mut g = vec![0.0; n];
+        let f = fg(x, &mut g);
+        let mut jac = Matrix::zeros(constraints.len(), n);
+        let c = constraints
+            .iter()
+            .enumerate()
+            .map(|(i, con)| con.eval(x, jac.row_mut(i)))
+            .collect();
+        Point {
+            x: x.to_vec(),
+            f,
+            g,
+            c,
+            jac,
+        }
+    }
+
+    pub(crate) fn is_finite(&self) -> bool {
+        self.f.is_finite()
+            && self.g.iter().chain(&self.c).all(|v| v.is_finite())
+            && self.jac.is_finite()
+    }
+
+    /// Gradient of the Lagrangian for the multipliers `lambda`
+    pub(crate) fn lagrangian_gradient(&self, lambda: &[f64]) -> Vec<f64> {
+        let mut grad = self.g.clone();
+        for (gi, ji) in grad.iter_mut().zip(self.jac.tr_mul_vec(lambda)) {
+            *gi -= ji;
+        }
+        grad
+    }
+
+    pub(crate) fn violation<C: ConstraintKind>(&self, constraints: &[C]) -> f64 {
+        self.c
+            .iter()
+            .zip(constraints)
+            .map(|(&c, con)| {
+                if con.is_equality() {
+                    c.abs()
+                } else {
+                    (-c).max(0.0)
+                }
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
+// Validates the starting point and evaluates the problem there.
+pub(crate) fn start<F>(fg: &F, constraints: &[Constraint], x0: &[f64]) -> Result<Point, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    super::check_start(x0)?;
+    let p = Point::new(fg, constraints, x0);
+    if !p.is_finite() {
+        return Err(NumalError::InvalidInput(
+            "objective, constraints or their gradients are not finite at the initial guess"
+                .to_string(),
+        ));
+    }
+    Ok(p)
+}
+
+// KKT measures at `p` with multipliers `lambda`, and whether they all meet
+// the tolerance. Stationarity is judged relative to the objective gradient.
+pub(crate) fn kkt<C: ConstraintKind>(
+    p: &Point,
+    constraints: &[C],
+    lambda: &[f64],
+    tol: Tolerance,
+) -> ([f64; 3], bool) {
+    let inf_norm = |v: &[f64]| v.iter().fold(0.0, |m: f64, x| m.max(x.abs()));
+    let stationarity = inf_norm(&p.lagrangian_gradient(lambda));
+    let violation = p.violation(constraints);
+    let complementarity =
+        p.c.iter()
+            .zip(lambda)
+            .zip(constraints)
+            .filter(|(_, con)| !con.is_equality())
+            .map(|((c, l), _)| (c * l).abs())
+            .fold(0.0, f64::max);
+    let sign_ok = lambda
+        .iter()
+        .zip(constraints)
+        .all(|(&l, con)| con.is_equality() || l >= -tol.eps_abs());
+    let ok = stationarity <= tol.eps_abs() + tol.eps_rel() * inf_norm(&p.g)
+        && violation <= tol.eps_abs()
+        && complementarity <= tol.eps_abs()
+        && sign_ok;
+    ([stationarity, violation, complementarity], ok)
+}
+
+pub(crate) fn constrained_minimum<C: ConstraintKind>(
+    p: Point,
+    constraints: &[C],
+    multipliers: Vec<f64>,
+    iterations: usize,
+    evaluations: usize,
+) -> ConstrainedMinimum {
+    let ([kkt_residual, constraint_violation, complementarity], _) =
+        kkt(&p, constraints, &multipliers, Tolerance::Default);
+    ConstrainedMinimum {
+        x: p.x,
+        fx: p.f,
+        multipliers,
+        kkt_residual,
+        constraint_violation,
+        complementarity,
+        iterations,
+        evaluations,
+    }
+}
@@ -0,0 +1,282 @@
+//! Differential evolution (Storn & Price, 1997).
+//!
+//! A population spread uniformly over a finite box evolves by adding scaled
+//! differences of members to a base vector, mixing the result with the
+//! target member by binomial crossover and keeping whichever of the two is
+//! better. JADE (Zhang & Sanderson, 2009) replaces the fixed differential
+//! weight and crossover rate with ones sampled around means that adapt to
+//! the successful trials, and mutates towards a random member of the best
+//! tenth of the population, with an archive of replaced members adding
+//! diversity to the differences.
+//!
+//! The initial guess replaces one member of the initial population. The
+//! search stops once every member is within [`Tolerance`] of the best member
+//! component-wise and in value. Objective values that are NaN count as
+//! `+inf`.
+
+use super::{Bounds, Budgeted, Minimum, argmin, finite_box, population_converged};
+use crate::NumalError;
+use crate::core::rng::Rng;
+use crate::core::tolerance::Tolerance;
+
+// JADE: fraction of the population eligible as the "p-best" member, the
+// adaptation rate of the parameter means and the spread of the sampled
+// parameters around them.
+const JADE_P: f64 = 0.1;
+const JADE_C: f64 = 0.1;
+const JADE_SPREAD: f64 = 0.1;
+
+/// Mutation and parameter control of [`differential_evolution`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeStrategy {
+    /// Difference of two random members added to a third, `DE/rand/1/bin`
+    #[default]
+    Rand1Bin,
+    /// Difference of two random members added to the best, `DE/best/1/bin`
+    Best1Bin,
+    /// Adaptive `DE/current-to-pbest/1/bin` with an archive
+    Jade,
+}
+
+/// Options for [`differential_evolution`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeOptions {
+    pub strategy: DeStrategy,
+    /// Population size, at least 4; `None` for `10 n`, at least 20
+    pub population: Option<usize>,
+    /// Differential weight `F` of the fixed strategies, in `(0, 2]`
+    pub mutation: f64,
+    /// Crossover probability `CR` of the fixed strategies, in `[0, 1]`
+    pub crossover: f64,
+    /// Population-spread tolerance
+    pub tol: Tolerance,
+    pub max_evaluations: usize,
+    /// Seed of the random number generator; equal seeds give equal runs
+    pub seed: u64,
+}
+
+impl Default for DeOptions {
+    fn default() -> Self {
+        DeOptions {
+            strategy: DeStrategy::Rand1Bin,
+            population: None,
+            mutation: 0.7,
+            crossover: 0.9,
+            tol: Tolerance::Default,
+            max_evaluations: 100_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Minimizes `f` by differential evolution from `x0` over `bounds`, which
+/// must be finite.
+///
+/// Trial components that leave the box are placed halfway between the
+/// violated bound and the target member. Returns
+/// [`NumalError::DidNotConverge`] if the evaluation budget runs out before
+/// the population has contracted.
+pub fn differential_evolution<F>(
+    f: F,
+    x0: &[f64],
+    bounds: Option<&Bounds>,
+    opts: &DeOptions,
+) -> Result<Minimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let (start, bounds) = finite_box(x0, bounds, "differential evolution")?;
+    let n = start.len();
+    let np = opts.population.unwrap_or((10 * n).max(20));
+    if np < 4 {
+        return Err(NumalError::InvalidInput(format!(
+            "population must have at least 4 members, got {np}"
+        )));
+    }
+    let valid_weight = opts.mutation > 0.0 && opts.mutation <= 2.0;
+    if !(valid_weight && (0.0..=1.0).contains(&opts.crossover)) {
+        return Err(NumalError::InvalidInput(format!(
+            "need 0 < F <= 2 and 0 <= CR <= 1, got F = {} and CR = {}",
+            opts.mutation, opts.crossover
+        )));
+    }
+    let (lower, upper) = (bounds.lower(), bounds.upper());
+    let mut rng = Rng::new(opts.seed);
+    let mut objective = Budgeted::new(&f, opts.max_evaluations);
+    let mut pop: Vec<Vec<f64>> = (0..np)
+        .map(|_| (0..n).map(|j| rng.uniform_in(lower[j], upper[j])).collect())
+        .collect();
+    pop[0] = start;
+    let mut values = Vec::with_capacity(np);
+    for x in &pop {
+        values.push(objective.eval(x).ok_or(NumalError::DidNotConverge)?);
+    }
+    let mut archive: Vec<Vec<f64>> = Vec::new();
+    let (mut mean_cr, mut mean_f) = (0.5, 0.5);
+    let mut generation = 0;
+    loop {
+        let best = argmin(&values);
+        if population_converged(&pop, &values, best, opts.tol) {
+            return Ok(objective.minimum(generation));
+        }
+        generation += 1;
+        // Members ranked by value, for the JADE p-best choice
+        let mut ranked: Vec<usize> = (0..np).collect();
+        ranked.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+        let top = ((JADE_P * np as f64).round() as usize).max(1);
+        let (mut good_cr, mut good_f) = (Vec::new(), Vec::new());
+        let mut next = pop.clone();
+        for i in 0..np {
+            let (weight, cr) = match opts.strategy {
+                DeStrategy::Jade => {
+                    let cr = (mean_cr + JADE_SPREAD * rng.normal()).clamp(0.0, 1.0);
+                    let mut weight = rng.cauchy(mean_f, JADE_SPREAD);
+                    while weight <= 0.0 {
+                        weight = rng.cauchy(mean_f, JADE_SPREAD);
+                    }
+                    (weight.min(1.0), cr)
+                }
+                _ => (opts.mutation, opts.crossover),
+            };
+            let x = &pop[i];
+            let mutant: Vec<f64> = match opts.strategy {
+                DeStrategy::Rand1Bin => {
+                    let [r1, r2, r3] = distinct(&mut rng, np, i);
+                    (0..n)
+                        .map(|j| pop[r1][j] + weight * (pop[r2][j] - pop[r3][j]))
+                        .collect()
+                }
+                DeStrategy::Best1Bin => {
+                    let [r1, r2, _] = distinct(&mut rng, np, i);
+                    (0..n)
+                        .map(|j| pop[best][j] + weight * (pop[r1][j] - pop[r2][j]))
+                        .collect()
+                }
+                DeStrategy::Jade => {
+                    let pbest = &pop[ranked[rng.below(top)]];
+                    let r1 = loop {
+                        let r = rng.below(np);
+                        if r != i {
+                            break r;
+                        }
+                    };
+                    // The second difference vector may come from the archive
+                    let other = loop {
+                        let r = rng.below(np + archive.len());
+                        if r != i && r != r1 {
+                            break if r < np { &pop[r] } else { &archive[r - np] };
+                        }
+                    };
+                    (0..n)
+                        .map(|j| {
+                            x[j] + weight * (pbest[j] - x[j]) + weight * (pop[r1][j] - other[j])
+                        })
+                        .collect()
+                }
+            };
+            let forced = rng.below(n);
+            let trial: Vec<f64> = (0..n)
+                .map(|j| {
+                    let v = if j == forced || rng.uniform() < cr {
+                        mutant[j]
+                    } else {
+                        x[j]
+                    };
+                    if v < lower[j] {
+                        0.5 * (lower[j] + x[j])
+                    } else if v > upper[j] {
+                        0.5 * (upper[j] + x[j])
+                    } else {
+                        v
+                    }
+                })
+                .collect();
+            let ft = objective.eval(&trial).ok_or(NumalError::DidNotConverge)?;
+            if ft <= values[i] {
+                if opts.strategy == DeStrategy::Jade {
+                    if ft < values[i] {
+                        good_cr.push(cr);
+                        good_f.push(weight);
+                    }
+                    archive.push(x.clone());
+                }
+                next[i] = trial;
+                values[i] = ft;
+            }
+        }
+        pop = next;
+        while archive.len() > np {
+            archive.swap_remove(rng.below(archive.len()));
+        }
+        if !good_f.is_empty() {
+            mean_cr = (1.0 - JADE_C) * mean_cr
+                + JADE_C * good_cr.iter().sum::<f64>() / good_cr.len() as f64;
+            // Lehmer mean, which favours larger weights
+            mean_f = (1.0 - JADE_C) * mean_f
+                + JADE_C * good_f.iter().map(|v| v * v).sum::<f64>() / good_f.iter().sum::<f64>();
+        }
+    }
+}
+
+// Three distinct indices in `0..n`, all different from `exclude`.
+fn distinct(rng: &mut Rng, n: usize, exclude: usize) -> [usize; 3] {
+    let mut r = [exclude; 3];
+    for k in 0..3 {
+        r[k] = loop {
+            let c = rng.below(n);
+            if c != exclude && !r[..k].contains(&c) {
+                break c;
+            }
+        };
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::{cube, rastrigin, rosenbrock};
+
+    #[test]
+    fn rand_and_jade_find_global_minimum_of_rastrigin() {
+        // DE/best/1 converges too greedily for this landscape
+        for strategy in [DeStrategy::Rand1Bin, DeStrategy::Jade] {
+            let opts = DeOptions {
+                strategy,
+                seed: 3,
+                ..Default::default()
+            };
+            let r =
+                differential_evolution(rastrigin, &[3.0; 2], Some(&cube(2, 5.12)), &opts).unwrap();
+            assert!(r.fx < 1e-10, "{strategy:?}: {r:?}");
+            assert!(r.x.iter().all(|v| v.abs() < 1e-6), "{strategy:?}: {r:?}");
+        }
+    }
+
+    #[test]
+    fn all_strategies_minimize_rosenbrock() {
+        for strategy in [DeStrategy::Rand1Bin, DeStrategy::Best1Bin, DeStrategy::Jade] {
+            let opts = DeOptions {
+                strategy,
+                tol: Tolerance::Strict,
+                ..Default::default()
+            };
+            let r =
+                differential_evolution(rosenbrock, &[0.0; 4], Some(&cube(4, 5.0)), &opts).unwrap();
+            assert!(
+                r.x.iter().all(|v| (v - 1.0).abs() < 1e-8),
+                "{strategy:?}: {r:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        let opts = DeOptions {
+            population: Some(3),
+            ..Default::default()
+        };
+        let r = differential_evolution(rastrigin, &[0.5; 2], Some(&cube(2, 1.0)), &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
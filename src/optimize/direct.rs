@@ -0,0 +1,395 @@
+//! DIRECT, the DIviding RECTangles algorithm of Jones, Perttunen & Stuckman
+//! (1993), and its locally biased variant DIRECT-L (Gablonsky & Kelley,
+//! 2001).
+//!
+//! The box is scaled to the unit hypercube and partitioned into
+//! hyperrectangles, each sampled at its centre. Every iteration trisects the
+//! potentially optimal rectangles: those with the lowest centre value for
+//! some Lipschitz constant `K > 0` given their size, which lie on the lower
+//! right convex hull of the (size, value) pairs, and whose bound
+//! `f - K size` would improve on the best value by at least
+//! `epsilon |f_min|`. A rectangle is trisected along its longest sides, in
+//! increasing order of the best value sampled a third of a side away from
+//! the centre, so that the best samples end up in the largest pieces.
+//!
+//! DIRECT measures a rectangle by its half-diagonal and divides every
+//! potentially optimal rectangle of each size. DIRECT-L measures it by its
+//! longest side, which groups more rectangles together, and divides only
+//! one rectangle per size, concentrating the effort near the best points.
+//!
+//! The search stops once the rectangle around the best point has a size,
+//! relative to the box, of at most `eps_rel` of the [`Tolerance`]. Jones et
+//! al. suggest `epsilon = 1e-4`, but then the best rectangle is seldom
+//! divided once the values near it agree to four digits, and tight
+//! tolerances take very many evaluations; the default ties `epsilon` to the
+//! tolerance instead. Objective values that are NaN count as `+inf`.
+
+use super::{Bounds, Budgeted, Minimum};
+use crate::NumalError;
+use crate::core::tolerance::Tolerance;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How [`direct`] measures and selects rectangles
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DirectMethod {
+    /// DIRECT: half-diagonal size, every tied rectangle divided
+    #[default]
+    Original,
+    /// DIRECT-L: longest-side size, one rectangle per size divided
+    LocallyBiased,
+}
+
+/// Options for [`direct`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirectOptions {
+    pub method: DirectMethod,
+    /// Least relative improvement a rectangle must promise to be divided;
+    /// `None` for `eps_rel^2`, the accuracy in value that goes with locating
+    /// the minimizer to `eps_rel`
+    pub epsilon: Option<f64>,
+    /// Size tolerance of the rectangle around the best point
+    pub tol: Tolerance,
+    pub max_evaluations: usize,
+}
+
+impl Default for DirectOptions {
+    fn default() -> Self {
+        DirectOptions {
+            method: DirectMethod::Original,
+            epsilon: None,
+            tol: Tolerance::Default,
+            max_evaluations: 20_000,
+        }
+    }
+}
+
+/// Minimizes `f` over the finite box `bounds` by DIRECT or DIRECT-L.
+///
+/// Returns [`NumalError::DidNotConverge`] if the evaluation budget runs out
+/// before the best point is located to within the tolerance.
+pub fn direct<F>(f: F, bounds: &Bounds, opts: &DirectOptions) -> Result<Minimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let n = bounds.dim();
+    if n == 0 || !bounds.is_finite() {
+        return Err(NumalError::InvalidInput(
+            "DIRECT requires finite bounds in at least one dimension".to_string(),
+        ));
+    }
+    let epsilon = opts.epsilon.unwrap_or(opts.tol.eps_rel().powi(2));
+    if !(epsilon >= 0.0 && epsilon.is_finite()) {
+        return Err(NumalError::InvalidInput(format!(
+            "epsilon must be finite and non-negative, got {epsilon}"
+        )));
+    }
+    let (lower, upper) = (bounds.lower(), bounds.upper());
+    let mut objective = Budgeted::new(&f, opts.max_evaluations);
+    let mut sample = |c: &[f64]| {
+        let x: Vec<f64> = (0..n)
+            .map(|j| lower[j] + c[j] * (upper[j] - lower[j]))
+            .collect();
+        objective.eval(&x).ok_or(NumalError::DidNotConverge)
+    };
+    let center = vec![0.5; n];
+    let f0 = sample(&center)?;
+    let mut partition = Partition {
+        method: opts.method,
+        rects: Vec::new(),
+        classes: BTreeMap::new(),
+    };
+    partition.push(Rect {
+        center,
+        levels: vec![0; n],
+        f: f0,
+    });
+    let mut iter = 0;
+    loop {
+        if partition.size(partition.best()) <= opts.tol.eps_rel() {
+            return Ok(objective.minimum(iter));
+        }
+        iter += 1;
+        for i in partition.potentially_optimal(epsilon) {
+            partition.divide(i, &mut sample)?;
+        }
+    }
+}
+
+// A hyperrectangle of the unit cube. Side j has length `3^-levels[j]`; the
+// sides only ever differ by one level.
+struct Rect {
+    center: Vec<f64>,
+    levels: Vec<u32>,
+    f: f64,
+}
+
+impl Rect {
+    // The level of the longest sides and how many sides have it.
+    fn shape(&self) -> (u32, usize) {
+        let k = self.levels.iter().copied().min().unwrap_or(0);
+        (k, self.levels.iter().filter(|&&l| l == k).count())
+    }
+}
+
+// Size class of a rectangle, ordered by increasing size.
+type Class = (Reverse<u32>, usize);
+
+// The rectangles, indexed by size class and, within a class, by centre
+// value, so that an iteration need not look at every rectangle.
+struct Partition {
+    method: DirectMethod,
+    rects: Vec<Rect>,
+    classes: BTreeMap<Class, BTreeSet<(i64, usize)>>,
+}
+
+impl Partition {
+    fn class(&self, i: usize) -> Class {
+        let (k, count) = self.rects[i].shape();
+        match self.method {
+            DirectMethod::Original => (Reverse(k), count),
+            DirectMethod::LocallyBiased => (Reverse(k), 0),
+        }
+    }
+
+    fn insert(&mut self, i: usize) {
+        let class = self.class(i);
+        let key = (ordered(self.rects[i].f), i);
+        self.classes.entry(class).or_default().insert(key);
+    }
+
+    fn remove(&mut self, i: usize) {
+        let class = self.class(i);
+        if let Some(set) = self.classes.get_mut(&class) {
+            set.remove(&(ordered(self.rects[i].f), i));
+            if set.is_empty() {
+                self.classes.remove(&class);
+            }
+        }
+    }
+
+    fn push(&mut self, rect: Rect) {
+        self.rects.push(rect);
+        self.insert(self.rects.len() - 1);
+    }
+
+    // Index of the rectangle with the least centre value.
+    fn best(&self) -> usize {
+        self.classes
+            .values()
+            .filter_map(|set| set.first())
+            .min()
+            .map_or(0, |&(_, i)| i)
+    }
+
+    // Size of rectangle `i`, relative to the unit cube.
+    fn size(&self, i: usize) -> f64 {
+        let n = self.rects[i].levels.len();
+        let (k, count) = self.rects[i].shape();
+        let long = 3f64.powi(-(k as i32));
+        match self.method {
+            DirectMethod::Original => {
+                let squares = count as f64 + (n - count) as f64 / 9.0;
+                0.5 * long * squares.sqrt()
+            }
+            DirectMethod::LocallyBiased => long,
+        }
+    }
+
+    // Indices of the rectangles to divide in this iteration.
+    fn potentially_optimal(&self, epsilon: f64) -> Vec<usize> {
+        // Size, least value and the members attaining it, by increasing size
+        let groups: Vec<(f64, f64, Vec<usize>)> = self
+            .classes
+            .values()
+            .filter_map(|set| {
+                let &(v, i) = set.first()?;
+                let members = match self.method {
+                    DirectMethod::Original => set
+                        .iter()
+                        .take_while(|&&(w, _)| w == v)
+                        .map(|&(_, j)| j)
+                        .collect(),
+                    DirectMethod::LocallyBiased => vec![i],
+                };
+                Some((self.size(i), self.rects[i].f, members))
+            })
+            .collect();
+        let finite: Vec<usize> = (0..groups.len())
+            .filter(|&g| groups[g].1.is_finite())
+            .collect();
+        let Some(&first) = finite.iter().min_by(|&&a, &&b| {
+            groups[a]
+                .1
+                .total_cmp(&groups[b].1)
+                .then(groups[b].0.total_cmp(&groups[a].0))
+        }) else {
+            // Nothing finite has been seen yet: explore the largest rectangles
+            return groups.last().map(|g| g.2.clone()).unwrap_or_default();
+        };
+        let f_min = groups[first].1;
+        // Lower convex hull of the groups from the best one up to the largest
+        let mut hull: Vec<usize> = Vec::new();
+        for &g in finite.iter().filter(|&&g| g >= first) {
+            while let [.., a, b] = hull[..] {
+                let (da, fa) = (groups[a].0, groups[a].1);
+                let (db, fb) = (groups[b].0, groups[b].1);
+                let (dg, fg) = (groups[g].0, groups[g].1);
+                if (db - da) * (fg - fa) - (fb - fa) * (dg - da) < 0.0 {
+                    hull.pop();
+                } else {
+                    break;
+                }
+            }
+            hull.push(g);
+        }
+        let mut selected = Vec::new();
+        for (h, &g) in hull.iter().enumerate() {
+            let (d, fg) = (groups[g].0, groups[g].1);
+            // The largest Lipschitz constant for which this group is the best
+            let promising = match hull.get(h + 1) {
+                Some(&next) => {
+                    let slope = (groups[next].1 - fg) / (groups[next].0 - d);
+                    fg - slope * d <= f_min - epsilon * f_min.abs()
+                }
+                None => true,
+            };
+            if promising {
+                selected.extend_from_slice(&groups[g].2);
+            }
+        }
+        selected
+    }
+
+    // Trisects rectangle `i` along its longest sides.
+    fn divide<S>(&mut self, i: usize, sample: &mut S) -> Result<(), NumalError>
+    where
+        S: FnMut(&[f64]) -> Result<f64, NumalError>,
+    {
+        let (k, _) = self.rects[i].shape();
+        let delta = 3f64.powi(-(k as i32) - 1);
+        let mut pieces = Vec::new();
+        for j in 0..self.rects[i].levels.len() {
+            if self.rects[i].levels[j] != k {
+                continue;
+            }
+            let mut plus = self.rects[i].center.clone();
+            plus[j] += delta;
+            let f_plus = sample(&plus)?;
+            let mut minus = self.rects[i].center.clone();
+            minus[j] -= delta;
+            let f_minus = sample(&minus)?;
+            pieces.push((f_plus.min(f_minus), j, [(plus, f_plus), (minus, f_minus)]));
+        }
+        pieces.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.remove(i);
+        for (_, j, sides) in pieces {
+            self.rects[i].levels[j] += 1;
+            for (center, f) in sides {
+                let levels = self.rects[i].levels.clone();
+                self.push(Rect { center, levels, f });
+            }
+        }
+        self.insert(i);
+        Ok(())
+    }
+}
+
+// Maps a value to an integer with the order of `f64::total_cmp`.
+fn ordered(f: f64) -> i64 {
+    let bits = f.to_bits() as i64;
+    bits ^ (((bits >> 63) as u64) >> 1) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Three global minima with value 0.397887
+    fn branin(x: &[f64]) -> f64 {
+        let pi = std::f64::consts::PI;
+        let (a, b, c) = (1.0, 5.1 / (4.0 * pi * pi), 5.0 / pi);
+        let (r, s, t) = (6.0, 10.0, 1.0 / (8.0 * pi));
+        a * (x[1] - b * x[0] * x[0] + c * x[0] - r).powi(2) + s * (1.0 - t) * x[0].cos() + s
+    }
+
+    // Global minimum 3 at (0, -1), with several local minima
+    fn goldstein_price(x: &[f64]) -> f64 {
+        let (u, v) = (x[0], x[1]);
+        let a = 1.0
+            + (u + v + 1.0).powi(2)
+                * (19.0 - 14.0 * u + 3.0 * u * u - 14.0 * v + 6.0 * u * v + 3.0 * v * v);
+        let b = 30.0
+            + (2.0 * u - 3.0 * v).powi(2)
+                * (18.0 - 32.0 * u + 12.0 * u * u + 48.0 * v - 36.0 * u * v + 27.0 * v * v);
+        a * b
+    }
+
+    #[test]
+    fn both_methods_minimize_branin() {
+        let bounds = Bounds::new(vec![-5.0, 0.0], vec![10.0, 15.0]).unwrap();
+        for method in [DirectMethod::Original, DirectMethod::LocallyBiased] {
+            let opts = DirectOptions {
+                method,
+                ..Default::default()
+            };
+            let r = direct(branin, &bounds, &opts).unwrap();
+            assert!((r.fx - 0.397887357729738).abs() < 1e-9, "{method:?}: {r:?}");
+        }
+    }
+
+    #[test]
+    fn both_methods_minimize_goldstein_price() {
+        let bounds = Bounds::new(vec![-2.0; 2], vec![2.0; 2]).unwrap();
+        for method in [DirectMethod::Original, DirectMethod::LocallyBiased] {
+            let opts = DirectOptions {
+                method,
+                ..Default::default()
+            };
+            let r = direct(goldstein_price, &bounds, &opts).unwrap();
+            assert!((r.fx - 3.0).abs() < 1e-8, "{method:?}: {r:?}");
+            assert!(r.x[0].abs() < 1e-4 && (r.x[1] + 1.0).abs() < 1e-4, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn jones_epsilon_reaches_loose_tolerance() {
+        let bounds = Bounds::new(vec![-5.0, 0.0], vec![10.0, 15.0]).unwrap();
+        let opts = DirectOptions {
+            epsilon: Some(1e-4),
+            tol: Tolerance::Loose,
+            ..Default::default()
+        };
+        let r = direct(branin, &bounds, &opts).unwrap();
+        assert!((r.fx - 0.397887357729738).abs() < 1e-5, "{r:?}");
+    }
+
+    #[test]
+    fn locally_biased_needs_fewer_evaluations() {
+        let bounds = Bounds::new(vec![-3.0; 4], vec![4.0; 4]).unwrap();
+        let f = |x: &[f64]| x.iter().map(|v| (v - 1.0).powi(2)).sum::<f64>();
+        let run = |method| {
+            let opts = DirectOptions {
+                method,
+                ..Default::default()
+            };
+            direct(f, &bounds, &opts).unwrap()
+        };
+        let (original, local) = (
+            run(DirectMethod::Original),
+            run(DirectMethod::LocallyBiased),
+        );
+        assert!(local.x.iter().all(|v| (v - 1.0).abs() < 1e-5), "{local:?}");
+        assert!(local.evaluations < original.evaluations);
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        let opts = DirectOptions {
+            epsilon: Some(-1.0),
+            ..Default::default()
+        };
+        let r = direct(branin, &Bounds::new(vec![0.0], vec![1.0]).unwrap(), &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
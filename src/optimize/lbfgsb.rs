@@ -0,0 +1,403 @@
+//! Bound-constrained limited-memory BFGS (L-BFGS-B).
+//!
+//! Follows Byrd, Lu, Nocedal & Zhu (1995). The limited-memory Hessian is
+//! kept in the compact form `B = theta I - W M W^T`. Each iteration first
+//! finds the generalized Cauchy point, the first local minimizer of the
+//! quadratic model along the projected steepest-descent path, which fixes
+//! the variables that reach a bound. The model is then minimized over the
+//! remaining free variables and the result truncated to the box. A
+//! backtracking line search along the resulting feasible direction never
+//! leaves the box.
+//!
+//! Convergence is measured by the projected gradient `x - P(x - g)`, which
+//! vanishes exactly at points satisfying the first-order conditions.
+
+use super::linesearch::LineSearch;
+use super::{
+    Bounds, GradientMinimum, Termination, gradient_minimum, gradient_termination, initial_gradient,
+};
+use crate::NumalError;
+use crate::core::linalg::{Lu, Matrix, dot, norm};
+use crate::core::tolerance::Tolerance;
+use std::collections::VecDeque;
+
+/// Options for [`lbfgsb`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LbfgsbOptions {
+    /// Projected-gradient, step and function-change tolerance
+    pub tol: Tolerance,
+    pub max_iter: usize,
+    /// Number of curvature pairs kept
+    pub history: usize,
+}
+
+impl Default for LbfgsbOptions {
+    fn default() -> Self {
+        LbfgsbOptions {
+            tol: Tolerance::Default,
+            max_iter: 1_000,
+            history: 10,
+        }
+    }
+}
+
+// Compact representation B = theta I - W M W^T, with W = [Y, theta S].
+struct Compact {
+    theta: f64,
+    w: Matrix,
+    m: Matrix,
+}
+
+impl Compact {
+    fn new(pairs: &VecDeque<(Vec<f64>, Vec<f64>)>, n: usize) -> Option<Compact> {
+        let k = pairs.len();
+        let Some((s, y)) = pairs.back() else {
+            return Some(Compact {
+                theta: 1.0,
+                w: Matrix::zeros(n, 0),
+                m: Matrix::zeros(0, 0),
+            });
+        };
+        let theta = dot(y, y) / dot(s, y);
+        let mut w = Matrix::zeros(n, 2 * k);
+        for (j, (s, y)) in pairs.iter().enumerate() {
+            for i in 0..n {
+                w[(i, j)] = y[i];
+                w[(i, k + j)] = theta * s[i];
+            }
+        }
+        // M^{-1} = [[-D, L^T], [L, theta S^T S]] with D = diag(s_i·y_i) and
+        // L the strictly lower triangle of S^T Y
+        let minv = Matrix::from_fn(2 * k, 2 * k, |a, b| {
+            let (sa, ya) = &pairs[a % k];
+            let (sb, yb) = &pairs[b % k];
+            match (a < k, b < k) {
+                (true, true) if a == b => -dot(sa, ya),
+                (true, true) => 0.0,
+                (true, false) if a > b % k => dot(sa, yb),
+                (false, true) if a % k > b => dot(sa, yb),
+                (false, false) => theta * dot(sa, sb),
+                _ => 0.0,
+            }
+        });
+        let m = Lu::new(&minv)?.inverse();
+        Some(Compact { theta, w, m })
+    }
+}
+
+/// Minimizes `fg` subject to `bounds` with the L-BFGS-B method.
+///
+/// `fg(x, g)` returns `f(x)` and writes the gradient into `g`. An initial
+/// guess outside the box is first projected onto it. The reported
+/// [`Termination::GradientNorm`] refers to the projected gradient, while
+/// `grad` holds the plain gradient at the solution.
+pub fn lbfgsb<F>(
+    fg: F,
+    x0: &[f64],
+    bounds: &Bounds,
+    opts: &LbfgsbOptions,
+) -> Result<GradientMinimum, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    if opts.history == 0 {
+        return Err(NumalError::InvalidInput(
+            "L-BFGS-B history length must be at least 1".to_string(),
+        ));
+    }
+    super::check_start(x0)?;
+    bounds.check_dim(x0.len())?;
+    let n = x0.len();
+    let mut x = x0.to_vec();
+    bounds.project(&mut x);
+    let (mut f, mut g) = initial_gradient(&fg, &x)?;
+    let mut evals = 1;
+    let line_search = LineSearch::backtracking();
+    let mut pairs: VecDeque<(Vec<f64>, Vec<f64>)> = VecDeque::with_capacity(opts.history);
+    if projected_gradient(&x, &g, bounds)
+        .iter()
+        .all(|v| v.abs() <= opts.tol.eps_abs())
+    {
+        return Ok(gradient_minimum(
+            x,
+            f,
+            g,
+            0,
+            evals,
+            Termination::GradientNorm,
+            None,
+        ));
+    }
+    for iter in 1..=opts.max_iter {
+        let mut d = Compact::new(&pairs, n)
+            .and_then(|b| direction(&x, &g, bounds, &b))
+            .unwrap_or_default();
+        if d.is_empty() || dot(&d, &g) >= 0.0 {
+            pairs.clear();
+            d = direction(&x, &g, bounds, &Compact::new(&pairs, n).unwrap()).unwrap_or_default();
+        }
+        // The direction ends at a feasible point, so steps up to 1 stay in
+        // the box; without curvature pairs it is unscaled
+        let step = if pairs.is_empty() {
+            (1.0 / norm(&d)).min(1.0)
+        } else {
+            1.0
+        };
+        let ls = match line_search.search(&fg, &x, f, &g, &d, step) {
+            Err(NumalError::LineSearchFailed { .. }) if !pairs.is_empty() => {
+                pairs.clear();
+                continue;
+            }
+            r => r?,
+        };
+        evals += ls.evaluations;
+        let mut x_new = ls.x;
+        bounds.project(&mut x_new);
+        let s: Vec<f64> = x_new.iter().zip(&x).map(|(a, b)| a - b).collect();
+        let y: Vec<f64> = ls.grad.iter().zip(&g).map(|(a, b)| a - b).collect();
+        let (x_old, f_old) = (std::mem::replace(&mut x, x_new), f);
+        f = ls.fx;
+        g = ls.grad;
+        let pg = projected_gradient(&x, &g, bounds);
+        if let Some(reason) = gradient_termination(&pg, &x_old, &x, f_old, f, opts.tol) {
+            return Ok(gradient_minimum(x, f, g, iter, evals, reason, None));
+        }
+        if dot(&s, &y) > f64::EPSILON * dot(&y, &y) {
+            if pairs.len() == opts.history {
+                pairs.pop_front();
+            }
+            pairs.push_back((s, y));
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+// x - P(x - g): zero exactly at first-order points of the bounded problem.
+fn projected_gradient(x: &[f64], g: &[f64], bounds: &Bounds) -> Vec<f64> {
+    x.iter()
+        .zip(g)
+        .zip(bounds.lower().iter().zip(bounds.upper()))
+        .map(|((&xi, &gi), (&l, &u))| xi - (xi - gi).clamp(l, u))
+        .collect()
+}
+
+// Search direction from `x` to the truncated subspace minimizer, or `None`
+// when the reduced system is singular.
+fn direction(x: &[f64], g: &[f64], bounds: &Bounds, b: &Compact) -> Option<Vec<f64>> {
+    let (xc, c) = cauchy_point(x, g, bounds, b);
+    let xbar = subspace_minimum(x, g, bounds, b, xc, &c)?;
+    Some(xbar.iter().zip(x).map(|(a, b)| a - b).collect())
+}
+
+// Generalized Cauchy point (Byrd et al., Algorithm CP). Returns the point
+// and c = W^T (xc - x), needed by the subspace minimization.
+fn cauchy_point(x: &[f64], g: &[f64], bounds: &Bounds, b: &Compact) -> (Vec<f64>, Vec<f64>) {
+    let (lower, upper) = (bounds.lower(), bounds.upper());
+    let theta = b.theta;
+    let mut d: Vec<f64> = g.iter().map(|v| -v).collect();
+    let mut breaks = Vec::new();
+    for i in 0..x.len() {
+        let t = if g[i] < 0.0 {
+            (x[i] - upper[i]) / g[i]
+        } else if g[i] > 0.0 {
+            (x[i] - lower[i]) / g[i]
+        } else {
+            f64::INFINITY
+        };
+        if t == 0.0 {
+            d[i] = 0.0;
+        } else if t.is_finite() {
+            breaks.push((t, i));
+        }
+    }
+    breaks.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut xc = x.to_vec();
+    let mut p = b.w.tr_mul_vec(&d);
+    let mut c = vec![0.0; p.len()];
+    let mut fp = -dot(&d, &d);
+    let fpp0 = -theta * fp - dot(&p, &b.m.mul_vec(&p));
+    let mut fpp = fpp0;
+    let mut dt_min = -fp / fpp;
+    let mut t_old = 0.0;
+    for &(t, i) in &breaks {
+        let dt = t - t_old;
+        if dt_min < dt {
+            break;
+        }
+        xc[i] = if d[i] > 0.0 { upper[i] } else { lower[i] };
+        let z = xc[i] - x[i];
+        let gb = g[i];
+        let wb = b.w.row(i);
+        c.iter_mut().zip(&p).for_each(|(ci, pi)| *ci += dt * pi);
+        let mwb = b.m.mul_vec(wb);
+        fp += dt * fpp + gb * gb + theta * gb * z - gb * dot(&mwb, &c);
+        fpp -= theta * gb * gb + 2.0 * gb * dot(&mwb, &p) + gb * gb * dot(&mwb, wb);
+        fpp = fpp.max(f64::EPSILON * fpp0);
+        p.iter_mut().zip(wb).for_each(|(pi, wi)| *pi += gb * wi);
+        d[i] = 0.0;
+        dt_min = -fp / fpp;
+        t_old = t;
+    }
+    let dt_min = dt_min.max(0.0);
+    let t = t_old + dt_min;
+    for (xi, (x0, di)) in xc.iter_mut().zip(x.iter().zip(&d)) {
+        if *di != 0.0 {
+            *xi = x0 + t * di;
+        }
+    }
+    bounds.project(&mut xc);
+    c.iter_mut().zip(&p).for_each(|(ci, pi)| *ci += dt_min * pi);
+    (xc, c)
+}
+
+// Minimizes the model over the variables that are free at the Cauchy point
+// by the direct primal method, then truncates the step to the box.
+fn subspace_minimum(
+    x: &[f64],
+    g: &[f64],
+    bounds: &Bounds,
+    b: &Compact,
+    mut xc: Vec<f64>,
+    c: &[f64],
+) -> Option<Vec<f64>> {
+    let (lower, upper) = (bounds.lower(), bounds.upper());
+    let free: Vec<usize> = (0..x.len())
+        .filter(|&i| lower[i] < xc[i] && xc[i] < upper[i])
+        .collect();
+    if free.is_empty() {
+        return Some(xc);
+    }
+    let theta = b.theta;
+    let k2 = b.w.cols();
+    let wmc = b.w.mul_vec(&b.m.mul_vec(c));
+    // Reduced gradient of the model at the Cauchy point
+    let r: Vec<f64> = free
+        .iter()
+        .map(|&i| g[i] + theta * (xc[i] - x[i]) - wmc[i])
+        .collect();
+    let wz = Matrix::from_fn(free.len(), k2, |a, j| b.w[(free[a], j)]);
+    let mut du: Vec<f64> = r.iter().map(|v| -v / theta).collect();
+    if k2 > 0 {
+        let v = b.m.mul_vec(&wz.tr_mul_vec(&r));
+        let mut n_mat = b.m.matmul(&wz.transpose().matmul(&wz));
+        for v in n_mat.as_mut_slice() {
+            *v /= -theta;
+        }
+        for i in 0..k2 {
+            n_mat[(i, i)] += 1.0;
+        }
+        let v = Lu::new(&n_mat)?.solve(&v);
+        let wv = wz.mul_vec(&v);
+        du.iter_mut()
+            .zip(&wv)
+            .for_each(|(di, wi)| *di -= wi / (theta * theta));
+    }
+    // Largest step along du, up to 1, that keeps the free variables feasible
+    let mut alpha: f64 = 1.0;
+    for (&i, &di) in free.iter().zip(&du) {
+        if di > 0.0 {
+            alpha = alpha.min((upper[i] - xc[i]) / di);
+        } else if di < 0.0 {
+            alpha = alpha.min((lower[i] - xc[i]) / di);
+        }
+    }
+    for (&i, &di) in free.iter().zip(&du) {
+        xc[i] += alpha * di;
+    }
+    bounds.project(&mut xc);
+    Some(xc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::rosenbrock_grad;
+    use crate::optimize::{LbfgsOptions, lbfgs};
+
+    #[test]
+    fn active_bound_on_rosenbrock() {
+        // With x <= 0.5 the minimum moves to (0.5, 0.25)
+        let bounds = Bounds::new(vec![-2.0, -1.0], vec![0.5, 2.0]).unwrap();
+        let opts = LbfgsbOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = lbfgsb(rosenbrock_grad, &[-1.2, 1.0], &bounds, &opts).unwrap();
+        assert!(
+            (r.x[0] - 0.5).abs() < 1e-8 && (r.x[1] - 0.25).abs() < 1e-6,
+            "{r:?}"
+        );
+        assert!(r.grad[0] < 0.0);
+    }
+
+    #[test]
+    fn infinite_bounds_match_lbfgs() {
+        let inf = f64::INFINITY;
+        let bounds = Bounds::new(vec![-inf; 2], vec![inf; 2]).unwrap();
+        let opts = LbfgsbOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = lbfgsb(rosenbrock_grad, &[-1.2, 1.0], &bounds, &opts).unwrap();
+        let u = lbfgs(rosenbrock_grad, &[-1.2, 1.0], &LbfgsOptions::default()).unwrap();
+        for (a, b) in r.x.iter().zip(&u.x) {
+            assert!((a - b).abs() < 1e-4, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn many_active_bounds() {
+        // Separable quadratic whose unconstrained minimizer lies outside the
+        // box in most coordinates: the solution is its projection
+        let target: Vec<f64> = (0..30).map(|i| (i as f64 - 15.0) / 3.0).collect();
+        let fg = |x: &[f64], g: &mut [f64]| {
+            let mut f = 0.0;
+            for (i, (gi, (xi, ti))) in g.iter_mut().zip(x.iter().zip(&target)).enumerate() {
+                let w = 1.0 + i as f64;
+                *gi = 2.0 * w * (xi - ti);
+                f += w * (xi - ti).powi(2);
+            }
+            f
+        };
+        let bounds = Bounds::new(vec![-1.0; 30], vec![2.0; 30]).unwrap();
+        let opts = LbfgsbOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = lbfgsb(fg, &[5.0; 30], &bounds, &opts).unwrap();
+        for (xi, ti) in r.x.iter().zip(&target) {
+            assert!((xi - ti.clamp(-1.0, 2.0)).abs() < 1e-7, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn fixed_variable_is_respected() {
+        let bounds = Bounds::new(vec![0.3, -5.0], vec![0.3, 5.0]).unwrap();
+        let r = lbfgsb(
+            rosenbrock_grad,
+            &[0.0, 0.0],
+            &bounds,
+            &LbfgsbOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(r.x[0], 0.3);
+        assert!((r.x[1] - 0.09).abs() < 1e-6, "{r:?}");
+    }
+
+    #[test]
+    fn inconsistent_bounds_are_rejected() {
+        let r = Bounds::new(vec![0.0, 1.0], vec![1.0, 0.5]);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = Bounds::new(vec![0.0, f64::NAN], vec![1.0, 1.0]);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let bounds = Bounds::new(vec![0.0], vec![1.0]).unwrap();
+        let r = lbfgsb(
+            rosenbrock_grad,
+            &[0.0, 0.0],
+            &bounds,
+            &LbfgsbOptions::default(),
+        );
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,972 @@
+//! Line searches along a descent direction.
+//!
+//! Given a point `x`, the objective value and gradient there, and a
+//! direction `d` with `g·d < 0`, a line search chooses a step `t > 0` so
+//! that `x + t d` sufficiently decreases the objective. The objective is
+//! supplied as `fg(x, g)`, returning `f(x)` and writing the gradient into
+//! `g`, the same convention used by every gradient-based optimizer in
+//! [`crate::optimize`].
+//!
+//! The searches are independent of any particular descent method and can be
+//! driven directly through [`LineSearch::search`]. A search that cannot meet
+//! its acceptance conditions within its budget fails with
+//! [`NumalError::LineSearchFailed`], reporting the best step it tried, rather
+//! than returning an arbitrarily small step.
+
+use crate::NumalError;
+use crate::core::error::LineSearchFailure;
+use crate::core::linalg::dot;
+use std::convert::Infallible;
+
+// Upper bound on the step of the More-Thuente search.
+const MT_STEP_MAX: f64 = 1e20;
+// Bracket expansion factor of the Hager-Zhang search.
+const HZ_EXPANSION: f64 = 5.0;
+
+/// Line-search strategy and its parameters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineSearch {
+    /// Backtracking until the Armijo sufficient-decrease condition holds.
+    /// Cheap, but gives no control over the curvature of the accepted step.
+    Backtracking {
+        /// Sufficient-decrease constant, `0 < c1 < 1`
+        c1: f64,
+        /// Contraction factor applied to a rejected step, `0 < rho < 1`
+        rho: f64,
+        /// Maximum number of trial steps
+        max_iter: usize,
+    },
+    /// Strong Wolfe conditions enforced by bracketing and cubic-interpolation
+    /// zoom (Nocedal & Wright, Algorithms 3.5 and 3.6).
+    StrongWolfe {
+        /// Sufficient-decrease constant, `0 < c1 < c2`
+        c1: f64,
+        /// Curvature constant, `c1 < c2 < 1`
+        c2: f64,
+        /// Maximum number of trial steps
+        max_iter: usize,
+    },
+    /// Strong Wolfe conditions enforced by the safeguarded interval updates
+    /// of More & Thuente (1994), as in MINPACK-2's `dcsrch`.
+    MoreThuente {
+        /// Sufficient-decrease constant, `0 < ftol < gtol`
+        ftol: f64,
+        /// Curvature constant, `ftol < gtol < 1`
+        gtol: f64,
+        /// Relative width below which the interval of uncertainty is
+        /// considered collapsed
+        xtol: f64,
+        /// Maximum number of trial steps
+        max_iter: usize,
+    },
+    /// Standard or approximate Wolfe conditions enforced by the secant and
+    /// bisection steps of Hager & Zhang (2005), as used by CG_DESCENT. The
+    /// approximate conditions remain attainable near a minimizer where the
+    /// sufficient-decrease test is swamped by rounding error.
+    HagerZhang {
+        /// Sufficient-decrease constant, `0 < delta < 1/2`
+        delta: f64,
+        /// Curvature constant, `delta <= sigma < 1`
+        sigma: f64,
+        /// Allowed relative increase of the objective for the approximate
+        /// conditions
+        epsilon: f64,
+        /// Bisection ratio of the interval update, `0 < theta < 1`
+        theta: f64,
+        /// Required shrinkage of the interval per secant step, `0 < gamma < 1`
+        gamma: f64,
+        /// Maximum number of trial steps
+        max_iter: usize,
+    },
+}
+
+impl Default for LineSearch {
+    fn default() -> Self {
+        LineSearch::StrongWolfe {
+            c1: 1e-4,
+            c2: 0.9,
+            max_iter: 30,
+        }
+    }
+}
+
+impl LineSearch {
+    /// Armijo backtracking with `c1 = 1e-4` and halving of rejected steps
+    pub fn backtracking() -> Self {
+        LineSearch::Backtracking {
+            c1: 1e-4,
+            rho: 0.5,
+            max_iter: 50,
+        }
+    }
+
+    /// More-Thuente search with the usual quasi-Newton constants
+    pub fn more_thuente() -> Self {
+        LineSearch::MoreThuente {
+            ftol: 1e-4,
+            gtol: 0.9,
+            xtol: 1e-10,
+            max_iter: 30,
+        }
+    }
+
+    /// Hager-Zhang search with the constants recommended by its authors
+    pub fn hager_zhang() -> Self {
+        LineSearch::HagerZhang {
+            delta: 0.1,
+            sigma: 0.9,
+            epsilon: 1e-6,
+            theta: 0.5,
+            gamma: 0.66,
+            max_iter: 50,
+        }
+    }
+}
+
+/// Accepted step of a line search
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineSearchResult {
+    /// Step length `t`
+    pub step: f64,
+    /// The new point `x + t d`
+    pub x: Vec<f64>,
+    /// Objective value at the new point
+    pub fx: f64,
+    /// Gradient at the new point
+    pub grad: Vec<f64>,
+    /// Number of objective/gradient evaluations used
+    pub evaluations: usize,
+}
+
+// A trial point on the line, with the directional derivative there.
+#[derive(Clone)]
+struct Trial {
+    t: f64,
+    x: Vec<f64>,
+    f: f64,
+    g: Vec<f64>,
+    dphi: f64,
+}
+
+// The objective restricted to the ray x + t d, remembering the step with
+// the lowest value seen so far for failure reports.
+struct Ray<'a, F> {
+    fg: &'a F,
+    x: &'a [f64],
+    d: &'a [f64],
+    evals: usize,
+    best: (f64, f64),
+}
+
+impl<F> Ray<'_, F>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    fn at(&mut self, t: f64) -> Trial {
+        let x: Vec<f64> = self
+            .x
+            .iter()
+            .zip(self.d)
+            .map(|(xi, di)| xi + t * di)
+            .collect();
+        let mut g = vec![0.0; x.len()];
+        let mut f = (self.fg)(&x, &mut g);
+        self.evals += 1;
+        if f.is_nan() {
+            f = f64::INFINITY;
+        }
+        if f < self.best.1 {
+            self.best = (t, f);
+        }
+        let dphi = dot(&g, self.d);
+        Trial { t, x, f, g, dphi }
+    }
+
+    fn fail(&self, reason: LineSearchFailure) -> NumalError {
+        NumalError::LineSearchFailed {
+            reason,
+            step: self.best.0,
+            fx: self.best.1,
+        }
+    }
+}
+
+impl Trial {
+    fn accept(self, evaluations: usize) -> LineSearchResult {
+        LineSearchResult {
+            step: self.t,
+            x: self.x,
+            fx: self.f,
+            grad: self.g,
+            evaluations,
+        }
+    }
+}
+
+impl LineSearch {
+    /// Searches along `d` from `x`, where `fx` and `grad` are the objective
+    /// value and gradient at `x`, starting with the trial step `step`.
+    pub fn search<F>(
+        &self,
+        fg: &F,
+        x: &[f64],
+        fx: f64,
+        grad: &[f64],
+        d: &[f64],
+        step: f64,
+    ) -> Result<LineSearchResult, NumalError>
+    where
+        F: Fn(&[f64], &mut [f64]) -> f64,
+    {
+        let dphi0 = dot(grad, d);
+        if dphi0 >= 0.0 || dphi0.is_nan() {
+            return Err(NumalError::InvalidInput(format!(
+                "search direction is not a descent direction (g·d = {dphi0})"
+            )));
+        }
+        if !(step > 0.0 && step.is_finite()) {
+            return Err(NumalError::InvalidInput(format!(
+                "initial step must be positive and finite, got {step}"
+            )));
+        }
+        let mut ray = Ray {
+            fg,
+            x,
+            d,
+            evals: 0,
+            best: (0.0, fx),
+        };
+        let origin = Trial {
+            t: 0.0,
+            x: x.to_vec(),
+            f: fx,
+            g: grad.to_vec(),
+            dphi: dphi0,
+        };
+        match *self {
+            LineSearch::Backtracking { c1, rho, max_iter } => {
+                if !(0.0 < c1 && c1 < 1.0 && 0.0 < rho && rho < 1.0) {
+                    return Err(NumalError::InvalidInput(format!(
+                        "backtracking constants must lie in (0, 1), got c1 = {c1}, rho = {rho}"
+                    )));
+                }
+                backtracking(&mut ray, origin, step, c1, rho, max_iter)
+            }
+            LineSearch::StrongWolfe { c1, c2, max_iter } => {
+                check_wolfe_constants(c1, c2)?;
+                strong_wolfe(&mut ray, origin, step, c1, c2, max_iter)
+            }
+            LineSearch::MoreThuente {
+                ftol,
+                gtol,
+                xtol,
+                max_iter,
+            } => {
+                check_wolfe_constants(ftol, gtol)?;
+                if xtol < 0.0 || xtol.is_nan() {
+                    return Err(NumalError::InvalidInput(format!(
+                        "xtol must be non-negative, got {xtol}"
+                    )));
+                }
+                more_thuente(&mut ray, origin, step, (ftol, gtol, xtol), max_iter)
+            }
+            LineSearch::HagerZhang {
+                delta,
+                sigma,
+                epsilon,
+                theta,
+                gamma,
+                max_iter,
+            } => {
+                let valid = 0.0 < delta
+                    && delta < 0.5
+                    && delta <= sigma
+                    && sigma < 1.0
+                    && epsilon >= 0.0
+                    && 0.0 < theta
+                    && theta < 1.0
+                    && 0.0 < gamma
+                    && gamma < 1.0;
+                if !valid {
+                    return Err(NumalError::InvalidInput(format!(
+                        "invalid Hager-Zhang constants: delta = {delta}, sigma = {sigma}, \
+                         epsilon = {epsilon}, theta = {theta}, gamma = {gamma}"
+                    )));
+                }
+                let mut hz = HagerZhang {
+                    f0: fx,
+                    dphi0,
+                    delta,
+                    sigma,
+                    eps_f: epsilon * fx.abs(),
+                    theta,
+                    iter: 0,
+                    max_iter,
+                    ray: &mut ray,
+                };
+                match hz.run(origin, step, gamma) {
+                    Stop::Accept(p) => Ok(p.accept(ray.evals)),
+                    Stop::Fail(reason) => Err(ray.fail(reason)),
+                }
+            }
+        }
+    }
+}
+
+fn check_wolfe_constants(c1: f64, c2: f64) -> Result<(), NumalError> {
+    if !(0.0 < c1 && c1 < c2 && c2 < 1.0) {
+        return Err(NumalError::InvalidInput(format!(
+            "line-search constants must satisfy 0 < c1 < c2 < 1, got c1 = {c1}, c2 = {c2}"
+        )));
+    }
+    Ok(())
+}
+
+// Minimizer of the cubic interpolating values and slopes at `a` and `b`.
+fn cubic_min(a: &Trial, b: &Trial) -> Option<f64> {
+    let d1 = a.dphi + b.dphi - 3.0 * (a.f - b.f) / (a.t - b.t);
+    let disc = d1 * d1 - a.dphi * b.dphi;
+    if disc < 0.0 || disc.is_nan() {
+        return None;
+    }
+    let d2 = disc.sqrt().copysign(b.t - a.t);
+    let t = b.t - (b.t - a.t) * (b.dphi + d2 - d1) / (b.dphi - a.dphi + 2.0 * d2);
+    t.is_finite().then_some(t)
+}
+
+fn strong_wolfe<F>(
+    ray: &mut Ray<'_, F>,
+    origin: Trial,
+    step: f64,
+    c1: f64,
+    c2: f64,
+    max_iter: usize,
+) -> Result<LineSearchResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    let (f0, dphi0) = (origin.f, origin.dphi);
+    let armijo = |p: &Trial| p.f <= f0 + c1 * p.t * dphi0;
+    let curvature = |p: &Trial| p.dphi.abs() <= -c2 * dphi0;
+
+    let mut prev = origin;
+    let mut t = step;
+    let mut iter = 0;
+    let (mut lo, mut hi) = loop {
+        if iter == max_iter {
+            return Err(ray.fail(LineSearchFailure::MaxIterations));
+        }
+        iter += 1;
+        let p = ray.at(t);
+        if !armijo(&p) || (iter > 1 && p.f >= prev.f) {
+            break (prev, p);
+        }
+        if curvature(&p) {
+            return Ok(p.accept(ray.evals));
+        }
+        if p.dphi >= 0.0 {
+            break (p, prev);
+        }
+        t = 2.0 * p.t;
+        prev = p;
+    };
+    // Zoom: `lo` satisfies sufficient decrease and has the lowest value so
+    // far; the interval between `lo` and `hi` contains acceptable steps.
+    while iter < max_iter {
+        iter += 1;
+        let width = hi.t - lo.t;
+        let (left, right) = (lo.t.min(hi.t), lo.t.max(hi.t));
+        let guard = 0.1 * width.abs();
+        let t = match cubic_min(&lo, &hi) {
+            Some(t) if hi.f.is_finite() && t > left + guard && t < right - guard => t,
+            _ => lo.t + 0.5 * width,
+        };
+        let p = ray.at(t);
+        if !armijo(&p) || p.f >= lo.f {
+            hi = p;
+        } else {
+            if curvature(&p) {
+                return Ok(p.accept(ray.evals));
+            }
+            if p.dphi * (hi.t - lo.t) >= 0.0 {
+                hi = lo;
+            }
+            lo = p;
+        }
+    }
+    Err(ray.fail(LineSearchFailure::MaxIterations))
+}
+
+fn backtracking<F>(
+    ray: &mut Ray<'_, F>,
+    origin: Trial,
+    step: f64,
+    c1: f64,
+    rho: f64,
+    max_iter: usize,
+) -> Result<LineSearchResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    let mut t = step;
+    for _ in 0..max_iter {
+        let p = ray.at(t);
+        if p.f <= origin.f + c1 * t * origin.dphi {
+            return Ok(p.accept(ray.evals));
+        }
+        t *= rho;
+    }
+    Err(ray.fail(LineSearchFailure::MaxIterations))
+}
+
+// Step, value and derivative at an endpoint of the More-Thuente interval.
+#[derive(Clone, Copy)]
+struct Endpoint {
+    t: f64,
+    f: f64,
+    d: f64,
+}
+
+// Safeguarded step of More & Thuente (MINPACK-2 `dcstep`). `x` is the best
+// endpoint so far, `y` the other endpoint and `p` the current trial; the
+// endpoints are updated in place and the next trial step is returned.
+fn mt_step(
+    x: &mut Endpoint,
+    y: &mut Endpoint,
+    p: Endpoint,
+    brackt: &mut bool,
+    stmin: f64,
+    stmax: f64,
+) -> f64 {
+    let sgnd = p.d * x.d.signum();
+    let cubic_terms = |a: Endpoint, b: Endpoint| {
+        let theta = 3.0 * (a.f - b.f) / (b.t - a.t) + a.d + b.d;
+        let s = theta.abs().max(a.d.abs()).max(b.d.abs());
+        let gamma = s
+            * ((theta / s).powi(2) - (a.d / s) * (b.d / s))
+                .max(0.0)
+                .sqrt();
+        (theta, gamma)
+    };
+    let stpf = if p.f > x.f {
+        // Higher value: the minimum is bracketed; take the cubic step if it
+        // is closer to x than the quadratic one, else their average
+        let (theta, mut gamma) = cubic_terms(*x, p);
+        if p.t < x.t {
+            gamma = -gamma;
+        }
+        let r = ((gamma - x.d) + theta) / (((gamma - x.d) + gamma) + p.d);
+        let stpc = x.t + r * (p.t - x.t);
+        let stpq = x.t + (x.d / ((x.f - p.f) / (p.t - x.t) + x.d)) / 2.0 * (p.t - x.t);
+        *brackt = true;
+        if (stpc - x.t).abs() < (stpq - x.t).abs() {
+            stpc
+        } else {
+            stpc + (stpq - stpc) / 2.0
+        }
+    } else if sgnd < 0.0 {
+        // Derivatives of opposite sign: the minimum is bracketed; take the
+        // cubic or secant step, whichever is farther from p
+        let (theta, mut gamma) = cubic_terms(*x, p);
+        if p.t > x.t {
+            gamma = -gamma;
+        }
+        let r = ((gamma - p.d) + theta) / (((gamma - p.d) + gamma) + x.d);
+        let stpc = p.t + r * (x.t - p.t);
+        let stpq = p.t + (p.d / (p.d - x.d)) * (x.t - p.t);
+        *brackt = true;
+        if (stpc - p.t).abs() > (stpq - p.t).abs() {
+            stpc
+        } else {
+            stpq
+        }
+    } else if p.d.abs() < x.d.abs() {
+        // The derivative decreases in magnitude: the cubic step is used only
+        // if it heads towards the minimum, and the step is kept inside the
+        // bracket or the extrapolation bounds
+        let (theta, mut gamma) = cubic_terms(*x, p);
+        if p.t > x.t {
+            gamma = -gamma;
+        }
+        let r = ((gamma - p.d) + theta) / ((gamma + (x.d - p.d)) + gamma);
+        let stpc = if r < 0.0 && gamma != 0.0 {
+            p.t + r * (x.t - p.t)
+        } else if p.t > x.t {
+            stmax
+        } else {
+            stmin
+        };
+        let stpq = p.t + (p.d / (p.d - x.d)) * (x.t - p.t);
+        if *brackt {
+            let t = if (stpc - p.t).abs() < (stpq - p.t).abs() {
+                stpc
+            } else {
+                stpq
+            };
+            let limit = p.t + 0.66 * (y.t - p.t);
+            if p.t > x.t {
+                t.min(limit)
+            } else {
+                t.max(limit)
+            }
+        } else {
+            let t = if (stpc - p.t).abs() > (stpq - p.t).abs() {
+                stpc
+            } else {
+                stpq
+            };
+            t.clamp(stmin, stmax)
+        }
+    } else if *brackt {
+        // The derivative does not decrease: cubic step towards y
+        let (theta, mut gamma) = cubic_terms(p, *y);
+        if p.t > y.t {
+            gamma = -gamma;
+        }
+        let r = ((gamma - p.d) + theta) / (((gamma - p.d) + gamma) + y.d);
+        p.t + r * (y.t - p.t)
+    } else if p.t > x.t {
+        stmax
+    } else {
+        stmin
+    };
+    if p.f > x.f {
+        *y = p;
+    } else {
+        if sgnd < 0.0 {
+            *y = *x;
+        }
+        *x = p;
+    }
+    if stpf.is_finite() {
+        stpf
+    } else {
+        x.t + 0.5 * (y.t - x.t)
+    }
+}
+
+fn more_thuente<F>(
+    ray: &mut Ray<'_, F>,
+    origin: Trial,
+    step: f64,
+    (ftol, gtol, xtol): (f64, f64, f64),
+    max_iter: usize,
+) -> Result<LineSearchResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    let (finit, ginit) = (origin.f, origin.dphi);
+    let gtest = ftol * ginit;
+    let (stpmin, stpmax) = (0.0, MT_STEP_MAX);
+    let mut width = stpmax - stpmin;
+    let mut width1 = 2.0 * width;
+    let mut x = Endpoint {
+        t: 0.0,
+        f: finit,
+        d: ginit,
+    };
+    let mut y = x;
+    let mut brackt = false;
+    let mut stage1 = true;
+    let (mut stmin, mut stmax) = (0.0, 5.0 * step);
+    // Smallest step at which the objective was found to be infinite
+    let mut cap = f64::INFINITY;
+
+    let mut p = ray.at(step.min(stpmax));
+    let mut iter = 1;
+    loop {
+        let ftest = finit + p.t * gtest;
+        if p.f <= ftest && p.dphi.abs() <= -gtol * ginit {
+            return Ok(p.accept(ray.evals));
+        }
+        if iter == max_iter {
+            return Err(ray.fail(LineSearchFailure::MaxIterations));
+        }
+        let stp = if p.f.is_finite() {
+            if brackt && (p.t <= stmin || p.t >= stmax || stmax - stmin <= xtol * stmax) {
+                return Err(ray.fail(LineSearchFailure::IntervalCollapsed));
+            }
+            if (p.t == stpmax && p.f <= ftest && p.dphi <= gtest)
+                || (p.t == stpmin && (p.f > ftest || p.dphi >= gtest))
+            {
+                return Err(ray.fail(LineSearchFailure::StepBound));
+            }
+            if stage1 && p.f <= ftest && p.dphi >= 0.0 {
+                stage1 = false;
+            }
+            let trial = Endpoint {
+                t: p.t,
+                f: p.f,
+                d: p.dphi,
+            };
+            let mut stp = if stage1 && p.f <= x.f && p.f > ftest {
+                // Work with the auxiliary function f(t) - f(0) - ftol t f'(0)
+                // until a step with sufficient decrease and nonnegative slope
+                // has been found
+                let shift = |e: Endpoint| Endpoint {
+                    t: e.t,
+                    f: e.f - e.t * gtest,
+                    d: e.d - gtest,
+                };
+                let unshift = |e: Endpoint| Endpoint {
+                    t: e.t,
+                    f: e.f + e.t * gtest,
+                    d: e.d + gtest,
+                };
+                let (mut xm, mut ym) = (shift(x), shift(y));
+                let t = mt_step(&mut xm, &mut ym, shift(trial), &mut brackt, stmin, stmax);
+                x = unshift(xm);
+                y = unshift(ym);
+                t
+            } else {
+                mt_step(&mut x, &mut y, trial, &mut brackt, stmin, stmax)
+            };
+            if brackt {
+                // Force sufficient shrinkage of the interval, bisecting if
+                // two steps failed to reduce it by a third
+                if (y.t - x.t).abs() >= 0.66 * width1 {
+                    stp = x.t + 0.5 * (y.t - x.t);
+                }
+                width1 = width;
+                width = (y.t - x.t).abs();
+                stmin = x.t.min(y.t);
+                stmax = x.t.max(y.t);
+            } else {
+                stmin = stp + 1.1 * (stp - x.t);
+                stmax = stp + 4.0 * (stp - x.t);
+            }
+            stp = stp.clamp(stpmin, stpmax);
+            if brackt && (stp <= stmin || stp >= stmax || stmax - stmin <= xtol * stmax) {
+                stp = x.t;
+            }
+            stp
+        } else {
+            // The objective is undefined here: retreat towards the best step
+            cap = cap.min(p.t);
+            x.t + 0.5 * (p.t - x.t)
+        };
+        let stp = if stp >= cap {
+            x.t + 0.5 * (cap - x.t)
+        } else {
+            stp
+        };
+        p = ray.at(stp);
+        iter += 1;
+    }
+}
+
+// Outcome that ends a Hager-Zhang search early.
+enum Stop {
+    Accept(Trial),
+    Fail(LineSearchFailure),
+}
+
+struct HagerZhang<'r, 'a, F> {
+    ray: &'r mut Ray<'a, F>,
+    f0: f64,
+    dphi0: f64,
+    delta: f64,
+    sigma: f64,
+    eps_f: f64,
+    theta: f64,
+    iter: usize,
+    max_iter: usize,
+}
+
+impl<F> HagerZhang<'_, '_, F>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    fn run(&mut self, origin: Trial, step: f64, gamma: f64) -> Stop {
+        let Err(stop) = self.search(origin, step, gamma);
+        stop
+    }
+
+    // Bracketing followed by secant² steps, bisecting whenever they fail to
+    // shrink the interval by the factor `gamma`; only returns by stopping.
+    fn search(&mut self, origin: Trial, step: f64, gamma: f64) -> Result<Infallible, Stop> {
+        let (mut a, mut b) = self.bracket(origin, step)?;
+        loop {
+            let width = b.t - a.t;
+            let (na, nb) = self.secant2(a, b)?;
+            (a, b) = if nb.t - na.t > gamma * width {
+                let c = self.probe(0.5 * (na.t + nb.t))?;
+                self.update(na, nb, c)?
+            } else {
+                (na, nb)
+            };
+            if b.t - a.t <= f64::EPSILON * b.t {
+                return Err(Stop::Fail(LineSearchFailure::IntervalCollapsed));
+            }
+        }
+    }
+
+    // Evaluates at `t`, stopping the search if the budget is exhausted or
+    // the standard or approximate Wolfe conditions hold there.
+    fn probe(&mut self, t: f64) -> Result<Trial, Stop> {
+        if self.iter == self.max_iter {
+            return Err(Stop::Fail(LineSearchFailure::MaxIterations));
+        }
+        self.iter += 1;
+        let p = self.ray.at(t);
+        let curvature = p.dphi >= self.sigma * self.dphi0;
+        let wolfe = p.f - self.f0 <= self.delta * p.t * self.dphi0;
+        let approximate = (2.0 * self.delta - 1.0) * self.dphi0 >= p.dphi && self.acceptable(&p);
+        if curvature && (wolfe || approximate) {
+            return Err(Stop::Accept(p));
+        }
+        Ok(p)
+    }
+
+    fn acceptable(&self, p: &Trial) -> bool {
+        p.f <= self.f0 + self.eps_f
+    }
+
+    // Initial bracket: expand the step until the slope turns nonnegative or
+    // the value rises above the acceptable level.
+    fn bracket(&mut self, origin: Trial, step: f64) -> Result<(Trial, Trial), Stop> {
+        let mut last = origin;
+        let mut c = self.probe(step)?;
+        loop {
+            if c.dphi >= 0.0 {
+                return Ok((last, c));
+            }
+            if !self.acceptable(&c) {
+                return self.bisect(last, c);
+            }
+            let t = HZ_EXPANSION * c.t;
+            last = c;
+            c = self.probe(t)?;
+        }
+    }
+
+    // Interval update with the trial `c` (Hager & Zhang, procedure U).
+    fn update(&mut self, a: Trial, b: Trial, c: Trial) -> Result<(Trial, Trial), Stop> {
+        if c.t <= a.t || c.t >= b.t {
+            Ok((a, b))
+        } else if c.dphi >= 0.0 {
+            Ok((a, c))
+        } else if self.acceptable(&c) {
+            Ok((c, b))
+        } else {
+            self.bisect(a, c)
+        }
+    }
+
+    // Shrinks [a, b], where b has a negative slope but too high a value,
+    // until an endpoint with nonnegative slope is found.
+    fn bisect(&mut self, mut a: Trial, mut b: Trial) -> Result<(Trial, Trial), Stop> {
+        loop {
+            if b.t - a.t <= f64::EPSILON * b.t {
+                return Err(Stop::Fail(LineSearchFailure::IntervalCollapsed));
+            }
+            let d = self.probe((1.0 - self.theta) * a.t + self.theta * b.t)?;
+            if d.dphi >= 0.0 {
+                return Ok((a, d));
+            }
+            if self.acceptable(&d) {
+                a = d;
+            } else {
+                b = d;
+            }
+        }
+    }
+
+    // Double secant step (Hager & Zhang, procedure secant²).
+    fn secant2(&mut self, a: Trial, b: Trial) -> Result<(Trial, Trial), Stop> {
+        let c = secant(&a, &b);
+        if !(c > a.t && c < b.t) {
+            return Ok((a, b));
+        }
+        let pc = self.probe(c)?;
+        let (na, nb) = self.update(a.clone(), b.clone(), pc)?;
+        let c2 = if nb.t == c {
+            secant(&b, &nb)
+        } else if na.t == c {
+            secant(&a, &na)
+        } else {
+            return Ok((na, nb));
+        };
+        if c2 > na.t && c2 < nb.t {
+            let p = self.probe(c2)?;
+            self.update(na, nb, p)
+        } else {
+            Ok((na, nb))
+        }
+    }
+}
+
+// Zero of the linear interpolant of the directional derivative.
+fn secant(a: &Trial, b: &Trial) -> f64 {
+    (a.t * b.dphi - b.t * a.dphi) / (b.dphi - a.dphi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::rosenbrock_grad;
+
+    #[test]
+    fn strong_wolfe_conditions_hold() {
+        let x = [-1.2, 1.0];
+        let mut g = [0.0; 2];
+        let f = rosenbrock_grad(&x, &mut g);
+        let d = [-g[0], -g[1]];
+        let (c1, c2) = (1e-4, 0.1);
+        let ls = LineSearch::StrongWolfe {
+            c1,
+            c2,
+            max_iter: 50,
+        };
+        let r = ls.search(&rosenbrock_grad, &x, f, &g, &d, 1.0).unwrap();
+        let dphi0 = dot(&g, &d);
+        assert!(r.fx <= f + c1 * r.step * dphi0);
+        assert!(dot(&r.grad, &d).abs() <= -c2 * dphi0);
+    }
+
+    #[test]
+    fn exact_step_on_quadratic() {
+        // f = x^2, from x = 1 along d = -1 the curvature condition with a
+        // tight c2 forces a step close to 1
+        let fg = |x: &[f64], g: &mut [f64]| {
+            g[0] = 2.0 * x[0];
+            x[0] * x[0]
+        };
+        let ls = LineSearch::StrongWolfe {
+            c1: 1e-4,
+            c2: 0.01,
+            max_iter: 50,
+        };
+        let r = ls.search(&fg, &[1.0], 1.0, &[2.0], &[-1.0], 0.1).unwrap();
+        assert!((r.step - 1.0).abs() < 0.01, "{r:?}");
+    }
+
+    #[test]
+    fn every_method_decreases_rosenbrock() {
+        let x = [-1.2, 1.0];
+        let mut g = [0.0; 2];
+        let f = rosenbrock_grad(&x, &mut g);
+        let d = [-g[0], -g[1]];
+        let dphi0 = dot(&g, &d);
+        for ls in [
+            LineSearch::backtracking(),
+            LineSearch::default(),
+            LineSearch::more_thuente(),
+            LineSearch::hager_zhang(),
+        ] {
+            let r = ls.search(&rosenbrock_grad, &x, f, &g, &d, 1.0).unwrap();
+            assert!(r.fx < f, "{ls:?}: {r:?}");
+            assert!(r.fx <= f + 1e-4 * r.step * dphi0 || r.fx <= f + 1e-6 * f.abs());
+        }
+    }
+
+    #[test]
+    fn more_thuente_strong_wolfe_conditions_hold() {
+        let x = [-1.2, 1.0];
+        let mut g = [0.0; 2];
+        let f = rosenbrock_grad(&x, &mut g);
+        let d = [-g[0], -g[1]];
+        let (ftol, gtol) = (1e-4, 0.1);
+        let ls = LineSearch::MoreThuente {
+            ftol,
+            gtol,
+            xtol: 1e-10,
+            max_iter: 50,
+        };
+        // Start from a tiny step so the search must extrapolate
+        let r = ls.search(&rosenbrock_grad, &x, f, &g, &d, 1e-6).unwrap();
+        let dphi0 = dot(&g, &d);
+        assert!(r.fx <= f + ftol * r.step * dphi0);
+        assert!(dot(&r.grad, &d).abs() <= -gtol * dphi0);
+    }
+
+    #[test]
+    fn hager_zhang_satisfies_wolfe_on_quadratic() {
+        let fg = |x: &[f64], g: &mut [f64]| {
+            g[0] = 2.0 * (x[0] - 3.0);
+            (x[0] - 3.0).powi(2)
+        };
+        let (f, g) = (9.0, [-6.0]);
+        let r = LineSearch::hager_zhang()
+            .search(&fg, &[0.0], f, &g, &[1.0], 100.0)
+            .unwrap();
+        assert!(r.grad[0] >= 0.9 * g[0] && r.fx < f, "{r:?}");
+    }
+
+    #[test]
+    fn failures_report_best_step() {
+        // Undefined for any positive step: no method may return a step
+        let fg = |x: &[f64], g: &mut [f64]| {
+            g[0] = 1.0;
+            if x[0] < 0.0 { f64::NAN } else { x[0] }
+        };
+        for ls in [
+            LineSearch::backtracking(),
+            LineSearch::default(),
+            LineSearch::more_thuente(),
+            LineSearch::hager_zhang(),
+        ] {
+            match ls.search(&fg, &[0.0], 0.0, &[1.0], &[-1.0], 1.0) {
+                Err(NumalError::LineSearchFailed { step, fx, .. }) => {
+                    assert_eq!((step, fx), (0.0, 0.0), "{ls:?}");
+                }
+                r => panic!("{ls:?}: {r:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn exhausted_budget_is_reported() {
+        let ls = LineSearch::Backtracking {
+            c1: 1e-4,
+            rho: 0.5,
+            max_iter: 2,
+        };
+        let r = ls.search(
+            &rosenbrock_grad,
+            &[-1.2, 1.0],
+            24.2,
+            &[-215.6, -88.0],
+            &[215.6, 88.0],
+            1.0,
+        );
+        assert!(matches!(
+            r,
+            Err(NumalError::LineSearchFailed {
+                reason: LineSearchFailure::MaxIterations,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn ascent_direction_is_rejected() {
+        let r = LineSearch::default().search(
+            &rosenbrock_grad,
+            &[0.0, 0.0],
+            1.0,
+            &[-2.0, 0.0],
+            &[-1.0, 0.0],
+            1.0,
+        );
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn invalid_constants_are_rejected() {
+        let ls = LineSearch::StrongWolfe {
+            c1: 0.5,
+            c2: 0.1,
+            max_iter: 10,
+        };
+        let r = ls.search(
+            &rosenbrock_grad,
+            &[0.0, 0.0],
+            1.0,
+            &[-2.0, 0.0],
+            &[1.0, 0.0],
+            1.0,
+        );
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
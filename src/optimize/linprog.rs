@@ -0,0 +1,888 @@
+//! Linear programming.
+//!
+//! Problems are `min c·x` subject to `A_ub x <= b_ub`, `A_eq x = b_eq` and
+//! `x >= 0`. Both methods work on the standard form `A x = b, x >= 0`
+//! obtained by adding a slack variable to every inequality row.
+//!
+//! The revised simplex method runs in two phases, minimizing the sum of
+//! artificial variables first. It prices with Dantzig's rule and falls back
+//! to Bland's rule, which cannot cycle, after a run of degenerate pivots.
+//! The interior-point method is Mehrotra's predictor-corrector applied to
+//! the homogeneous self-dual embedding of Xu, Hung & Ye, so that infeasible
+//! and unbounded problems are detected from the iterates rather than by
+//! divergence (Andersen & Andersen, 2000). It works on a copy of the
+//! program with equilibrated rows and normalized `b` and `c`, and checks a
+//! certificate suggested by the iterates before reporting it.
+
+use crate::NumalError;
+use crate::core::linalg::{Cholesky, Lu, Matrix, dot, norm};
+use crate::core::tolerance::Tolerance;
+
+// Degenerate pivots in a row after which Bland's rule takes over.
+const DEGENERATE_RUN: usize = 20;
+// Pivots between refactorizations of the basis inverse.
+const REFACTOR: usize = 50;
+// Entries of a pivot column below this are treated as zero.
+const PIVOT_TOL: f64 = 1e-9;
+// Fraction of the step to the boundary taken by the interior-point method.
+const STEP_TO_BOUNDARY: f64 = 0.995;
+
+/// A linear program `min c·x` subject to `A_ub x <= b_ub`, `A_eq x = b_eq`
+/// and `x >= 0`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearProgram {
+    pub c: Vec<f64>,
+    pub a_ub: Matrix,
+    pub b_ub: Vec<f64>,
+    pub a_eq: Matrix,
+    pub b_eq: Vec<f64>,
+}
+
+impl LinearProgram {
+    /// The standard-form program `min c·x` subject to `A x = b`, `x >= 0`
+    pub fn standard(c: Vec<f64>, a: Matrix, b: Vec<f64>) -> Self {
+        LinearProgram {
+            a_ub: Matrix::zeros(0, c.len()),
+            b_ub: Vec::new(),
+            a_eq: a,
+            b_eq: b,
+            c,
+        }
+    }
+
+    /// The inequality-form program `min c·x` subject to `A x <= b`, `x >= 0`
+    pub fn inequality(c: Vec<f64>, a: Matrix, b: Vec<f64>) -> Self {
+        LinearProgram {
+            a_eq: Matrix::zeros(0, c.len()),
+            b_eq: Vec::new(),
+            a_ub: a,
+            b_ub: b,
+            c,
+        }
+    }
+
+    fn validate(&self) -> Result<(), NumalError> {
+        let n = self.c.len();
+        if n == 0 {
+            return Err(NumalError::InvalidInput(
+                "linear program has no variables".to_string(),
+            ));
+        }
+        for (name, a, b) in [
+            ("A_ub", &self.a_ub, &self.b_ub),
+            ("A_eq", &self.a_eq, &self.b_eq),
+        ] {
+            if a.cols() != n || a.rows() != b.len() {
+                return Err(NumalError::InvalidInput(format!(
+                    "{name} is {}x{}, expected {}x{n}",
+                    a.rows(),
+                    a.cols(),
+                    b.len()
+                )));
+            }
+        }
+        let finite = self
+            .c
+            .iter()
+            .chain(&self.b_ub)
+            .chain(&self.b_eq)
+            .all(|v| v.is_finite())
+            && self.a_ub.is_finite()
+            && self.a_eq.is_finite();
+        if !finite {
+            return Err(NumalError::InvalidInput(
+                "linear program data must be finite".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Algorithm used by [`linprog`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LpMethod {
+    /// Two-phase revised simplex with Bland's rule against cycling
+    #[default]
+    Simplex,
+    /// Mehrotra predictor-corrector on the homogeneous self-dual embedding
+    InteriorPoint,
+}
+
+/// Options for [`linprog`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinprogOptions {
+    pub method: LpMethod,
+    /// Feasibility and optimality tolerance; the interior-point method
+    /// measures its residuals relative to the data with `eps_rel`
+    pub tol: Tolerance,
+    pub max_iter: usize,
+}
+
+impl Default for LinprogOptions {
+    fn default() -> Self {
+        LinprogOptions {
+            method: LpMethod::default(),
+            tol: Tolerance::Default,
+            max_iter: 10_000,
+        }
+    }
+}
+
+/// Optimal solution of a linear program
+#[derive(Clone, Debug, PartialEq)]
+pub struct LpSolution {
+    pub x: Vec<f64>,
+    /// Objective value `c·x`
+    pub objective: f64,
+    /// Dual values of the inequality rows, the sensitivity of the objective
+    /// to `b_ub`; nonpositive at an optimum
+    pub ineq_marginals: Vec<f64>,
+    /// Dual values of the equality rows, the sensitivity of the objective
+    /// to `b_eq`
+    pub eq_marginals: Vec<f64>,
+    /// Number of pivots or interior-point iterations
+    pub iterations: usize,
+}
+
+// The program in standard form with `b >= 0`: rows with a negative right
+// hand side are negated, as recorded in `sign`.
+struct Standard {
+    a: Matrix,
+    b: Vec<f64>,
+    c: Vec<f64>,
+    sign: Vec<f64>,
+}
+
+impl Standard {
+    fn new(lp: &LinearProgram) -> Standard {
+        let (n, m_ub, m_eq) = (lp.c.len(), lp.b_ub.len(), lp.b_eq.len());
+        let m = m_ub + m_eq;
+        let mut a = Matrix::zeros(m, n + m_ub);
+        let mut b = Vec::with_capacity(m);
+        let mut sign = Vec::with_capacity(m);
+        for i in 0..m {
+            let (row, rhs) = if i < m_ub {
+                a[(i, n + i)] = 1.0;
+                (lp.a_ub.row(i), lp.b_ub[i])
+            } else {
+                (lp.a_eq.row(i - m_ub), lp.b_eq[i - m_ub])
+            };
+            a.row_mut(i)[..n].copy_from_slice(row);
+            let s = if rhs < 0.0 { -1.0 } else { 1.0 };
+            a.row_mut(i).iter_mut().for_each(|v| *v *= s);
+            b.push(s * rhs);
+            sign.push(s);
+        }
+        let mut c = lp.c.clone();
+        c.resize(n + m_ub, 0.0);
+        Standard { a, b, c, sign }
+    }
+
+    // Multipliers of the rows of `lp` from those of the standard form.
+    fn marginals(&self, y: &[f64]) -> Vec<f64> {
+        y.iter().zip(&self.sign).map(|(y, s)| y * s).collect()
+    }
+
+    fn solution(&self, lp: &LinearProgram, x: &[f64], y: &[f64], iterations: usize) -> LpSolution {
+        let n = lp.c.len();
+        let marginals = self.marginals(y);
+        let (ineq, eq) = marginals.split_at(lp.b_ub.len());
+        LpSolution {
+            x: x[..n].to_vec(),
+            objective: dot(&lp.c, &x[..n]),
+            ineq_marginals: ineq.to_vec(),
+            eq_marginals: eq.to_vec(),
+            iterations,
+        }
+    }
+
+    // The program with every row scaled to unit infinity norm and b and c
+    // divided by their infinity norms, when above one. Returns it with the
+    // row scales and the divisors of b and c.
+    fn scaled(&self) -> (Standard, Vec<f64>, f64, f64) {
+        let max_abs = |v: &[f64]| v.iter().fold(0.0, |m: f64, x| m.max(x.abs()));
+        let mut a = self.a.clone();
+        let rows: Vec<f64> = (0..a.rows())
+            .map(|i| {
+                let r = max_abs(a.row(i));
+                let r = if r > 0.0 { r } else { 1.0 };
+                a.row_mut(i).iter_mut().for_each(|v| *v /= r);
+                r
+            })
+            .collect();
+        let b: Vec<f64> = self.b.iter().zip(&rows).map(|(b, r)| b / r).collect();
+        let beta = max_abs(&b).max(1.0);
+        let gamma = max_abs(&self.c).max(1.0);
+        let scaled = Standard {
+            a,
+            b: b.iter().map(|v| v / beta).collect(),
+            c: self.c.iter().map(|v| v / gamma).collect(),
+            sign: self.sign.clone(),
+        };
+        (scaled, rows, beta, gamma)
+    }
+
+    // Whether `y` certifies that `A x = b, x >= 0` has no solution:
+    // A'y <= 0 and b·y > 0, to a tolerance relative to `y`.
+    fn is_farkas(&self, y: &[f64], tol: f64) -> bool {
+        let eps = tol * norm(y);
+        dot(&self.b, y) > eps && self.a.tr_mul_vec(y).iter().all(|&v| v <= eps)
+    }
+
+    // Whether `d` is a ray of unbounded decrease: d >= 0, A d = 0 and
+    // c·d < 0, to a tolerance relative to `d`.
+    fn is_ray(&self, d: &[f64], tol: f64) -> bool {
+        let eps = tol * norm(d);
+        d.iter().all(|&v| v >= 0.0)
+            && dot(&self.c, d) < -eps
+            && self.a.mul_vec(d).iter().all(|v| v.abs() <= eps)
+    }
+
+    // Restates a certificate of the standard form in terms of `lp`, scaled
+    // to unit infinity norm.
+    fn certificate(&self, lp: &LinearProgram, err: NumalError) -> NumalError {
+        let unit = |mut v: Vec<f64>| {
+            let scale = v.iter().fold(0.0, |m: f64, x| m.max(x.abs()));
+            if scale > 0.0 {
+                v.iter_mut().for_each(|x| *x /= scale);
+            }
+            v
+        };
+        match err {
+            NumalError::Infeasible { certificate } => NumalError::Infeasible {
+                certificate: unit(self.marginals(&certificate)),
+            },
+            NumalError::Unbounded { direction } => NumalError::Unbounded {
+                direction: unit(direction[..lp.c.len()].to_vec()),
+            },
+            e => e,
+        }
+    }
+}
+
+/// Solves the linear program `lp`.
+///
+/// Returns [`NumalError::Infeasible`] when no point satisfies the
+/// constraints and [`NumalError::Unbounded`] when the objective decreases
+/// without bound on the feasible set. The certificate of infeasibility holds
+/// multipliers `y = (y_ub, y_eq)` of the rows with `y_ub <= 0`,
+/// `A_ub'y_ub + A_eq'y_eq <= 0` and `b_ub·y_ub + b_eq·y_eq > 0`. The
+/// direction of an unbounded problem is a ray `d >= 0` with `A_ub d <= 0`,
+/// `A_eq d = 0` and `c·d < 0`.
+pub fn linprog(lp: &LinearProgram, opts: &LinprogOptions) -> Result<LpSolution, NumalError> {
+    lp.validate()?;
+    let std = Standard::new(lp);
+    let (x, y, iterations) = match opts.method {
+        LpMethod::Simplex => simplex(&std, opts),
+        LpMethod::InteriorPoint => interior_point(&std, opts),
+    }
+    .map_err(|e| std.certificate(lp, e))?;
+    Ok(std.solution(lp, &x, &y, iterations))
+}
+
+// Basis of the revised simplex method over the columns of `[A I]`, the
+// identity holding the artificial variables, with an explicit inverse.
+struct Basis<'a> {
+    a: &'a Matrix,
+    index: Vec<usize>,
+    inverse: Matrix,
+    xb: Vec<f64>,
+}
+
+impl Basis<'_> {
+    fn column(&self, j: usize) -> Vec<f64> {
+        let m = self.a.rows();
+        if j < self.a.cols() {
+            self.a.col(j)
+        } else {
+            (0..m)
+                .map(|i| if i == j - self.a.cols() { 1.0 } else { 0.0 })
+                .collect()
+        }
+    }
+
+    // B⁻¹ a_j
+    fn ftran(&self, j: usize) -> Vec<f64> {
+        self.inverse.mul_vec(&self.column(j))
+    }
+
+    // Simplex multipliers y = B⁻ᵀ c_B
+    fn duals(&self, cost: &[f64]) -> Vec<f64> {
+        let cb: Vec<f64> = self.index.iter().map(|&j| cost[j]).collect();
+        self.inverse.tr_mul_vec(&cb)
+    }
+
+    fn pivot(&mut self, r: usize, q: usize, w: &[f64], theta: f64) {
+        for (i, (x, wi)) in self.xb.iter_mut().zip(w).enumerate() {
+            *x = if i == r {
+                theta
+            } else {
+                (*x - theta * wi).max(0.0)
+            };
+        }
+        let pivot_row: Vec<f64> = self.inverse.row(r).iter().map(|v| v / w[r]).collect();
+        for (i, &wi) in w.iter().enumerate() {
+            let row = self.inverse.row_mut(i);
+            if i == r {
+                row.copy_from_slice(&pivot_row);
+            } else if wi != 0.0 {
+                row.iter_mut()
+                    .zip(&pivot_row)
+                    .for_each(|(v, p)| *v -= wi * p);
+            }
+        }
+        self.index[r] = q;
+    }
+
+    // Recomputes the inverse and the basic solution from scratch to stop
+    // rounding errors from accumulating in the product-form updates.
+    fn refactor(&mut self, b: &[f64]) -> Result<(), NumalError> {
+        let m = self.index.len();
+        let mut bmat = Matrix::zeros(m, m);
+        for (k, &j) in self.index.iter().enumerate() {
+            for (i, v) in self.column(j).into_iter().enumerate() {
+                bmat[(i, k)] = v;
+            }
+        }
+        let lu = Lu::new(&bmat)
+            .ok_or_else(|| NumalError::LibErr("simplex basis became singular".to_string()))?;
+        self.inverse = lu.inverse();
+        self.xb = lu.solve(b).into_iter().map(|v| v.max(0.0)).collect();
+        Ok(())
+    }
+}
+
+// Runs simplex pivots on `basis` for the costs `cost` until optimal. Only
+// columns below `allowed` may enter. Returns the number of pivots.
+fn simplex_phase(
+    basis: &mut Basis,
+    b: &[f64],
+    cost: &[f64],
+    allowed: usize,
+    tol: f64,
+    budget: usize,
+) -> Result<usize, NumalError> {
+    let mut degenerate = 0;
+    for iter in 0..budget {
+        if iter > 0 && iter % REFACTOR == 0 {
+            basis.refactor(b)?;
+        }
+        let y = basis.duals(cost);
+        let bland = degenerate >= DEGENERATE_RUN;
+        let mut entering: Option<(usize, f64)> = None;
+        for j in (0..allowed).filter(|j| !basis.index.contains(j)) {
+            let d = cost[j] - dot(&y, &basis.column(j));
+            if d < -tol && entering.is_none_or(|(_, best)| d < best) {
+                entering = Some((j, d));
+                if bland {
+                    break;
+                }
+            }
+        }
+        let Some((q, _)) = entering else {
+            return Ok(iter);
+        };
+        let w = basis.ftran(q);
+        // Ratio test; ties go to the largest pivot, or under Bland's rule to
+        // the smallest variable index
+        let mut leaving: Option<(usize, f64)> = None;
+        for (i, &wi) in w.iter().enumerate() {
+            if wi <= PIVOT_TOL {
+                continue;
+            }
+            let ratio = basis.xb[i] / wi;
+            let better = match leaving {
+                None => true,
+                Some((r, best)) => {
+                    ratio < best - tol
+                        || (ratio <= best + tol
+                            && if bland {
+                                basis.index[i] < basis.index[r]
+                            } else {
+                                wi > w[r]
+                            })
+                }
+            };
+            if better {
+                leaving = Some((i, ratio));
+            }
+        }
+        let Some((r, theta)) = leaving else {
+            // Increasing x_q while the basic variables move by -w is a
+            // feasible ray of decrease
+            let mut direction = vec![0.0; basis.a.cols() + b.len()];
+            direction[q] = 1.0;
+            for (&j, wi) in basis.index.iter().zip(&w) {
+                direction[j] = -wi;
+            }
+            return Err(NumalError::Unbounded { direction });
+        };
+        degenerate = if theta <= tol { degenerate + 1 } else { 0 };
+        basis.pivot(r, q, &w, theta);
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+fn simplex(
+    std: &Standard,
+    opts: &LinprogOptions,
+) -> Result<(Vec<f64>, Vec<f64>, usize), NumalError> {
+    let (m, n) = (std.a.rows(), std.a.cols());
+    let tol = opts.tol.eps_abs();
+    let mut basis = Basis {
+        a: &std.a,
+        index: (n..n + m).collect(),
+        inverse: Matrix::identity(m),
+        xb: std.b.clone(),
+    };
+    // Phase 1: minimize the sum of the artificial variables
+    let mut cost = vec![0.0; n + m];
+    cost[n..].iter_mut().for_each(|v| *v = 1.0);
+    let mut iterations = simplex_phase(&mut basis, &std.b, &cost, n, tol, opts.max_iter)?;
+    let scale = 1.0 + std.b.iter().fold(0.0, |a: f64, v| a.max(v.abs()));
+    let infeasibility: f64 = basis
+        .index
+        .iter()
+        .zip(&basis.xb)
+        .filter(|&(&j, _)| j >= n)
+        .map(|(_, x)| x)
+        .sum();
+    if infeasibility > tol * scale {
+        // The phase 1 duals have A'y <= 0 from the optimal reduced costs
+        // and b·y equal to the remaining infeasibility
+        return Err(NumalError::Infeasible {
+            certificate: basis.duals(&cost),
+        });
+    }
+    // Drive artificial variables out of the basis with degenerate pivots;
+    // one that cannot leave marks a redundant row and stays at zero
+    for r in 0..m {
+        if basis.index[r] < n {
+            continue;
+        }
+        let row = basis.inverse.row(r).to_vec();
+        let entering = (0..n)
+            .filter(|j| !basis.index.contains(j))
+            .find(|&j| dot(&row, &basis.column(j)).abs() > PIVOT_TOL);
+        if let Some(q) = entering {
+            let w = basis.ftran(q);
+            basis.pivot(r, q, &w, 0.0);
+            iterations += 1;
+        }
+    }
+    // Phase 2
+    cost[..n].copy_from_slice(&std.c);
+    cost[n..].iter_mut().for_each(|v| *v = 0.0);
+    basis.refactor(&std.b)?;
+    iterations += simplex_phase(
+        &mut basis,
+        &std.b,
+        &cost,
+        n,
+        tol,
+        opts.max_iter.saturating_sub(iterations),
+    )?;
+    let mut x = vec![0.0; n];
+    for (&j, &v) in basis.index.iter().zip(&basis.xb) {
+        if j < n {
+            x[j] = v;
+        }
+    }
+    Ok((x, basis.duals(&cost), iterations))
+}
+
+// Iterate of the homogeneous self-dual embedding
+//
+//   A x - b tau = 0,  A'y + z - c tau = 0,  b·y - c·x - kappa = 0,
+//
+// with x, z, tau, kappa >= 0. At a solution with tau > 0, (x, y, z) / tau
+// solves the program; with kappa > 0 instead, x or y is a certificate of
+// dual or primal infeasibility.
+struct Embedding {
+    x: Vec<f64>,
+    y: Vec<f64>,
+    z: Vec<f64>,
+    tau: f64,
+    kappa: f64,
+}
+
+struct Direction {
+    x: Vec<f64>,
+    y: Vec<f64>,
+    z: Vec<f64>,
+    tau: f64,
+    kappa: f64,
+}
+
+impl Embedding {
+    fn mu(&self) -> f64 {
+        (dot(&self.x, &self.z) + self.tau * self.kappa) / (self.x.len() + 1) as f64
+    }
+
+    // Largest step in [0, 1] along `d` keeping x, z, tau and kappa
+    // nonnegative.
+    fn max_step(&self, d: &Direction) -> f64 {
+        let pairs = self
+            .x
+            .iter()
+            .zip(&d.x)
+            .chain(self.z.iter().zip(&d.z))
+            .chain([(&self.tau, &d.tau), (&self.kappa, &d.kappa)]);
+        pairs.fold(
+            1.0,
+            |a: f64, (v, dv)| if *dv < 0.0 { a.min(-v / dv) } else { a },
+        )
+    }
+
+    fn step(&mut self, d: &Direction, alpha: f64) {
+        let axpy =
+            |v: &mut [f64], dv: &[f64]| v.iter_mut().zip(dv).for_each(|(a, b)| *a += alpha * b);
+        axpy(&mut self.x, &d.x);
+        axpy(&mut self.y, &d.y);
+        axpy(&mut self.z, &d.z);
+        self.tau += alpha * d.tau;
+        self.kappa += alpha * d.kappa;
+    }
+}
+
+// Residuals of the linear equations of the embedding.
+struct Residuals {
+    primal: Vec<f64>,
+    dual: Vec<f64>,
+    gap: f64,
+}
+
+impl Residuals {
+    fn new(std: &Standard, e: &Embedding) -> Residuals {
+        let primal = std
+            .a
+            .mul_vec(&e.x)
+            .iter()
+            .zip(&std.b)
+            .map(|(ax, b)| ax - b * e.tau)
+            .collect();
+        let dual = std
+            .a
+            .tr_mul_vec(&e.y)
+            .iter()
+            .zip(&e.z)
+            .zip(&std.c)
+            .map(|((aty, z), c)| aty + z - c * e.tau)
+            .collect();
+        let gap = dot(&std.b, &e.y) - dot(&std.c, &e.x) - e.kappa;
+        Residuals { primal, dual, gap }
+    }
+}
+
+// Newton direction for the embedding: the linear residuals are reduced by
+// the factor `1 - eta` and x∘z, tau kappa are driven to `rxz`, `rtk` more
+// than their current values. The normal equations matrix `A D A'`, with
+// D = X/Z, is factored once per iteration in `chol`.
+fn newton(
+    std: &Standard,
+    e: &Embedding,
+    res: &Residuals,
+    chol: &Cholesky,
+    eta: f64,
+    rxz: &[f64],
+    rtk: f64,
+) -> Direction {
+    let a = &std.a;
+    let d: Vec<f64> = e.x.iter().zip(&e.z).map(|(x, z)| x / z).collect();
+    // dy = p dtau + q and dx = u dtau + v
+    let dc: Vec<f64> = d.iter().zip(&std.c).map(|(d, c)| d * c).collect();
+    let rhs_p: Vec<f64> = a
+        .mul_vec(&dc)
+        .iter()
+        .zip(&std.b)
+        .map(|(a, b)| a + b)
+        .collect();
+    let p = chol.solve(&rhs_p);
+    let w: Vec<f64> = res
+        .dual
+        .iter()
+        .zip(rxz)
+        .zip(&e.x)
+        .map(|((rd, r), x)| eta * rd + r / x)
+        .collect();
+    let dw: Vec<f64> = d.iter().zip(&w).map(|(d, w)| d * w).collect();
+    let rhs_q: Vec<f64> = a
+        .mul_vec(&dw)
+        .iter()
+        .zip(&res.primal)
+        .map(|(adw, rp)| -eta * rp - adw)
+        .collect();
+    let q = chol.solve(&rhs_q);
+    let u: Vec<f64> = a
+        .tr_mul_vec(&p)
+        .iter()
+        .zip(&std.c)
+        .zip(&d)
+        .map(|((atp, c), d)| d * (atp - c))
+        .collect();
+    let v: Vec<f64> = a
+        .tr_mul_vec(&q)
+        .iter()
+        .zip(&w)
+        .zip(&d)
+        .map(|((atq, w), d)| d * (atq + w))
+        .collect();
+    let dtau = (-eta * res.gap + dot(&std.c, &v) - dot(&std.b, &q) + rtk / e.tau)
+        / (dot(&std.b, &p) - dot(&std.c, &u) + e.kappa / e.tau);
+    let dx: Vec<f64> = u.iter().zip(&v).map(|(u, v)| u * dtau + v).collect();
+    let dy: Vec<f64> = p.iter().zip(&q).map(|(p, q)| p * dtau + q).collect();
+    let dz = dx
+        .iter()
+        .zip(rxz)
+        .zip(e.x.iter().zip(&e.z))
+        .map(|((dx, r), (x, z))| (r - z * dx) / x)
+        .collect();
+    Direction {
+        x: dx,
+        y: dy,
+        z: dz,
+        tau: dtau,
+        kappa: (rtk - e.kappa * dtau) / e.tau,
+    }
+}
+
+fn interior_point(
+    std: &Standard,
+    opts: &LinprogOptions,
+) -> Result<(Vec<f64>, Vec<f64>, usize), NumalError> {
+    let (scaled, rows, beta, gamma) = std.scaled();
+    match embedding(&scaled, opts) {
+        Ok((x, y, iterations)) => {
+            let x = x.iter().map(|v| beta * v).collect();
+            let y = y.iter().zip(&rows).map(|(v, r)| gamma * v / r).collect();
+            Ok((x, y, iterations))
+        }
+        Err(NumalError::Infeasible { certificate }) => Err(NumalError::Infeasible {
+            certificate: certificate.iter().zip(&rows).map(|(v, r)| v / r).collect(),
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+// Mehrotra's method on the embedding of `std`, which should be scaled so
+// that the relative tests below do not depend on the units of the data.
+fn embedding(
+    std: &Standard,
+    opts: &LinprogOptions,
+) -> Result<(Vec<f64>, Vec<f64>, usize), NumalError> {
+    let (m, n) = (std.a.rows(), std.a.cols());
+    let tol = opts.tol.eps_rel();
+    let mut e = Embedding {
+        x: vec![1.0; n],
+        y: vec![0.0; m],
+        z: vec![1.0; n],
+        tau: 1.0,
+        kappa: 1.0,
+    };
+    let res0 = Residuals::new(std, &e);
+    let (p0, d0, g0) = (
+        norm(&res0.primal).max(1.0),
+        norm(&res0.dual).max(1.0),
+        res0.gap.abs().max(1.0),
+    );
+    let mu0 = e.mu();
+    for iter in 0..=opts.max_iter {
+        let res = Residuals::new(std, &e);
+        let (rho_p, rho_d, rho_g) = (
+            norm(&res.primal) / p0,
+            norm(&res.dual) / d0,
+            res.gap.abs() / g0,
+        );
+        let (cx, by) = (dot(&std.c, &e.x), dot(&std.b, &e.y));
+        let rho_a = (cx - by).abs() / (e.tau + by.abs());
+        if rho_p <= tol && rho_d <= tol && rho_a <= tol {
+            let x: Vec<f64> = e.x.iter().map(|v| v / e.tau).collect();
+            let y: Vec<f64> = e.y.iter().map(|v| v / e.tau).collect();
+            return Ok((x, y, iter));
+        }
+        if rho_p <= tol && rho_d <= tol && rho_g <= tol && e.tau <= tol * e.kappa.max(1.0) {
+            // b·y > 0 certifies that A x = b, x >= 0 has no solution, and
+            // c·x < 0 that the objective decreases along a feasible ray.
+            // The iterate only suggests a certificate, so it is checked;
+            // one that does not hold leaves the iteration to go on
+            if std.is_farkas(&e.y, tol) {
+                return Err(NumalError::Infeasible {
+                    certificate: e.y.clone(),
+                });
+            }
+            if std.is_ray(&e.x, tol) {
+                return Err(NumalError::Unbounded {
+                    direction: e.x.clone(),
+                });
+            }
+        }
+        if e.mu() / mu0 <= tol * tol && e.tau <= tol * e.kappa.min(1.0) {
+            // Neither an optimum nor a certificate is approached
+            return Err(NumalError::DidNotConverge);
+        }
+        if iter == opts.max_iter {
+            break;
+        }
+
+        let mut nmat = Matrix::zeros(m, m);
+        for k in 0..n {
+            let dk = e.x[k] / e.z[k];
+            let col = std.a.col(k);
+            nmat.rank1_update(dk, &col, &col);
+        }
+        // A tiny shift keeps the factorization alive with redundant rows
+        let shift = f64::EPSILON.sqrt() * nmat.max_abs().max(1.0) * 1e-4;
+        let chol = Cholesky::new(&nmat, shift).ok_or(NumalError::DidNotConverge)?;
+
+        // Predictor: the affine-scaling direction
+        let mu = e.mu();
+        let rxz: Vec<f64> = e.x.iter().zip(&e.z).map(|(x, z)| -x * z).collect();
+        let aff = newton(std, &e, &res, &chol, 1.0, &rxz, -e.tau * e.kappa);
+        let alpha_aff = e.max_step(&aff);
+        let mut trial = Embedding {
+            x: e.x.clone(),
+            y: e.y.clone(),
+            z: e.z.clone(),
+            ..e
+        };
+        trial.step(&aff, alpha_aff);
+        let sigma = (trial.mu() / mu).powi(3).min(1.0);
+
+        // Corrector: centering plus the second-order term of the predictor
+        let rxz: Vec<f64> = (0..n)
+            .map(|k| sigma * mu - e.x[k] * e.z[k] - aff.x[k] * aff.z[k])
+            .collect();
+        let rtk = sigma * mu - e.tau * e.kappa - aff.tau * aff.kappa;
+        let dir = newton(std, &e, &res, &chol, 1.0 - sigma, &rxz, rtk);
+        let alpha = (STEP_TO_BOUNDARY * e.max_step(&dir)).min(1.0);
+        e.step(&dir, alpha);
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn both_methods() -> [LinprogOptions; 2] {
+        [
+            LinprogOptions::default(),
+            LinprogOptions {
+                method: LpMethod::InteriorPoint,
+                tol: Tolerance::Strict,
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn inequality_form_with_marginals() {
+        // max 3x + 5y s.t. x <= 4, 2y <= 12, 3x + 2y <= 18: (2, 6), value 36
+        let a = Matrix::from_row_slice(3, 2, &[1.0, 0.0, 0.0, 2.0, 3.0, 2.0]);
+        let lp = LinearProgram::inequality(vec![-3.0, -5.0], a, vec![4.0, 12.0, 18.0]);
+        for opts in both_methods() {
+            let r = linprog(&lp, &opts).unwrap();
+            assert!(
+                (r.x[0] - 2.0).abs() < 1e-6 && (r.x[1] - 6.0).abs() < 1e-6,
+                "{r:?}"
+            );
+            assert!((r.objective + 36.0).abs() < 1e-6);
+            let expected = [0.0, -1.5, -1.0];
+            for (y, e) in r.ineq_marginals.iter().zip(expected) {
+                assert!((y - e).abs() < 1e-6, "{r:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn standard_form_with_redundant_row() {
+        // x1 + x2 + x3 = 1 twice, x1 - x2 = -0.5: min x1 + 2 x2 + 3 x3
+        let a = Matrix::from_row_slice(3, 3, &[1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, -1.0, 0.0]);
+        let lp = LinearProgram::standard(vec![1.0, 2.0, 3.0], a, vec![1.0, 1.0, -0.5]);
+        for opts in both_methods() {
+            let r = linprog(&lp, &opts).unwrap();
+            let expected = [0.25, 0.75, 0.0];
+            for (x, e) in r.x.iter().zip(expected) {
+                assert!((x - e).abs() < 1e-6, "{r:?}");
+            }
+            assert!((r.objective - 1.75).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn degenerate_problem_does_not_cycle() {
+        // Beale's example, on which Dantzig's rule with the smallest-index
+        // tie break cycles
+        let a = Matrix::from_row_slice(
+            3,
+            4,
+            &[
+                0.25, -60.0, -0.04, 9.0, 0.5, -90.0, -0.02, 3.0, 0.0, 0.0, 1.0, 0.0,
+            ],
+        );
+        let lp = LinearProgram::inequality(vec![-0.75, 150.0, -0.02, 6.0], a, vec![0.0, 0.0, 1.0]);
+        for opts in both_methods() {
+            let r = linprog(&lp, &opts).unwrap();
+            assert!((r.objective + 0.05).abs() < 1e-6, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn infeasible_and_unbounded_are_reported() {
+        // x + y <= 1 and -x - y <= -2
+        let a = Matrix::from_row_slice(2, 2, &[1.0, 1.0, -1.0, -1.0]);
+        let infeasible = LinearProgram::inequality(vec![1.0, 1.0], a, vec![1.0, -2.0]);
+        // min -x with x - y <= 1
+        let a = Matrix::from_row_slice(1, 2, &[1.0, -1.0]);
+        let unbounded = LinearProgram::inequality(vec![-1.0, 0.0], a, vec![1.0]);
+        for opts in both_methods() {
+            match linprog(&infeasible, &opts) {
+                Err(NumalError::Infeasible { certificate: y }) => {
+                    // y_ub <= 0, A'y <= 0 and b·y > 0
+                    assert!(y.iter().all(|&v| v <= 1e-8), "{y:?}");
+                    assert!(infeasible.a_ub.tr_mul_vec(&y).iter().all(|&v| v <= 1e-8));
+                    assert!(dot(&infeasible.b_ub, &y) > 0.0);
+                }
+                r => panic!("{r:?}"),
+            }
+            match linprog(&unbounded, &opts) {
+                Err(NumalError::Unbounded { direction: d }) => {
+                    assert!(d.iter().all(|&v| v >= -1e-8), "{d:?}");
+                    assert!(unbounded.a_ub.mul_vec(&d)[0] <= 1e-8);
+                    assert!(dot(&unbounded.c, &d) < 0.0);
+                }
+                r => panic!("{r:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn large_right_hand_side_is_not_unbounded() {
+        // max x + y s.t. x + y <= 1e7, and with x + 2y <= 1e7, 3x + y <= 2e7
+        let one = LinearProgram::inequality(
+            vec![-1.0, -1.0],
+            Matrix::from_row_slice(1, 2, &[1.0, 1.0]),
+            vec![1e7],
+        );
+        let two = LinearProgram::inequality(
+            vec![-1.0, -1.0],
+            Matrix::from_row_slice(2, 2, &[1.0, 2.0, 3.0, 1.0]),
+            vec![1e7, 2e7],
+        );
+        let opts = LinprogOptions {
+            method: LpMethod::InteriorPoint,
+            ..Default::default()
+        };
+        for (lp, objective) in [(one, -1e7), (two, -8e6)] {
+            let r = linprog(&lp, &opts).unwrap();
+            assert!((r.objective / objective - 1.0).abs() < 1e-6, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn mismatched_dimensions_are_rejected() {
+        let lp = LinearProgram::inequality(vec![1.0, 1.0], Matrix::zeros(2, 3), vec![1.0, 1.0]);
+        let r = linprog(&lp, &LinprogOptions::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
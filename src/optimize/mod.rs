@@ -0,0 +1,407 @@
+//! Local and global minimization of scalar and multivariate functions, and
+//! linear and quadratic programming.
+//!
+//! The stochastic global methods, [`differential_evolution`], [`cmaes()`],
+//! [`simulated_annealing`], [`basin_hopping`] and [`particle_swarm`], share
+//! one interface: they are called as `(f, x0, bounds, opts)` with optional
+//! [`Bounds`], basin hopping with its local minimizer after `f`, and stop with [`NumalError::DidNotConverge`] once the
+//! `max_evaluations` of their options are spent.
+
+pub mod annealing;
+pub mod auglag;
+pub mod basinhopping;
+pub mod bobyqa;
+pub mod branchbound;
+pub mod cg;
+pub mod cmaes;
+pub mod cobyla;
+pub mod constrained;
+pub mod diffevol;
+pub mod direct;
+pub mod lbfgsb;
+pub mod linesearch;
+pub mod linprog;
+pub mod neldermead;
+pub mod powell;
+pub mod pso;
+pub mod qp;
+pub mod quasinewton;
+pub mod scalar;
+pub mod sqp;
+#[cfg(test)]
+mod testproblems;
+pub mod trustregion;
+
+pub use annealing::{AnnealingOptions, Cooling, CoolingSchedule, simulated_annealing};
+pub use auglag::{AugLagOptions, augmented_lagrangian};
+pub use basinhopping::{BasinHoppingOptions, basin_hopping};
+pub use bobyqa::{BobyqaOptions, bobyqa};
+pub use branchbound::{
+    BranchBoundOptions, IntervalMinimum, branch_and_bound, branch_and_bound_grad,
+};
+pub use cg::{CgBeta, CgOptions, nonlinear_cg};
+pub use cmaes::{CmaEsOptions, cmaes};
+pub use cobyla::{CobylaConstraint, CobylaOptions, cobyla};
+pub use constrained::{ConstrainedMinimum, Constraint};
+pub use diffevol::{DeOptions, DeStrategy, differential_evolution};
+pub use direct::{DirectMethod, DirectOptions, direct};
+pub use lbfgsb::{LbfgsbOptions, lbfgsb};
+pub use linesearch::{LineSearch, LineSearchResult};
+pub use linprog::{LinearProgram, LinprogOptions, LpMethod, LpSolution, linprog};
+pub use neldermead::{InitialSimplex, NelderMeadOptions, nelder_mead};
+pub use powell::{PowellOptions, powell};
+pub use pso::{PsoOptions, Topology, particle_swarm};
+pub use qp::{QpMethod, QpOptions, QpSolution, QuadraticProgram, quadprog};
+pub use quasinewton::{BfgsOptions, LbfgsOptions, bfgs, lbfgs};
+pub use sqp::{SqpOptions, sqp};
+pub use trustregion::{
+    RadiusUpdate, Subproblem, TrustRegionOptions, trust_region, trust_region_hvp,
+};
+
+use crate::NumalError;
+pub(crate) use crate::core::check_start;
+use crate::core::linalg::{Matrix, norm};
+use crate::core::tolerance::{Tolerance, is_close};
+
+/// Outcome of a successful multivariate minimization
+#[derive(Clone, Debug, PartialEq)]
+pub struct Minimum {
+    /// Location of the minimum
+    pub x: Vec<f64>,
+    /// Objective value at `x`
+    pub fx: f64,
+    /// Number of iterations performed
+    pub iterations: usize,
+    /// Number of objective evaluations
+    pub evaluations: usize,
+}
+
+/// Why a gradient-based optimizer stopped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Termination {
+    /// Every gradient component satisfies `|g_i| <= eps_abs`
+    GradientNorm,
+    /// Every component of the last step is within tolerance of the iterate
+    StepSize,
+    /// The objective changed by less than `eps_abs^2 + eps_rel^2 * |f|`.
+    /// Near a minimum `f - f*` is quadratic in the distance to the
+    /// minimizer, so this matches locating it to within the tolerance.
+    FunctionChange,
+}
+
+/// Outcome of a successful gradient-based minimization
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradientMinimum {
+    /// Location of the minimum
+    pub x: Vec<f64>,
+    /// Objective value at `x`
+    pub fx: f64,
+    /// Gradient at `x`
+    pub grad: Vec<f64>,
+    /// Euclidean norm of `grad`
+    pub grad_norm: f64,
+    /// Number of iterations performed
+    pub iterations: usize,
+    /// Number of objective/gradient evaluations
+    pub evaluations: usize,
+    /// Which convergence test was satisfied
+    pub termination: Termination,
+    /// Final Hessian approximation, for methods that maintain one
+    pub hessian: Option<Matrix>,
+}
+
+impl From<GradientMinimum> for Minimum {
+    fn from(m: GradientMinimum) -> Minimum {
+        Minimum {
+            x: m.x,
+            fx: m.fx,
+            iterations: m.iterations,
+            evaluations: m.evaluations,
+        }
+    }
+}
+
+/// Simple bounds `lower <= x <= upper` on each component; infinite entries
+/// leave that side unconstrained
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bounds {
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+}
+
+impl Bounds {
+    /// Validates the bounds: both vectors must have the same length, contain
+    /// no NaN and satisfy `lower[i] <= upper[i]` with some finite value in
+    /// between.
+    pub fn new(lower: Vec<f64>, upper: Vec<f64>) -> Result<Bounds, NumalError> {
+        if lower.len() != upper.len() {
+            return Err(NumalError::InvalidInput(format!(
+                "{} lower bounds but {} upper bounds",
+                lower.len(),
+                upper.len()
+            )));
+        }
+        for (i, (&l, &u)) in lower.iter().zip(&upper).enumerate() {
+            if !(l <= u && l < f64::INFINITY && u > f64::NEG_INFINITY) {
+                return Err(NumalError::InvalidInput(format!(
+                    "inconsistent bounds for component {i}: lower = {l}, upper = {u}"
+                )));
+            }
+        }
+        Ok(Bounds { lower, upper })
+    }
+
+    pub fn lower(&self) -> &[f64] {
+        &self.lower
+    }
+
+    pub fn upper(&self) -> &[f64] {
+        &self.upper
+    }
+
+    /// Number of bounded components
+    pub fn dim(&self) -> usize {
+        self.lower.len()
+    }
+
+    /// Whether every bound is finite
+    pub fn is_finite(&self) -> bool {
+        self.lower.iter().chain(&self.upper).all(|v| v.is_finite())
+    }
+
+    /// Clamps `x` into the box
+    pub fn project(&self, x: &mut [f64]) {
+        for ((xi, &l), &u) in x.iter_mut().zip(&self.lower).zip(&self.upper) {
+            *xi = xi.clamp(l, u);
+        }
+    }
+
+    pub(crate) fn check_dim(&self, n: usize) -> Result<(), NumalError> {
+        if self.dim() != n {
+            return Err(NumalError::InvalidInput(format!(
+                "bounds have dimension {} but the initial guess has {n} components",
+                self.dim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+// Validates a starting point and optional bounds, returning the start
+// projected into the box.
+pub(crate) fn bounded_start(x0: &[f64], bounds: Option<&Bounds>) -> Result<Vec<f64>, NumalError> {
+    check_start(x0)?;
+    let mut x = x0.to_vec();
+    if let Some(b) = bounds {
+        b.check_dim(x0.len())?;
+        b.project(&mut x);
+    }
+    Ok(x)
+}
+
+// Validates the start and bounds of a population method, which spreads its
+// members over a finite box.
+pub(crate) fn finite_box<'b>(
+    x0: &[f64],
+    bounds: Option<&'b Bounds>,
+    method: &str,
+) -> Result<(Vec<f64>, &'b Bounds), NumalError> {
+    let x = bounded_start(x0, bounds)?;
+    match bounds {
+        Some(b) if b.is_finite() => Ok((x, b)),
+        _ => Err(NumalError::InvalidInput(format!(
+            "{method} requires finite bounds"
+        ))),
+    }
+}
+
+// Bound on the values that BOBYQA and COBYLA interpolate. As in Powell's
+// later codes, larger values, infinities included, are clamped to it so that
+// the models stay finite.
+pub(crate) const HUGE: f64 = 1e30;
+
+// Objective of the derivative-free methods: counts evaluations against a
+// budget, treats NaN as `+inf` and keeps the best point seen.
+pub(crate) struct Budgeted<'a, F> {
+    f: &'a F,
+    max_evaluations: usize,
+    pub(crate) evaluations: usize,
+    pub(crate) best: Vec<f64>,
+    pub(crate) f_best: f64,
+}
+
+impl<'a, F: Fn(&[f64]) -> f64> Budgeted<'a, F> {
+    pub(crate) fn new(f: &'a F, max_evaluations: usize) -> Self {
+        Budgeted {
+            f,
+            max_evaluations,
+            evaluations: 0,
+            best: Vec::new(),
+            f_best: f64::INFINITY,
+        }
+    }
+
+    // Evaluations left in the budget.
+    pub(crate) fn remaining(&self) -> usize {
+        self.max_evaluations - self.evaluations
+    }
+
+    // The objective at `x`, or `None` once the budget is spent.
+    pub(crate) fn eval(&mut self, x: &[f64]) -> Option<f64> {
+        if self.evaluations >= self.max_evaluations {
+            return None;
+        }
+        self.evaluations += 1;
+        let v = (self.f)(x);
+        let v = if v.is_nan() { f64::INFINITY } else { v };
+        if v < self.f_best || self.best.is_empty() {
+            (self.best, self.f_best) = (x.to_vec(), v);
+        }
+        Some(v)
+    }
+
+    // The objective at `x` clamped to `[-HUGE, HUGE]`, for the methods
+    // that interpolate it, or `DidNotConverge` once the budget is spent.
+    pub(crate) fn eval_clamped(&mut self, x: &[f64]) -> Result<f64, NumalError> {
+        let v = self.eval(x).ok_or(NumalError::DidNotConverge)?;
+        Ok(v.clamp(-HUGE, HUGE))
+    }
+
+    pub(crate) fn minimum(self, iterations: usize) -> Minimum {
+        Minimum {
+            x: self.best,
+            fx: self.f_best,
+            iterations,
+            evaluations: self.evaluations,
+        }
+    }
+}
+
+// Index of the smallest value.
+pub(crate) fn argmin(values: &[f64]) -> usize {
+    (0..values.len())
+        .min_by(|&a, &b| values[a].total_cmp(&values[b]))
+        .unwrap_or(0)
+}
+
+// Whether every member of a population is within `tol` of member `best`,
+// component-wise and in objective value.
+pub(crate) fn population_converged(
+    points: &[Vec<f64>],
+    values: &[f64],
+    best: usize,
+    tol: Tolerance,
+) -> bool {
+    let (xb, fb) = (&points[best], values[best]);
+    points.iter().zip(values).all(|(x, &fx)| {
+        (fx == fb || is_close(fx, fb, tol).is_ok())
+            && x.iter().zip(xb).all(|(&a, &b)| is_close(a, b, tol).is_ok())
+    })
+}
+
+// Runs `run` from `x0`, then restarts it from the best point found up to
+// `restarts` times, stopping early once a restart no longer improves the
+// objective under `tol`. Each run is handed the objective at its starting
+// point; `fx0` is the value at `x0`, which the caller evaluated once and is
+// counted here. `budget` is the iteration budget shared by all runs; a
+// restart that exhausts it leaves the best converged result in place, while
+// any other failure of a restart is returned.
+pub(crate) fn with_restarts<R>(
+    x0: &[f64],
+    fx0: f64,
+    restarts: usize,
+    tol: Tolerance,
+    mut budget: usize,
+    mut run: R,
+) -> Result<Minimum, NumalError>
+where
+    R: FnMut(&[f64], f64, &mut usize) -> Result<Minimum, NumalError>,
+{
+    let mut best = run(x0, fx0, &mut budget)?;
+    best.evaluations += 1;
+    for _ in 0..restarts {
+        let next = match run(&best.x, best.fx, &mut budget) {
+            Ok(next) => next,
+            Err(NumalError::DidNotConverge) if budget == 0 => break,
+            Err(e) => return Err(e),
+        };
+        let improved = is_close(next.fx, best.fx, tol).is_err();
+        let (iterations, evaluations) = (
+            best.iterations + next.iterations,
+            best.evaluations + next.evaluations,
+        );
+        if next.fx <= best.fx {
+            best = next;
+        }
+        best.iterations = iterations;
+        best.evaluations = evaluations;
+        if !improved {
+            break;
+        }
+    }
+    Ok(best)
+}
+
+// Evaluates `fg` at the starting point, rejecting non-finite results.
+pub(crate) fn initial_gradient<F>(fg: &F, x0: &[f64]) -> Result<(f64, Vec<f64>), NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    check_start(x0)?;
+    let mut g = vec![0.0; x0.len()];
+    let f = fg(x0, &mut g);
+    if !f.is_finite() || g.iter().any(|v| !v.is_finite()) {
+        return Err(NumalError::InvalidInput(
+            "objective or gradient is not finite at the initial guess".to_string(),
+        ));
+    }
+    Ok((f, g))
+}
+
+// Convergence tests shared by the gradient-based optimizers, checked in
+// order: gradient, step, function change.
+pub(crate) fn gradient_termination(
+    g: &[f64],
+    x_old: &[f64],
+    x: &[f64],
+    f_old: f64,
+    f: f64,
+    tol: Tolerance,
+) -> Option<Termination> {
+    if g.iter().all(|v| v.abs() <= tol.eps_abs()) {
+        Some(Termination::GradientNorm)
+    } else if x
+        .iter()
+        .zip(x_old)
+        .all(|(&a, &b)| is_close(a, b, tol).is_ok())
+    {
+        Some(Termination::StepSize)
+    } else if (f - f_old).abs()
+        <= tol.eps_abs().powi(2) + tol.eps_rel().powi(2) * f.abs().max(f_old.abs())
+    {
+        Some(Termination::FunctionChange)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn gradient_minimum(
+    x: Vec<f64>,
+    fx: f64,
+    grad: Vec<f64>,
+    iterations: usize,
+    evaluations: usize,
+    termination: Termination,
+    hessian: Option<Matrix>,
+) -> GradientMinimum {
+    GradientMinimum {
+        grad_norm: norm(&grad),
+        x,
+        fx,
+        grad,
+        iterations,
+        evaluations,
+        termination,
+        hessian,
+    }
+}
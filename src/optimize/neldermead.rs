@@ -0,0 +1,396 @@
+//! Nelder–Mead downhill simplex method.
+//!
+//! The search stops once every vertex is within [`Tolerance`] of the best
+//! vertex component-wise (simplex size) and every vertex value is within
+//! tolerance of the best value (function spread). Objective values that are
+//! NaN away from the starting point are treated as `+inf`, so the simplex
+//! simply moves away from regions where the objective is undefined.
+
+use super::{Minimum, with_restarts};
+use crate::NumalError;
+use crate::core::tolerance::{Tolerance, is_close};
+
+/// How the starting simplex is built around the initial guess `x0`
+#[derive(Clone, Debug, PartialEq)]
+pub enum InitialSimplex {
+    /// Perturb each coordinate by 5%, or by 0.00025 when it is zero
+    Pfeffer,
+    /// Axis-aligned steps `x0 + h e_i`
+    Axis(f64),
+    /// A regular simplex with edge length `h` (Spendley, Hext and Himsworth)
+    Regular(f64),
+    /// Explicit vertices; `n + 1` points of dimension `n`. On restart the
+    /// same shape is translated onto the current best point.
+    Custom(Vec<Vec<f64>>),
+}
+
+/// Options for [`nelder_mead`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct NelderMeadOptions {
+    /// Simplex-size and function-spread tolerance
+    pub tol: Tolerance,
+    /// Iteration budget shared by the initial run and all restarts
+    pub max_iter: usize,
+    /// Use the dimension-dependent coefficients of Gao and Han (2012)
+    /// instead of the standard (1, 2, 1/2, 1/2)
+    pub adaptive: bool,
+    pub initial_simplex: InitialSimplex,
+    /// Maximum number of restarts from the best point with a fresh simplex.
+    /// Restarting stops early once a restart no longer improves the value.
+    pub restarts: usize,
+}
+
+impl Default for NelderMeadOptions {
+    fn default() -> Self {
+        NelderMeadOptions {
+            tol: Tolerance::Default,
+            max_iter: 10_000,
+            adaptive: true,
+            initial_simplex: InitialSimplex::Pfeffer,
+            restarts: 1,
+        }
+    }
+}
+
+fn build_simplex(x0: &[f64], init: &InitialSimplex) -> Result<Vec<Vec<f64>>, NumalError> {
+    let n = x0.len();
+    let vertex = |i: usize, step: &dyn Fn(usize, usize) -> f64| -> Vec<f64> {
+        x0.iter()
+            .enumerate()
+            .map(|(j, &v)| v + step(i, j))
+            .collect()
+    };
+    let mut simplex = vec![x0.to_vec()];
+    match init {
+        InitialSimplex::Pfeffer => {
+            for i in 0..n {
+                simplex.push(vertex(i, &|i, j| match (i == j, x0[j] == 0.0) {
+                    (false, _) => 0.0,
+                    (true, true) => 0.00025,
+                    (true, false) => 0.05 * x0[j],
+                }));
+            }
+        }
+        InitialSimplex::Axis(h) | InitialSimplex::Regular(h) if !(h.is_finite() && *h != 0.0) => {
+            return Err(NumalError::InvalidInput(format!(
+                "simplex step must be finite and non-zero, got {h}"
+            )));
+        }
+        InitialSimplex::Axis(h) => {
+            for i in 0..n {
+                simplex.push(vertex(i, &|i, j| if i == j { *h } else { 0.0 }));
+            }
+        }
+        InitialSimplex::Regular(h) => {
+            let nf = n as f64;
+            let root = (nf + 1.0).sqrt();
+            let p = h / (nf * 2f64.sqrt()) * (root + nf - 1.0);
+            let q = h / (nf * 2f64.sqrt()) * (root - 1.0);
+            for i in 0..n {
+                simplex.push(vertex(i, &|i, j| if i == j { p } else { q }));
+            }
+        }
+        InitialSimplex::Custom(vertices) => {
+            if vertices.len() != n + 1 || vertices.iter().any(|v| v.len() != n) {
+                return Err(NumalError::InvalidInput(format!(
+                    "custom simplex must have {} vertices of dimension {n}",
+                    n + 1
+                )));
+            }
+            let origin = &vertices[0];
+            for v in &vertices[1..] {
+                simplex.push(
+                    x0.iter()
+                        .zip(v)
+                        .zip(origin)
+                        .map(|((x, v), o)| x + v - o)
+                        .collect(),
+                );
+            }
+        }
+    }
+    Ok(simplex)
+}
+
+/// Minimizes `f` with the Nelder–Mead simplex method.
+pub fn nelder_mead<F>(f: F, x0: &[f64], opts: &NelderMeadOptions) -> Result<Minimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    super::check_start(x0)?;
+    let fx0 = f(x0);
+    if fx0.is_nan() {
+        return Err(NumalError::InvalidInput(
+            "objective is NaN at the initial guess".to_string(),
+        ));
+    }
+    with_restarts(
+        x0,
+        fx0,
+        opts.restarts,
+        opts.tol,
+        opts.max_iter,
+        |x, fx, budget| run(&f, x, fx, opts, budget),
+    )
+}
+
+fn run<F>(
+    f: &F,
+    x0: &[f64],
+    fx0: f64,
+    opts: &NelderMeadOptions,
+    budget: &mut usize,
+) -> Result<Minimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let n = x0.len();
+    let nf = n as f64;
+    let (alpha, beta, gamma, delta) = if opts.adaptive && n > 1 {
+        (1.0, 1.0 + 2.0 / nf, 0.75 - 0.5 / nf, 1.0 - 1.0 / nf)
+    } else {
+        (1.0, 2.0, 0.5, 0.5)
+    };
+    let tol = opts.tol;
+    let mut evals = 0;
+    let eval = |x: &[f64], evals: &mut usize| {
+        *evals += 1;
+        let v = f(x);
+        if v.is_nan() { f64::INFINITY } else { v }
+    };
+
+    let simplex = build_simplex(x0, &opts.initial_simplex)?;
+    let mut pts: Vec<(Vec<f64>, f64)> = simplex
+        .into_iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let fx = if i == 0 { fx0 } else { eval(&x, &mut evals) };
+            (x, fx)
+        })
+        .collect();
+
+    let mut iter = 0;
+    loop {
+        pts.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (best, fbest) = (&pts[0].0, pts[0].1);
+        let small = pts[1..].iter().all(|(x, _)| {
+            x.iter()
+                .zip(best)
+                .all(|(&xi, &bi)| is_close(xi, bi, tol).is_ok())
+        });
+        let flat = pts[1..]
+            .iter()
+            .all(|&(_, fx)| fx == fbest || is_close(fx, fbest, tol).is_ok());
+        if small && flat {
+            let (x, fx) = pts.swap_remove(0);
+            return Ok(Minimum {
+                x,
+                fx,
+                iterations: iter,
+                evaluations: evals,
+            });
+        }
+        if *budget == 0 {
+            return Err(NumalError::DidNotConverge);
+        }
+        *budget -= 1;
+        iter += 1;
+
+        let mut centroid = vec![0.0; n];
+        for (x, _) in &pts[..n] {
+            for (c, xi) in centroid.iter_mut().zip(x) {
+                *c += xi / nf;
+            }
+        }
+        let along = |t: f64, to: &[f64]| -> Vec<f64> {
+            centroid
+                .iter()
+                .zip(to)
+                .map(|(c, x)| c + t * (x - c))
+                .collect()
+        };
+        let (worst, fworst) = (pts[n].0.clone(), pts[n].1);
+        let xr = along(-alpha, &worst);
+        let fr = eval(&xr, &mut evals);
+        let replacement = if fr < pts[0].1 {
+            let xe = along(-alpha * beta, &worst);
+            let fe = eval(&xe, &mut evals);
+            Some(if fe < fr { (xe, fe) } else { (xr, fr) })
+        } else if fr < pts[n - 1].1 {
+            Some((xr, fr))
+        } else if fr < fworst {
+            let xc = along(-alpha * gamma, &worst);
+            let fc = eval(&xc, &mut evals);
+            (fc <= fr).then_some((xc, fc))
+        } else {
+            let xc = along(gamma, &worst);
+            let fc = eval(&xc, &mut evals);
+            (fc < fworst).then_some((xc, fc))
+        };
+        match replacement {
+            Some(p) => pts[n] = p,
+            None => {
+                let x_best = pts[0].0.clone();
+                for (x, fx) in pts.iter_mut().skip(1) {
+                    for (xi, bi) in x.iter_mut().zip(&x_best) {
+                        *xi = bi + delta * (*xi - bi);
+                    }
+                    *fx = eval(x, &mut evals);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::rosenbrock;
+
+    #[test]
+    fn minimizes_rosenbrock() {
+        let opts = NelderMeadOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = nelder_mead(rosenbrock, &[-1.2, 1.0], &opts).unwrap();
+        for xi in &r.x {
+            assert!((xi - 1.0).abs() < 1e-8, "{r:?}");
+        }
+        assert!(r.fx < 1e-16);
+    }
+
+    #[test]
+    fn adaptive_parameters_help_in_higher_dimension() {
+        let sphere = |x: &[f64]| {
+            x.iter()
+                .enumerate()
+                .map(|(i, v)| (i + 1) as f64 * (v - 1.0).powi(2))
+                .sum::<f64>()
+        };
+        let x0 = vec![0.0; 10];
+        let opts = NelderMeadOptions {
+            max_iter: 100_000,
+            ..Default::default()
+        };
+        let r = nelder_mead(sphere, &x0, &opts).unwrap();
+        for xi in &r.x {
+            assert!((xi - 1.0).abs() < 1e-4, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn all_initial_simplex_strategies_converge() {
+        let f = |x: &[f64]| (x[0] - 3.0).powi(2) + (x[1] + 1.0).powi(2);
+        for init in [
+            InitialSimplex::Pfeffer,
+            InitialSimplex::Axis(0.5),
+            InitialSimplex::Regular(1.0),
+            InitialSimplex::Custom(vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![0.0, 1.0]]),
+        ] {
+            let opts = NelderMeadOptions {
+                initial_simplex: init.clone(),
+                ..Default::default()
+            };
+            let r = nelder_mead(f, &[0.0, 0.0], &opts).unwrap();
+            assert!(
+                (r.x[0] - 3.0).abs() < 1e-4 && (r.x[1] + 1.0).abs() < 1e-4,
+                "{init:?}: {r:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn restarts_accumulate_work() {
+        let f = |x: &[f64]| x[0] * x[0] + 5.0 * x[1] * x[1];
+        let once = NelderMeadOptions {
+            restarts: 0,
+            ..Default::default()
+        };
+        let twice = NelderMeadOptions {
+            restarts: 1,
+            ..Default::default()
+        };
+        let a = nelder_mead(f, &[2.0, 1.0], &once).unwrap();
+        let b = nelder_mead(f, &[2.0, 1.0], &twice).unwrap();
+        assert!(b.evaluations > a.evaluations);
+        assert!(b.fx <= a.fx);
+    }
+
+    #[test]
+    fn evaluations_count_every_call() {
+        let calls = std::cell::Cell::new(0);
+        let f = |x: &[f64]| {
+            calls.set(calls.get() + 1);
+            rosenbrock(x)
+        };
+        let r = nelder_mead(f, &[-1.2, 1.0], &NelderMeadOptions::default()).unwrap();
+        assert_eq!(r.evaluations, calls.get());
+    }
+
+    #[test]
+    fn restart_out_of_budget_keeps_converged_result() {
+        let once = NelderMeadOptions {
+            restarts: 0,
+            ..Default::default()
+        };
+        let a = nelder_mead(rosenbrock, &[-1.2, 1.0], &once).unwrap();
+        let opts = NelderMeadOptions {
+            restarts: 1,
+            max_iter: a.iterations + 1,
+            ..Default::default()
+        };
+        let b = nelder_mead(rosenbrock, &[-1.2, 1.0], &opts).unwrap();
+        assert!(b.fx <= a.fx, "{b:?}");
+    }
+
+    #[test]
+    fn nan_region_is_avoided() {
+        let f = |x: &[f64]| {
+            if x[0] < 0.0 {
+                f64::NAN
+            } else {
+                (x[0] - 0.5).powi(2)
+            }
+        };
+        let r = nelder_mead(f, &[2.0], &NelderMeadOptions::default()).unwrap();
+        assert!((r.x[0] - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let opts = NelderMeadOptions::default();
+        assert!(matches!(
+            nelder_mead(rosenbrock, &[], &opts),
+            Err(NumalError::InvalidInput(_))
+        ));
+        let opts = NelderMeadOptions {
+            initial_simplex: InitialSimplex::Custom(vec![vec![0.0, 0.0]]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            nelder_mead(rosenbrock, &[0.0, 0.0], &opts),
+            Err(NumalError::InvalidInput(_))
+        ));
+        let opts = NelderMeadOptions {
+            initial_simplex: InitialSimplex::Axis(0.0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            nelder_mead(rosenbrock, &[0.0, 0.0], &opts),
+            Err(NumalError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn exhausted_budget_did_not_converge() {
+        let opts = NelderMeadOptions {
+            max_iter: 5,
+            ..Default::default()
+        };
+        assert_eq!(
+            nelder_mead(rosenbrock, &[-1.2, 1.0], &opts),
+            Err(NumalError::DidNotConverge)
+        );
+    }
+}
@@ -0,0 +1,321 @@
+//! Powell's conjugate-direction method.
+//!
+//! Minimizes along each direction of a set in turn, then replaces the
+//! direction of largest decrease by the overall displacement of the sweep
+//! when the heuristic of Powell (1964) indicates that doing so keeps the set
+//! well conditioned. Each line minimization brackets the minimum and then
+//! applies Brent's method from [`super::scalar`].
+//!
+//! The search stops once a full sweep decreases the objective by no more
+//! than the [`Tolerance`] allows relative to its magnitude.
+
+use super::scalar::{brent, search_bracket};
+use super::{Minimum, with_restarts};
+use crate::NumalError;
+use crate::core::tolerance::Tolerance;
+
+// Expansion budget used when bracketing along a search direction.
+const BRACKET_ITER: usize = 100;
+
+/// Options for [`powell`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PowellOptions {
+    /// Function-decrease tolerance, also used by the line minimizations
+    pub tol: Tolerance,
+    /// Iteration budget shared by the initial run and all restarts
+    pub max_iter: usize,
+    /// Initial search directions, one per row; the coordinate axes if `None`
+    pub directions: Option<Vec<Vec<f64>>>,
+    /// Maximum number of restarts from the best point with the initial
+    /// directions. Restarting stops early once a restart no longer improves
+    /// the value.
+    pub restarts: usize,
+}
+
+impl Default for PowellOptions {
+    fn default() -> Self {
+        PowellOptions {
+            tol: Tolerance::Default,
+            max_iter: 1_000,
+            directions: None,
+            restarts: 1,
+        }
+    }
+}
+
+/// Minimizes `f` with Powell's conjugate-direction method.
+pub fn powell<F>(f: F, x0: &[f64], opts: &PowellOptions) -> Result<Minimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    super::check_start(x0)?;
+    let n = x0.len();
+    let directions = match &opts.directions {
+        None => (0..n)
+            .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+            .collect(),
+        Some(d) => {
+            let valid = d.len() == n
+                && d.iter().all(|u| {
+                    u.len() == n && u.iter().all(|v| v.is_finite()) && u.iter().any(|&v| v != 0.0)
+                });
+            if !valid {
+                return Err(NumalError::InvalidInput(format!(
+                    "expected {n} finite, non-zero directions of dimension {n}"
+                )));
+            }
+            d.clone()
+        }
+    };
+    let fx0 = f(x0);
+    if fx0.is_nan() {
+        return Err(NumalError::InvalidInput(
+            "objective is NaN at the initial guess".to_string(),
+        ));
+    }
+    with_restarts(
+        x0,
+        fx0,
+        opts.restarts,
+        opts.tol,
+        opts.max_iter,
+        |x, fx, budget| run(&f, x, fx, directions.clone(), opts.tol, budget),
+    )
+}
+
+fn run<F>(
+    f: &F,
+    x0: &[f64],
+    fx0: f64,
+    mut dirs: Vec<Vec<f64>>,
+    tol: Tolerance,
+    budget: &mut usize,
+) -> Result<Minimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let n = x0.len();
+    let mut evals = 0;
+    let eval = |x: &[f64], evals: &mut usize| {
+        *evals += 1;
+        let v = f(x);
+        if v.is_nan() { f64::INFINITY } else { v }
+    };
+    let mut x = x0.to_vec();
+    let mut fx = fx0;
+    let mut iter = 0;
+    loop {
+        if *budget == 0 {
+            return Err(NumalError::DidNotConverge);
+        }
+        *budget -= 1;
+        iter += 1;
+
+        let (x_start, f_start) = (x.clone(), fx);
+        let (mut biggest, mut decrease) = (0, 0.0);
+        for (i, u) in dirs.iter().enumerate() {
+            let f_before = fx;
+            fx = line_minimize(&eval, &mut x, fx, u, tol, &mut evals)?;
+            if f_before - fx > decrease {
+                decrease = f_before - fx;
+                biggest = i;
+            }
+        }
+        if 2.0 * (f_start - fx) <= tol.eps_rel() * (f_start.abs() + fx.abs()) + tol.eps_abs() {
+            return Ok(Minimum {
+                x,
+                fx,
+                iterations: iter,
+                evaluations: evals,
+            });
+        }
+        let u: Vec<f64> = x.iter().zip(&x_start).map(|(a, b)| a - b).collect();
+        let extrapolated: Vec<f64> = x.iter().zip(&u).map(|(a, d)| a + d).collect();
+        let f_ext = eval(&extrapolated, &mut evals);
+        if f_ext < f_start {
+            let t = 2.0 * (f_start - 2.0 * fx + f_ext) * (f_start - fx - decrease).powi(2)
+                - decrease * (f_start - f_ext).powi(2);
+            if t < 0.0 {
+                fx = line_minimize(&eval, &mut x, fx, &u, tol, &mut evals)?;
+                dirs[biggest] = dirs[n - 1].clone();
+                dirs[n - 1] = u;
+            }
+        }
+    }
+}
+
+// Minimizes along `x + t u`, moving `x` to the minimizer and returning the
+// new objective value. A direction along which the objective is flat leaves
+// `x` unchanged; one along which it decreases without bound is an error.
+fn line_minimize<E>(
+    eval: &E,
+    x: &mut [f64],
+    fx: f64,
+    u: &[f64],
+    tol: Tolerance,
+    evals: &mut usize,
+) -> Result<f64, NumalError>
+where
+    E: Fn(&[f64], &mut usize) -> f64,
+{
+    let count = std::cell::Cell::new(0);
+    let point = |t: f64| -> Vec<f64> { x.iter().zip(u).map(|(xi, ui)| xi + t * ui).collect() };
+    let phi = |t: f64| {
+        let mut k = 0;
+        let v = eval(&point(t), &mut k);
+        count.set(count.get() + k);
+        v
+    };
+    let found = match search_bracket(phi, 0.0, 1.0, tol, BRACKET_ITER)? {
+        (b, true) => Some(b),
+        (last, false) => {
+            if last.fc < fx {
+                *evals += count.get();
+                return Err(NumalError::DidNotConverge);
+            }
+            None
+        }
+    };
+    let mut result = fx;
+    if let Some(b) = found {
+        let m = brent(phi, b.a, b.c, tol, 100)?;
+        if m.fx < fx {
+            let moved = point(m.x);
+            x.copy_from_slice(&moved);
+            result = m.fx;
+        }
+    }
+    *evals += count.get();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::rosenbrock;
+
+    #[test]
+    fn minimizes_rosenbrock() {
+        let opts = PowellOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = powell(rosenbrock, &[-1.2, 1.0], &opts).unwrap();
+        assert!(
+            (r.x[0] - 1.0).abs() < 1e-6 && (r.x[1] - 1.0).abs() < 1e-6,
+            "{r:?}"
+        );
+    }
+
+    #[test]
+    fn quadratic_converges_in_few_sweeps() {
+        // A coupled quadratic is minimized exactly after n conjugate sweeps
+        let f = |x: &[f64]| {
+            let (a, b, c) = (x[0] - 1.0, x[1] + 2.0, x[2] - 0.5);
+            a * a + 2.0 * b * b + 3.0 * c * c + a * b + b * c
+        };
+        let opts = PowellOptions {
+            restarts: 0,
+            ..Default::default()
+        };
+        let r = powell(f, &[0.0, 0.0, 0.0], &opts).unwrap();
+        for (xi, ei) in r.x.iter().zip([1.0, -2.0, 0.5]) {
+            assert!((xi - ei).abs() < 1e-5, "{r:?}");
+        }
+        assert!(r.iterations <= 6, "{r:?}");
+    }
+
+    #[test]
+    fn evaluations_count_every_call() {
+        let calls = std::cell::Cell::new(0);
+        let f = |x: &[f64]| {
+            calls.set(calls.get() + 1);
+            rosenbrock(x)
+        };
+        let r = powell(f, &[-1.2, 1.0], &PowellOptions::default()).unwrap();
+        assert_eq!(r.evaluations, calls.get());
+    }
+
+    #[test]
+    fn restart_out_of_budget_keeps_converged_result() {
+        let once = PowellOptions {
+            restarts: 0,
+            ..Default::default()
+        };
+        let a = powell(rosenbrock, &[-1.2, 1.0], &once).unwrap();
+        // A restart from the minimum can finish in one sweep, so leave it none
+        let opts = PowellOptions {
+            restarts: 1,
+            max_iter: a.iterations,
+            ..Default::default()
+        };
+        let b = powell(rosenbrock, &[-1.2, 1.0], &opts).unwrap();
+        assert!(b.fx <= a.fx, "{b:?}");
+    }
+
+    #[test]
+    fn restart_failure_is_returned() {
+        // The objective turns unbounded below once the first run is over, so
+        // the restart cannot bracket a minimum along the first direction
+        let once = PowellOptions {
+            restarts: 0,
+            ..Default::default()
+        };
+        let a = powell(rosenbrock, &[-1.2, 1.0], &once).unwrap();
+        let calls = std::cell::Cell::new(0);
+        let f = |x: &[f64]| {
+            calls.set(calls.get() + 1);
+            if calls.get() <= a.evaluations {
+                rosenbrock(x)
+            } else {
+                -x[0]
+            }
+        };
+        let opts = PowellOptions {
+            restarts: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            powell(f, &[-1.2, 1.0], &opts),
+            Err(NumalError::DidNotConverge)
+        );
+    }
+
+    #[test]
+    fn custom_directions() {
+        let f = |x: &[f64]| (x[0] + x[1] - 2.0).powi(2) + (x[0] - x[1]).powi(2);
+        let opts = PowellOptions {
+            directions: Some(vec![vec![1.0, 1.0], vec![1.0, -1.0]]),
+            ..Default::default()
+        };
+        let r = powell(f, &[5.0, -3.0], &opts).unwrap();
+        assert!(
+            (r.x[0] - 1.0).abs() < 1e-6 && (r.x[1] - 1.0).abs() < 1e-6,
+            "{r:?}"
+        );
+    }
+
+    #[test]
+    fn flat_direction_is_tolerated() {
+        let f = |x: &[f64]| (x[0] - 3.0).powi(2);
+        let r = powell(f, &[0.0, 7.0], &PowellOptions::default()).unwrap();
+        assert!((r.x[0] - 3.0).abs() < 1e-6);
+        assert_eq!(r.x[1], 7.0);
+    }
+
+    #[test]
+    fn unbounded_objective_did_not_converge() {
+        let r = powell(|x| x[0] + x[1], &[0.0, 0.0], &PowellOptions::default());
+        assert_eq!(r, Err(NumalError::DidNotConverge));
+    }
+
+    #[test]
+    fn invalid_directions_are_rejected() {
+        let opts = PowellOptions {
+            directions: Some(vec![vec![1.0, 0.0], vec![0.0, 0.0]]),
+            ..Default::default()
+        };
+        let r = powell(rosenbrock, &[0.0, 0.0], &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
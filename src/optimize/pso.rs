@@ -0,0 +1,189 @@
+//! Particle swarm optimization with constriction (Clerc & Kennedy, 2002).
+//!
+//! Each particle moves with a velocity pulled towards its own best position
+//! and the best position of its neighbourhood, with random weights in every
+//! component, and damped by the constriction factor
+//! `chi = 2 / |2 - phi - sqrt(phi^2 - 4 phi)|`, where `phi` is the sum of the
+//! two acceleration coefficients and must exceed 4. This keeps the swarm
+//! from diverging without a velocity limit. The ring topology, in which a
+//! particle sees only its two neighbours, spreads information slowly and
+//! explores multimodal landscapes better than the global one.
+//!
+//! The initial guess is the starting position of one particle. The search
+//! stops once every particle is within [`Tolerance`] of the best one
+//! component-wise and in value, and returns the best position seen.
+//! Objective values that are NaN count as `+inf`.
+
+use super::{Bounds, Budgeted, Minimum, argmin, finite_box, population_converged};
+use crate::NumalError;
+use crate::core::rng::Rng;
+use crate::core::tolerance::Tolerance;
+
+/// Which particles inform each other
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Topology {
+    /// Every particle sees the best position of the whole swarm
+    Global,
+    /// Each particle sees itself and its two neighbours on a ring
+    #[default]
+    Ring,
+}
+
+/// Options for [`particle_swarm`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PsoOptions {
+    /// Number of particles, at least 2
+    pub particles: usize,
+    /// Acceleration towards the particle's own best position
+    pub cognitive: f64,
+    /// Acceleration towards the neighbourhood's best position
+    pub social: f64,
+    pub topology: Topology,
+    /// Swarm-spread tolerance
+    pub tol: Tolerance,
+    pub max_evaluations: usize,
+    /// Seed of the random number generator; equal seeds give equal runs
+    pub seed: u64,
+}
+
+impl Default for PsoOptions {
+    fn default() -> Self {
+        PsoOptions {
+            particles: 40,
+            cognitive: 2.05,
+            social: 2.05,
+            topology: Topology::Ring,
+            tol: Tolerance::Default,
+            max_evaluations: 100_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Minimizes `f` by particle swarm optimization from `x0` over `bounds`,
+/// which must be finite.
+///
+/// Particles leaving the box are stopped at its boundary. Returns
+/// [`NumalError::DidNotConverge`] if the evaluation budget runs out first.
+pub fn particle_swarm<F>(
+    f: F,
+    x0: &[f64],
+    bounds: Option<&Bounds>,
+    opts: &PsoOptions,
+) -> Result<Minimum, NumalError>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let (start, bounds) = finite_box(x0, bounds, "particle swarm optimization")?;
+    let n = start.len();
+    let np = opts.particles;
+    if np < 2 {
+        return Err(NumalError::InvalidInput(format!(
+            "swarm must have at least 2 particles, got {np}"
+        )));
+    }
+    let (c1, c2) = (opts.cognitive, opts.social);
+    let phi = c1 + c2;
+    if !(c1 > 0.0 && c2 > 0.0 && phi > 4.0 && phi.is_finite()) {
+        return Err(NumalError::InvalidInput(format!(
+            "acceleration coefficients must be positive with a sum above 4, got {c1} and {c2}"
+        )));
+    }
+    let chi = 2.0 / (2.0 - phi - (phi * phi - 4.0 * phi).sqrt()).abs();
+    let (lower, upper) = (bounds.lower(), bounds.upper());
+    let mut rng = Rng::new(opts.seed);
+    let mut objective = Budgeted::new(&f, opts.max_evaluations);
+    let mut xs: Vec<Vec<f64>> = Vec::with_capacity(np);
+    let mut vs: Vec<Vec<f64>> = Vec::with_capacity(np);
+    for _ in 0..np {
+        let x: Vec<f64> = (0..n).map(|j| rng.uniform_in(lower[j], upper[j])).collect();
+        let v = (0..n)
+            .map(|j| 0.5 * (rng.uniform_in(lower[j], upper[j]) - x[j]))
+            .collect();
+        xs.push(x);
+        vs.push(v);
+    }
+    xs[0] = start;
+    let mut values = Vec::with_capacity(np);
+    for x in &xs {
+        values.push(objective.eval(x).ok_or(NumalError::DidNotConverge)?);
+    }
+    let (mut personal, mut personal_values) = (xs.clone(), values.clone());
+    let mut iter = 0;
+    loop {
+        if population_converged(&xs, &values, argmin(&values), opts.tol) {
+            return Ok(objective.minimum(iter));
+        }
+        iter += 1;
+        let global = argmin(&personal_values);
+        for i in 0..np {
+            let informant = match opts.topology {
+                Topology::Global => global,
+                Topology::Ring => [(i + np - 1) % np, i, (i + 1) % np]
+                    .into_iter()
+                    .min_by(|&a, &b| personal_values[a].total_cmp(&personal_values[b]))
+                    .unwrap_or(i),
+            };
+            for j in 0..n {
+                let (r1, r2) = (rng.uniform(), rng.uniform());
+                let pull = c1 * r1 * (personal[i][j] - xs[i][j])
+                    + c2 * r2 * (personal[informant][j] - xs[i][j]);
+                vs[i][j] = chi * (vs[i][j] + pull);
+                let x = xs[i][j] + vs[i][j];
+                xs[i][j] = x.clamp(lower[j], upper[j]);
+                if xs[i][j] != x {
+                    vs[i][j] = 0.0;
+                }
+            }
+        }
+        for i in 0..np {
+            values[i] = objective.eval(&xs[i]).ok_or(NumalError::DidNotConverge)?;
+            if values[i] < personal_values[i] {
+                personal[i].clone_from(&xs[i]);
+                personal_values[i] = values[i];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::{cube, rastrigin, rosenbrock};
+
+    #[test]
+    fn both_topologies_minimize_rastrigin() {
+        for topology in [Topology::Global, Topology::Ring] {
+            let opts = PsoOptions {
+                topology,
+                seed: 2,
+                ..Default::default()
+            };
+            let r = particle_swarm(rastrigin, &[3.0; 2], Some(&cube(2, 5.12)), &opts).unwrap();
+            assert!(r.fx < 1e-10, "{topology:?}: {r:?}");
+        }
+    }
+
+    #[test]
+    fn minimizes_rosenbrock() {
+        let r = particle_swarm(
+            rosenbrock,
+            &[0.0; 2],
+            Some(&cube(2, 5.0)),
+            &PsoOptions::default(),
+        )
+        .unwrap();
+        assert!(r.x.iter().all(|v| (v - 1.0).abs() < 1e-5), "{r:?}");
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        let opts = PsoOptions {
+            cognitive: 1.5,
+            social: 1.5,
+            ..Default::default()
+        };
+        let r = particle_swarm(rastrigin, &[0.5; 2], Some(&cube(2, 1.0)), &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,762 @@
+//! Convex quadratic programming.
+//!
+//! Problems are `min x·Px/2 + q·x` subject to `l <= A x <= u`, with `P`
+//! symmetric positive semidefinite. Rows with `l_i = u_i` are equalities and
+//! an infinite bound leaves that side of a row free, so bounds on the
+//! variables are rows of the identity.
+//!
+//! The active-set method is the dual method of Goldfarb & Idnani (1983). It
+//! starts from the unconstrained minimizer and adds violated constraints one
+//! at a time, which ends in finitely many steps with the exact solution up
+//! to rounding, but needs `P` positive definite. The ADMM method follows
+//! OSQP (Stellato et al., 2020): one factorization of `P + sigma I + A'RA`
+//! serves many cheap iterations, the step size `rho` is adapted to balance
+//! the residuals, and the differences of successive iterates detect primal
+//! and dual infeasibility.
+//!
+//! Multipliers follow OSQP, so `P x + q + A'y = 0` at a solution, with
+//! `y_i >= 0` where the upper bound is active and `y_i <= 0` at the lower.
+
+use crate::NumalError;
+use crate::core::linalg::{Cholesky, Lu, Matrix, dot, norm};
+use crate::core::tolerance::Tolerance;
+
+// Scale of the step size of equality rows relative to inequality rows,
+// and the step size of rows without bounds.
+const RHO_EQ_SCALE: f64 = 1e3;
+const RHO_MIN: f64 = 1e-6;
+const RHO_MAX: f64 = 1e6;
+// Iterations between updates of the ADMM step size, and the change that
+// is worth a new factorization.
+const ADAPT_INTERVAL: usize = 25;
+const ADAPT_FACTOR: f64 = 5.0;
+
+/// A quadratic program `min x·Px/2 + q·x` subject to `l <= A x <= u`
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuadraticProgram {
+    pub p: Matrix,
+    pub q: Vec<f64>,
+    pub a: Matrix,
+    /// Lower bounds `l` of the rows; may be `-inf`
+    pub lower: Vec<f64>,
+    /// Upper bounds `u` of the rows; may be `+inf`
+    pub upper: Vec<f64>,
+}
+
+impl QuadraticProgram {
+    fn validate(&self) -> Result<(), NumalError> {
+        let n = self.q.len();
+        if n == 0 {
+            return Err(NumalError::InvalidInput(
+                "quadratic program has no variables".to_string(),
+            ));
+        }
+        if self.p.rows() != n || self.p.cols() != n {
+            return Err(NumalError::InvalidInput(format!(
+                "P is {}x{}, expected {n}x{n}",
+                self.p.rows(),
+                self.p.cols()
+            )));
+        }
+        let m = self.a.rows();
+        if self.a.cols() != n || self.lower.len() != m || self.upper.len() != m {
+            return Err(NumalError::InvalidInput(format!(
+                "A is {m}x{} with {} lower and {} upper bounds, expected {n} columns and {m} of each",
+                self.a.cols(),
+                self.lower.len(),
+                self.upper.len()
+            )));
+        }
+        if !(self.p.is_finite() && self.a.is_finite() && self.q.iter().all(|v| v.is_finite())) {
+            return Err(NumalError::InvalidInput(
+                "P, q and A must be finite".to_string(),
+            ));
+        }
+        let scale = 1e-12 * (1.0 + self.p.max_abs());
+        if (0..n).any(|i| (0..i).any(|j| (self.p[(i, j)] - self.p[(j, i)]).abs() > scale)) {
+            return Err(NumalError::InvalidInput("P must be symmetric".to_string()));
+        }
+        for (i, (&l, &u)) in self.lower.iter().zip(&self.upper).enumerate() {
+            if !(l <= u && l < f64::INFINITY && u > f64::NEG_INFINITY) {
+                return Err(NumalError::InvalidInput(format!(
+                    "row {i} has bounds [{l}, {u}]"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Algorithm used by [`quadprog`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QpMethod {
+    /// Dual active-set method of Goldfarb & Idnani; `P` must be positive
+    /// definite
+    #[default]
+    ActiveSet,
+    /// Operator splitting in the manner of OSQP
+    Admm,
+}
+
+/// Options for [`quadprog`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct QpOptions {
+    pub method: QpMethod,
+    /// Stopping tolerance of the ADMM method on the primal and dual
+    /// residuals, each relative to the terms it is made of
+    pub tol: Tolerance,
+    /// Maximum number of ADMM iterations, or of constraints added and
+    /// dropped by the active-set method
+    pub max_iter: usize,
+    /// Initial ADMM step size
+    pub rho: f64,
+    /// ADMM regularization of `P`
+    pub sigma: f64,
+    /// ADMM relaxation parameter, in (0, 2)
+    pub alpha: f64,
+    /// Tolerance of the ADMM infeasibility tests
+    pub infeasibility_tol: f64,
+}
+
+impl Default for QpOptions {
+    fn default() -> Self {
+        QpOptions {
+            method: QpMethod::default(),
+            tol: Tolerance::Default,
+            max_iter: 4_000,
+            rho: 0.1,
+            sigma: 1e-6,
+            alpha: 1.6,
+            infeasibility_tol: 1e-5,
+        }
+    }
+}
+
+/// Solution of a quadratic program
+#[derive(Clone, Debug, PartialEq)]
+pub struct QpSolution {
+    pub x: Vec<f64>,
+    /// Multipliers of the rows of `A`, with `P x + q + A'y = 0`
+    pub y: Vec<f64>,
+    /// Objective value `x·Px/2 + q·x`
+    pub objective: f64,
+    /// Largest violation of `l <= A x <= u`
+    pub primal_residual: f64,
+    /// Largest component of `P x + q + A'y`
+    pub dual_residual: f64,
+    /// Number of ADMM iterations, or of constraints added and dropped
+    pub iterations: usize,
+}
+
+/// Solves the quadratic program `qp`, warm started from `start` if given.
+///
+/// Returns [`NumalError::Infeasible`] when no point satisfies the
+/// constraints, with a certificate `y` of the rows such that `A'y = 0` and
+/// `u·max(y, 0) + l·min(y, 0) < 0`. The ADMM method returns
+/// [`NumalError::Unbounded`] when the objective decreases without bound,
+/// with a direction `d` such that `P d = 0`, `q·d < 0` and `A d` respects
+/// the finite sides of every row.
+pub fn quadprog(
+    qp: &QuadraticProgram,
+    start: Option<&QpSolution>,
+    opts: &QpOptions,
+) -> Result<QpSolution, NumalError> {
+    qp.validate()?;
+    if let Some(s) = start {
+        if s.x.len() != qp.q.len() || s.y.len() != qp.a.rows() {
+            return Err(NumalError::InvalidInput(format!(
+                "warm start has {} variables and {} multipliers, expected {} and {}",
+                s.x.len(),
+                s.y.len(),
+                qp.q.len(),
+                qp.a.rows()
+            )));
+        }
+        if !s.x.iter().chain(&s.y).all(|v| v.is_finite()) {
+            return Err(NumalError::InvalidInput(
+                "warm start must be finite".to_string(),
+            ));
+        }
+    }
+    match opts.method {
+        QpMethod::ActiveSet => active_set(qp, start, opts),
+        QpMethod::Admm => admm(qp, start, opts),
+    }
+}
+
+fn inf_norm(v: &[f64]) -> f64 {
+    v.iter().fold(0.0, |m: f64, x| m.max(x.abs()))
+}
+
+// Scales a certificate to unit infinity norm.
+fn unit(mut v: Vec<f64>) -> Vec<f64> {
+    let scale = inf_norm(&v);
+    if scale > 0.0 {
+        v.iter_mut().for_each(|x| *x /= scale);
+    }
+    v
+}
+
+fn solution(qp: &QuadraticProgram, x: Vec<f64>, y: Vec<f64>, iterations: usize) -> QpSolution {
+    let px = qp.p.mul_vec(&x);
+    let ax = qp.a.mul_vec(&x);
+    let primal_residual = ax
+        .iter()
+        .zip(qp.lower.iter().zip(&qp.upper))
+        .map(|(v, (l, u))| (l - v).max(v - u).max(0.0))
+        .fold(0.0, f64::max);
+    let dual: Vec<f64> = px
+        .iter()
+        .zip(&qp.q)
+        .zip(qp.a.tr_mul_vec(&y))
+        .map(|((px, q), aty)| px + q + aty)
+        .collect();
+    QpSolution {
+        objective: 0.5 * dot(&x, &px) + dot(&qp.q, &x),
+        x,
+        y,
+        primal_residual,
+        dual_residual: inf_norm(&dual),
+        iterations,
+    }
+}
+
+// Outcome of the Goldfarb-Idnani method.
+pub(crate) enum ActiveSet {
+    Solved {
+        x: Vec<f64>,
+        multipliers: Vec<f64>,
+        iterations: usize,
+    },
+    // Multipliers psi, nonnegative on the inequalities, with
+    // sum(psi_j n_j) = 0 and psi·b > 0
+    Infeasible(Vec<f64>),
+}
+
+// Dual active-set method of Goldfarb & Idnani (1983) for the strictly
+// convex quadratic program
+//
+//   min x·Gx/2 + a·x  subject to  n_j·x = b_j (j < meq),  n_j·x >= b_j,
+//
+// with `chol` the factorization of G and the normals n_j as the rows of
+// `normals`. Starting from the unconstrained minimizer, a violated
+// constraint is added to the active set, dropping constraints whose
+// multipliers would turn negative. Violated inequalities listed in `hint`
+// are added before the most violated one. Returns `None` if the method
+// breaks down or runs out of its `max_iter` additions and drops.
+pub(crate) fn goldfarb_idnani(
+    chol: &Cholesky,
+    a: &[f64],
+    normals: &Matrix,
+    b: &[f64],
+    meq: usize,
+    hint: &[usize],
+    max_iter: usize,
+) -> Option<ActiveSet> {
+    let m = b.len();
+    let mut x: Vec<f64> = chol.solve(a).into_iter().map(|v| -v).collect();
+    // Active constraints with the sign applied to their normal (equalities
+    // may enter from either side) and their multipliers
+    let mut active: Vec<(usize, f64)> = Vec::new();
+    let mut u: Vec<f64> = Vec::new();
+    let mut skipped = vec![false; meq];
+    let mut iterations = 0;
+    let slack = |x: &[f64], j: usize| dot(normals.row(j), x) - b[j];
+    let tolerance = |x: &[f64], j: usize| {
+        1e3 * f64::EPSILON * (b[j].abs() + norm(normals.row(j)) * norm(x) + 1.0)
+    };
+
+    while iterations <= max_iter {
+        let inactive = |j: usize| !active.iter().any(|&(k, _)| k == j);
+        let violated = |x: &[f64], j: usize| slack(x, j) < -tolerance(x, j);
+        // An inactive equality, else a hinted or the most violated inequality
+        let pick = (0..meq)
+            .find(|&j| !skipped[j] && inactive(j))
+            .map(|j| (j, if slack(&x, j) > 0.0 { -1.0 } else { 1.0 }))
+            .or_else(|| {
+                hint.iter()
+                    .copied()
+                    .find(|&j| j >= meq && inactive(j) && violated(&x, j))
+                    .map(|j| (j, 1.0))
+            })
+            .or_else(|| {
+                (meq..m)
+                    .filter(|&j| inactive(j))
+                    .map(|j| {
+                        (
+                            j,
+                            slack(&x, j) / norm(normals.row(j)).max(f64::MIN_POSITIVE),
+                        )
+                    })
+                    .filter(|&(j, s)| violated(&x, j) && s.is_finite())
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(j, _)| (j, 1.0))
+            });
+        let Some((j, sign)) = pick else {
+            let mut multipliers = vec![0.0; m];
+            for (&(k, sk), uk) in active.iter().zip(&u) {
+                multipliers[k] = sk * uk;
+            }
+            return Some(ActiveSet::Solved {
+                x,
+                multipliers,
+                iterations,
+            });
+        };
+        let np: Vec<f64> = normals.row(j).iter().map(|v| sign * v).collect();
+        let mut up = 0.0;
+        loop {
+            // z = H np is the primal step direction and r = N* np gives the
+            // change of the active multipliers, from the KKT system of the
+            // active set
+            let ginv_n: Vec<Vec<f64>> = active
+                .iter()
+                .map(|&(k, sk)| {
+                    let nk: Vec<f64> = normals.row(k).iter().map(|v| sk * v).collect();
+                    chol.solve(&nk)
+                })
+                .collect();
+            let ginv_np = chol.solve(&np);
+            let q = active.len();
+            let r = if q > 0 {
+                let s = Matrix::from_fn(q, q, |i, l| {
+                    let (k, sk) = active[i];
+                    sk * dot(normals.row(k), &ginv_n[l])
+                });
+                let rhs: Vec<f64> = active
+                    .iter()
+                    .map(|&(k, sk)| sk * dot(normals.row(k), &ginv_np))
+                    .collect();
+                Lu::new(&s)?.solve(&rhs)
+            } else {
+                Vec::new()
+            };
+            let mut z = ginv_np.clone();
+            for (gk, rk) in ginv_n.iter().zip(&r) {
+                z.iter_mut().zip(gk).for_each(|(zi, gi)| *zi -= rk * gi);
+            }
+            let zn = dot(&z, &np);
+            let dependent = zn <= 1e-12 * dot(&ginv_np, &np);
+
+            // Partial step: largest step keeping active inequality
+            // multipliers nonnegative
+            let mut t1 = f64::INFINITY;
+            let mut drop = None;
+            for (i, (&(k, _), &rk)) in active.iter().zip(&r).enumerate() {
+                if k >= meq && rk > 0.0 && u[i] / rk < t1 {
+                    t1 = u[i] / rk;
+                    drop = Some(i);
+                }
+            }
+            // Full step: satisfy constraint j exactly
+            let sp = sign * slack(&x, j);
+            let t2 = if dependent { f64::INFINITY } else { -sp / zn };
+            let t = t1.min(t2);
+            if t == f64::INFINITY {
+                if j < meq && sp.abs() <= tolerance(&x, j) {
+                    // A redundant equality already satisfied
+                    skipped[j] = true;
+                    break;
+                }
+                // np = sum(r_k n_k) with no r_k > 0 on the inequalities:
+                // the new constraint contradicts the active ones
+                let mut psi = vec![0.0; m];
+                psi[j] = sign;
+                for (&(k, sk), rk) in active.iter().zip(&r) {
+                    psi[k] -= sk * rk;
+                }
+                return Some(ActiveSet::Infeasible(psi));
+            }
+            if !dependent {
+                x.iter_mut().zip(&z).for_each(|(xi, zi)| *xi += t * zi);
+            }
+            u.iter_mut().zip(&r).for_each(|(ui, ri)| *ui -= t * ri);
+            up += t;
+            iterations += 1;
+            if t2 <= t1 {
+                active.push((j, sign));
+                u.push(up);
+                break;
+            }
+            let i = drop.expect("partial step has a constraint to drop");
+            active.remove(i);
+            u.remove(i);
+        }
+    }
+    None
+}
+
+fn active_set(
+    qp: &QuadraticProgram,
+    start: Option<&QpSolution>,
+    opts: &QpOptions,
+) -> Result<QpSolution, NumalError> {
+    let chol = Cholesky::new(&qp.p, 0.0).ok_or_else(|| {
+        NumalError::InvalidInput("the active-set method needs P positive definite".to_string())
+    })?;
+    let (m, n) = (qp.a.rows(), qp.q.len());
+    // Each row becomes constraints s a·x >= b: an equality, or the finite
+    // ones among its lower (s = 1) and upper (s = -1) sides
+    let mut sides: Vec<(usize, f64)> = (0..m)
+        .filter(|&i| qp.lower[i] == qp.upper[i])
+        .map(|i| (i, 1.0))
+        .collect();
+    let meq = sides.len();
+    for i in (0..m).filter(|&i| qp.lower[i] != qp.upper[i]) {
+        if qp.lower[i].is_finite() {
+            sides.push((i, 1.0));
+        }
+        if qp.upper[i].is_finite() {
+            sides.push((i, -1.0));
+        }
+    }
+    let normals = Matrix::from_fn(sides.len(), n, |k, j| sides[k].1 * qp.a[(sides[k].0, j)]);
+    let rhs: Vec<f64> = sides
+        .iter()
+        .map(|&(i, s)| if s > 0.0 { qp.lower[i] } else { -qp.upper[i] })
+        .collect();
+    // Sides active at the warm start, as told by the sign of their multiplier
+    let hint: Vec<usize> = start
+        .map(|s| {
+            (meq..sides.len())
+                .filter(|&k| sides[k].1 * s.y[sides[k].0] < 0.0)
+                .collect()
+        })
+        .unwrap_or_default();
+    // Row multipliers in the convention of OSQP, y_i = -sum(s v_k) over the
+    // sides k of row i
+    let to_rows = |v: &[f64]| {
+        let mut y = vec![0.0; m];
+        for (&(i, s), vk) in sides.iter().zip(v) {
+            y[i] -= s * vk;
+        }
+        y
+    };
+    match goldfarb_idnani(&chol, &qp.q, &normals, &rhs, meq, &hint, opts.max_iter) {
+        Some(ActiveSet::Solved {
+            x,
+            multipliers,
+            iterations,
+        }) => Ok(solution(qp, x, to_rows(&multipliers), iterations)),
+        Some(ActiveSet::Infeasible(psi)) => Err(NumalError::Infeasible {
+            certificate: unit(to_rows(&psi)),
+        }),
+        None => Err(NumalError::DidNotConverge),
+    }
+}
+
+// Whether the change `dy` of the ADMM multipliers certifies that the
+// constraints are inconsistent. Components pushing against an infinite
+// bound are dropped first, as they cannot belong to a certificate.
+fn primal_infeasible(qp: &QuadraticProgram, dy: &mut [f64], eps: f64) -> bool {
+    for (d, (l, u)) in dy.iter_mut().zip(qp.lower.iter().zip(&qp.upper)) {
+        if (*d > 0.0 && *u == f64::INFINITY) || (*d < 0.0 && *l == f64::NEG_INFINITY) {
+            *d = 0.0;
+        }
+    }
+    let scale = eps * inf_norm(dy);
+    let support: f64 = dy
+        .iter()
+        .zip(qp.lower.iter().zip(&qp.upper))
+        .map(|(&d, (l, u))| {
+            if d > 0.0 {
+                u * d
+            } else if d < 0.0 {
+                l * d
+            } else {
+                0.0
+            }
+        })
+        .sum();
+    inf_norm(&qp.a.tr_mul_vec(dy)) <= scale && support < -scale
+}
+
+// Whether the change `dx` of the ADMM iterate is a direction along which
+// the objective decreases without bound.
+fn dual_infeasible(qp: &QuadraticProgram, dx: &[f64], eps: f64) -> bool {
+    let scale = eps * inf_norm(dx);
+    let rows_ok =
+        qp.a.mul_vec(dx)
+            .iter()
+            .zip(qp.lower.iter().zip(&qp.upper))
+            .all(|(&v, (l, u))| {
+                (*u == f64::INFINITY || v <= scale) && (*l == f64::NEG_INFINITY || v >= -scale)
+            });
+    rows_ok && inf_norm(&qp.p.mul_vec(dx)) <= scale && dot(&qp.q, dx) < -scale
+}
+
+fn admm(
+    qp: &QuadraticProgram,
+    start: Option<&QpSolution>,
+    opts: &QpOptions,
+) -> Result<QpSolution, NumalError> {
+    if !(opts.rho > 0.0 && opts.sigma > 0.0 && opts.alpha > 0.0 && opts.alpha < 2.0) {
+        return Err(NumalError::InvalidInput(format!(
+            "rho and sigma must be positive and alpha in (0, 2), got {}, {} and {}",
+            opts.rho, opts.sigma, opts.alpha
+        )));
+    }
+    let (m, n) = (qp.a.rows(), qp.q.len());
+    let alpha = opts.alpha;
+    let project = |v: &mut [f64]| {
+        for (vi, (l, u)) in v.iter_mut().zip(qp.lower.iter().zip(&qp.upper)) {
+            *vi = vi.clamp(*l, *u);
+        }
+    };
+    // Per-row step sizes: equalities are stiffer and free rows nearly
+    // ignored
+    let row_rho = |rho: f64| -> Vec<f64> {
+        qp.lower
+            .iter()
+            .zip(&qp.upper)
+            .map(|(&l, &u)| {
+                if l == u {
+                    RHO_EQ_SCALE * rho
+                } else if l == f64::NEG_INFINITY && u == f64::INFINITY {
+                    RHO_MIN
+                } else {
+                    rho
+                }
+            })
+            .collect()
+    };
+    let factor = |r: &[f64]| -> Result<Cholesky, NumalError> {
+        let mut k = qp.p.clone();
+        for i in 0..n {
+            k[(i, i)] += opts.sigma;
+        }
+        for (i, &ri) in r.iter().enumerate() {
+            k.rank1_update(ri, qp.a.row(i), qp.a.row(i));
+        }
+        Cholesky::new(&k, 0.0)
+            .ok_or_else(|| NumalError::InvalidInput("P must be positive semidefinite".to_string()))
+    };
+
+    let mut x = start.map_or_else(|| vec![0.0; n], |s| s.x.clone());
+    let mut y = start.map_or_else(|| vec![0.0; m], |s| s.y.clone());
+    let mut z = qp.a.mul_vec(&x);
+    project(&mut z);
+    let mut rho = opts.rho;
+    let mut r = row_rho(rho);
+    let mut chol = factor(&r)?;
+    for iter in 1..=opts.max_iter {
+        // x~ solves the equality-constrained subproblem, after which the
+        // relaxed z is projected onto [l, u] and y takes a dual ascent step
+        let shifted: Vec<f64> = (0..m).map(|i| r[i] * z[i] - y[i]).collect();
+        let rhs: Vec<f64> =
+            qp.a.tr_mul_vec(&shifted)
+                .iter()
+                .zip(x.iter().zip(&qp.q))
+                .map(|(ats, (x, q))| opts.sigma * x - q + ats)
+                .collect();
+        let xt = chol.solve(&rhs);
+        let zt = qp.a.mul_vec(&xt);
+        let x_new: Vec<f64> = xt
+            .iter()
+            .zip(&x)
+            .map(|(xt, x)| alpha * xt + (1.0 - alpha) * x)
+            .collect();
+        let z_relax: Vec<f64> = zt
+            .iter()
+            .zip(&z)
+            .map(|(zt, z)| alpha * zt + (1.0 - alpha) * z)
+            .collect();
+        let mut z_new: Vec<f64> = (0..m).map(|i| z_relax[i] + y[i] / r[i]).collect();
+        project(&mut z_new);
+        let y_new: Vec<f64> = (0..m)
+            .map(|i| y[i] + r[i] * (z_relax[i] - z_new[i]))
+            .collect();
+        let dx: Vec<f64> = x_new.iter().zip(&x).map(|(a, b)| a - b).collect();
+        let mut dy: Vec<f64> = y_new.iter().zip(&y).map(|(a, b)| a - b).collect();
+        (x, y, z) = (x_new, y_new, z_new);
+
+        let ax = qp.a.mul_vec(&x);
+        let px = qp.p.mul_vec(&x);
+        let aty = qp.a.tr_mul_vec(&y);
+        let r_prim = inf_norm(&ax.iter().zip(&z).map(|(a, b)| a - b).collect::<Vec<_>>());
+        let r_dual = inf_norm(&(0..n).map(|i| px[i] + qp.q[i] + aty[i]).collect::<Vec<_>>());
+        let prim_scale = inf_norm(&ax).max(inf_norm(&z));
+        let dual_scale = inf_norm(&px).max(inf_norm(&aty)).max(inf_norm(&qp.q));
+        if r_prim <= opts.tol.eps_abs() + opts.tol.eps_rel() * prim_scale
+            && r_dual <= opts.tol.eps_abs() + opts.tol.eps_rel() * dual_scale
+        {
+            return Ok(solution(qp, x, y, iter));
+        }
+        if primal_infeasible(qp, &mut dy, opts.infeasibility_tol) {
+            return Err(NumalError::Infeasible {
+                certificate: unit(dy),
+            });
+        }
+        if dual_infeasible(qp, &dx, opts.infeasibility_tol) {
+            return Err(NumalError::Unbounded {
+                direction: unit(dx),
+            });
+        }
+        if iter % ADAPT_INTERVAL == 0 {
+            // Balance the relative residuals, refactoring only when the
+            // step size changes markedly
+            let ratio = (r_prim / prim_scale.max(f64::MIN_POSITIVE))
+                / (r_dual / dual_scale.max(f64::MIN_POSITIVE));
+            let next = (rho * ratio.sqrt()).clamp(RHO_MIN, RHO_MAX);
+            if next.is_finite() && (next > ADAPT_FACTOR * rho || next < rho / ADAPT_FACTOR) {
+                rho = next;
+                r = row_rho(rho);
+                chol = factor(&r)?;
+            }
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn both_methods() -> [QpOptions; 2] {
+        [
+            QpOptions::default(),
+            QpOptions {
+                method: QpMethod::Admm,
+                ..Default::default()
+            },
+        ]
+    }
+
+    // The example of the OSQP documentation: x = (0.3, 0.7) with the first
+    // row an equality and the upper bound of the third active
+    fn osqp_example() -> QuadraticProgram {
+        QuadraticProgram {
+            p: Matrix::from_row_slice(2, 2, &[4.0, 1.0, 1.0, 2.0]),
+            q: vec![1.0, 1.0],
+            a: Matrix::from_row_slice(3, 2, &[1.0, 1.0, 1.0, 0.0, 0.0, 1.0]),
+            lower: vec![1.0, 0.0, 0.0],
+            upper: vec![1.0, 0.7, 0.7],
+        }
+    }
+
+    #[test]
+    fn solves_osqp_example() {
+        let qp = osqp_example();
+        for opts in both_methods() {
+            let r = quadprog(&qp, None, &opts).unwrap();
+            for (x, e) in r.x.iter().zip([0.3, 0.7]) {
+                assert!((x - e).abs() < 1e-5, "{r:?}");
+            }
+            for (y, e) in r.y.iter().zip([-2.9, 0.0, 0.2]) {
+                assert!((y - e).abs() < 1e-4, "{r:?}");
+            }
+            assert!((r.objective - 1.88).abs() < 1e-5);
+            assert!(
+                r.primal_residual <= 1e-5 && r.dual_residual <= 1e-5,
+                "{r:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn warm_start_saves_iterations() {
+        // x0 + x1 <= 1.5 is the most violated constraint at the unconstrained
+        // minimizer (4, 3) but inactive at the solution (1, 0), so that a
+        // cold start of the active-set method adds it and drops it again
+        let qp = QuadraticProgram {
+            p: Matrix::identity(2),
+            q: vec![-4.0, -3.0],
+            a: Matrix::from_row_slice(3, 2, &[1.0, 1.0, 0.0, 1.0, 1.0, 0.0]),
+            lower: vec![f64::NEG_INFINITY; 3],
+            upper: vec![1.5, 0.0, 1.0],
+        };
+        for opts in both_methods() {
+            let cold = quadprog(&qp, None, &opts).unwrap();
+            let mut perturbed = qp.clone();
+            perturbed.q = vec![-4.01, -2.99];
+            let warm = quadprog(&perturbed, Some(&cold), &opts).unwrap();
+            let fresh = quadprog(&perturbed, None, &opts).unwrap();
+            assert!(
+                warm.iterations < fresh.iterations,
+                "{:?}: {} vs {}",
+                opts.method,
+                warm.iterations,
+                fresh.iterations
+            );
+            for (a, b) in warm.x.iter().zip(&fresh.x) {
+                assert!((a - b).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn infeasible_constraints_have_certificate() {
+        // x >= 1 and x <= 0
+        let qp = QuadraticProgram {
+            p: Matrix::identity(1),
+            q: vec![0.0],
+            a: Matrix::from_row_slice(2, 1, &[1.0, 1.0]),
+            lower: vec![1.0, f64::NEG_INFINITY],
+            upper: vec![f64::INFINITY, 0.0],
+        };
+        for opts in both_methods() {
+            match quadprog(&qp, None, &opts) {
+                Err(NumalError::Infeasible { certificate: y }) => {
+                    assert!(inf_norm(&qp.a.tr_mul_vec(&y)) <= 1e-4, "{y:?}");
+                    assert!(y[0] < 0.0 && y[1] > 0.0, "{y:?}");
+                }
+                r => panic!("{r:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn unbounded_objective_is_reported() {
+        // min -x0 subject to x0 - x1 <= 1 and x0 >= 0, with P = 0
+        let qp = QuadraticProgram {
+            p: Matrix::zeros(2, 2),
+            q: vec![-1.0, 0.0],
+            a: Matrix::from_row_slice(2, 2, &[1.0, -1.0, 1.0, 0.0]),
+            lower: vec![f64::NEG_INFINITY, 0.0],
+            upper: vec![1.0, f64::INFINITY],
+        };
+        let opts = QpOptions {
+            method: QpMethod::Admm,
+            ..Default::default()
+        };
+        match quadprog(&qp, None, &opts) {
+            Err(NumalError::Unbounded { direction: d }) => {
+                assert!(d[0] > 0.0 && d[0] - d[1] <= 1e-4, "{d:?}");
+            }
+            r => panic!("{r:?}"),
+        }
+        let r = quadprog(&qp, None, &QpOptions::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn goldfarb_idnani_multipliers() {
+        // min x^2 + y^2 subject to x + y = 1 and x >= 0.75
+        let chol = Cholesky::new(&Matrix::identity(2), 0.0).unwrap();
+        let normals = Matrix::from_row_slice(2, 2, &[1.0, 1.0, 1.0, 0.0]);
+        let Some(ActiveSet::Solved {
+            x, multipliers: u, ..
+        }) = goldfarb_idnani(&chol, &[0.0, 0.0], &normals, &[1.0, 0.75], 1, &[], 100)
+        else {
+            panic!("no solution");
+        };
+        assert!((x[0] - 0.75).abs() < 1e-14 && (x[1] - 0.25).abs() < 1e-14);
+        assert!((u[0] - 0.25).abs() < 1e-14 && (u[1] - 0.5).abs() < 1e-14);
+        // x >= 1 and -x >= 0 cannot both hold: psi = (1, 1)
+        let normals = Matrix::from_row_slice(2, 2, &[1.0, 0.0, -1.0, 0.0]);
+        let Some(ActiveSet::Infeasible(psi)) =
+            goldfarb_idnani(&chol, &[0.0, 0.0], &normals, &[1.0, 0.0], 0, &[], 100)
+        else {
+            panic!("infeasibility not detected");
+        };
+        assert!((psi[0] - 1.0).abs() < 1e-14 && (psi[1] - 1.0).abs() < 1e-14);
+    }
+
+    #[test]
+    fn invalid_bounds_are_rejected() {
+        let mut qp = osqp_example();
+        qp.lower[1] = 1.0;
+        let r = quadprog(&qp, None, &QpOptions::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
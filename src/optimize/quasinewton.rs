@@ -0,0 +1,382 @@
+//! Quasi-Newton methods for unconstrained minimization: dense BFGS and
+//! limited-memory L-BFGS.
+//!
+//! Both methods build a curvature model from successive gradient
+//! differences and take steps chosen by a [`LineSearch`]. Curvature pairs
+//! with `y·s <= 0` are skipped so the model stays positive definite. A
+//! steepest descent step, taken before the model has been scaled, says
+//! little about the distance to the minimum on badly scaled problems, so
+//! only the gradient test may end the iteration after one.
+
+use super::linesearch::LineSearch;
+use super::{
+    GradientMinimum, Termination, gradient_minimum, gradient_termination, initial_gradient,
+};
+use crate::NumalError;
+use crate::core::linalg::{Lu, Matrix, dot, norm};
+use crate::core::tolerance::Tolerance;
+use std::collections::VecDeque;
+
+/// Options for [`bfgs`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BfgsOptions {
+    /// Gradient, step and function-change tolerance
+    pub tol: Tolerance,
+    pub max_iter: usize,
+    pub line_search: LineSearch,
+}
+
+impl Default for BfgsOptions {
+    fn default() -> Self {
+        BfgsOptions {
+            tol: Tolerance::Default,
+            max_iter: 1_000,
+            line_search: LineSearch::default(),
+        }
+    }
+}
+
+/// Options for [`lbfgs`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LbfgsOptions {
+    /// Gradient, step and function-change tolerance
+    pub tol: Tolerance,
+    pub max_iter: usize,
+    /// Number of curvature pairs kept
+    pub history: usize,
+    pub line_search: LineSearch,
+}
+
+impl Default for LbfgsOptions {
+    fn default() -> Self {
+        LbfgsOptions {
+            tol: Tolerance::Default,
+            max_iter: 1_000,
+            history: 10,
+            line_search: LineSearch::default(),
+        }
+    }
+}
+
+// Initial trial step: a unit step, shortened on the first iteration when no
+// curvature information is available yet.
+fn initial_step(first: bool, g: &[f64]) -> f64 {
+    if first { (1.0 / norm(g)).min(1.0) } else { 1.0 }
+}
+
+// Tests for convergence after a step, accepting only a small gradient if
+// the step was an unscaled steepest descent step.
+fn termination(
+    g: &[f64],
+    x_old: &[f64],
+    x: &[f64],
+    f_old: f64,
+    f: f64,
+    tol: Tolerance,
+    scaled: bool,
+) -> Option<Termination> {
+    gradient_termination(g, x_old, x, f_old, f, tol)
+        .filter(|&r| scaled || r == Termination::GradientNorm)
+}
+
+fn usable_pair(s: &[f64], y: &[f64]) -> bool {
+    dot(s, y) > f64::EPSILON * norm(s) * norm(y)
+}
+
+/// Minimizes `fg` with the BFGS method.
+///
+/// `fg(x, g)` returns `f(x)` and writes the gradient into `g`. The result
+/// carries the final Hessian approximation, the inverse of the maintained
+/// inverse-Hessian estimate.
+pub fn bfgs<F>(fg: F, x0: &[f64], opts: &BfgsOptions) -> Result<GradientMinimum, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    let (mut f, mut g) = initial_gradient(&fg, x0)?;
+    let n = x0.len();
+    let mut x = x0.to_vec();
+    let mut evals = 1;
+    let mut h = Matrix::identity(n);
+    let mut scaled = false;
+    let hessian = |h: &Matrix| Lu::new(h).map(|lu| lu.inverse());
+    if g.iter().all(|v| v.abs() <= opts.tol.eps_abs()) {
+        let b = hessian(&h);
+        return Ok(gradient_minimum(
+            x,
+            f,
+            g,
+            0,
+            evals,
+            Termination::GradientNorm,
+            b,
+        ));
+    }
+    for iter in 1..=opts.max_iter {
+        let mut d: Vec<f64> = h.mul_vec(&g).into_iter().map(|v| -v).collect();
+        if dot(&d, &g) >= 0.0 {
+            h = Matrix::identity(n);
+            scaled = false;
+            d = g.iter().map(|v| -v).collect();
+        }
+        let ls = opts
+            .line_search
+            .search(&fg, &x, f, &g, &d, initial_step(!scaled, &g))?;
+        evals += ls.evaluations;
+        let s: Vec<f64> = ls.x.iter().zip(&x).map(|(a, b)| a - b).collect();
+        let y: Vec<f64> = ls.grad.iter().zip(&g).map(|(a, b)| a - b).collect();
+        let (x_old, f_old) = (std::mem::replace(&mut x, ls.x), f);
+        f = ls.fx;
+        g = ls.grad;
+        if let Some(reason) = termination(&g, &x_old, &x, f_old, f, opts.tol, scaled) {
+            return Ok(gradient_minimum(x, f, g, iter, evals, reason, hessian(&h)));
+        }
+        if usable_pair(&s, &y) {
+            let ys = dot(&y, &s);
+            if !scaled {
+                // Nocedal & Wright (6.20): rescale the identity before the
+                // first update
+                h = Matrix::identity(n);
+                for i in 0..n {
+                    h[(i, i)] = ys / dot(&y, &y);
+                }
+                scaled = true;
+            }
+            let rho = 1.0 / ys;
+            let hy = h.mul_vec(&y);
+            let yhy = dot(&y, &hy);
+            h.rank1_update(-rho, &hy, &s);
+            h.rank1_update(-rho, &s, &hy);
+            h.rank1_update(rho + rho * rho * yhy, &s, &s);
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+/// Minimizes `fg` with the limited-memory BFGS method.
+///
+/// Only the last `history` curvature pairs are stored, and the search
+/// direction is formed by the two-loop recursion in O(n * history) work.
+pub fn lbfgs<F>(fg: F, x0: &[f64], opts: &LbfgsOptions) -> Result<GradientMinimum, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    if opts.history == 0 {
+        return Err(NumalError::InvalidInput(
+            "L-BFGS history length must be at least 1".to_string(),
+        ));
+    }
+    let (mut f, mut g) = initial_gradient(&fg, x0)?;
+    let mut x = x0.to_vec();
+    let mut evals = 1;
+    let mut pairs: VecDeque<(Vec<f64>, Vec<f64>, f64)> = VecDeque::with_capacity(opts.history);
+    if g.iter().all(|v| v.abs() <= opts.tol.eps_abs()) {
+        return Ok(gradient_minimum(
+            x,
+            f,
+            g,
+            0,
+            evals,
+            Termination::GradientNorm,
+            None,
+        ));
+    }
+    for iter in 1..=opts.max_iter {
+        let mut d = two_loop(&pairs, &g);
+        if dot(&d, &g) >= 0.0 {
+            pairs.clear();
+            d = g.iter().map(|v| -v).collect();
+        }
+        let scaled = !pairs.is_empty();
+        let ls = opts
+            .line_search
+            .search(&fg, &x, f, &g, &d, initial_step(!scaled, &g))?;
+        evals += ls.evaluations;
+        let s: Vec<f64> = ls.x.iter().zip(&x).map(|(a, b)| a - b).collect();
+        let y: Vec<f64> = ls.grad.iter().zip(&g).map(|(a, b)| a - b).collect();
+        let (x_old, f_old) = (std::mem::replace(&mut x, ls.x), f);
+        f = ls.fx;
+        g = ls.grad;
+        if let Some(reason) = termination(&g, &x_old, &x, f_old, f, opts.tol, scaled) {
+            return Ok(gradient_minimum(x, f, g, iter, evals, reason, None));
+        }
+        if usable_pair(&s, &y) {
+            if pairs.len() == opts.history {
+                pairs.pop_front();
+            }
+            let rho = 1.0 / dot(&y, &s);
+            pairs.push_back((s, y, rho));
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+// Two-loop recursion: returns -H g for the implicit L-BFGS inverse Hessian.
+fn two_loop(pairs: &VecDeque<(Vec<f64>, Vec<f64>, f64)>, g: &[f64]) -> Vec<f64> {
+    let mut q: Vec<f64> = g.to_vec();
+    let mut alphas = Vec::with_capacity(pairs.len());
+    for (s, y, rho) in pairs.iter().rev() {
+        let a = rho * dot(s, &q);
+        for (qi, yi) in q.iter_mut().zip(y) {
+            *qi -= a * yi;
+        }
+        alphas.push(a);
+    }
+    if let Some((s, y, _)) = pairs.back() {
+        let gamma = dot(s, y) / dot(y, y);
+        q.iter_mut().for_each(|v| *v *= gamma);
+    }
+    for ((s, y, rho), a) in pairs.iter().zip(alphas.into_iter().rev()) {
+        let b = rho * dot(y, &q);
+        for (qi, si) in q.iter_mut().zip(s) {
+            *qi += (a - b) * si;
+        }
+    }
+    q.iter().map(|v| -v).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::rosenbrock_grad;
+
+    #[test]
+    fn bfgs_minimizes_rosenbrock() {
+        let opts = BfgsOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = bfgs(rosenbrock_grad, &[-1.2, 1.0], &opts).unwrap();
+        assert!(
+            (r.x[0] - 1.0).abs() < 1e-6 && (r.x[1] - 1.0).abs() < 1e-6,
+            "{r:?}"
+        );
+        assert!(r.grad_norm < 1e-5);
+    }
+
+    #[test]
+    fn bfgs_hessian_approximates_true_hessian() {
+        // f = x^T A x / 2 - b^T x with A = [[4, 1], [1, 3]]
+        let fg = |x: &[f64], g: &mut [f64]| {
+            g[0] = 4.0 * x[0] + x[1] - 1.0;
+            g[1] = x[0] + 3.0 * x[1] - 2.0;
+            0.5 * (4.0 * x[0] * x[0] + 2.0 * x[0] * x[1] + 3.0 * x[1] * x[1]) - x[0] - 2.0 * x[1]
+        };
+        let opts = BfgsOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = bfgs(fg, &[5.0, -5.0], &opts).unwrap();
+        assert_eq!(r.termination, Termination::GradientNorm);
+        let h = r.hessian.unwrap();
+        for (a, e) in h.as_slice().iter().zip([4.0, 1.0, 1.0, 3.0]) {
+            assert!((a - e).abs() < 1e-3, "{h:?}");
+        }
+    }
+
+    #[test]
+    fn lbfgs_minimizes_extended_rosenbrock() {
+        let x0: Vec<f64> = (0..100)
+            .map(|i| if i % 2 == 0 { -1.2 } else { 1.0 })
+            .collect();
+        let opts = LbfgsOptions {
+            tol: Tolerance::Strict,
+            max_iter: 5_000,
+            ..Default::default()
+        };
+        let r = lbfgs(rosenbrock_grad, &x0, &opts).unwrap();
+        assert!(
+            r.x.iter().all(|v| (v - 1.0).abs() < 1e-5),
+            "{:?}",
+            r.termination
+        );
+        assert!(r.hessian.is_none());
+    }
+
+    #[test]
+    fn lbfgs_with_each_line_search() {
+        for line_search in [LineSearch::more_thuente(), LineSearch::hager_zhang()] {
+            let opts = LbfgsOptions {
+                tol: Tolerance::Strict,
+                line_search,
+                ..Default::default()
+            };
+            let r = lbfgs(rosenbrock_grad, &[-1.2, 1.0, -1.2, 1.0], &opts).unwrap();
+            assert!(
+                r.x.iter().all(|v| (v - 1.0).abs() < 1e-5),
+                "{line_search:?}: {r:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn lbfgs_history_length_matters() {
+        let x0 = vec![-1.2, 1.0, -1.2, 1.0, -1.2, 1.0];
+        let short = LbfgsOptions {
+            history: 1,
+            ..Default::default()
+        };
+        let long = LbfgsOptions {
+            history: 20,
+            ..Default::default()
+        };
+        let a = lbfgs(rosenbrock_grad, &x0, &short).unwrap();
+        let b = lbfgs(rosenbrock_grad, &x0, &long).unwrap();
+        assert!(
+            b.iterations < a.iterations,
+            "{} vs {}",
+            b.iterations,
+            a.iterations
+        );
+    }
+
+    #[test]
+    fn steepest_descent_step_does_not_end_iteration() {
+        // Off the floor of a steep valley the first step only reaches the
+        // floor, far from the minimum at (1, 1)
+        let fg = |x: &[f64], g: &mut [f64]| {
+            let v = x[1] - x[0];
+            g[0] = -1e6 * v + (x[0] - 1.0);
+            g[1] = 1e6 * v;
+            0.5e6 * v * v + 0.5 * (x[0] - 1.0).powi(2)
+        };
+        let x0 = [1e3, 1e3 + 1e-3];
+        let a = bfgs(fg, &x0, &BfgsOptions::default()).unwrap();
+        let b = lbfgs(fg, &x0, &LbfgsOptions::default()).unwrap();
+        for r in [a, b] {
+            assert!(r.x.iter().all(|v| (v - 1.0).abs() < 1e-6), "{r:?}");
+        }
+    }
+
+    #[test]
+    fn starting_at_minimum_returns_immediately() {
+        let r = bfgs(rosenbrock_grad, &[1.0, 1.0], &BfgsOptions::default()).unwrap();
+        assert_eq!(r.iterations, 0);
+        assert_eq!(r.termination, Termination::GradientNorm);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let r = lbfgs(rosenbrock_grad, &[f64::NAN, 1.0], &LbfgsOptions::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let opts = LbfgsOptions {
+            history: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            lbfgs(rosenbrock_grad, &[0.0, 0.0], &opts),
+            Err(NumalError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn exhausted_budget_did_not_converge() {
+        let opts = BfgsOptions {
+            max_iter: 3,
+            ..Default::default()
+        };
+        assert_eq!(
+            bfgs(rosenbrock_grad, &[-1.2, 1.0], &opts),
+            Err(NumalError::DidNotConverge)
+        );
+    }
+}
@@ -0,0 +1,439 @@
+//! Minimization of functions of one variable.
+//!
+//! [`bracket_minimum`] searches downhill for a triple `a < b < c` with
+//! `f(b)` below both `f(a)` and `f(c)`; [`golden_section`] and [`brent`]
+//! then locate the minimum inside an interval known to contain one. All
+//! three compare function values or abscissae under a [`Tolerance`].
+//!
+//! Interval-based methods cannot locate a minimum to better than about
+//! `sqrt(f64::EPSILON)` relative accuracy, so the relative part of the
+//! [`Tolerance`] is floored at that value.
+
+use crate::NumalError;
+use crate::core::tolerance::Tolerance;
+
+const GOLDEN: f64 = 1.618_033_988_749_895;
+// 2 - GOLDEN, the fraction of an interval taken by a golden-section step
+const CGOLD: f64 = 0.381_966_011_250_105_1;
+
+/// Outcome of a successful one-dimensional minimization
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScalarMinimum {
+    /// Location of the minimum
+    pub x: f64,
+    /// Objective value at `x`
+    pub fx: f64,
+    /// Number of iterations performed
+    pub iterations: usize,
+    /// Number of objective evaluations
+    pub evaluations: usize,
+}
+
+/// A triple `a < b < c` with `f(b) <= f(a)` and `f(b) < f(c)`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bracket {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub fa: f64,
+    pub fb: f64,
+    pub fc: f64,
+    /// Number of objective evaluations used to find the bracket
+    pub evaluations: usize,
+}
+
+/// Failure of [`bracket_minimum`]
+#[derive(Debug, PartialEq)]
+pub struct BracketFailure {
+    /// [`NumalError::DidNotConverge`] when the search gave up, else the
+    /// error that stopped it
+    pub error: NumalError,
+    /// The last triple tried, in no particular order, when the search gave
+    /// up
+    pub last: Option<Bracket>,
+}
+
+impl From<BracketFailure> for NumalError {
+    fn from(failure: BracketFailure) -> Self {
+        failure.error
+    }
+}
+
+fn eval<F: Fn(f64) -> f64>(f: &F, x: f64, evals: &mut usize) -> Result<f64, NumalError> {
+    *evals += 1;
+    let fx = f(x);
+    if fx.is_nan() {
+        return Err(NumalError::InvalidInput(format!(
+            "objective returned NaN at x = {x}"
+        )));
+    }
+    Ok(fx)
+}
+
+fn check_interval(a: f64, b: f64) -> Result<(), NumalError> {
+    if !a.is_finite() || !b.is_finite() || a >= b {
+        return Err(NumalError::InvalidInput(format!(
+            "interval must be finite with a < b, got [{a}, {b}]"
+        )));
+    }
+    Ok(())
+}
+
+// Absolute accuracy requested around `x`.
+fn tolerance_at(x: f64, tol: Tolerance) -> f64 {
+    tol.eps_rel().max(f64::EPSILON.sqrt()) * x.abs() + tol.eps_abs()
+}
+
+/// Finds a bracket around a minimum by stepping downhill from `a` and `b`
+/// with golden-ratio magnification and parabolic extrapolation, in the
+/// manner of Numerical Recipes' `mnbrak`.
+///
+/// The search gives up once the three values differ by no more than the
+/// absolute part of `tol`, as on a function that is flat along the search.
+/// When no bracket is found within `max_iter` expansions, the function
+/// stops being finite or it is flat, the [`BracketFailure`] holds
+/// [`NumalError::DidNotConverge`] and the last triple tried.
+pub fn bracket_minimum<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<Bracket, BracketFailure>
+where
+    F: Fn(f64) -> f64,
+{
+    match search_bracket(f, a, b, tol, max_iter) {
+        Ok((bracket, true)) => Ok(bracket),
+        Ok((last, false)) => Err(BracketFailure {
+            error: NumalError::DidNotConverge,
+            last: Some(last),
+        }),
+        Err(error) => Err(BracketFailure { error, last: None }),
+    }
+}
+
+// The search behind [`bracket_minimum`], returning the last triple tried
+// and whether it brackets a minimum. The triple is only ordered when it
+// does.
+pub(crate) fn search_bracket<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<(Bracket, bool), NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    const GROW_LIMIT: f64 = 100.0;
+    const TINY: f64 = 1e-20;
+    if !a.is_finite() || !b.is_finite() || a == b {
+        return Err(NumalError::InvalidInput(format!(
+            "starting points must be finite and distinct, got {a} and {b}"
+        )));
+    }
+    let mut evals = 0;
+    let (mut a, mut b) = (a, b);
+    let mut fa = eval(&f, a, &mut evals)?;
+    let mut fb = eval(&f, b, &mut evals)?;
+    if fb > fa {
+        (a, b, fa, fb) = (b, a, fb, fa);
+    }
+    let mut c = b + GOLDEN * (b - a);
+    let mut fc = eval(&f, c, &mut evals)?;
+    let mut iter = 0;
+    while fb >= fc {
+        iter += 1;
+        let flat = (fa - fb).abs() <= tol.eps_abs() && (fc - fb).abs() <= tol.eps_abs();
+        if iter > max_iter || flat || !fc.is_finite() || !c.is_finite() {
+            let bracket = Bracket {
+                a,
+                b,
+                c,
+                fa,
+                fb,
+                fc,
+                evaluations: evals,
+            };
+            return Ok((bracket, false));
+        }
+        let r = (b - a) * (fb - fc);
+        let q = (b - c) * (fb - fa);
+        let denom = 2.0 * (q - r).abs().max(TINY).copysign(q - r);
+        let mut u = b - ((b - c) * q - (b - a) * r) / denom;
+        let ulim = b + GROW_LIMIT * (c - b);
+        let mut fu;
+        if (b - u) * (u - c) > 0.0 {
+            // Parabolic minimum between b and c
+            fu = eval(&f, u, &mut evals)?;
+            if fu < fc {
+                (a, fa, b, fb) = (b, fb, u, fu);
+                break;
+            } else if fu > fb {
+                (c, fc) = (u, fu);
+                break;
+            }
+            u = c + GOLDEN * (c - b);
+            fu = eval(&f, u, &mut evals)?;
+        } else if (c - u) * (u - ulim) > 0.0 {
+            // Parabolic minimum between c and the growth limit
+            fu = eval(&f, u, &mut evals)?;
+            if fu < fc {
+                (b, fb, c, fc) = (c, fc, u, fu);
+                u = c + GOLDEN * (c - b);
+                fu = eval(&f, u, &mut evals)?;
+            }
+        } else if (u - ulim) * (ulim - c) >= 0.0 {
+            u = ulim;
+            fu = eval(&f, u, &mut evals)?;
+        } else {
+            u = c + GOLDEN * (c - b);
+            fu = eval(&f, u, &mut evals)?;
+        }
+        (a, b, c) = (b, c, u);
+        (fa, fb, fc) = (fb, fc, fu);
+    }
+    if a > c {
+        (a, c, fa, fc) = (c, a, fc, fa);
+    }
+    let bracket = Bracket {
+        a,
+        b,
+        c,
+        fa,
+        fb,
+        fc,
+        evaluations: evals,
+    };
+    Ok((bracket, true))
+}
+
+/// Golden-section search for a minimum of a unimodal `f` on `[a, b]`.
+///
+/// Shrinks the interval by the golden ratio each iteration, reusing one
+/// interior evaluation.
+pub fn golden_section<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<ScalarMinimum, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    check_interval(a, b)?;
+    let mut evals = 0;
+    let (mut a, mut b) = (a, b);
+    let mut x1 = a + CGOLD * (b - a);
+    let mut x2 = b - CGOLD * (b - a);
+    let mut f1 = eval(&f, x1, &mut evals)?;
+    let mut f2 = eval(&f, x2, &mut evals)?;
+    for iter in 1..=max_iter {
+        if f1 < f2 {
+            (b, x2, f2) = (x2, x1, f1);
+            x1 = a + CGOLD * (b - a);
+            f1 = eval(&f, x1, &mut evals)?;
+        } else {
+            (a, x1, f1) = (x1, x2, f2);
+            x2 = b - CGOLD * (b - a);
+            f2 = eval(&f, x2, &mut evals)?;
+        }
+        let (x, fx) = if f1 < f2 { (x1, f1) } else { (x2, f2) };
+        if b - a <= 2.0 * tolerance_at(x, tol) {
+            return Ok(ScalarMinimum {
+                x,
+                fx,
+                iterations: iter,
+                evaluations: evals,
+            });
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+/// Brent's method for a minimum of `f` on `[a, b]`.
+///
+/// Takes parabolic-interpolation steps through the three best points when
+/// they are well behaved and golden-section steps otherwise.
+pub fn brent<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<ScalarMinimum, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    check_interval(a, b)?;
+    let mut evals = 0;
+    let (mut a, mut b) = (a, b);
+    let mut x = a + CGOLD * (b - a);
+    let (mut w, mut v) = (x, x);
+    let mut fx = eval(&f, x, &mut evals)?;
+    let (mut fw, mut fv) = (fx, fx);
+    let (mut d, mut e): (f64, f64) = (0.0, 0.0);
+    for iter in 1..=max_iter {
+        let m = 0.5 * (a + b);
+        let tol1 = tolerance_at(x, tol) / 3.0;
+        let tol2 = 2.0 * tol1;
+        if (x - m).abs() <= tol2 - 0.5 * (b - a) {
+            return Ok(ScalarMinimum {
+                x,
+                fx,
+                iterations: iter - 1,
+                evaluations: evals,
+            });
+        }
+        let mut golden = true;
+        if e.abs() > tol1 {
+            let r = (x - w) * (fx - fv);
+            let mut q = (x - v) * (fx - fw);
+            let mut p = (x - v) * q - (x - w) * r;
+            q = 2.0 * (q - r);
+            if q > 0.0 {
+                p = -p;
+            }
+            q = q.abs();
+            let e_prev = e;
+            e = d;
+            if p.abs() < (0.5 * q * e_prev).abs() && p > q * (a - x) && p < q * (b - x) {
+                d = p / q;
+                let u = x + d;
+                if u - a < tol2 || b - u < tol2 {
+                    d = tol1.copysign(m - x);
+                }
+                golden = false;
+            }
+        }
+        if golden {
+            e = if x >= m { a - x } else { b - x };
+            d = CGOLD * e;
+        }
+        let u = if d.abs() >= tol1 {
+            x + d
+        } else {
+            x + tol1.copysign(d)
+        };
+        let fu = eval(&f, u, &mut evals)?;
+        if fu <= fx {
+            if u >= x {
+                a = x;
+            } else {
+                b = x;
+            }
+            (v, fv, w, fw, x, fx) = (w, fw, x, fx, u, fu);
+        } else {
+            if u < x {
+                a = u;
+            } else {
+                b = u;
+            }
+            if fu <= fw || w == x {
+                (v, fv, w, fw) = (w, fw, u, fu);
+            } else if fu <= fv || v == x || v == w {
+                (v, fv) = (u, fu);
+            }
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn golden_section_finds_quadratic_minimum() {
+        let r = golden_section(
+            |x| (x - 2.0).powi(2) + 1.0,
+            0.0,
+            5.0,
+            Tolerance::Strict,
+            200,
+        )
+        .unwrap();
+        assert!((r.x - 2.0).abs() < 1e-7, "{r:?}");
+        assert!((r.fx - 1.0).abs() < 1e-14);
+        assert_eq!(r.evaluations, r.iterations + 2);
+    }
+
+    #[test]
+    fn brent_finds_cosine_minimum() {
+        let r = brent(f64::cos, 0.0, 2.0 * PI, Tolerance::Strict, 200).unwrap();
+        assert!((r.x - PI).abs() < 1e-7, "{r:?}");
+        assert!((r.fx + 1.0).abs() < 1e-14);
+    }
+
+    #[test]
+    fn brent_uses_fewer_evaluations_than_golden_section() {
+        let f = |x: f64| x.exp() - 4.0 * x;
+        let g = golden_section(f, 0.0, 3.0, Tolerance::Default, 200).unwrap();
+        let b = brent(f, 0.0, 3.0, Tolerance::Default, 200).unwrap();
+        assert!((b.x - 4f64.ln()).abs() < 1e-6);
+        assert!(b.evaluations < g.evaluations);
+    }
+
+    #[test]
+    fn bracket_then_minimize() {
+        let f = |x: f64| (x - 10.0).powi(2);
+        let br = bracket_minimum(f, 0.0, 1.0, Tolerance::Default, 50).unwrap();
+        assert!(br.a < br.b && br.b < br.c, "{br:?}");
+        assert!(br.fb <= br.fa && br.fb < br.fc, "{br:?}");
+        assert!(br.a <= 10.0 && 10.0 <= br.c);
+        let r = brent(f, br.a, br.c, Tolerance::Default, 100).unwrap();
+        assert!((r.x - 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bracket_accepts_uphill_start() {
+        let br = bracket_minimum(|x| (x + 3.0).powi(2), 1.0, 0.0, Tolerance::Default, 50).unwrap();
+        assert!(br.a <= -3.0 && -3.0 <= br.c, "{br:?}");
+    }
+
+    #[test]
+    fn unbounded_function_did_not_converge() {
+        let r = bracket_minimum(|x| -x, 0.0, 1.0, Tolerance::Default, 10).unwrap_err();
+        assert_eq!(r.error, NumalError::DidNotConverge);
+        let last = r.last.unwrap();
+        assert!(last.a < last.b && last.b < last.c, "{last:?}");
+    }
+
+    #[test]
+    fn flat_function_stops_the_search() {
+        let r = bracket_minimum(|_| 1.0, 0.0, 1.0, Tolerance::Default, 50).unwrap_err();
+        assert_eq!(r.last.unwrap().evaluations, 3);
+    }
+
+    #[test]
+    fn large_offset_is_not_flat() {
+        let f = |x: f64| 1e8 + (x - 10.0).powi(2);
+        let br = bracket_minimum(f, 0.0, 1.0, Tolerance::Default, 50).unwrap();
+        assert!(br.a <= 10.0 && 10.0 <= br.c, "{br:?}");
+    }
+
+    #[test]
+    fn invalid_interval_is_rejected() {
+        let r = brent(|x| x * x, 1.0, -1.0, Tolerance::Default, 100);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = golden_section(|x| x * x, 0.0, f64::INFINITY, Tolerance::Default, 100);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = bracket_minimum(|x| x * x, 1.0, 1.0, Tolerance::Default, 10);
+        assert!(matches!(
+            r,
+            Err(BracketFailure {
+                error: NumalError::InvalidInput(_),
+                last: None
+            })
+        ));
+    }
+
+    #[test]
+    fn exhausted_budget_did_not_converge() {
+        let r = brent(|x| (x - 1.0).powi(2), -100.0, 100.0, Tolerance::Strict, 3);
+        assert_eq!(r, Err(NumalError::DidNotConverge));
+    }
+}
@@ -0,0 +1,266 @@
+//! Sequential quadratic programming.
+//!
+//! Each iteration solves the quadratic program
+//!
+//! `min g·d + d·Bd/2`  subject to  `c_i + a_i·d = 0` or `>= 0`,
+//!
+//! where `B` is a damped BFGS approximation (Powell, 1978) of the Hessian of
+//! the Lagrangian, which keeps it positive definite. The step is then
+//! shortened by backtracking on the l1 merit function
+//! `f + mu (sum |c_eq| + sum max(0, -c_ineq))`, with the penalty `mu` kept
+//! above the largest multiplier estimate. When the linearized constraints
+//! are inconsistent, the constraint residuals are scaled down until the
+//! quadratic program becomes feasible.
+
+use super::constrained::{ConstrainedMinimum, Constraint, Point, constrained_minimum, kkt, start};
+use super::qp::{ActiveSet, goldfarb_idnani};
+use crate::NumalError;
+use crate::core::error::LineSearchFailure;
+use crate::core::linalg::{Cholesky, Matrix, dot};
+use crate::core::tolerance::Tolerance;
+
+// Sufficient-decrease constant and trial budget of the merit line search.
+const ARMIJO: f64 = 1e-4;
+const MERIT_STEPS: usize = 40;
+// Number of times the linearized constraints are relaxed before giving up.
+const RELAXATIONS: usize = 20;
+
+/// Options for [`sqp`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SqpOptions {
+    /// KKT tolerance on stationarity, violation and complementarity
+    pub tol: Tolerance,
+    pub max_iter: usize,
+}
+
+impl Default for SqpOptions {
+    fn default() -> Self {
+        SqpOptions {
+            tol: Tolerance::Default,
+            max_iter: 500,
+        }
+    }
+}
+
+/// Minimizes `fg` subject to `constraints` by sequential quadratic
+/// programming.
+///
+/// `fg(x, g)` returns `f(x)` and writes the gradient into `g`.
+pub fn sqp<F>(
+    fg: F,
+    constraints: &[Constraint],
+    x0: &[f64],
+    opts: &SqpOptions,
+) -> Result<ConstrainedMinimum, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+{
+    let mut p = start(&fg, constraints, x0)?;
+    let n = x0.len();
+    let m = constraints.len();
+    let mut evals = 1;
+    let mut b = Matrix::identity(n);
+    let mut lambda = vec![0.0; m];
+    let mut mu: f64 = 0.0;
+    // Equalities first, as the quadratic programming solver expects
+    let order: Vec<usize> = (0..m)
+        .filter(|&j| constraints[j].is_equality())
+        .chain((0..m).filter(|&j| !constraints[j].is_equality()))
+        .collect();
+    let meq = constraints.iter().filter(|c| c.is_equality()).count();
+    let l1_violation = |q: &Point| -> f64 {
+        q.c.iter()
+            .zip(constraints)
+            .map(|(&c, con)| {
+                if con.is_equality() {
+                    c.abs()
+                } else {
+                    (-c).max(0.0)
+                }
+            })
+            .sum()
+    };
+
+    for iter in 0..=opts.max_iter {
+        if kkt(&p, constraints, &lambda, opts.tol).1 {
+            return Ok(constrained_minimum(p, constraints, lambda, iter, evals));
+        }
+        if iter == opts.max_iter {
+            break;
+        }
+        let normals = Matrix::from_fn(m, n, |i, k| p.jac[(order[i], k)]);
+        // Damped updates keep B positive definite, barring rounding
+        let chol = Cholesky::new(&b, 0.0).unwrap_or_else(|| {
+            b = Matrix::identity(n);
+            Cholesky::new(&b, 0.0).expect("identity is positive definite")
+        });
+        let budget = 10 * (m + n) + 100;
+        // Relax the residuals of violated constraints until the linearized
+        // constraints are consistent; with tau = 0 the zero step is feasible
+        let mut tau = 1.0;
+        let (d, lam_hat) = loop {
+            let rhs: Vec<f64> = order
+                .iter()
+                .map(|&j| {
+                    let c = p.c[j];
+                    if constraints[j].is_equality() || c < 0.0 {
+                        -tau * c
+                    } else {
+                        -c
+                    }
+                })
+                .collect();
+            let outcome = goldfarb_idnani(&chol, &p.g, &normals, &rhs, meq, &[], budget);
+            if let Some(ActiveSet::Solved {
+                x: d, multipliers, ..
+            }) = outcome
+            {
+                let mut lam = vec![0.0; m];
+                for (&j, uj) in order.iter().zip(multipliers) {
+                    lam[j] = uj;
+                }
+                break (d, lam);
+            }
+            if tau == 0.0 {
+                return Err(NumalError::DidNotConverge);
+            }
+            tau = if tau < 0.5f64.powi(RELAXATIONS as i32) {
+                0.0
+            } else {
+                0.5 * tau
+            };
+        };
+
+        let lam_max = lam_hat.iter().fold(0.0, |a: f64, v| a.max(v.abs()));
+        if mu < 1.1 * lam_max {
+            mu = 2.0 * lam_max;
+        }
+        let merit = |q: &Point| q.f + mu * l1_violation(q);
+        let phi0 = merit(&p);
+        let slope = dot(&p.g, &d) - mu * tau * l1_violation(&p);
+        let mut alpha = 1.0;
+        let mut best = (0.0, phi0);
+        let q = loop {
+            let x: Vec<f64> = p.x.iter().zip(&d).map(|(a, b)| a + alpha * b).collect();
+            let q = Point::new(&fg, constraints, &x);
+            evals += 1;
+            let phi = if q.is_finite() {
+                merit(&q)
+            } else {
+                f64::INFINITY
+            };
+            if phi < best.1 {
+                best = (alpha, phi);
+            }
+            if phi <= phi0 + ARMIJO * alpha * slope.min(0.0) {
+                break q;
+            }
+            alpha *= 0.5;
+            if alpha < 0.5f64.powi(MERIT_STEPS as i32) {
+                return Err(NumalError::LineSearchFailed {
+                    reason: LineSearchFailure::MaxIterations,
+                    step: best.0,
+                    fx: best.1,
+                });
+            }
+        };
+
+        for (l, lh) in lambda.iter_mut().zip(&lam_hat) {
+            *l += alpha * (lh - *l);
+        }
+        let s: Vec<f64> = q.x.iter().zip(&p.x).map(|(a, b)| a - b).collect();
+        let y: Vec<f64> = q
+            .lagrangian_gradient(&lambda)
+            .iter()
+            .zip(p.lagrangian_gradient(&lambda))
+            .map(|(a, b)| a - b)
+            .collect();
+        damped_bfgs(&mut b, &s, &y);
+        p = q;
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+// Powell's damped BFGS update, which keeps `b` positive definite by
+// interpolating `y` towards `b s` when the curvature `s·y` is too small.
+fn damped_bfgs(b: &mut Matrix, s: &[f64], y: &[f64]) {
+    let bs = b.mul_vec(s);
+    let sbs = dot(s, &bs);
+    if sbs <= f64::EPSILON * dot(s, s) * b.max_abs() || sbs.is_nan() {
+        return;
+    }
+    let sy = dot(s, y);
+    let theta = if sy >= 0.2 * sbs {
+        1.0
+    } else {
+        0.8 * sbs / (sbs - sy)
+    };
+    let r: Vec<f64> = y
+        .iter()
+        .zip(&bs)
+        .map(|(yi, bi)| theta * yi + (1.0 - theta) * bi)
+        .collect();
+    let sr = dot(s, &r);
+    if sr <= 0.0 || !sr.is_finite() || !r.iter().all(|v| v.is_finite()) {
+        return;
+    }
+    b.rank1_update(1.0 / sr, &r, &r);
+    b.rank1_update(-1.0 / sbs, &bs, &bs);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems;
+
+    #[test]
+    fn solves_hs71() {
+        let (f, cons) = testproblems::hs71();
+        let opts = SqpOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = sqp(f, &cons, &[1.0, 5.0, 5.0, 1.0], &opts).unwrap();
+        let expected = [1.0, 4.742_999_64, 3.821_149_98, 1.379_408_29];
+        for (x, e) in r.x.iter().zip(expected) {
+            assert!((x - e).abs() < 1e-7, "{r:?}");
+        }
+        assert!(
+            r.constraint_violation <= 1e-12 && r.kkt_residual <= 1e-9,
+            "{r:?}"
+        );
+        assert!(r.multipliers[0] > 0.0 && r.multipliers[2] > 0.0);
+        assert!(r.multipliers[3..].iter().all(|&l| l == 0.0));
+    }
+
+    #[test]
+    fn infeasible_start_on_nonconvex_constraint() {
+        // min (x - 2)^2 + (y - 1)^2 with x^2 <= y, x + y <= 2: the solution
+        // is (1, 1) with multipliers (2/3, 2/3)
+        let f = |x: &[f64], g: &mut [f64]| {
+            g[0] = 2.0 * (x[0] - 2.0);
+            g[1] = 2.0 * (x[1] - 1.0);
+            (x[0] - 2.0).powi(2) + (x[1] - 1.0).powi(2)
+        };
+        let cons = [
+            Constraint::inequality(|x: &[f64], g: &mut [f64]| {
+                g[0] = -2.0 * x[0];
+                g[1] = 1.0;
+                x[1] - x[0] * x[0]
+            }),
+            Constraint::inequality(|x: &[f64], g: &mut [f64]| {
+                g[0] = -1.0;
+                g[1] = -1.0;
+                2.0 - x[0] - x[1]
+            }),
+        ];
+        let r = sqp(f, &cons, &[3.0, -2.0], &SqpOptions::default()).unwrap();
+        assert!(
+            (r.x[0] - 1.0).abs() < 1e-6 && (r.x[1] - 1.0).abs() < 1e-6,
+            "{r:?}"
+        );
+        for l in &r.multipliers {
+            assert!((l - 2.0 / 3.0).abs() < 1e-5, "{r:?}");
+        }
+    }
+}
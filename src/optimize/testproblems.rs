@@ -0,0 +1,269 @@
+//! Test problems shared by the optimizer tests.
+
+use std::f64::consts::PI;
+
+use super::{Bounds, Constraint};
+
+// Hock-Schittkowski problem 71: four variables, one equality, one
+// inequality and bounds written as inequalities
+pub(super) fn hs71() -> (impl Fn(&[f64], &mut [f64]) -> f64, Vec<Constraint<'static>>) {
+    let f = |x: &[f64], g: &mut [f64]| {
+        g[0] = x[3] * (2.0 * x[0] + x[1] + x[2]);
+        g[1] = x[0] * x[3];
+        g[2] = x[0] * x[3] + 1.0;
+        g[3] = x[0] * (x[0] + x[1] + x[2]);
+        x[0] * x[3] * (x[0] + x[1] + x[2]) + x[2]
+    };
+    let mut cons = vec![
+        Constraint::inequality(|x: &[f64], g: &mut [f64]| {
+            g[0] = x[1] * x[2] * x[3];
+            g[1] = x[0] * x[2] * x[3];
+            g[2] = x[0] * x[1] * x[3];
+            g[3] = x[0] * x[1] * x[2];
+            x[0] * x[1] * x[2] * x[3] - 25.0
+        }),
+        Constraint::equality(|x: &[f64], g: &mut [f64]| {
+            for (gi, xi) in g.iter_mut().zip(x) {
+                *gi = 2.0 * xi;
+            }
+            x.iter().map(|v| v * v).sum::<f64>() - 40.0
+        }),
+    ];
+    for i in 0..4 {
+        cons.push(Constraint::inequality(move |x: &[f64], g: &mut [f64]| {
+            g.iter_mut().for_each(|v| *v = 0.0);
+            g[i] = 1.0;
+            x[i] - 1.0
+        }));
+        cons.push(Constraint::inequality(move |x: &[f64], g: &mut [f64]| {
+            g.iter_mut().for_each(|v| *v = 0.0);
+            g[i] = -1.0;
+            5.0 - x[i]
+        }));
+    }
+    (f, cons)
+}
+
+// Extended Rosenbrock function, with minimum 0 at (1, ..., 1)
+pub(super) fn rosenbrock(x: &[f64]) -> f64 {
+    x.windows(2)
+        .map(|w| 100.0 * (w[1] - w[0] * w[0]).powi(2) + (1.0 - w[0]).powi(2))
+        .sum()
+}
+
+pub(super) fn rosenbrock_grad(x: &[f64], g: &mut [f64]) -> f64 {
+    g.iter_mut().for_each(|v| *v = 0.0);
+    let mut f = 0.0;
+    for i in 0..x.len() - 1 {
+        let (a, b) = (x[i], x[i + 1]);
+        f += 100.0 * (b - a * a).powi(2) + (1.0 - a).powi(2);
+        g[i] += -400.0 * a * (b - a * a) - 2.0 * (1.0 - a);
+        g[i + 1] += 200.0 * (b - a * a);
+    }
+    f
+}
+
+// Rastrigin function: minimum 0 at the origin among a grid of local minima
+// near the integer points
+pub(super) fn rastrigin(x: &[f64]) -> f64 {
+    x.iter()
+        .map(|v| v * v - 10.0 * (2.0 * PI * v).cos() + 10.0)
+        .sum()
+}
+
+pub(super) fn rastrigin_grad(x: &[f64], g: &mut [f64]) -> f64 {
+    for (gi, v) in g.iter_mut().zip(x) {
+        *gi = 2.0 * v + 20.0 * PI * (2.0 * PI * v).sin();
+    }
+    rastrigin(x)
+}
+
+// The box [-half_width, half_width]^n
+pub(super) fn cube(n: usize, half_width: f64) -> Bounds {
+    Bounds::new(vec![-half_width; n], vec![half_width; n]).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NumalError;
+    use crate::optimize::{
+        AnnealingOptions, BasinHoppingOptions, BobyqaOptions, CmaEsOptions, CobylaOptions, Cooling,
+        DeOptions, DirectOptions, Minimum, NelderMeadOptions, PsoOptions, basin_hopping, bobyqa,
+        cmaes, cobyla, differential_evolution, direct, nelder_mead, particle_swarm,
+        simulated_annealing,
+    };
+
+    // A global method called with a start, optional bounds and a seed
+    type Method = fn(&[f64], Option<&Bounds>, u64) -> Result<Minimum, NumalError>;
+
+    fn seeded() -> [(&'static str, Method); 5] {
+        [
+            ("differential evolution", |x0, b, seed| {
+                let opts = DeOptions {
+                    seed,
+                    ..Default::default()
+                };
+                differential_evolution(rastrigin, x0, b, &opts)
+            }),
+            ("CMA-ES", |x0, b, seed| {
+                let opts = CmaEsOptions {
+                    restarts: 2,
+                    seed,
+                    ..Default::default()
+                };
+                cmaes(rastrigin, x0, b, &opts)
+            }),
+            ("simulated annealing", |x0, b, seed| {
+                let opts = AnnealingOptions {
+                    seed,
+                    ..Default::default()
+                };
+                simulated_annealing(rastrigin, x0, b, &Cooling::default(), &opts)
+            }),
+            ("particle swarm", |x0, b, seed| {
+                let opts = PsoOptions {
+                    seed,
+                    ..Default::default()
+                };
+                particle_swarm(rastrigin, x0, b, &opts)
+            }),
+            ("basin hopping", |x0, b, seed| {
+                let local = |f: &dyn Fn(&[f64]) -> f64, x: &[f64]| {
+                    nelder_mead(f, x, &NelderMeadOptions::default())
+                };
+                let opts = BasinHoppingOptions {
+                    hops: 10,
+                    seed,
+                    ..Default::default()
+                };
+                basin_hopping(rastrigin, local, x0, b, &opts)
+            }),
+        ]
+    }
+
+    #[test]
+    fn seed_makes_runs_reproducible() {
+        let bounds = cube(2, 5.12);
+        for (name, method) in seeded() {
+            let run = |seed| method(&[1.0, 1.0], Some(&bounds), seed).unwrap();
+            assert_eq!(run(3), run(3), "{name}");
+            assert_ne!(run(3), run(4), "{name}");
+        }
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let half_infinite = Bounds::new(vec![0.0, 0.0], vec![1.0, f64::INFINITY]).unwrap();
+        for (name, method) in seeded() {
+            for (x0, bounds) in [(&[][..], None), (&[0.5][..], Some(&cube(2, 1.0)))] {
+                let r = method(x0, bounds, 0);
+                assert!(matches!(r, Err(NumalError::InvalidInput(_))), "{name}");
+            }
+            // The population methods spread their members over the box
+            if matches!(name, "differential evolution" | "particle swarm") {
+                let r = method(&[0.5, 0.5], Some(&half_infinite), 0);
+                assert!(matches!(r, Err(NumalError::InvalidInput(_))), "{name}");
+                let r = method(&[0.5, 0.5], None, 0);
+                assert!(matches!(r, Err(NumalError::InvalidInput(_))), "{name}");
+            }
+        }
+        let r = direct(rastrigin, &half_infinite, &DirectOptions::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = bobyqa(rosenbrock, &[], None, &BobyqaOptions::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = cobyla(rosenbrock, &[], &[], &CobylaOptions::default());
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn minimum_on_boundary_is_found() {
+        // The unconstrained minimum (-1, 3) lies outside the box
+        let f = |x: &[f64]| (x[0] + 1.0).powi(2) + (x[1] - 3.0).powi(2);
+        let bounds = cube(2, 2.0);
+        let results = [
+            differential_evolution(f, &[0.0, 0.0], Some(&bounds), &DeOptions::default()),
+            cmaes(f, &[0.0, 0.0], Some(&bounds), &CmaEsOptions::default()),
+            particle_swarm(f, &[0.0, 0.0], Some(&bounds), &PsoOptions::default()),
+            direct(f, &bounds, &DirectOptions::default()),
+            bobyqa(f, &[0.0, 0.0], Some(&bounds), &BobyqaOptions::default()),
+        ];
+        for r in results {
+            let r = r.unwrap();
+            assert!(
+                (r.x[0] + 1.0).abs() < 1e-5 && (r.x[1] - 2.0).abs() < 1e-5,
+                "{r:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn exhausted_budget_did_not_converge() {
+        let (x0, bounds) = ([-1.2, 1.0], cube(2, 5.0));
+        let results = [
+            differential_evolution(
+                rosenbrock,
+                &x0,
+                Some(&bounds),
+                &DeOptions {
+                    max_evaluations: 100,
+                    ..Default::default()
+                },
+            ),
+            cmaes(
+                rosenbrock,
+                &x0,
+                None,
+                &CmaEsOptions {
+                    max_evaluations: 50,
+                    ..Default::default()
+                },
+            ),
+            simulated_annealing(
+                rosenbrock,
+                &x0,
+                None,
+                &Cooling::Fast,
+                &AnnealingOptions {
+                    max_evaluations: 100,
+                    ..Default::default()
+                },
+            ),
+            particle_swarm(
+                rosenbrock,
+                &x0,
+                Some(&bounds),
+                &PsoOptions {
+                    max_evaluations: 200,
+                    ..Default::default()
+                },
+            ),
+            direct(
+                rosenbrock,
+                &bounds,
+                &DirectOptions {
+                    max_evaluations: 50,
+                    ..Default::default()
+                },
+            ),
+            bobyqa(
+                rosenbrock,
+                &x0,
+                None,
+                &BobyqaOptions {
+                    max_evaluations: 30,
+                    ..Default::default()
+                },
+            ),
+        ];
+        for r in results {
+            assert_eq!(r, Err(NumalError::DidNotConverge));
+        }
+        let opts = CobylaOptions {
+            max_evaluations: 20,
+            ..Default::default()
+        };
+        let r = cobyla(rosenbrock, &[], &x0, &opts);
+        assert_eq!(r, Err(NumalError::DidNotConverge));
+    }
+}
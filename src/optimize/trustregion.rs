@@ -0,0 +1,536 @@
+//! Trust-region Newton methods.
+//!
+//! Each iteration minimizes the quadratic model `f + g·p + p·Bp/2` over the
+//! ball `|p| <= radius`, where `B` is the Hessian supplied by the caller,
+//! either as a matrix or through Hessian-vector products. The step is
+//! accepted when the ratio of actual to predicted reduction is large enough,
+//! and the radius is adapted from the same ratio. Unlike a line search the
+//! model does not need `B` to be positive definite, so negative curvature is
+//! exploited rather than discarded.
+
+use super::{
+    GradientMinimum, Termination, gradient_minimum, gradient_termination, initial_gradient,
+};
+use crate::NumalError;
+use crate::core::linalg::{Cholesky, Matrix, dot, norm};
+use crate::core::tolerance::Tolerance;
+
+// Relative accuracy |p| = radius (1 ± KAPPA) of the exact subproblem solver.
+const MS_KAPPA: f64 = 1e-2;
+const MS_MAX_ITER: usize = 50;
+
+/// Solver for the subproblem `min g·p + p·Bp/2` subject to `|p| <= radius`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Subproblem {
+    /// Minimizer of the model along the steepest-descent direction
+    Cauchy,
+    /// Powell's dogleg path through the Cauchy and Newton points. Falls back
+    /// to the Cauchy point when the Hessian is not positive definite.
+    Dogleg,
+    /// Steihaug-Toint truncated conjugate gradients, stopping at the
+    /// boundary or on negative curvature; needs only Hessian-vector products
+    #[default]
+    SteihaugToint,
+    /// Moré-Sorensen iteration on the multiplier of the constraint, with a
+    /// Cholesky factorization per iteration; handles the hard case
+    Exact,
+}
+
+/// Radius update driven by the ratio `rho` of actual to predicted reduction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RadiusUpdate {
+    /// The radius shrinks when `rho` falls below this value
+    pub shrink_below: f64,
+    /// The radius grows when `rho` exceeds this value and the step reached
+    /// the boundary
+    pub expand_above: f64,
+    /// Factor in (0, 1) applied to the step length when shrinking
+    pub shrink: f64,
+    /// Factor greater than 1 applied to the radius when expanding
+    pub expand: f64,
+}
+
+impl Default for RadiusUpdate {
+    fn default() -> Self {
+        RadiusUpdate {
+            shrink_below: 0.25,
+            expand_above: 0.75,
+            shrink: 0.25,
+            expand: 2.0,
+        }
+    }
+}
+
+/// Options for [`trust_region`] and [`trust_region_hvp`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrustRegionOptions {
+    /// Gradient, step and function-change tolerance
+    pub tol: Tolerance,
+    pub max_iter: usize,
+    pub subproblem: Subproblem,
+    pub initial_radius: f64,
+    pub max_radius: f64,
+    /// A step is accepted when `rho` exceeds this value
+    pub accept_ratio: f64,
+    pub radius_update: RadiusUpdate,
+}
+
+impl Default for TrustRegionOptions {
+    fn default() -> Self {
+        TrustRegionOptions {
+            tol: Tolerance::Default,
+            max_iter: 1_000,
+            subproblem: Subproblem::default(),
+            initial_radius: 1.0,
+            max_radius: 1e10,
+            accept_ratio: 1e-4,
+            radius_update: RadiusUpdate::default(),
+        }
+    }
+}
+
+type Product<'a> = Box<dyn Fn(&[f64]) -> Vec<f64> + 'a>;
+
+// Curvature information at the current iterate.
+enum Curvature<'a> {
+    Matrix(Matrix),
+    Product(Product<'a>),
+}
+
+impl Curvature<'_> {
+    fn apply(&self, v: &[f64]) -> Vec<f64> {
+        match self {
+            Curvature::Matrix(b) => b.mul_vec(v),
+            Curvature::Product(hv) => hv(v),
+        }
+    }
+}
+
+/// Minimizes `fg` by a trust-region Newton method with an explicit Hessian.
+///
+/// `fg(x, g)` returns `f(x)` and writes the gradient into `g`; `hess(x, h)`
+/// writes the symmetric Hessian at `x` into the `n x n` matrix `h`. The
+/// result carries the Hessian at the solution.
+pub fn trust_region<F, H>(
+    fg: F,
+    hess: H,
+    x0: &[f64],
+    opts: &TrustRegionOptions,
+) -> Result<GradientMinimum, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+    H: Fn(&[f64], &mut Matrix),
+{
+    let n = x0.len();
+    run(&fg, x0, opts, |x| {
+        let mut h = Matrix::zeros(n, n);
+        hess(x, &mut h);
+        if !h.is_finite() {
+            return Err(NumalError::DerivativeNotComputable);
+        }
+        Ok(Curvature::Matrix(h))
+    })
+}
+
+/// Minimizes `fg` by a trust-region Newton method using Hessian-vector
+/// products.
+///
+/// `hvp(x, v, hv)` writes the product of the Hessian at `x` with `v` into
+/// `hv`. Only the [`Subproblem::Cauchy`] and [`Subproblem::SteihaugToint`]
+/// solvers can work from products alone.
+pub fn trust_region_hvp<F, H>(
+    fg: F,
+    hvp: H,
+    x0: &[f64],
+    opts: &TrustRegionOptions,
+) -> Result<GradientMinimum, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+    H: Fn(&[f64], &[f64], &mut [f64]),
+{
+    if matches!(opts.subproblem, Subproblem::Dogleg | Subproblem::Exact) {
+        return Err(NumalError::InvalidInput(format!(
+            "the {:?} subproblem solver needs an explicit Hessian",
+            opts.subproblem
+        )));
+    }
+    let hvp = &hvp;
+    run(&fg, x0, opts, |x| {
+        let x = x.to_vec();
+        Ok(Curvature::Product(Box::new(move |v: &[f64]| {
+            let mut hv = vec![0.0; v.len()];
+            hvp(&x, v, &mut hv);
+            hv
+        })))
+    })
+}
+
+fn check_options(opts: &TrustRegionOptions) -> Result<(), NumalError> {
+    let u = &opts.radius_update;
+    if !(opts.initial_radius > 0.0 && opts.initial_radius <= opts.max_radius) {
+        return Err(NumalError::InvalidInput(format!(
+            "radii must satisfy 0 < initial_radius <= max_radius, got {} and {}",
+            opts.initial_radius, opts.max_radius
+        )));
+    }
+    let valid = 0.0 <= opts.accept_ratio
+        && opts.accept_ratio < 1.0
+        && 0.0 < u.shrink_below
+        && u.shrink_below <= u.expand_above
+        && u.expand_above < 1.0
+        && 0.0 < u.shrink
+        && u.shrink < 1.0
+        && u.expand > 1.0;
+    if !valid {
+        return Err(NumalError::InvalidInput(format!(
+            "invalid trust-region ratios: accept_ratio = {}, {u:?}",
+            opts.accept_ratio
+        )));
+    }
+    Ok(())
+}
+
+fn run<'a, F, C>(
+    fg: &F,
+    x0: &[f64],
+    opts: &TrustRegionOptions,
+    curvature: C,
+) -> Result<GradientMinimum, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]) -> f64,
+    C: Fn(&[f64]) -> Result<Curvature<'a>, NumalError>,
+{
+    check_options(opts)?;
+    let (mut f, mut g) = initial_gradient(fg, x0)?;
+    let mut x = x0.to_vec();
+    let mut evals = 1;
+    let mut b = curvature(&x)?;
+    let hessian = |b: Curvature| match b {
+        Curvature::Matrix(h) => Some(h),
+        Curvature::Product(_) => None,
+    };
+    if g.iter().all(|v| v.abs() <= opts.tol.eps_abs()) {
+        let h = hessian(b);
+        return Ok(gradient_minimum(
+            x,
+            f,
+            g,
+            0,
+            evals,
+            Termination::GradientNorm,
+            h,
+        ));
+    }
+    let u = opts.radius_update;
+    let mut radius = opts.initial_radius;
+    for iter in 1..=opts.max_iter {
+        let p = match (opts.subproblem, &b) {
+            (Subproblem::Cauchy, _) => cauchy(&g, &b, radius),
+            (Subproblem::Dogleg, Curvature::Matrix(h)) => dogleg(&g, h, radius),
+            (Subproblem::Exact, Curvature::Matrix(h)) => exact(&g, h, radius),
+            _ => steihaug(&g, &b, radius),
+        };
+        let bp = b.apply(&p);
+        let predicted = -(dot(&g, &p) + 0.5 * dot(&p, &bp));
+        let step = norm(&p);
+        let x_new: Vec<f64> = x.iter().zip(&p).map(|(a, b)| a + b).collect();
+        let mut g_new = vec![0.0; x.len()];
+        let f_new = fg(&x_new, &mut g_new);
+        evals += 1;
+        let finite = f_new.is_finite() && g_new.iter().all(|v| v.is_finite());
+        let rho = if predicted > 0.0 && finite {
+            (f - f_new) / predicted
+        } else {
+            f64::NEG_INFINITY
+        };
+
+        if rho < u.shrink_below {
+            radius = u.shrink * step.min(radius);
+        } else if rho > u.expand_above && step >= 0.99 * radius {
+            radius = (u.expand * radius).min(opts.max_radius);
+        }
+        if rho > opts.accept_ratio {
+            let (x_old, f_old) = (std::mem::replace(&mut x, x_new), f);
+            f = f_new;
+            g = g_new;
+            b = curvature(&x)?;
+            if let Some(reason) = gradient_termination(&g, &x_old, &x, f_old, f, opts.tol) {
+                return Ok(gradient_minimum(x, f, g, iter, evals, reason, hessian(b)));
+            }
+        } else if radius <= f64::EPSILON * norm(&x).max(1.0) {
+            // The model no longer predicts the objective at any resolvable
+            // step length
+            return Err(NumalError::DidNotConverge);
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+// Largest `t >= 0` with `|p + t d| = radius`, for `|p| <= radius`.
+fn to_boundary(p: &[f64], d: &[f64], radius: f64) -> f64 {
+    let a = dot(d, d);
+    let b = 2.0 * dot(p, d);
+    let c = dot(p, p) - radius * radius;
+    let disc = (b * b - 4.0 * a * c).max(0.0).sqrt();
+    // Avoid cancellation between -b and disc
+    let t = if b > 0.0 {
+        -2.0 * c / (b + disc)
+    } else {
+        (disc - b) / (2.0 * a)
+    };
+    if t.is_finite() { t.max(0.0) } else { 0.0 }
+}
+
+fn cauchy(g: &[f64], b: &Curvature, radius: f64) -> Vec<f64> {
+    let gn = norm(g);
+    let gbg = dot(g, &b.apply(g));
+    let tau = if gbg <= 0.0 {
+        1.0
+    } else {
+        (gn.powi(3) / (radius * gbg)).min(1.0)
+    };
+    g.iter().map(|v| -tau * radius / gn * v).collect()
+}
+
+fn dogleg(g: &[f64], b: &Matrix, radius: f64) -> Vec<f64> {
+    let Some(chol) = Cholesky::new(b, 0.0) else {
+        return cauchy(g, &Curvature::Matrix(b.clone()), radius);
+    };
+    let newton: Vec<f64> = chol.solve(g).into_iter().map(|v| -v).collect();
+    if norm(&newton) <= radius {
+        return newton;
+    }
+    let gbg = dot(g, &b.mul_vec(g));
+    let scale = -dot(g, g) / gbg;
+    let steepest: Vec<f64> = g.iter().map(|v| scale * v).collect();
+    let sn = norm(&steepest);
+    if sn >= radius {
+        return steepest.iter().map(|v| v * radius / sn).collect();
+    }
+    let leg: Vec<f64> = newton.iter().zip(&steepest).map(|(a, b)| a - b).collect();
+    let t = to_boundary(&steepest, &leg, radius);
+    steepest.iter().zip(&leg).map(|(s, l)| s + t * l).collect()
+}
+
+fn steihaug(g: &[f64], b: &Curvature, radius: f64) -> Vec<f64> {
+    let n = g.len();
+    let gn = norm(g);
+    let eps = gn * gn.sqrt().min(0.5);
+    let mut z = vec![0.0; n];
+    let mut r = g.to_vec();
+    let mut d: Vec<f64> = g.iter().map(|v| -v).collect();
+    let mut rr = dot(&r, &r);
+    let boundary = |z: &[f64], d: &[f64]| -> Vec<f64> {
+        let t = to_boundary(z, d, radius);
+        z.iter().zip(d).map(|(a, b)| a + t * b).collect()
+    };
+    for _ in 0..2 * n {
+        let bd = b.apply(&d);
+        let dbd = dot(&d, &bd);
+        if dbd <= 0.0 || !dbd.is_finite() {
+            return boundary(&z, &d);
+        }
+        let alpha = rr / dbd;
+        let z_new: Vec<f64> = z.iter().zip(&d).map(|(a, b)| a + alpha * b).collect();
+        if norm(&z_new) >= radius {
+            return boundary(&z, &d);
+        }
+        r.iter_mut().zip(&bd).for_each(|(ri, bi)| *ri += alpha * bi);
+        let rr_new = dot(&r, &r);
+        z = z_new;
+        if rr_new.sqrt() < eps {
+            break;
+        }
+        let beta = rr_new / rr;
+        d.iter_mut()
+            .zip(&r)
+            .for_each(|(di, ri)| *di = beta * *di - ri);
+        rr = rr_new;
+    }
+    z
+}
+
+// Moré & Sorensen (1983): Newton's method on 1/|p(l)| - 1/radius, where
+// (B + l I) p(l) = -g, safeguarded by an interval [lo, hi] known to contain
+// the optimal multiplier.
+fn exact(g: &[f64], b: &Matrix, radius: f64) -> Vec<f64> {
+    let n = g.len();
+    let gn = norm(g);
+    let b_norm = (0..n)
+        .map(|i| b.row(i).iter().map(|v| v.abs()).sum::<f64>())
+        .fold(0.0, f64::max);
+    let min_diag = (0..n).map(|i| b[(i, i)]).fold(f64::INFINITY, f64::min);
+    let mut lo = (gn / radius - b_norm).max(-min_diag).max(0.0);
+    let mut hi = gn / radius + b_norm;
+    let mut lambda = 0.0;
+    let mut last = None;
+    for _ in 0..MS_MAX_ITER {
+        let midpoint = |lo: f64, hi: f64| (lo * hi).sqrt().max(lo + 1e-3 * (hi - lo));
+        let Some(chol) = Cholesky::new(b, lambda) else {
+            lo = lo.max(lambda);
+            lambda = midpoint(lo, hi);
+            continue;
+        };
+        let p: Vec<f64> = chol.solve(g).into_iter().map(|v| -v).collect();
+        let pn = norm(&p);
+        if (lambda == 0.0 && pn <= radius) || (pn - radius).abs() <= MS_KAPPA * radius {
+            return p;
+        }
+        if pn < radius {
+            hi = lambda;
+        } else {
+            lo = lambda;
+        }
+        let q = chol.solve_lower(&p);
+        let next = lambda + (pn / norm(&q)).powi(2) * (pn - radius) / radius;
+        last = Some((p, chol));
+        if hi - lo <= f64::EPSILON * hi {
+            break;
+        }
+        lambda = if next > lo && next < hi {
+            next
+        } else {
+            midpoint(lo, hi)
+        };
+    }
+    let Some((p, chol)) = last else {
+        return cauchy(g, &Curvature::Matrix(b.clone()), radius);
+    };
+    let pn = norm(&p);
+    if pn > radius {
+        return p.iter().map(|v| v * radius / pn).collect();
+    }
+    // Hard case: g is (nearly) orthogonal to the eigenvectors of the
+    // smallest eigenvalue. Reach the boundary along an approximate such
+    // eigenvector, found by inverse iteration with the last factorization.
+    let k = (0..n)
+        .min_by(|&i, &j| b[(i, i)].total_cmp(&b[(j, j)]))
+        .unwrap_or(0);
+    let mut z = vec![0.0; n];
+    z[k] = 1.0;
+    for _ in 0..5 {
+        z = chol.solve(&z);
+        let zn = norm(&z);
+        z.iter_mut().for_each(|v| *v /= zn);
+    }
+    let t = to_boundary(&p, &z, radius);
+    p.iter().zip(&z).map(|(a, b)| a + t * b).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::testproblems::rosenbrock_grad;
+
+    fn rosenbrock_hessian(x: &[f64], h: &mut Matrix) {
+        let (a, b) = (x[0], x[1]);
+        let off = -400.0 * a;
+        *h = Matrix::from_row_slice(2, 2, &[1200.0 * a * a - 400.0 * b + 2.0, off, off, 200.0]);
+    }
+
+    #[test]
+    fn every_subproblem_minimizes_rosenbrock() {
+        for subproblem in [
+            Subproblem::Cauchy,
+            Subproblem::Dogleg,
+            Subproblem::SteihaugToint,
+            Subproblem::Exact,
+        ] {
+            let opts = TrustRegionOptions {
+                tol: Tolerance::Strict,
+                max_iter: 50_000,
+                subproblem,
+                ..Default::default()
+            };
+            let r = trust_region(rosenbrock_grad, rosenbrock_hessian, &[-1.2, 1.0], &opts).unwrap();
+            assert!(
+                r.x.iter().all(|v| (v - 1.0).abs() < 1e-5),
+                "{subproblem:?}: {r:?}"
+            );
+            assert!(r.hessian.is_some());
+        }
+    }
+
+    #[test]
+    fn hessian_vector_products() {
+        // Extended Rosenbrock in 20 variables; the Hessian is block diagonal
+        let fg = |x: &[f64], g: &mut [f64]| {
+            let mut f = 0.0;
+            for ((xs, gs), _) in x.chunks(2).zip(g.chunks_mut(2)).zip(0..) {
+                f += rosenbrock_grad(xs, gs);
+            }
+            f
+        };
+        let hvp = |x: &[f64], v: &[f64], hv: &mut [f64]| {
+            let mut h = Matrix::zeros(2, 2);
+            for ((xs, vs), out) in x.chunks(2).zip(v.chunks(2)).zip(hv.chunks_mut(2)) {
+                rosenbrock_hessian(xs, &mut h);
+                out.copy_from_slice(&h.mul_vec(vs));
+            }
+        };
+        let x0: Vec<f64> = (0..20)
+            .map(|i| if i % 2 == 0 { -1.2 } else { 1.0 })
+            .collect();
+        let opts = TrustRegionOptions {
+            tol: Tolerance::Strict,
+            ..Default::default()
+        };
+        let r = trust_region_hvp(fg, hvp, &x0, &opts).unwrap();
+        assert!(r.x.iter().all(|v| (v - 1.0).abs() < 1e-5), "{r:?}");
+        assert!(r.hessian.is_none());
+    }
+
+    #[test]
+    fn exact_solver_escapes_saddle_in_hard_case() {
+        // f = x^2 - y^2 + y^4 / 4 from (1, 0): the gradient has no component
+        // along the direction of negative curvature, so only the exact solver
+        // leaves the axis y = 0 and finds a minimum at (0, ±√2)
+        let fg = |x: &[f64], g: &mut [f64]| {
+            g[0] = 2.0 * x[0];
+            g[1] = -2.0 * x[1] + x[1].powi(3);
+            x[0] * x[0] - x[1] * x[1] + x[1].powi(4) / 4.0
+        };
+        let hess = |x: &[f64], h: &mut Matrix| {
+            *h = Matrix::from_row_slice(2, 2, &[2.0, 0.0, 0.0, -2.0 + 3.0 * x[1] * x[1]]);
+        };
+        let opts = TrustRegionOptions {
+            subproblem: Subproblem::Exact,
+            ..Default::default()
+        };
+        let r = trust_region(fg, hess, &[1.0, 0.0], &opts).unwrap();
+        assert!((r.fx + 1.0).abs() < 1e-8, "{r:?}");
+        assert!((r.x[1].abs() - 2f64.sqrt()).abs() < 1e-4, "{r:?}");
+    }
+
+    #[test]
+    fn inconsistent_model_did_not_converge() {
+        // The gradient points the wrong way, so no step is ever accepted
+        let fg = |x: &[f64], g: &mut [f64]| {
+            g[0] = -2.0 * x[0];
+            x[0] * x[0]
+        };
+        let hess = |_: &[f64], h: &mut Matrix| *h = Matrix::identity(1);
+        let r = trust_region(fg, hess, &[1.0], &TrustRegionOptions::default());
+        assert_eq!(r, Err(NumalError::DidNotConverge));
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        let hvp = |_: &[f64], v: &[f64], hv: &mut [f64]| hv.copy_from_slice(v);
+        let opts = TrustRegionOptions {
+            subproblem: Subproblem::Exact,
+            ..Default::default()
+        };
+        let r = trust_region_hvp(rosenbrock_grad, hvp, &[0.0, 0.0], &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let opts = TrustRegionOptions {
+            radius_update: RadiusUpdate {
+                expand: 0.5,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let r = trust_region(rosenbrock_grad, rosenbrock_hessian, &[0.0, 0.0], &opts);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,463 @@
+//! Bracketed solvers for f(x) = 0 on an interval [a, b] with a sign change.
+//!
+//! Every solver keeps the root enclosed at each step, so convergence is
+//! guaranteed for continuous `f` given a large enough iteration budget.
+
+use super::{RootResult, converged};
+use crate::NumalError;
+use crate::core::tolerance::Tolerance;
+
+// A validated starting bracket, or a root sitting on one of its endpoints.
+pub(super) enum Start {
+    Root(RootResult),
+    Bracket { a: f64, b: f64, fa: f64, fb: f64 },
+}
+
+pub(super) fn start<F: Fn(f64) -> f64>(f: &F, a: f64, b: f64) -> Result<Start, NumalError> {
+    if !a.is_finite() || !b.is_finite() {
+        return Err(NumalError::InvalidInput(format!(
+            "bracket endpoints must be finite, got [{a}, {b}]"
+        )));
+    }
+    if a == b {
+        return Err(NumalError::InvalidInput(format!(
+            "bracket must have non-zero width, got [{a}, {b}]"
+        )));
+    }
+    let (a, b) = if a < b { (a, b) } else { (b, a) };
+    let (fa, fb) = (f(a), f(b));
+    if fa.is_nan() || fb.is_nan() {
+        return Err(NumalError::InvalidInput(format!(
+            "function is NaN at a bracket endpoint: f({a}) = {fa}, f({b}) = {fb}"
+        )));
+    }
+    for (x, fx) in [(a, fa), (b, fb)] {
+        if fx == 0.0 {
+            return Ok(Start::Root(RootResult {
+                root: x,
+                iterations: 0,
+                bracket_width: 0.0,
+            }));
+        }
+    }
+    if fa.signum() == fb.signum() {
+        return Err(NumalError::InvalidInput(format!(
+            "no sign change on [{a}, {b}]: f(a) = {fa}, f(b) = {fb}"
+        )));
+    }
+    Ok(Start::Bracket { a, b, fa, fb })
+}
+
+pub(super) fn eval<F: Fn(f64) -> f64>(f: &F, x: f64) -> Result<f64, NumalError> {
+    let fx = f(x);
+    if fx.is_nan() {
+        return Err(NumalError::InvalidInput(format!(
+            "function returned NaN at x = {x}"
+        )));
+    }
+    Ok(fx)
+}
+
+/// Bisection method.
+///
+/// Halves the bracket every iteration until its width satisfies `tol`.
+pub fn bisection<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<RootResult, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    let (mut a, mut b, mut fa) = match start(&f, a, b)? {
+        Start::Root(r) => return Ok(r),
+        Start::Bracket { a, b, fa, .. } => (a, b, fa),
+    };
+    for iter in 1..=max_iter {
+        let m = a + 0.5 * (b - a);
+        let fm = eval(&f, m)?;
+        if fm == 0.0 {
+            return Ok(RootResult {
+                root: m,
+                iterations: iter,
+                bracket_width: 0.0,
+            });
+        }
+        if fm.signum() == fa.signum() {
+            a = m;
+            fa = fm;
+        } else {
+            b = m;
+        }
+        if converged(a, b, tol) {
+            return Ok(RootResult {
+                root: a + 0.5 * (b - a),
+                iterations: iter,
+                bracket_width: b - a,
+            });
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+/// Regula falsi with the Illinois modification.
+///
+/// Halves the function value at an endpoint that is retained twice in a row,
+/// which avoids the one-sided stagnation of plain false position.
+pub fn illinois<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<RootResult, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    let (mut a, mut b, mut fa, mut fb) = match start(&f, a, b)? {
+        Start::Root(r) => return Ok(r),
+        Start::Bracket { a, b, fa, fb } => (a, b, fa, fb),
+    };
+    for iter in 1..=max_iter {
+        let c = b - fb * (b - a) / (fb - fa);
+        let fc = eval(&f, c)?;
+        if fc == 0.0 {
+            return Ok(RootResult {
+                root: c,
+                iterations: iter,
+                bracket_width: 0.0,
+            });
+        }
+        if fc.signum() != fb.signum() {
+            a = b;
+            fa = fb;
+        } else {
+            fa *= 0.5;
+        }
+        b = c;
+        fb = fc;
+        if converged(a, b, tol) {
+            return Ok(RootResult {
+                root: b,
+                iterations: iter,
+                bracket_width: (b - a).abs(),
+            });
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+/// Ridders' method.
+///
+/// Fits an exponential through the endpoints and midpoint of the bracket,
+/// giving quadratic convergence while never leaving the bracket.
+pub fn ridders<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<RootResult, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    let (mut a, mut b, mut fa, mut fb) = match start(&f, a, b)? {
+        Start::Root(r) => return Ok(r),
+        Start::Bracket { a, b, fa, fb } => (a, b, fa, fb),
+    };
+    for iter in 1..=max_iter {
+        let m = a + 0.5 * (b - a);
+        let fm = eval(&f, m)?;
+        let s = (fm * fm - fa * fb).sqrt();
+        if fm == 0.0 || s == 0.0 {
+            return Ok(RootResult {
+                root: m,
+                iterations: iter,
+                bracket_width: 0.0,
+            });
+        }
+        let x = m + (m - a) * (fa - fb).signum() * fm / s;
+        let fx = eval(&f, x)?;
+        if fx == 0.0 {
+            return Ok(RootResult {
+                root: x,
+                iterations: iter,
+                bracket_width: 0.0,
+            });
+        }
+        if fm.signum() != fx.signum() {
+            (a, fa, b, fb) = if m < x {
+                (m, fm, x, fx)
+            } else {
+                (x, fx, m, fm)
+            };
+        } else if fa.signum() != fx.signum() {
+            b = x;
+            fb = fx;
+        } else {
+            a = x;
+            fa = fx;
+        }
+        if converged(a, b, tol) {
+            return Ok(RootResult {
+                root: x,
+                iterations: iter,
+                bracket_width: b - a,
+            });
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+/// Brent–Dekker method.
+///
+/// Combines inverse quadratic interpolation and the secant step with
+/// bisection as a fallback, so it is never slower than bisection by more
+/// than a constant factor.
+pub fn brent<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<RootResult, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    let (mut a, mut b, mut fa, mut fb) = match start(&f, a, b)? {
+        Start::Root(r) => return Ok(r),
+        Start::Bracket { a, b, fa, fb } => (a, b, fa, fb),
+    };
+    let (mut c, mut fc) = (b, fb);
+    let (mut d, mut e) = (b - a, b - a);
+    for iter in 1..=max_iter {
+        if fb.signum() == fc.signum() {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            (a, b, c) = (b, c, b);
+            (fa, fb, fc) = (fb, fc, fb);
+        }
+        let tol1 = 2.0 * f64::EPSILON * b.abs() + 0.5 * (tol.eps_abs() + tol.eps_rel() * b.abs());
+        let xm = 0.5 * (c - b);
+        if xm.abs() <= tol1 || fb == 0.0 {
+            return Ok(RootResult {
+                root: b,
+                iterations: iter - 1,
+                bracket_width: (c - b).abs(),
+            });
+        }
+        if e.abs() >= tol1 && fa.abs() > fb.abs() {
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2.0 * xm * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * xm * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            }
+            p = p.abs();
+            if 2.0 * p < (3.0 * xm * q - (tol1 * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                d = xm;
+                e = d;
+            }
+        } else {
+            d = xm;
+            e = d;
+        }
+        a = b;
+        fa = fb;
+        b += if d.abs() > tol1 { d } else { tol1.copysign(xm) };
+        fb = eval(&f, b)?;
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+/// ITP (Interpolate, Truncate, Project) method of Oliveira and Takahashi.
+///
+/// Matches the worst-case iteration count of bisection plus one while
+/// converging superlinearly on well-behaved functions. Uses the
+/// recommended hyper-parameters `k1 = 0.2 / (b - a)`, `k2 = 2`, `n0 = 1`.
+pub fn itp<F>(
+    f: F,
+    a: f64,
+    b: f64,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<RootResult, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    let (mut a, mut b, mut fa, mut fb) = match start(&f, a, b)? {
+        Start::Root(r) => return Ok(r),
+        Start::Bracket { a, b, fa, fb } => (a, b, fa, fb),
+    };
+    let eps = 0.5 * (tol.eps_abs() + tol.eps_rel() * a.abs().min(b.abs()));
+    // The bound on the iteration count is infinite without a positive
+    // tolerance
+    if eps <= 0.0 {
+        return Err(NumalError::InvalidInput(format!(
+            "itp needs a positive tolerance on [{a}, {b}], got {eps}"
+        )));
+    }
+    let k1 = 0.2 / (b - a);
+    let k2 = 2.0;
+    let n_half = ((b - a) / (2.0 * eps)).log2().ceil().max(0.0) as i32;
+    let n_max = n_half + 1;
+    for iter in 1..=max_iter {
+        if b - a <= 2.0 * eps {
+            return Ok(RootResult {
+                root: a + 0.5 * (b - a),
+                iterations: iter - 1,
+                bracket_width: b - a,
+            });
+        }
+        let x_half = a + 0.5 * (b - a);
+        let r = eps * 2f64.powi(n_max - iter as i32 + 1) - 0.5 * (b - a);
+        let delta = k1 * (b - a).powf(k2);
+        let x_f = (b * fa - a * fb) / (fa - fb);
+        let sigma = (x_half - x_f).signum();
+        let x_t = if delta <= (x_half - x_f).abs() {
+            x_f + sigma * delta
+        } else {
+            x_half
+        };
+        let x = if (x_t - x_half).abs() <= r {
+            x_t
+        } else {
+            x_half - sigma * r
+        };
+        let fx = eval(&f, x)?;
+        if fx == 0.0 {
+            return Ok(RootResult {
+                root: x,
+                iterations: iter,
+                bracket_width: 0.0,
+            });
+        }
+        if fx.signum() == fa.signum() {
+            a = x;
+            fa = fx;
+        } else {
+            b = x;
+            fb = fx;
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Solver = fn(fn(f64) -> f64, f64, f64, Tolerance, usize) -> Result<RootResult, NumalError>;
+
+    const SOLVERS: [(&str, Solver); 5] = [
+        ("bisection", bisection),
+        ("illinois", illinois),
+        ("ridders", ridders),
+        ("brent", brent),
+        ("itp", itp),
+    ];
+
+    #[test]
+    fn all_solvers_find_sqrt_two() {
+        for (name, solve) in SOLVERS {
+            let r = solve(|x| x * x - 2.0, 0.0, 2.0, Tolerance::Strict, 200).unwrap();
+            assert!((r.root - 2f64.sqrt()).abs() < 1e-10, "{name}: {r:?}");
+            assert!(r.bracket_width <= 1e-9, "{name}: {r:?}");
+        }
+    }
+
+    #[test]
+    fn all_solvers_handle_transcendental_root() {
+        // cos(x) = x at x ≈ 0.739085133215161
+        for (name, solve) in SOLVERS {
+            let r = solve(|x| x.cos() - x, 0.0, 1.0, Tolerance::Strict, 200).unwrap();
+            assert!(
+                (r.root - 0.739_085_133_215_160_6).abs() < 1e-10,
+                "{name}: {r:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn reversed_bracket_is_accepted() {
+        for (name, solve) in SOLVERS {
+            let r = solve(|x| x - 0.25, 1.0, -1.0, Tolerance::Default, 200).unwrap();
+            assert!((r.root - 0.25).abs() < 1e-7, "{name}: {r:?}");
+        }
+    }
+
+    #[test]
+    fn superlinear_methods_beat_bisection() {
+        let f = |x: f64| x.exp() - 3.0;
+        let bis = bisection(f, 0.0, 2.0, Tolerance::Strict, 200).unwrap();
+        for solve in [brent as Solver, ridders, itp, illinois] {
+            let r = solve(f, 0.0, 2.0, Tolerance::Strict, 200).unwrap();
+            assert!(r.iterations < bis.iterations);
+        }
+    }
+
+    #[test]
+    fn root_at_endpoint_returns_immediately() {
+        for (name, solve) in SOLVERS {
+            let r = solve(|x| x - 1.0, 1.0, 3.0, Tolerance::Default, 10).unwrap();
+            assert_eq!(r.root, 1.0, "{name}");
+            assert_eq!(r.iterations, 0, "{name}");
+        }
+    }
+
+    #[test]
+    fn no_sign_change_is_invalid_input() {
+        for (name, solve) in SOLVERS {
+            let r = solve(|x| x * x + 1.0, -1.0, 1.0, Tolerance::Default, 100);
+            assert!(matches!(r, Err(NumalError::InvalidInput(_))), "{name}");
+        }
+    }
+
+    #[test]
+    fn nan_endpoint_is_invalid_input() {
+        for (name, solve) in SOLVERS {
+            let r = solve(|x| x.ln(), -1.0, 2.0, Tolerance::Default, 100);
+            assert!(matches!(r, Err(NumalError::InvalidInput(_))), "{name}");
+            let r = solve(|x| x, f64::NAN, 2.0, Tolerance::Default, 100);
+            assert!(matches!(r, Err(NumalError::InvalidInput(_))), "{name}");
+        }
+    }
+
+    #[test]
+    fn itp_rejects_zero_tolerance() {
+        let relative = Tolerance::Custom {
+            eps_abs: 0.0,
+            eps_rel: 1e-10,
+        };
+        // The relative tolerance vanishes at the endpoint 0
+        let r = itp(|x| x - 1.0, 0.0, 3.0, relative, 100);
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        let r = itp(|x| x - 1.0, 0.5, 3.0, relative, 100).unwrap();
+        assert!((r.root - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn exhausted_budget_did_not_converge() {
+        for (name, solve) in SOLVERS {
+            let r = solve(|x| x * x * x - 0.3, 0.0, 10.0, Tolerance::Strict, 2);
+            assert_eq!(r, Err(NumalError::DidNotConverge), "{name}");
+        }
+    }
+}
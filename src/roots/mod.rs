@@ -0,0 +1,29 @@
+//! Root finding for scalar equations, polynomials and nonlinear systems.
+
+pub mod bracket;
+pub mod open;
+pub mod poly;
+pub mod system;
+
+pub use bracket::{bisection, brent, illinois, itp, ridders};
+pub use open::{halley, newton, schroder, secant, steffensen};
+pub use poly::{PolyMethod, PolyRoot, poly_roots, poly_roots_complex};
+
+use crate::core::tolerance::{Tolerance, is_close};
+
+/// Outcome of a successful root search
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RootResult {
+    /// The approximate root
+    pub root: f64,
+    /// Number of iterations performed
+    pub iterations: usize,
+    /// Width of the final bracket around the root. Open methods run without
+    /// a safeguarding bracket report the size of their last step instead.
+    pub bracket_width: f64,
+}
+
+// Two iterates are considered converged when they are close under `tol`.
+pub(crate) fn converged(a: f64, b: f64, tol: Tolerance) -> bool {
+    is_close(a, b, tol).is_ok()
+}
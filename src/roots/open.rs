@@ -0,0 +1,355 @@
+//! Open (non-bracketing) solvers for f(x) = 0 started from an initial guess.
+//!
+//! Each solver accepts an optional safeguarding bracket `(a, b)` with a sign
+//! change. When present, the bracket is tightened after every evaluation and
+//! any step that leaves it, or that cannot be computed, is replaced by a
+//! bisection step. Without a bracket a zero, NaN or infinite derivative is
+//! reported as [`NumalError::DerivativeNotComputable`].
+
+use super::bracket::{Start, eval, start};
+use super::{RootResult, converged};
+use crate::NumalError;
+use crate::core::tolerance::Tolerance;
+
+// Bracket maintained alongside the iterates when safeguarding is requested.
+struct Guard {
+    lo: f64,
+    hi: f64,
+    f_lo: f64,
+}
+
+impl Guard {
+    fn update(&mut self, x: f64, fx: f64) {
+        if x > self.lo && x < self.hi {
+            if fx.signum() == self.f_lo.signum() {
+                self.lo = x;
+                self.f_lo = fx;
+            } else {
+                self.hi = x;
+            }
+        }
+    }
+
+    fn contains(&self, x: f64) -> bool {
+        x > self.lo && x < self.hi
+    }
+
+    fn mid(&self) -> f64 {
+        self.lo + 0.5 * (self.hi - self.lo)
+    }
+}
+
+fn derivative(d: f64) -> Result<f64, NumalError> {
+    if d == 0.0 || !d.is_finite() {
+        Err(NumalError::DerivativeNotComputable)
+    } else {
+        Ok(d)
+    }
+}
+
+// Shared iteration loop. `step` evaluates the function at `x` and proposes a
+// correction `dx`, or fails with `DerivativeNotComputable`.
+fn iterate<F, S>(
+    f: F,
+    mut step: S,
+    x0: f64,
+    tol: Tolerance,
+    max_iter: usize,
+    bracket: Option<(f64, f64)>,
+) -> Result<RootResult, NumalError>
+where
+    F: Fn(f64) -> f64,
+    S: FnMut(f64) -> Result<(f64, Result<f64, NumalError>), NumalError>,
+{
+    if !x0.is_finite() {
+        return Err(NumalError::InvalidInput(format!(
+            "initial guess must be finite, got {x0}"
+        )));
+    }
+    let mut guard = match bracket {
+        None => None,
+        Some((a, b)) => match start(&f, a, b)? {
+            Start::Root(r) => return Ok(r),
+            Start::Bracket { a, b, fa, .. } => Some(Guard {
+                lo: a,
+                hi: b,
+                f_lo: fa,
+            }),
+        },
+    };
+    let mut x = match &guard {
+        Some(g) if !(g.lo..=g.hi).contains(&x0) => g.mid(),
+        _ => x0,
+    };
+    for iter in 1..=max_iter {
+        let (fx, dx) = step(x)?;
+        if fx.is_nan() {
+            return Err(NumalError::InvalidInput(format!(
+                "function returned NaN at x = {x}"
+            )));
+        }
+        if fx.abs() <= tol.eps_abs() {
+            let bracket_width = guard.as_ref().map_or(0.0, |g| g.hi - g.lo);
+            return Ok(RootResult {
+                root: x,
+                iterations: iter,
+                bracket_width,
+            });
+        }
+        let x_new = match guard.as_mut() {
+            Some(g) => {
+                g.update(x, fx);
+                match dx {
+                    Ok(dx) if g.contains(x + dx) => x + dx,
+                    Ok(_) | Err(NumalError::DerivativeNotComputable) => g.mid(),
+                    Err(e) => return Err(e),
+                }
+            }
+            None => x + dx?,
+        };
+        if !x_new.is_finite() {
+            return Err(NumalError::DidNotConverge);
+        }
+        let bracket_converged = guard.as_ref().is_some_and(|g| converged(g.lo, g.hi, tol));
+        if converged(x, x_new, tol) || bracket_converged {
+            let bracket_width = guard.as_ref().map_or((x_new - x).abs(), |g| g.hi - g.lo);
+            return Ok(RootResult {
+                root: x_new,
+                iterations: iter,
+                bracket_width,
+            });
+        }
+        x = x_new;
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+/// Newton–Raphson method.
+///
+/// `fdf` returns `(f(x), f'(x))`.
+pub fn newton<F>(
+    fdf: F,
+    x0: f64,
+    tol: Tolerance,
+    max_iter: usize,
+    bracket: Option<(f64, f64)>,
+) -> Result<RootResult, NumalError>
+where
+    F: Fn(f64) -> (f64, f64),
+{
+    let step = |x| {
+        let (f, df) = fdf(x);
+        Ok((f, derivative(df).map(|df| -f / df)))
+    };
+    iterate(|x| fdf(x).0, step, x0, tol, max_iter, bracket)
+}
+
+/// Halley's method, cubically convergent for simple roots.
+///
+/// `fdf` returns `(f(x), f'(x), f''(x))`.
+pub fn halley<F>(
+    fdf: F,
+    x0: f64,
+    tol: Tolerance,
+    max_iter: usize,
+    bracket: Option<(f64, f64)>,
+) -> Result<RootResult, NumalError>
+where
+    F: Fn(f64) -> (f64, f64, f64),
+{
+    let step = |x| {
+        let (f, df, d2f) = fdf(x);
+        let dx = derivative(df).and_then(|df| {
+            let denom = derivative(2.0 * df * df - f * d2f)?;
+            Ok(-2.0 * f * df / denom)
+        });
+        Ok((f, dx))
+    };
+    iterate(|x| fdf(x).0, step, x0, tol, max_iter, bracket)
+}
+
+/// Schröder's method, a second-derivative correction to Newton's step that
+/// behaves better than Halley near multiple roots.
+///
+/// `fdf` returns `(f(x), f'(x), f''(x))`.
+pub fn schroder<F>(
+    fdf: F,
+    x0: f64,
+    tol: Tolerance,
+    max_iter: usize,
+    bracket: Option<(f64, f64)>,
+) -> Result<RootResult, NumalError>
+where
+    F: Fn(f64) -> (f64, f64, f64),
+{
+    let step = |x| {
+        let (f, df, d2f) = fdf(x);
+        let dx = derivative(df).map(|df| {
+            let newton = f / df;
+            -newton - 0.5 * d2f * newton * newton / df
+        });
+        Ok((f, dx))
+    };
+    iterate(|x| fdf(x).0, step, x0, tol, max_iter, bracket)
+}
+
+/// Secant method started from the two points `x0` and `x1`.
+pub fn secant<F>(
+    f: F,
+    x0: f64,
+    x1: f64,
+    tol: Tolerance,
+    max_iter: usize,
+    bracket: Option<(f64, f64)>,
+) -> Result<RootResult, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    let mut prev = (x0, eval(&f, x0)?);
+    let step = |x| {
+        let fx = f(x);
+        let (xp, fp) = prev;
+        prev = (x, fx);
+        Ok((
+            fx,
+            derivative((fx - fp) / (x - xp)).map(|slope| -fx / slope),
+        ))
+    };
+    iterate(&f, step, x1, tol, max_iter, bracket)
+}
+
+/// Steffensen's method: quadratic convergence without derivatives, using
+/// the slope `(f(x + f(x)) - f(x)) / f(x)` in place of `f'(x)`.
+pub fn steffensen<F>(
+    f: F,
+    x0: f64,
+    tol: Tolerance,
+    max_iter: usize,
+    bracket: Option<(f64, f64)>,
+) -> Result<RootResult, NumalError>
+where
+    F: Fn(f64) -> f64,
+{
+    let step = |x| {
+        let fx = f(x);
+        let slope = derivative((f(x + fx) - fx) / fx);
+        Ok((fx, slope.map(|g| -fx / g)))
+    };
+    iterate(&f, step, x0, tol, max_iter, bracket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQRT2: f64 = std::f64::consts::SQRT_2;
+
+    #[test]
+    fn newton_finds_sqrt_two() {
+        let r = newton(|x| (x * x - 2.0, 2.0 * x), 1.0, Tolerance::Strict, 50, None).unwrap();
+        assert!((r.root - SQRT2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn second_order_methods_find_cube_root() {
+        let fdf = |x: f64| (x * x * x - 5.0, 3.0 * x * x, 6.0 * x);
+        let expected = 5f64.cbrt();
+        for solve in [halley, schroder] {
+            let r = solve(fdf, 1.0, Tolerance::Strict, 50, None).unwrap();
+            assert!((r.root - expected).abs() < 1e-12, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn halley_needs_fewer_iterations_than_newton() {
+        let f = |x: f64| x.exp() - 10.0;
+        let n = newton(|x| (f(x), x.exp()), 0.0, Tolerance::Strict, 50, None).unwrap();
+        let h = halley(
+            |x| (f(x), x.exp(), x.exp()),
+            0.0,
+            Tolerance::Strict,
+            50,
+            None,
+        )
+        .unwrap();
+        assert!((h.root - 10f64.ln()).abs() < 1e-12);
+        assert!(h.iterations < n.iterations);
+    }
+
+    #[test]
+    fn derivative_free_methods_find_sqrt_two() {
+        let f = |x: f64| x * x - 2.0;
+        let r = secant(f, 1.0, 2.0, Tolerance::Strict, 50, None).unwrap();
+        assert!((r.root - SQRT2).abs() < 1e-12);
+        let r = steffensen(f, 1.5, Tolerance::Strict, 50, None).unwrap();
+        assert!((r.root - SQRT2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn zero_derivative_is_reported() {
+        let r = newton(
+            |x| (x * x - 1.0, 2.0 * x),
+            0.0,
+            Tolerance::Default,
+            50,
+            None,
+        );
+        assert_eq!(r, Err(NumalError::DerivativeNotComputable));
+        let r = secant(|_| 1.0, 0.0, 1.0, Tolerance::Default, 50, None);
+        assert_eq!(r, Err(NumalError::DerivativeNotComputable));
+    }
+
+    #[test]
+    fn nan_or_infinite_derivative_is_reported() {
+        let r = newton(|x| (x - 1.0, f64::NAN), 0.0, Tolerance::Default, 50, None);
+        assert_eq!(r, Err(NumalError::DerivativeNotComputable));
+        let r = halley(
+            |x| (x - 1.0, f64::INFINITY, 0.0),
+            0.0,
+            Tolerance::Default,
+            50,
+            None,
+        );
+        assert_eq!(r, Err(NumalError::DerivativeNotComputable));
+    }
+
+    #[test]
+    fn bracket_falls_back_to_bisection_on_bad_derivative() {
+        let fdf = |x: f64| (x * x - 1.0, 2.0 * x);
+        let r = newton(fdf, 0.0, Tolerance::Strict, 100, Some((0.0, 3.0))).unwrap();
+        assert!((r.root - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn bracket_contains_divergent_newton() {
+        // Plain Newton on atan diverges from |x0| > 1.39
+        let fdf = |x: f64| (x.atan(), 1.0 / (1.0 + x * x));
+        assert!(newton(fdf, 2.0, Tolerance::Strict, 100, None).is_err());
+        let r = newton(fdf, 2.0, Tolerance::Strict, 100, Some((-3.0, 5.0))).unwrap();
+        assert!(r.root.abs() < 1e-12);
+    }
+
+    #[test]
+    fn invalid_bracket_is_rejected() {
+        let r = newton(
+            |x| (x * x + 1.0, 2.0 * x),
+            0.5,
+            Tolerance::Default,
+            50,
+            Some((0.0, 1.0)),
+        );
+        assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn exhausted_budget_did_not_converge() {
+        let r = newton(
+            |x| (x * x - 2.0, 2.0 * x),
+            100.0,
+            Tolerance::Strict,
+            3,
+            None,
+        );
+        assert_eq!(r, Err(NumalError::DidNotConverge));
+    }
+}
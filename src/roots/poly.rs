@@ -0,0 +1,475 @@
+//! All roots of a polynomial with real or complex coefficients.
+//!
+//! Coefficients are given in ascending order, so `coeffs[k]` multiplies
+//! `x^k`. Roots are computed either by Aberth–Ehrlich simultaneous
+//! iteration or as eigenvalues of the companion matrix, then polished with
+//! Newton's method on the original polynomial. Approximations that agree
+//! under the caller's [`Tolerance`] are merged into a single root with a
+//! multiplicity.
+
+use crate::NumalError;
+use crate::core::complex::Complex;
+use crate::core::tolerance::Tolerance;
+use std::f64::consts::TAU;
+
+/// Algorithm used to locate the polynomial roots
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PolyMethod {
+    /// Aberth–Ehrlich simultaneous iteration
+    #[default]
+    Aberth,
+    /// Eigenvalues of the balanced companion matrix via shifted QR
+    Companion,
+}
+
+/// A distinct root together with its detected multiplicity
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PolyRoot {
+    pub root: Complex,
+    pub multiplicity: usize,
+}
+
+/// Roots of a polynomial with real coefficients.
+///
+/// Roots whose imaginary part is within `tol` of zero are returned as real.
+pub fn poly_roots(
+    coeffs: &[f64],
+    method: PolyMethod,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<Vec<PolyRoot>, NumalError> {
+    let coeffs: Vec<Complex> = coeffs.iter().map(|&c| Complex::from(c)).collect();
+    let mut roots = poly_roots_complex(&coeffs, method, tol, max_iter)?;
+    for r in &mut roots {
+        if within(r.root.im.abs(), r.root.re.abs(), tol) {
+            r.root.im = 0.0;
+        }
+    }
+    Ok(roots)
+}
+
+/// Roots of a polynomial with complex coefficients.
+pub fn poly_roots_complex(
+    coeffs: &[Complex],
+    method: PolyMethod,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<Vec<PolyRoot>, NumalError> {
+    if coeffs.iter().any(|c| !c.is_finite()) {
+        return Err(NumalError::InvalidInput(
+            "polynomial coefficients must be finite".to_string(),
+        ));
+    }
+    let Some(top) = coeffs.iter().rposition(|c| *c != Complex::ZERO) else {
+        return Err(NumalError::InvalidInput(
+            "polynomial coefficients are all zero".to_string(),
+        ));
+    };
+    if top == 0 {
+        return Err(NumalError::InvalidInput(
+            "polynomial has degree zero and no roots".to_string(),
+        ));
+    }
+    // Factor out x^zeros so the remaining polynomial has a non-zero constant
+    let zeros = coeffs.iter().position(|c| *c != Complex::ZERO).unwrap_or(0);
+    let p = &coeffs[zeros..=top];
+
+    let mut approx = match p.len() - 1 {
+        0 => Vec::new(),
+        1 => vec![-p[0] / p[1]],
+        _ => match method {
+            PolyMethod::Aberth => aberth(p, tol, max_iter)?,
+            PolyMethod::Companion => companion(p, max_iter)?,
+        },
+    };
+    for z in &mut approx {
+        *z = polish(p, *z);
+    }
+    approx.extend(std::iter::repeat_n(Complex::ZERO, zeros));
+
+    let mut roots = cluster(approx, tol);
+    roots.sort_by(|a, b| {
+        a.root
+            .re
+            .total_cmp(&b.root.re)
+            .then(a.root.im.total_cmp(&b.root.im))
+    });
+    Ok(roots)
+}
+
+fn within(diff: f64, scale: f64, tol: Tolerance) -> bool {
+    diff <= tol.eps_abs() + tol.eps_rel() * scale
+}
+
+// Horner evaluation of p(z) and p'(z).
+fn horner(p: &[Complex], z: Complex) -> (Complex, Complex) {
+    let mut v = Complex::ZERO;
+    let mut dv = Complex::ZERO;
+    for &c in p.iter().rev() {
+        dv = dv * z + v;
+        v = v * z + c;
+    }
+    (v, dv)
+}
+
+// Running bound on the rounding error committed by Horner's rule at |z|.
+fn horner_error_bound(p: &[Complex], z: Complex) -> f64 {
+    let r = z.abs();
+    let mut bound = 0.0;
+    for c in p.iter().rev() {
+        bound = bound * r + c.abs();
+    }
+    8.0 * f64::EPSILON * bound
+}
+
+fn aberth(p: &[Complex], tol: Tolerance, max_iter: usize) -> Result<Vec<Complex>, NumalError> {
+    let n = p.len() - 1;
+    // Start on a circle whose radius is the geometric mean of the root moduli
+    let radius = (p[0].abs() / p[n].abs()).powf(1.0 / n as f64);
+    let mut z: Vec<Complex> = (0..n)
+        .map(|k| Complex::from_polar(radius, TAU * k as f64 / n as f64 + 0.4))
+        .collect();
+    let mut done = vec![false; n];
+    for _ in 0..max_iter {
+        for i in 0..n {
+            if done[i] {
+                continue;
+            }
+            let (v, dv) = horner(p, z[i]);
+            if v.abs() <= horner_error_bound(p, z[i]) {
+                done[i] = true;
+                continue;
+            }
+            let ratio = v / dv;
+            let mut sum = Complex::ZERO;
+            for (j, &zj) in z.iter().enumerate() {
+                if j != i {
+                    sum += Complex::ONE / (z[i] - zj);
+                }
+            }
+            let w = ratio / (Complex::ONE - ratio * sum);
+            if !w.is_finite() {
+                continue;
+            }
+            z[i] -= w;
+            let scale = z[i].abs();
+            if within(w.abs(), scale, tol) || w.abs() <= 4.0 * f64::EPSILON * scale {
+                done[i] = true;
+            }
+        }
+        if done.iter().all(|&d| d) {
+            return Ok(z);
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+fn companion(p: &[Complex], max_iter: usize) -> Result<Vec<Complex>, NumalError> {
+    let n = p.len() - 1;
+    let lead = p[n];
+    let mut h = vec![vec![Complex::ZERO; n]; n];
+    for (j, row) in h[0].iter_mut().enumerate() {
+        *row = -p[n - 1 - j] / lead;
+    }
+    for i in 1..n {
+        h[i][i - 1] = Complex::ONE;
+    }
+    balance(&mut h);
+    hessenberg_eigenvalues(h, max_iter)
+}
+
+// Parlett–Reinsch diagonal balancing by powers of two.
+fn balance(a: &mut [Vec<Complex>]) {
+    let n = a.len();
+    let mut done = false;
+    while !done {
+        done = true;
+        for i in 0..n {
+            let mut c = 0.0;
+            let mut r = 0.0;
+            for j in (0..n).filter(|&j| j != i) {
+                c += a[j][i].l1_norm();
+                r += a[i][j].l1_norm();
+            }
+            if c == 0.0 || r == 0.0 {
+                continue;
+            }
+            let s = c + r;
+            let mut f = 1.0;
+            while c < r / 2.0 {
+                f *= 2.0;
+                c *= 4.0;
+            }
+            while c > r * 2.0 {
+                f /= 2.0;
+                c /= 4.0;
+            }
+            if (c + r) / f < 0.95 * s {
+                done = false;
+                for v in a[i].iter_mut() {
+                    *v = *v / f;
+                }
+                for row in a.iter_mut() {
+                    row[i] = row[i] * f;
+                }
+            }
+        }
+    }
+}
+
+// Eigenvalues of a complex upper Hessenberg matrix by single-shift QR with
+// Wilkinson shifts and deflation.
+fn hessenberg_eigenvalues(
+    mut h: Vec<Vec<Complex>>,
+    max_iter: usize,
+) -> Result<Vec<Complex>, NumalError> {
+    let n = h.len();
+    let mut eig = Vec::with_capacity(n);
+    let mut hi = n - 1;
+    let mut its = 0;
+    loop {
+        if hi == 0 {
+            eig.push(h[0][0]);
+            return Ok(eig);
+        }
+        // Find the start of the active unreduced block
+        let mut lo = hi;
+        while lo > 0 {
+            let s = h[lo - 1][lo - 1].l1_norm() + h[lo][lo].l1_norm();
+            if h[lo][lo - 1].l1_norm() <= f64::EPSILON * s {
+                h[lo][lo - 1] = Complex::ZERO;
+                break;
+            }
+            lo -= 1;
+        }
+        if lo == hi {
+            eig.push(h[hi][hi]);
+            hi -= 1;
+            its = 0;
+            continue;
+        }
+        if its == max_iter {
+            return Err(NumalError::DidNotConverge);
+        }
+        its += 1;
+
+        let shift = if its % 10 == 0 {
+            // Exceptional shift to break cycles
+            h[hi][hi] + Complex::from(1.5 * h[hi][hi - 1].abs())
+        } else {
+            wilkinson_shift(h[hi - 1][hi - 1], h[hi - 1][hi], h[hi][hi - 1], h[hi][hi])
+        };
+        for (k, row) in h.iter_mut().enumerate().take(hi + 1).skip(lo) {
+            row[k] -= shift;
+        }
+        let mut rotations = Vec::with_capacity(hi - lo);
+        for k in lo..hi {
+            let (x, y) = (h[k][k], h[k + 1][k]);
+            let r = (x.norm_sqr() + y.norm_sqr()).sqrt();
+            let (c, s) = if r == 0.0 {
+                (Complex::ONE, Complex::ZERO)
+            } else {
+                (x / r, y / r)
+            };
+            let (top, bottom) = h.split_at_mut(k + 1);
+            for (a, b) in top[k][k..=hi].iter_mut().zip(&mut bottom[0][k..=hi]) {
+                (*a, *b) = (c.conj() * *a + s.conj() * *b, c * *b - s * *a);
+            }
+            rotations.push((c, s));
+        }
+        for (k, &(c, s)) in (lo..hi).zip(&rotations) {
+            for row in h.iter_mut().take((k + 2).min(hi) + 1).skip(lo) {
+                let (a, b) = (row[k], row[k + 1]);
+                row[k] = a * c + b * s;
+                row[k + 1] = b * c.conj() - a * s.conj();
+            }
+        }
+        for (k, row) in h.iter_mut().enumerate().take(hi + 1).skip(lo) {
+            row[k] += shift;
+        }
+    }
+}
+
+// Eigenvalue of [[a, b], [c, d]] closest to d.
+fn wilkinson_shift(a: Complex, b: Complex, c: Complex, d: Complex) -> Complex {
+    let half = (a - d).scale(0.5);
+    let disc = (half * half + b * c).sqrt();
+    let (l1, l2) = (d + half + disc, d + half - disc);
+    if (l1 - d).abs() < (l2 - d).abs() {
+        l1
+    } else {
+        l2
+    }
+}
+
+// A few Newton steps on the original polynomial, kept only while the
+// residual keeps decreasing.
+fn polish(p: &[Complex], mut z: Complex) -> Complex {
+    let (mut v, mut dv) = horner(p, z);
+    for _ in 0..8 {
+        if dv == Complex::ZERO {
+            break;
+        }
+        let candidate = z - v / dv;
+        let (cv, cdv) = horner(p, candidate);
+        if cv.abs().is_nan() || cv.abs() >= v.abs() {
+            break;
+        }
+        (z, v, dv) = (candidate, cv, cdv);
+    }
+    z
+}
+
+// Greedily merge approximations that agree under `tol`, replacing each
+// cluster by its centroid.
+fn cluster(mut approx: Vec<Complex>, tol: Tolerance) -> Vec<PolyRoot> {
+    let mut roots = Vec::new();
+    while let Some(seed) = approx.pop() {
+        let mut members = vec![seed];
+        let mut i = 0;
+        while i < approx.len() {
+            let joins = members
+                .iter()
+                .any(|m| within((approx[i] - *m).abs(), m.abs(), tol));
+            if joins {
+                members.push(approx.swap_remove(i));
+                i = 0;
+            } else {
+                i += 1;
+            }
+        }
+        let n = members.len();
+        let sum = members.into_iter().fold(Complex::ZERO, |acc, z| acc + z);
+        roots.push(PolyRoot {
+            root: sum / n as f64,
+            multiplicity: n,
+        });
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METHODS: [PolyMethod; 2] = [PolyMethod::Aberth, PolyMethod::Companion];
+
+    fn close(a: Complex, b: Complex, eps: f64) -> bool {
+        (a - b).abs() < eps
+    }
+
+    #[test]
+    fn real_cubic_with_distinct_roots() {
+        // (x - 1)(x - 2)(x - 3)
+        for method in METHODS {
+            let r = poly_roots(&[-6.0, 11.0, -6.0, 1.0], method, Tolerance::Strict, 500).unwrap();
+            assert_eq!(r.len(), 3, "{method:?}");
+            for (root, expected) in r.iter().zip([1.0, 2.0, 3.0]) {
+                assert!(
+                    close(root.root, expected.into(), 1e-12),
+                    "{method:?}: {r:?}"
+                );
+                assert_eq!(root.multiplicity, 1);
+            }
+        }
+    }
+
+    #[test]
+    fn complex_conjugate_pair() {
+        for method in METHODS {
+            let r = poly_roots(&[1.0, 0.0, 1.0], method, Tolerance::Strict, 500).unwrap();
+            assert!(close(r[0].root, -Complex::I, 1e-14), "{method:?}: {r:?}");
+            assert!(close(r[1].root, Complex::I, 1e-14), "{method:?}: {r:?}");
+        }
+    }
+
+    #[test]
+    fn complex_coefficients() {
+        // (x - i)(x - 2) = x^2 - (2 + i)x + 2i
+        let p = [
+            Complex::new(0.0, 2.0),
+            Complex::new(-2.0, -1.0),
+            Complex::ONE,
+        ];
+        for method in METHODS {
+            let r = poly_roots_complex(&p, method, Tolerance::Strict, 500).unwrap();
+            assert!(close(r[0].root, Complex::I, 1e-13), "{method:?}: {r:?}");
+            assert!(
+                close(r[1].root, Complex::from(2.0), 1e-13),
+                "{method:?}: {r:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn multiple_roots_are_clustered() {
+        // (x - 1)^2 (x + 2) = x^3 - 3x + 2
+        for method in METHODS {
+            let r = poly_roots(&[2.0, -3.0, 0.0, 1.0], method, Tolerance::Default, 500).unwrap();
+            assert_eq!(r.len(), 2, "{method:?}: {r:?}");
+            assert!(close(r[0].root, Complex::from(-2.0), 1e-12));
+            assert_eq!(r[0].multiplicity, 1);
+            assert!(close(r[1].root, Complex::ONE, 1e-8));
+            assert_eq!(r[1].multiplicity, 2);
+        }
+    }
+
+    #[test]
+    fn zero_roots_are_factored_out() {
+        // x^2 (x - 1)
+        for method in METHODS {
+            let r = poly_roots(&[0.0, 0.0, -1.0, 1.0], method, Tolerance::Strict, 500).unwrap();
+            assert_eq!(
+                r[0],
+                PolyRoot {
+                    root: Complex::ZERO,
+                    multiplicity: 2
+                }
+            );
+            assert!(close(r[1].root, Complex::ONE, 1e-14));
+        }
+    }
+
+    #[test]
+    fn roots_of_unity_high_degree() {
+        let mut p = vec![0.0; 21];
+        p[0] = -1.0;
+        p[20] = 1.0;
+        for method in METHODS {
+            let r = poly_roots(&p, method, Tolerance::Strict, 500).unwrap();
+            assert_eq!(r.len(), 20, "{method:?}");
+            for root in &r {
+                assert!(
+                    (root.root.abs() - 1.0).abs() < 1e-13,
+                    "{method:?}: {root:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn trailing_zero_coefficients_reduce_degree() {
+        let r = poly_roots(
+            &[-2.0, 1.0, 0.0, 0.0],
+            PolyMethod::Aberth,
+            Tolerance::Strict,
+            100,
+        )
+        .unwrap();
+        assert_eq!(
+            r,
+            vec![PolyRoot {
+                root: Complex::from(2.0),
+                multiplicity: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn degenerate_inputs_are_invalid() {
+        for coeffs in [&[][..], &[0.0, 0.0], &[3.0], &[3.0, 0.0], &[1.0, f64::NAN]] {
+            let r = poly_roots(coeffs, PolyMethod::Aberth, Tolerance::Default, 100);
+            assert!(matches!(r, Err(NumalError::InvalidInput(_))), "{coeffs:?}");
+        }
+    }
+}
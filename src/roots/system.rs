@@ -0,0 +1,650 @@
+//! Solvers for square nonlinear systems F(x) = 0 with F: R^n -> R^n.
+//!
+//! The system is supplied as `f(x, fx)`, writing the residuals into `fx`.
+//! Jacobians are written into an `n x n` [`Matrix`] whose entry `(i, j)` is
+//! dF_i/dx_j; the `_fd` variants approximate it by forward differences.
+//!
+//! Convergence is judged per component: either every residual satisfies
+//! `|F_i| <= eps_abs`, or every component of the last full step is close to
+//! the current iterate under the [`Tolerance`].
+
+use super::converged;
+use crate::NumalError;
+use crate::core::check_start;
+use crate::core::linalg::{Lu, Matrix, dot, norm};
+use crate::core::tolerance::Tolerance;
+
+/// Outcome of a successful nonlinear system solve
+#[derive(Clone, Debug, PartialEq)]
+pub struct SystemResult {
+    /// The approximate solution
+    pub x: Vec<f64>,
+    /// Residuals F(x) at the solution
+    pub fx: Vec<f64>,
+    /// Euclidean norm of the residuals
+    pub residual_norm: f64,
+    /// Number of iterations performed
+    pub iterations: usize,
+    /// Number of evaluations of F, including finite-difference ones
+    pub evaluations: usize,
+}
+
+/// Secant update used by [`broyden`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BroydenUpdate {
+    /// Broyden's first ("good") method, updating the Jacobian
+    #[default]
+    Good,
+    /// Broyden's second ("bad") method, updating the inverse Jacobian
+    Bad,
+}
+
+// Smallest damping factor tried by the backtracking line search.
+const MIN_DAMPING: f64 = 1e-10;
+
+fn initial_residual<F>(f: &F, x: &[f64]) -> Result<Vec<f64>, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    let mut fx = vec![0.0; x.len()];
+    f(x, &mut fx);
+    if fx.iter().any(|v| !v.is_finite()) {
+        return Err(NumalError::InvalidInput(
+            "system is not finite at the initial guess".to_string(),
+        ));
+    }
+    Ok(fx)
+}
+
+fn residual_converged(fx: &[f64], tol: Tolerance) -> bool {
+    fx.iter().all(|v| v.abs() <= tol.eps_abs())
+}
+
+fn step_converged(x: &[f64], dx: &[f64], tol: Tolerance) -> bool {
+    x.iter()
+        .zip(dx)
+        .all(|(&xi, &di)| converged(xi + di, xi, tol))
+}
+
+fn axpy(x: &[f64], t: f64, dx: &[f64]) -> Vec<f64> {
+    x.iter().zip(dx).map(|(xi, di)| xi + t * di).collect()
+}
+
+fn finished(x: Vec<f64>, fx: Vec<f64>, iterations: usize, evaluations: usize) -> SystemResult {
+    SystemResult {
+        residual_norm: norm(&fx),
+        x,
+        fx,
+        iterations,
+        evaluations,
+    }
+}
+
+/// Forward-difference approximation of the Jacobian at `x`, where
+/// `fx = F(x)`. Returns the number of evaluations of F used.
+pub(crate) fn fd_jacobian<F>(
+    f: &F,
+    x: &[f64],
+    fx: &[f64],
+    jac: &mut Matrix,
+) -> Result<usize, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    let mut xh = x.to_vec();
+    let mut fh = vec![0.0; fx.len()];
+    let sqrt_eps = f64::EPSILON.sqrt();
+    for j in 0..x.len() {
+        let h = sqrt_eps * x[j].abs().max(1.0);
+        xh[j] = x[j] + h;
+        let h = xh[j] - x[j];
+        f(&xh, &mut fh);
+        xh[j] = x[j];
+        for (i, (&fhi, &fxi)) in fh.iter().zip(fx).enumerate() {
+            jac[(i, j)] = (fhi - fxi) / h;
+        }
+    }
+    if !jac.is_finite() {
+        return Err(NumalError::DerivativeNotComputable);
+    }
+    Ok(x.len())
+}
+
+// An iterate together with its residuals.
+type Point = (Vec<f64>, Vec<f64>);
+
+// Backtracking on ||F|| along `dx`. Returns the accepted point, its
+// residuals and the number of evaluations, or `None` if no damping factor
+// down to `MIN_DAMPING` gives sufficient decrease.
+fn backtrack<F>(f: &F, x: &[f64], fnorm: f64, dx: &[f64]) -> (Option<Point>, usize)
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    let mut ft = vec![0.0; x.len()];
+    let mut t = 1.0;
+    let mut evals = 0;
+    while t >= MIN_DAMPING {
+        let xt = axpy(x, t, dx);
+        f(&xt, &mut ft);
+        evals += 1;
+        let ftnorm = norm(&ft);
+        if ftnorm.is_finite() && ftnorm <= (1.0 - 1e-4 * t) * fnorm {
+            return (Some((xt, ft)), evals);
+        }
+        t *= 0.5;
+    }
+    (None, evals)
+}
+
+fn newton_impl<F, J>(
+    f: F,
+    mut jac: J,
+    x0: &[f64],
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<SystemResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+    J: FnMut(&F, &[f64], &[f64], &mut Matrix) -> Result<usize, NumalError>,
+{
+    check_start(x0)?;
+    let n = x0.len();
+    let mut x = x0.to_vec();
+    let mut fx = initial_residual(&f, &x)?;
+    let mut evals = 1;
+    if residual_converged(&fx, tol) {
+        return Ok(finished(x, fx, 0, evals));
+    }
+    let mut j = Matrix::zeros(n, n);
+    for iter in 1..=max_iter {
+        evals += jac(&f, &x, &fx, &mut j)?;
+        let lu = Lu::new(&j).ok_or(NumalError::DerivativeNotComputable)?;
+        let dx: Vec<f64> = lu.solve(&fx).into_iter().map(|v| -v).collect();
+        let small_step = step_converged(&x, &dx, tol);
+        let (accepted, used) = backtrack(&f, &x, norm(&fx), &dx);
+        evals += used;
+        let Some((xt, ft)) = accepted else {
+            return Err(NumalError::DidNotConverge);
+        };
+        x = xt;
+        fx = ft;
+        if small_step || residual_converged(&fx, tol) {
+            return Ok(finished(x, fx, iter, evals));
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+/// Damped Newton's method with a user-supplied Jacobian.
+///
+/// Each Newton step is halved until ||F|| decreases sufficiently. A
+/// singular or non-finite Jacobian is reported as
+/// [`NumalError::DerivativeNotComputable`].
+pub fn newton<F, J>(
+    f: F,
+    jac: J,
+    x0: &[f64],
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<SystemResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+    J: Fn(&[f64], &mut Matrix),
+{
+    let analytic = |_: &F, x: &[f64], _: &[f64], m: &mut Matrix| {
+        jac(x, m);
+        if m.is_finite() {
+            Ok(0)
+        } else {
+            Err(NumalError::DerivativeNotComputable)
+        }
+    };
+    newton_impl(f, analytic, x0, tol, max_iter)
+}
+
+/// Damped Newton's method with a forward-difference Jacobian.
+pub fn newton_fd<F>(
+    f: F,
+    x0: &[f64],
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<SystemResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    newton_impl(f, fd_jacobian, x0, tol, max_iter)
+}
+
+/// Broyden's quasi-Newton method.
+///
+/// Starts from a forward-difference Jacobian and then only applies rank-one
+/// secant updates, refreshing the Jacobian once if the line search stalls.
+pub fn broyden<F>(
+    f: F,
+    x0: &[f64],
+    update: BroydenUpdate,
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<SystemResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    check_start(x0)?;
+    let n = x0.len();
+    let mut x = x0.to_vec();
+    let mut fx = initial_residual(&f, &x)?;
+    let mut evals = 1;
+    if residual_converged(&fx, tol) {
+        return Ok(finished(x, fx, 0, evals));
+    }
+    // `b` holds the Jacobian for the good update and its inverse for the bad
+    let mut b = Matrix::zeros(n, n);
+    let mut fresh = false;
+    let refresh = |x: &[f64], fx: &[f64], b: &mut Matrix| -> Result<usize, NumalError> {
+        let used = fd_jacobian(&f, x, fx, b)?;
+        if update == BroydenUpdate::Bad {
+            *b = Lu::new(b)
+                .ok_or(NumalError::DerivativeNotComputable)?
+                .inverse();
+        }
+        Ok(used)
+    };
+    let mut needs_refresh = true;
+    for iter in 1..=max_iter {
+        if needs_refresh {
+            evals += refresh(&x, &fx, &mut b)?;
+            fresh = true;
+            needs_refresh = false;
+        }
+        let dx: Vec<f64> = match update {
+            BroydenUpdate::Good => Lu::new(&b)
+                .ok_or(NumalError::DerivativeNotComputable)?
+                .solve(&fx),
+            BroydenUpdate::Bad => b.mul_vec(&fx),
+        }
+        .into_iter()
+        .map(|v| -v)
+        .collect();
+        let small_step = step_converged(&x, &dx, tol);
+        let (accepted, used) = backtrack(&f, &x, norm(&fx), &dx);
+        evals += used;
+        let Some((xt, ft)) = accepted else {
+            if fresh {
+                return Err(NumalError::DidNotConverge);
+            }
+            needs_refresh = true;
+            continue;
+        };
+        let s: Vec<f64> = xt.iter().zip(&x).map(|(a, b)| a - b).collect();
+        let y: Vec<f64> = ft.iter().zip(&fx).map(|(a, b)| a - b).collect();
+        x = xt;
+        fx = ft;
+        if small_step || residual_converged(&fx, tol) {
+            return Ok(finished(x, fx, iter, evals));
+        }
+        match update {
+            BroydenUpdate::Good => {
+                let bs = b.mul_vec(&s);
+                let r: Vec<f64> = y.iter().zip(&bs).map(|(a, b)| a - b).collect();
+                b.rank1_update(1.0 / dot(&s, &s), &r, &s);
+            }
+            BroydenUpdate::Bad => {
+                let hy = b.mul_vec(&y);
+                let r: Vec<f64> = s.iter().zip(&hy).map(|(a, b)| a - b).collect();
+                let yy = dot(&y, &y);
+                if yy > 0.0 {
+                    b.rank1_update(1.0 / yy, &r, &y);
+                }
+            }
+        }
+        fresh = false;
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+// Powell dogleg step in the variables scaled by `diag`.
+fn dogleg(j: &Matrix, fx: &[f64], diag: &[f64], delta: f64) -> Result<Vec<f64>, NumalError> {
+    let gauss_newton =
+        Lu::new(j).map(|lu| lu.solve(fx).into_iter().map(|v| -v).collect::<Vec<_>>());
+    let scaled_norm = |p: &[f64]| norm(&p.iter().zip(diag).map(|(a, d)| a * d).collect::<Vec<_>>());
+    if let Some(gn) = &gauss_newton
+        && scaled_norm(gn) <= delta
+    {
+        return Ok(gn.clone());
+    }
+    // Scaled gradient of ||F||^2 / 2
+    let g: Vec<f64> = j
+        .tr_mul_vec(fx)
+        .iter()
+        .zip(diag)
+        .map(|(g, d)| g / d)
+        .collect();
+    let gnorm = norm(&g);
+    if gnorm == 0.0 {
+        return Err(NumalError::DerivativeNotComputable);
+    }
+    // Steepest-descent direction in x, scaled back from unit scaled gradient
+    let sd: Vec<f64> = g.iter().zip(diag).map(|(g, d)| -g / gnorm / d).collect();
+    let jsd = norm(&j.mul_vec(&sd));
+    let cauchy_len = if jsd > 0.0 {
+        gnorm / (jsd * jsd)
+    } else {
+        delta
+    };
+    if cauchy_len >= delta {
+        return Ok(sd.iter().map(|v| v * delta).collect());
+    }
+    let cauchy: Vec<f64> = sd.iter().map(|v| v * cauchy_len).collect();
+    let Some(gn) = gauss_newton else {
+        return Ok(cauchy);
+    };
+    // Point on the segment from the Cauchy point to the Gauss-Newton point
+    // at scaled distance `delta`
+    let d: Vec<f64> = gn.iter().zip(&cauchy).map(|(a, b)| a - b).collect();
+    let dc: Vec<f64> = cauchy.iter().zip(diag).map(|(a, s)| a * s).collect();
+    let dd: Vec<f64> = d.iter().zip(diag).map(|(a, s)| a * s).collect();
+    let (a, b, c) = (
+        dot(&dd, &dd),
+        2.0 * dot(&dc, &dd),
+        dot(&dc, &dc) - delta * delta,
+    );
+    let tau = (-b + (b * b - 4.0 * a * c).max(0.0).sqrt()) / (2.0 * a);
+    Ok(axpy(&cauchy, tau, &d))
+}
+
+fn hybrid_impl<F, J>(
+    f: F,
+    mut jac: J,
+    x0: &[f64],
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<SystemResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+    J: FnMut(&F, &[f64], &[f64], &mut Matrix) -> Result<usize, NumalError>,
+{
+    check_start(x0)?;
+    let n = x0.len();
+    let mut x = x0.to_vec();
+    let mut fx = initial_residual(&f, &x)?;
+    let mut fnorm = norm(&fx);
+    let mut evals = 1;
+    if residual_converged(&fx, tol) {
+        return Ok(finished(x, fx, 0, evals));
+    }
+    let mut j = Matrix::zeros(n, n);
+    let mut diag = vec![0.0f64; n];
+    let mut delta = 0.0;
+    let mut needs_jac = true;
+    let (mut ncsuc, mut ncfail, mut nslow) = (0, 0, 0);
+    let mut ft = vec![0.0; n];
+    for iter in 1..=max_iter {
+        if needs_jac {
+            evals += jac(&f, &x, &fx, &mut j)?;
+            for (k, dk) in diag.iter_mut().enumerate() {
+                let c = norm(&j.col(k));
+                *dk = (*dk).max(if c == 0.0 { 1.0 } else { c });
+            }
+            if iter == 1 {
+                let xnorm = norm(&x.iter().zip(&diag).map(|(a, d)| a * d).collect::<Vec<_>>());
+                delta = if xnorm > 0.0 { 100.0 * xnorm } else { 100.0 };
+            }
+            needs_jac = false;
+            ncfail = 0;
+        }
+        let p = dogleg(&j, &fx, &diag, delta)?;
+        let pnorm = norm(&p.iter().zip(&diag).map(|(a, d)| a * d).collect::<Vec<_>>());
+        if iter == 1 {
+            delta = delta.min(pnorm);
+        }
+        let xt = axpy(&x, 1.0, &p);
+        f(&xt, &mut ft);
+        evals += 1;
+        let ftnorm = norm(&ft);
+        let actred = if ftnorm.is_finite() && ftnorm < fnorm {
+            1.0 - (ftnorm / fnorm).powi(2)
+        } else {
+            -1.0
+        };
+        let jp = j.mul_vec(&p);
+        let model: Vec<f64> = fx.iter().zip(&jp).map(|(a, b)| a + b).collect();
+        let mnorm = norm(&model);
+        let prered = if mnorm < fnorm {
+            1.0 - (mnorm / fnorm).powi(2)
+        } else {
+            0.0
+        };
+        let ratio = if prered > 0.0 { actred / prered } else { 0.0 };
+
+        if ratio < 0.1 {
+            ncsuc = 0;
+            ncfail += 1;
+            delta *= 0.5;
+        } else {
+            ncfail = 0;
+            ncsuc += 1;
+            if ratio >= 0.5 || ncsuc > 1 {
+                delta = delta.max(2.0 * pnorm);
+            }
+            if (ratio - 1.0).abs() <= 0.1 {
+                delta = 2.0 * pnorm;
+            }
+        }
+        nslow = if actred >= 1e-3 { 0 } else { nslow + 1 };
+
+        let accepted = ratio >= 1e-4;
+        if accepted {
+            let small_step = step_converged(&x, &p, tol);
+            x = xt;
+            fx.copy_from_slice(&ft);
+            fnorm = ftnorm;
+            if small_step || residual_converged(&fx, tol) {
+                return Ok(finished(x, fx, iter, evals));
+            }
+        }
+        if nslow >= 10 || delta <= f64::EPSILON * norm(&x) {
+            return Err(NumalError::DidNotConverge);
+        }
+        if ncfail == 2 {
+            needs_jac = true;
+        } else if ftnorm.is_finite() {
+            // Broyden rank-one update of the Jacobian
+            let r: Vec<f64> = ft.iter().zip(&model).map(|(a, b)| a - b).collect();
+            j.rank1_update(1.0 / dot(&p, &p), &r, &p);
+        }
+    }
+    Err(NumalError::DidNotConverge)
+}
+
+/// Powell's hybrid dogleg method in the style of MINPACK `hybrj`, with a
+/// user-supplied Jacobian.
+///
+/// Combines Gauss–Newton and scaled steepest-descent steps inside a trust
+/// region and keeps the Jacobian current with Broyden rank-one updates,
+/// re-evaluating it only after repeated poor steps. A singular Jacobian
+/// falls back to the Cauchy step; it is only reported as
+/// [`NumalError::DerivativeNotComputable`] when the gradient of ||F||^2
+/// also vanishes.
+pub fn hybrid<F, J>(
+    f: F,
+    jac: J,
+    x0: &[f64],
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<SystemResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+    J: Fn(&[f64], &mut Matrix),
+{
+    let analytic = |_: &F, x: &[f64], _: &[f64], m: &mut Matrix| {
+        jac(x, m);
+        if m.is_finite() {
+            Ok(0)
+        } else {
+            Err(NumalError::DerivativeNotComputable)
+        }
+    };
+    hybrid_impl(f, analytic, x0, tol, max_iter)
+}
+
+/// Powell's hybrid dogleg method in the style of MINPACK `hybrd`, with a
+/// forward-difference Jacobian.
+pub fn hybrid_fd<F>(
+    f: F,
+    x0: &[f64],
+    tol: Tolerance,
+    max_iter: usize,
+) -> Result<SystemResult, NumalError>
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    hybrid_impl(f, fd_jacobian, x0, tol, max_iter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // x^2 + y^2 = 4, x = y
+    fn circle_line(x: &[f64], fx: &mut [f64]) {
+        fx[0] = x[0] * x[0] + x[1] * x[1] - 4.0;
+        fx[1] = x[0] - x[1];
+    }
+
+    fn circle_line_jac(x: &[f64], j: &mut Matrix) {
+        j[(0, 0)] = 2.0 * x[0];
+        j[(0, 1)] = 2.0 * x[1];
+        j[(1, 0)] = 1.0;
+        j[(1, 1)] = -1.0;
+    }
+
+    // Rosenbrock written as a system, root at (1, 1)
+    fn rosenbrock(x: &[f64], fx: &mut [f64]) {
+        fx[0] = 10.0 * (x[1] - x[0] * x[0]);
+        fx[1] = 1.0 - x[0];
+    }
+
+    // Singular everywhere: both equations depend on x + y only
+    fn degenerate(x: &[f64], fx: &mut [f64]) {
+        fx[0] = x[0] + x[1] - 1.0;
+        fx[1] = 2.0 * (x[0] + x[1]) - 3.0;
+    }
+
+    fn assert_solution(r: &SystemResult, expected: &[f64], eps: f64) {
+        for (xi, ei) in r.x.iter().zip(expected) {
+            assert!((xi - ei).abs() < eps, "{r:?}");
+        }
+    }
+
+    #[test]
+    fn newton_with_analytic_jacobian() {
+        let r = newton(
+            circle_line,
+            circle_line_jac,
+            &[1.0, 0.5],
+            Tolerance::Strict,
+            50,
+        )
+        .unwrap();
+        let s = 2f64.sqrt();
+        assert_solution(&r, &[s, s], 1e-12);
+        assert!(r.residual_norm < 1e-12);
+    }
+
+    #[test]
+    fn all_methods_solve_rosenbrock() {
+        let x0 = [-1.2, 1.0];
+        let results = [
+            newton_fd(rosenbrock, &x0, Tolerance::Strict, 100),
+            broyden(rosenbrock, &x0, BroydenUpdate::Good, Tolerance::Strict, 100),
+            broyden(rosenbrock, &x0, BroydenUpdate::Bad, Tolerance::Strict, 100),
+            hybrid_fd(rosenbrock, &x0, Tolerance::Strict, 100),
+        ];
+        for r in results {
+            assert_solution(&r.unwrap(), &[1.0, 1.0], 1e-9);
+        }
+    }
+
+    #[test]
+    fn hybrid_with_analytic_jacobian() {
+        let r = hybrid(
+            circle_line,
+            circle_line_jac,
+            &[3.0, -1.0],
+            Tolerance::Strict,
+            100,
+        )
+        .unwrap();
+        let s = 2f64.sqrt();
+        assert_solution(&r, &[s, s], 1e-10);
+    }
+
+    #[test]
+    fn hybrid_handles_powell_badly_scaled_function() {
+        // Moré, Garbow and Hillstrom test problem 3
+        let f = |x: &[f64], fx: &mut [f64]| {
+            fx[0] = 1e4 * x[0] * x[1] - 1.0;
+            fx[1] = (-x[0]).exp() + (-x[1]).exp() - 1.0001;
+        };
+        let r = hybrid_fd(f, &[0.0, 1.0], Tolerance::Strict, 500).unwrap();
+        assert!(r.residual_norm < 1e-10, "{r:?}");
+    }
+
+    #[test]
+    fn three_dimensional_system() {
+        let f = |x: &[f64], fx: &mut [f64]| {
+            fx[0] = x[0] + x[1] + x[2] - 6.0;
+            fx[1] = x[0] * x[1] * x[2] - 6.0;
+            fx[2] = x[0] * x[0] + x[1] - 3.0;
+        };
+        for r in [
+            newton_fd(f, &[0.8, 2.3, 2.7], Tolerance::Strict, 100),
+            hybrid_fd(f, &[0.8, 2.3, 2.7], Tolerance::Strict, 100),
+        ] {
+            assert_solution(&r.unwrap(), &[1.0, 2.0, 3.0], 1e-9);
+        }
+    }
+
+    #[test]
+    fn singular_jacobian_is_reported() {
+        let r = newton_fd(degenerate, &[0.0, 0.0], Tolerance::Default, 50);
+        assert_eq!(r, Err(NumalError::DerivativeNotComputable));
+        let r = broyden(
+            degenerate,
+            &[0.0, 0.0],
+            BroydenUpdate::Good,
+            Tolerance::Default,
+            50,
+        );
+        assert_eq!(r, Err(NumalError::DerivativeNotComputable));
+        let r = broyden(
+            degenerate,
+            &[0.0, 0.0],
+            BroydenUpdate::Bad,
+            Tolerance::Default,
+            50,
+        );
+        assert_eq!(r, Err(NumalError::DerivativeNotComputable));
+    }
+
+    #[test]
+    fn non_finite_jacobian_is_reported() {
+        let jac = |_: &[f64], j: &mut Matrix| j[(0, 0)] = f64::NAN;
+        let r = newton(circle_line, jac, &[1.0, 0.5], Tolerance::Default, 50);
+        assert_eq!(r, Err(NumalError::DerivativeNotComputable));
+    }
+
+    #[test]
+    fn invalid_initial_guess() {
+        for x0 in [&[][..], &[f64::NAN, 1.0]] {
+            let r = newton_fd(circle_line, x0, Tolerance::Default, 50);
+            assert!(matches!(r, Err(NumalError::InvalidInput(_))));
+        }
+    }
+
+    #[test]
+    fn exhausted_budget_did_not_converge() {
+        let r = hybrid_fd(rosenbrock, &[-1.2, 1.0], Tolerance::Strict, 2);
+        assert_eq!(r, Err(NumalError::DidNotConverge));
+    }
+}